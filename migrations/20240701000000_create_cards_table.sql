@@ -0,0 +1,21 @@
+-- Create cards table
+CREATE TABLE IF NOT EXISTS cards (
+    id UUID PRIMARY KEY,
+    set_id UUID NOT NULL REFERENCES card_sets(id) ON DELETE CASCADE,
+    collector_number INT NOT NULL,
+    name VARCHAR(255) NOT NULL,
+    card_type VARCHAR(50) NOT NULL,
+    card_energy VARCHAR(50),
+    rarity VARCHAR(50) NOT NULL,
+    cost INT,
+    artists TEXT[] NOT NULL DEFAULT '{}',
+    image_url TEXT,
+    created_at TIMESTAMPTZ NOT NULL DEFAULT NOW(),
+    updated_at TIMESTAMPTZ NOT NULL DEFAULT NOW(),
+    UNIQUE (set_id, collector_number)
+);
+
+-- Create indexes for the usual catalog filters
+CREATE INDEX idx_cards_name ON cards(name);
+CREATE INDEX idx_cards_card_type ON cards(card_type);
+CREATE INDEX idx_cards_rarity ON cards(rarity);
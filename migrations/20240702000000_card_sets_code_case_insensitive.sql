@@ -0,0 +1,4 @@
+-- La unicidad del código pasa a ser insensible a mayúsculas/minúsculas
+ALTER TABLE card_sets DROP CONSTRAINT IF EXISTS card_sets_code_key;
+
+CREATE UNIQUE INDEX IF NOT EXISTS card_sets_code_unique_idx ON card_sets (UPPER(code));
@@ -0,0 +1,34 @@
+-- Slugs legibles para los conjuntos de cartas
+ALTER TABLE card_sets ADD COLUMN IF NOT EXISTS slug VARCHAR(255);
+
+-- Si dos conjuntos producen el mismo slug, todos salvo el más antiguo llevan
+-- el código como sufijo; el código es único, así que el resultado también
+WITH base AS (
+    SELECT id, code, created_at, COALESCE(NULLIF(TRIM(BOTH '-' FROM REGEXP_REPLACE(
+        LOWER(TRANSLATE(name, 'ÁÉÍÓÚÜÑáéíóúüñ', 'AEIOUUNaeiouun')),
+        '[^a-z0-9]+', '-', 'g'
+    )), ''), 'conjunto') AS slug
+    FROM card_sets
+    WHERE slug IS NULL
+),
+ranked AS (
+    SELECT id, code, slug, ROW_NUMBER() OVER (PARTITION BY slug ORDER BY created_at, id) AS position
+    FROM base
+)
+UPDATE card_sets
+SET slug = CASE
+    WHEN ranked.position = 1 THEN ranked.slug
+    ELSE ranked.slug || '-' || TRIM(BOTH '-' FROM REGEXP_REPLACE(LOWER(ranked.code), '[^a-z0-9]+', '-', 'g'))
+END
+FROM ranked
+WHERE card_sets.id = ranked.id;
+
+ALTER TABLE card_sets ALTER COLUMN slug SET NOT NULL;
+ALTER TABLE card_sets ADD CONSTRAINT card_sets_slug_key UNIQUE (slug);
+
+-- Slugs anteriores, para redirigir cuando un conjunto cambia de nombre
+CREATE TABLE IF NOT EXISTS card_set_slug_history (
+    slug VARCHAR(255) PRIMARY KEY,
+    card_set_id UUID NOT NULL REFERENCES card_sets(id) ON DELETE CASCADE,
+    created_at TIMESTAMPTZ NOT NULL DEFAULT NOW()
+);
@@ -0,0 +1,8 @@
+-- Fecha de publicación explícita: si existe, prevalece sobre release_date
+ALTER TABLE card_sets ADD COLUMN IF NOT EXISTS publish_at TIMESTAMPTZ;
+
+-- Cartas reveladas antes del lanzamiento de su conjunto (spoilers)
+ALTER TABLE cards ADD COLUMN IF NOT EXISTS revealed_at TIMESTAMPTZ;
+
+CREATE INDEX IF NOT EXISTS idx_card_sets_visible_from ON card_sets (COALESCE(publish_at, release_date));
+CREATE INDEX IF NOT EXISTS idx_cards_revealed_at ON cards (revealed_at) WHERE revealed_at IS NOT NULL;
@@ -0,0 +1,3 @@
+-- Dimensiones, blurhash y variantes generadas para las imágenes subidas
+ALTER TABLE card_sets ADD COLUMN IF NOT EXISTS icon_details JSONB;
+ALTER TABLE cards ADD COLUMN IF NOT EXISTS image_details JSONB;
@@ -0,0 +1,8 @@
+-- Hash perceptual (pHash de 64 bits) de la imagen de cada carta, para identificarlas por foto.
+-- image_url indica sobre qué imagen se calculó: si la carta cambia de imagen, el hash deja de usarse
+CREATE TABLE IF NOT EXISTS card_image_hashes (
+    card_id UUID PRIMARY KEY REFERENCES cards(id) ON DELETE CASCADE,
+    image_url TEXT NOT NULL,
+    phash BIGINT NOT NULL,
+    computed_at TIMESTAMPTZ NOT NULL DEFAULT NOW()
+);
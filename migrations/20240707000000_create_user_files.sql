@@ -0,0 +1,12 @@
+-- Archivos privados de cada usuario; sólo se sirven mediante URLs firmadas
+CREATE TABLE IF NOT EXISTS user_files (
+    id UUID PRIMARY KEY,
+    owner_uid TEXT NOT NULL,
+    storage_key TEXT NOT NULL UNIQUE,
+    file_name TEXT,
+    content_type TEXT NOT NULL,
+    size_bytes BIGINT NOT NULL,
+    created_at TIMESTAMPTZ NOT NULL DEFAULT NOW()
+);
+
+CREATE INDEX IF NOT EXISTS idx_user_files_owner ON user_files (owner_uid, created_at DESC);
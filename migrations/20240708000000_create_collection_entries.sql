@@ -0,0 +1,15 @@
+-- Colección de cada usuario: copias por impresión (carta, estado, idioma y acabado)
+CREATE TABLE IF NOT EXISTS collection_entries (
+    id UUID PRIMARY KEY,
+    owner_uid TEXT NOT NULL,
+    card_id UUID NOT NULL REFERENCES cards(id) ON DELETE CASCADE,
+    condition TEXT NOT NULL,
+    language TEXT NOT NULL,
+    finish TEXT NOT NULL,
+    quantity INTEGER NOT NULL CHECK (quantity > 0),
+    created_at TIMESTAMPTZ NOT NULL DEFAULT NOW(),
+    updated_at TIMESTAMPTZ NOT NULL DEFAULT NOW(),
+    CONSTRAINT collection_entries_printing_key UNIQUE (owner_uid, card_id, condition, language, finish)
+);
+
+CREATE INDEX IF NOT EXISTS idx_collection_entries_card ON collection_entries (card_id);
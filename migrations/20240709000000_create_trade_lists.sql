@@ -0,0 +1,20 @@
+-- Copias de una entrada de la colección que el usuario ofrece para intercambio
+CREATE TABLE IF NOT EXISTS trade_haves (
+    entry_id UUID PRIMARY KEY REFERENCES collection_entries(id) ON DELETE CASCADE,
+    quantity INTEGER NOT NULL CHECK (quantity > 0),
+    created_at TIMESTAMPTZ NOT NULL DEFAULT NOW(),
+    updated_at TIMESTAMPTZ NOT NULL DEFAULT NOW()
+);
+
+-- Cartas que el usuario busca conseguir por intercambio
+CREATE TABLE IF NOT EXISTS trade_wants (
+    id UUID PRIMARY KEY,
+    owner_uid TEXT NOT NULL,
+    card_id UUID NOT NULL REFERENCES cards(id) ON DELETE CASCADE,
+    quantity INTEGER NOT NULL CHECK (quantity > 0),
+    created_at TIMESTAMPTZ NOT NULL DEFAULT NOW(),
+    updated_at TIMESTAMPTZ NOT NULL DEFAULT NOW(),
+    CONSTRAINT trade_wants_owner_card_key UNIQUE (owner_uid, card_id)
+);
+
+CREATE INDEX IF NOT EXISTS idx_trade_wants_card ON trade_wants (card_id);
@@ -0,0 +1,34 @@
+-- Solicitudes de gradación: el usuario envía una carta, un grader la toma de la cola
+-- y registra las subnotas, la nota final y el número de certificado
+CREATE SEQUENCE IF NOT EXISTS grading_certificate_seq START WITH 10000001;
+
+CREATE TABLE IF NOT EXISTS grading_submissions (
+    id UUID PRIMARY KEY,
+    owner_uid TEXT NOT NULL,
+    card_id UUID NOT NULL REFERENCES cards(id) ON DELETE CASCADE,
+    notes TEXT,
+    status TEXT NOT NULL DEFAULT 'submitted'
+        CHECK (status IN ('submitted', 'in_review', 'graded')),
+    grader_uid TEXT,
+    -- Notas de 1 a 10 en medios puntos
+    centering REAL CHECK (centering BETWEEN 1 AND 10),
+    corners REAL CHECK (corners BETWEEN 1 AND 10),
+    edges REAL CHECK (edges BETWEEN 1 AND 10),
+    surface REAL CHECK (surface BETWEEN 1 AND 10),
+    final_grade REAL CHECK (final_grade BETWEEN 1 AND 10),
+    certificate_number TEXT UNIQUE,
+    claimed_at TIMESTAMPTZ,
+    graded_at TIMESTAMPTZ,
+    created_at TIMESTAMPTZ NOT NULL DEFAULT NOW(),
+    updated_at TIMESTAMPTZ NOT NULL DEFAULT NOW(),
+    CONSTRAINT grading_submissions_graded_check CHECK (
+        status <> 'graded' OR (
+            grader_uid IS NOT NULL AND centering IS NOT NULL AND corners IS NOT NULL
+            AND edges IS NOT NULL AND surface IS NOT NULL AND final_grade IS NOT NULL
+            AND certificate_number IS NOT NULL AND graded_at IS NOT NULL
+        )
+    )
+);
+
+CREATE INDEX IF NOT EXISTS idx_grading_submissions_owner ON grading_submissions (owner_uid, created_at);
+CREATE INDEX IF NOT EXISTS idx_grading_submissions_queue ON grading_submissions (status, created_at);
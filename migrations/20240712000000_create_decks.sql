@@ -0,0 +1,25 @@
+-- Mazos de los usuarios, hasta ahora sólo en la colección `decks` de Firestore
+CREATE TABLE IF NOT EXISTS decks (
+    id UUID PRIMARY KEY,
+    owner_uid TEXT NOT NULL,
+    name TEXT NOT NULL,
+    description TEXT,
+    is_public BOOLEAN NOT NULL DEFAULT FALSE,
+    created_at TIMESTAMPTZ NOT NULL DEFAULT NOW(),
+    updated_at TIMESTAMPTZ NOT NULL DEFAULT NOW()
+);
+
+-- Los mismos índices que firestore.indexes.json: listados por propietario o públicos,
+-- ordenados por fecha de creación o de modificación
+CREATE INDEX IF NOT EXISTS idx_decks_owner_created ON decks (owner_uid, created_at DESC);
+CREATE INDEX IF NOT EXISTS idx_decks_owner_updated ON decks (owner_uid, updated_at DESC);
+CREATE INDEX IF NOT EXISTS idx_decks_public_created ON decks (is_public, created_at DESC);
+
+CREATE TABLE IF NOT EXISTS deck_cards (
+    deck_id UUID NOT NULL REFERENCES decks(id) ON DELETE CASCADE,
+    card_id UUID NOT NULL REFERENCES cards(id) ON DELETE CASCADE,
+    quantity INTEGER NOT NULL CHECK (quantity > 0),
+    PRIMARY KEY (deck_id, card_id)
+);
+
+CREATE INDEX IF NOT EXISTS idx_deck_cards_card ON deck_cards (card_id);
@@ -0,0 +1,13 @@
+-- Formatos de juego: las reglas de legalidad de cada uno se guardan como JSONB
+CREATE TABLE IF NOT EXISTS deck_formats (
+    code TEXT PRIMARY KEY,
+    name TEXT NOT NULL,
+    description TEXT,
+    rules JSONB NOT NULL DEFAULT '{}'::jsonb,
+    created_at TIMESTAMPTZ NOT NULL DEFAULT NOW(),
+    updated_at TIMESTAMPTZ NOT NULL DEFAULT NOW()
+);
+
+-- Formato en el que se juega cada mazo; al borrar un formato los mazos quedan sin él
+ALTER TABLE decks
+    ADD COLUMN IF NOT EXISTS format_code TEXT REFERENCES deck_formats(code) ON UPDATE CASCADE ON DELETE SET NULL;
@@ -0,0 +1,43 @@
+-- Cada vez que se guarda un mazo se conserva una copia inmutable de su contenido.
+-- Las cartas se guardan como JSONB para que la revisión no cambie aunque el
+-- catálogo lo haga; el formato tampoco es una clave foránea por el mismo motivo.
+CREATE TABLE IF NOT EXISTS deck_revisions (
+    id UUID PRIMARY KEY,
+    deck_id UUID NOT NULL REFERENCES decks(id) ON DELETE CASCADE,
+    revision INTEGER NOT NULL CHECK (revision > 0),
+    name TEXT NOT NULL,
+    description TEXT,
+    format_code TEXT,
+    card_count INTEGER NOT NULL,
+    cards JSONB NOT NULL,
+    author_uid TEXT NOT NULL,
+    -- Revisión restaurada, cuando el guardado fue una restauración
+    restored_from INTEGER,
+    created_at TIMESTAMPTZ NOT NULL DEFAULT NOW(),
+    UNIQUE (deck_id, revision)
+);
+
+-- Las revisiones no se modifican nunca; sólo desaparecen al borrar el mazo
+CREATE OR REPLACE FUNCTION reject_deck_revision_update() RETURNS TRIGGER AS $$
+BEGIN
+    RAISE EXCEPTION 'Las revisiones de mazos son inmutables';
+END;
+$$ LANGUAGE plpgsql;
+
+DROP TRIGGER IF EXISTS deck_revisions_immutable ON deck_revisions;
+CREATE TRIGGER deck_revisions_immutable
+    BEFORE UPDATE ON deck_revisions
+    FOR EACH ROW EXECUTE FUNCTION reject_deck_revision_update();
+
+-- Los mazos existentes empiezan su historial con su contenido actual
+INSERT INTO deck_revisions (id, deck_id, revision, name, description, format_code, card_count, cards, author_uid, created_at)
+SELECT
+    gen_random_uuid(), d.id, 1, d.name, d.description, d.format_code,
+    COALESCE((SELECT SUM(dc.quantity) FROM deck_cards dc WHERE dc.deck_id = d.id), 0),
+    COALESCE(
+        (SELECT jsonb_agg(jsonb_build_object('card_id', dc.card_id, 'quantity', dc.quantity)) FROM deck_cards dc WHERE dc.deck_id = d.id),
+        '[]'::jsonb
+    ),
+    d.owner_uid, d.updated_at
+FROM decks d
+WHERE NOT EXISTS (SELECT 1 FROM deck_revisions r WHERE r.deck_id = d.id);
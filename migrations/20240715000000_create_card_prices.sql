@@ -0,0 +1,9 @@
+-- Precio de referencia de cada impresión, en céntimos, mantenido por el staff.
+-- Se usa para estimar lo que cuesta completar un mazo.
+CREATE TABLE IF NOT EXISTS card_prices (
+    card_id UUID PRIMARY KEY REFERENCES cards(id) ON DELETE CASCADE,
+    price_cents BIGINT NOT NULL CHECK (price_cents >= 0),
+    currency TEXT NOT NULL CHECK (currency ~ '^[A-Z]{3}$'),
+    source TEXT,
+    updated_at TIMESTAMPTZ NOT NULL DEFAULT NOW()
+);
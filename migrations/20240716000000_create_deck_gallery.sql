@@ -0,0 +1,30 @@
+-- Interacciones con los mazos públicos de la galería
+CREATE TABLE IF NOT EXISTS deck_likes (
+    deck_id UUID NOT NULL REFERENCES decks(id) ON DELETE CASCADE,
+    user_uid TEXT NOT NULL,
+    created_at TIMESTAMPTZ NOT NULL DEFAULT NOW(),
+    PRIMARY KEY (deck_id, user_uid)
+);
+
+CREATE TABLE IF NOT EXISTS deck_bookmarks (
+    deck_id UUID NOT NULL REFERENCES decks(id) ON DELETE CASCADE,
+    user_uid TEXT NOT NULL,
+    created_at TIMESTAMPTZ NOT NULL DEFAULT NOW(),
+    PRIMARY KEY (deck_id, user_uid)
+);
+
+CREATE INDEX IF NOT EXISTS idx_deck_bookmarks_user ON deck_bookmarks (user_uid, created_at DESC);
+
+-- Origen de los mazos copiados de otro. El nombre y el propietario del original
+-- se guardan para mantener la atribución aunque el original se borre después.
+CREATE TABLE IF NOT EXISTS deck_forks (
+    deck_id UUID PRIMARY KEY REFERENCES decks(id) ON DELETE CASCADE,
+    source_deck_id UUID REFERENCES decks(id) ON DELETE SET NULL,
+    source_owner_uid TEXT NOT NULL,
+    source_name TEXT NOT NULL,
+    source_revision INTEGER,
+    created_at TIMESTAMPTZ NOT NULL DEFAULT NOW()
+);
+
+CREATE INDEX IF NOT EXISTS idx_deck_forks_source ON deck_forks (source_deck_id, created_at);
+CREATE INDEX IF NOT EXISTS idx_decks_format ON decks (format_code) WHERE is_public;
@@ -0,0 +1,28 @@
+-- Estadísticas de cartas jugadas juntas en los mazos públicos. Las cartas se
+-- identifican por su nombre normalizado: todas las impresiones cuentan igual.
+
+-- Nombres que cada mazo aporta a las estadísticas, para restarlos cuando el
+-- mazo cambia, pasa a privado o se borra (por eso no hay clave foránea)
+CREATE TABLE IF NOT EXISTS card_cooccurrence_decks (
+    deck_id UUID PRIMARY KEY,
+    card_names TEXT[] NOT NULL,
+    -- Versión del mazo indexada: se reindexa cuando decks.updated_at es posterior
+    deck_updated_at TIMESTAMPTZ NOT NULL,
+    indexed_at TIMESTAMPTZ NOT NULL DEFAULT NOW()
+);
+
+-- Mazos públicos que incluyen cada carta
+CREATE TABLE IF NOT EXISTS card_deck_counts (
+    card_name TEXT PRIMARY KEY,
+    deck_count INTEGER NOT NULL CHECK (deck_count >= 0)
+);
+
+-- Mazos públicos que incluyen ambas cartas; cada pareja se guarda en los dos sentidos
+CREATE TABLE IF NOT EXISTS card_pair_counts (
+    card_name TEXT NOT NULL,
+    other_name TEXT NOT NULL,
+    deck_count INTEGER NOT NULL CHECK (deck_count >= 0),
+    PRIMARY KEY (card_name, other_name)
+);
+
+CREATE INDEX IF NOT EXISTS idx_decks_public_updated ON decks (updated_at) WHERE is_public;
@@ -0,0 +1,5 @@
+-- Listas de deseos cuyas cartas cuentan como buscadas al emparejar intercambios
+ALTER TABLE wishlists ADD COLUMN IF NOT EXISTS for_trade BOOLEAN NOT NULL DEFAULT FALSE;
+
+CREATE INDEX IF NOT EXISTS idx_wishlists_for_trade ON wishlists (owner_uid) WHERE for_trade;
+CREATE INDEX IF NOT EXISTS idx_wishlist_items_card ON wishlist_items (card_id);
@@ -0,0 +1,12 @@
+-- Puntuación de tendencia de la galería guardada en el mazo para no recalcularla
+-- en cada consulta. Se actualiza al valorar o copiar un mazo y, para que las
+-- interacciones pierdan peso con el tiempo, en segundo plano cada
+-- TRENDING_REFRESH_SECS; la primera pasada al arrancar rellena los existentes.
+ALTER TABLE decks ADD COLUMN IF NOT EXISTS trending_score DOUBLE PRECISION NOT NULL DEFAULT 0;
+
+CREATE INDEX IF NOT EXISTS idx_decks_trending ON decks (trending_score DESC, created_at DESC, id);
+
+-- Los propietarios ya no pueden dar "me gusta" a sus propios mazos
+DELETE FROM deck_likes l
+USING decks d
+WHERE d.id = l.deck_id AND d.owner_uid = l.user_uid;
@@ -0,0 +1,12 @@
+-- Cada pareja de card_pair_counts se guarda una sola vez, con card_name antes
+-- que other_name. El orden es el de los bytes (COLLATE "C"), el mismo con el
+-- que el servidor compara los nombres al calcular los cambios.
+DELETE FROM card_pair_counts
+WHERE card_name COLLATE "C" > other_name COLLATE "C";
+
+ALTER TABLE card_pair_counts DROP CONSTRAINT IF EXISTS card_pair_counts_ordered;
+ALTER TABLE card_pair_counts
+    ADD CONSTRAINT card_pair_counts_ordered CHECK (card_name COLLATE "C" < other_name COLLATE "C");
+
+-- Para buscar las parejas de una carta por la segunda columna
+CREATE INDEX IF NOT EXISTS idx_card_pair_counts_other ON card_pair_counts (other_name);
@@ -135,6 +135,13 @@ const testUsers: TestUser[] = [
         role: 'moderator',
         photoURL: 'https://api.dicebear.com/7.x/initials/svg?seed=UR'
     },
+    {
+        email: 'grader@kodemcards.xyz',
+        password: 'grader123',
+        displayName: 'Grader User',
+        role: 'grader',
+        photoURL: 'https://api.dicebear.com/7.x/initials/svg?seed=GU'
+    },
     {
         email: 'user@kodemcards.xyz',
         password: 'user123',
@@ -0,0 +1,90 @@
+use uuid::Uuid;
+
+use crate::domain::decks::Deck;
+use crate::domain::wishlists::Wishlist;
+use crate::utils::extractors::AuthUser;
+use crate::utils::response::{ApiResponse, error_response};
+
+/// A resource that belongs to a user and can be made public, read and
+/// managed as in the Firestore rules: owners and admins manage it, anyone
+/// authenticated reads it if it is public
+pub trait OwnedResource {
+    fn owner_uid(&self) -> &str;
+    fn is_public(&self) -> bool;
+    fn not_found_message(id: Uuid) -> String;
+    fn forbidden_message() -> String;
+
+    /// Removes what only the owner may see before it is shown to other readers
+    fn redact(&mut self) {}
+}
+
+/// What the request needs to do with the resource
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Access {
+    Read,
+    Manage,
+}
+
+/// Checks `access` on a resource just looked up by `id` and returns it, or the
+/// response to send instead. A private resource of someone else is reported as
+/// missing: both look the same from outside.
+pub fn authorize<T: OwnedResource, R>(
+    lookup: anyhow::Result<Option<T>>,
+    id: Uuid,
+    user: &AuthUser,
+    access: Access,
+) -> Result<T, ApiResponse<R>> {
+    match lookup {
+        Ok(Some(resource)) if resource.owner_uid() == user.uid || user.is_admin() => Ok(resource),
+        Ok(Some(mut resource)) if resource.is_public() => match access {
+            Access::Read => {
+                resource.redact();
+                Ok(resource)
+            }
+            Access::Manage => Err(ApiResponse::forbidden(T::forbidden_message())),
+        },
+        Ok(_) => Err(error_response(T::not_found_message(id), 404)),
+        Err(e) => Err(error_response(e.to_string(), 500)),
+    }
+}
+
+impl OwnedResource for Deck {
+    fn owner_uid(&self) -> &str {
+        &self.owner_uid
+    }
+
+    fn is_public(&self) -> bool {
+        self.is_public
+    }
+
+    fn not_found_message(id: Uuid) -> String {
+        format!("Mazo con ID {} no encontrado", id)
+    }
+
+    fn forbidden_message() -> String {
+        "Sólo el propietario puede modificar este mazo".to_string()
+    }
+}
+
+impl OwnedResource for Wishlist {
+    fn owner_uid(&self) -> &str {
+        &self.owner_uid
+    }
+
+    fn is_public(&self) -> bool {
+        self.is_public
+    }
+
+    fn not_found_message(id: Uuid) -> String {
+        format!("Lista de deseos con ID {} no encontrada", id)
+    }
+
+    fn forbidden_message() -> String {
+        "Sólo el propietario puede modificar esta lista de deseos".to_string()
+    }
+
+    // El enlace para compartir sólo lo ve el propietario
+    fn redact(&mut self) {
+        self.share_token = None;
+    }
+}
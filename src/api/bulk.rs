@@ -0,0 +1,78 @@
+use axum::{
+    body::Body,
+    http::{header, HeaderMap, StatusCode},
+    response::{IntoResponse, Response},
+};
+use serde::Deserialize;
+
+use crate::domain::cards::{BulkFormat, ImportReport};
+use crate::utils::response::{ApiResponse, error_response, json_response};
+
+#[derive(Debug, Deserialize)]
+pub struct ImportQuery {
+    pub format: Option<String>,
+    #[serde(default)]
+    pub dry_run: bool,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct ExportQuery {
+    pub format: Option<String>,
+}
+
+// El formato se toma del parámetro `format` o, en su defecto, del Content-Type
+pub fn resolve_import_format(requested: Option<&str>, headers: &HeaderMap) -> Result<BulkFormat, String> {
+    resolve_format(requested, headers, header::CONTENT_TYPE)
+}
+
+// Para exportar se usa `format`, luego Accept y, por defecto, CSV
+pub fn resolve_export_format(requested: Option<&str>, headers: &HeaderMap) -> Result<BulkFormat, String> {
+    match requested {
+        Some(_) => resolve_format(requested, headers, header::ACCEPT),
+        None => Ok(resolve_format(None, headers, header::ACCEPT).unwrap_or(BulkFormat::Csv)),
+    }
+}
+
+fn resolve_format(requested: Option<&str>, headers: &HeaderMap, header_name: header::HeaderName) -> Result<BulkFormat, String> {
+    if let Some(requested) = requested {
+        return BulkFormat::parse(requested)
+            .ok_or_else(|| format!("Formato '{}' no soportado. Usa 'csv' o 'ndjson'", requested));
+    }
+
+    let from_header = headers
+        .get(header_name)
+        .and_then(|value| value.to_str().ok())
+        .and_then(|value| value.split(';').next())
+        .and_then(BulkFormat::parse);
+
+    from_header.ok_or_else(|| "Indica el formato con el parámetro 'format' (csv o ndjson)".to_string())
+}
+
+// Si alguna fila falla se responde 422 con el reporte completo; nada se ha guardado
+pub fn import_report_response(report: ImportReport) -> Response {
+    if report.has_errors() {
+        let message = format!("La importación contiene {} fila(s) con errores; no se guardó ningún cambio", report.errors.len());
+        return ApiResponse::error_with_data(report, message, StatusCode::UNPROCESSABLE_ENTITY).into_response();
+    }
+
+    json_response(report).into_response()
+}
+
+pub fn export_response(format: BulkFormat, body: anyhow::Result<Vec<u8>>, file_name: &str) -> Response {
+    let body = match body {
+        Ok(body) => body,
+        Err(e) => return error_response::<()>(e.to_string(), 500).into_response(),
+    };
+
+    let disposition = format!("attachment; filename=\"{}.{}\"", file_name, format.extension());
+
+    (
+        StatusCode::OK,
+        [
+            (header::CONTENT_TYPE, format.content_type().to_string()),
+            (header::CONTENT_DISPOSITION, disposition),
+        ],
+        Body::from(body),
+    )
+        .into_response()
+}
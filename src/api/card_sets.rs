@@ -1,24 +1,41 @@
 use axum::{
-    extract::{Path, State},
+    body::Bytes,
+    extract::{Path, Query, State},
+    http::HeaderMap,
+    response::{IntoResponse, Redirect, Response},
     routing::{get, post, put, delete, patch},
-    Json, Router,
+    Router,
 };
 use std::sync::Arc;
 use uuid::Uuid;
 use axum::http::StatusCode;
 
-use crate::domain::cards::{CardSet, CardSetService, PgCardSetRepository, CreateCardSetDto, UpdateCardSetDto, PatchCardSetDto, Validable};
-use crate::utils::response::{ApiResponse, json_response, error_response, validation_error};
+use crate::api::state::AppState;
+use crate::api::bulk::{ExportQuery, ImportQuery, export_response, import_report_response, resolve_export_format, resolve_import_format};
+use crate::domain::cards::{CardSet, CatalogVisibility, CreateCardSetDto, UpdateCardSetDto, PatchCardSetDto, Validable, export_rows, parse_rows};
+use crate::utils::cache::{PRIVATE_CACHE_CONTROL, conditional_response};
+use crate::utils::extractors::AuthUser;
+use crate::utils::response::{ApiResponse, json_response, error_response, service_error_response, validation_error};
 use crate::utils::extractors::ValidatedJson;
 
-pub struct AppState {
-    pub card_set_service: Arc<CardSetService<PgCardSetRepository>>,
+// Las respuestas que incluyen contenido no publicado no deben guardarse en cachés compartidas
+pub(crate) fn cache_control(policy: &str, visibility: CatalogVisibility) -> &str {
+    if visibility.includes_unreleased() {
+        PRIVATE_CACHE_CONTROL
+    } else {
+        policy
+    }
 }
 
 pub fn card_sets_routes(app_state: Arc<AppState>) -> Router {
     Router::new()
         .route("/cards/sets", get(get_all_card_sets))
         .route("/cards/sets", post(create_card_set))
+        .route("/cards/sets/import", post(import_card_sets))
+        .route("/cards/sets/export", get(export_card_sets))
+        .route("/cards/sets/upcoming", get(get_upcoming_card_sets))
+        .route("/cards/sets/by-code/:code", get(get_card_set_by_code))
+        .route("/cards/sets/by-slug/:slug", get(get_card_set_by_slug))
         .route("/cards/sets/:id", get(get_card_set_by_id))
         .route("/cards/sets/:id", put(update_card_set))
         .route("/cards/sets/:id", patch(patch_card_set))
@@ -28,68 +45,131 @@ pub fn card_sets_routes(app_state: Arc<AppState>) -> Router {
 
 async fn get_all_card_sets(
     State(state): State<Arc<AppState>>,
-) -> ApiResponse<Vec<CardSet>> {
-    match state.card_set_service.get_all_card_sets().await {
-        Ok(card_sets) => json_response(card_sets),
-        Err(e) => error_response(e.to_string(), 500),
+    user: Option<AuthUser>,
+    headers: HeaderMap,
+) -> Response {
+    let visibility = CatalogVisibility::for_user(user.as_ref());
+
+    match state.card_set_service.get_all_card_sets(visibility).await {
+        // Sin Last-Modified: borrar un conjunto no adelanta ninguna fecha, sólo el ETag lo detecta
+        Ok(card_sets) => conditional_response(&headers, card_sets, None, cache_control(&state.cache.card_sets_list, visibility)),
+        Err(e) => error_response::<()>(e.to_string(), 500).into_response(),
+    }
+}
+
+// Conjuntos programados, ordenados por fecha de publicación
+async fn get_upcoming_card_sets(
+    State(state): State<Arc<AppState>>,
+    user: Option<AuthUser>,
+    headers: HeaderMap,
+) -> Response {
+    let visibility = CatalogVisibility::for_user(user.as_ref());
+
+    match state.card_set_service.get_upcoming_card_sets(visibility).await {
+        Ok(card_sets) => conditional_response(&headers, card_sets, None, cache_control(&state.cache.card_sets_list, visibility)),
+        Err(e) => error_response::<()>(e.to_string(), 500).into_response(),
     }
 }
 
 async fn get_card_set_by_id(
     State(state): State<Arc<AppState>>,
     Path(id): Path<Uuid>,
-) -> ApiResponse<CardSet> {
-    match state.card_set_service.get_card_set_by_id(id).await {
-        Ok(Some(card_set)) => json_response(card_set),
-        Ok(None) => error_response(format!("Conjunto de cartas con ID {} no encontrado", id), 404),
-        Err(e) => error_response(e.to_string(), 500),
+    user: Option<AuthUser>,
+    headers: HeaderMap,
+) -> Response {
+    let visibility = CatalogVisibility::for_user(user.as_ref());
+
+    match state.card_set_service.get_visible_card_set_by_id(id, visibility).await {
+        Ok(Some(card_set)) => {
+            let last_modified = Some(card_set.last_modified());
+            conditional_response(&headers, card_set, last_modified, cache_control(&state.cache.card_set_detail, visibility))
+        },
+        Ok(None) => error_response::<()>(format!("Conjunto de cartas con ID {} no encontrado", id), 404).into_response(),
+        Err(e) => error_response::<()>(e.to_string(), 500).into_response(),
+    }
+}
+
+async fn get_card_set_by_code(
+    State(state): State<Arc<AppState>>,
+    Path(code): Path<String>,
+    user: Option<AuthUser>,
+    headers: HeaderMap,
+) -> Response {
+    let visibility = CatalogVisibility::for_user(user.as_ref());
+
+    match state.card_set_service.get_card_set_by_code(&code, visibility).await {
+        Ok(Some(card_set)) => {
+            let last_modified = Some(card_set.last_modified());
+            conditional_response(&headers, card_set, last_modified, cache_control(&state.cache.card_set_detail, visibility))
+        },
+        Ok(None) => error_response::<()>(format!("Conjunto de cartas con código {} no encontrado", code), 404).into_response(),
+        Err(e) => error_response::<()>(e.to_string(), 500).into_response(),
+    }
+}
+
+async fn get_card_set_by_slug(
+    State(state): State<Arc<AppState>>,
+    Path(slug): Path<String>,
+    user: Option<AuthUser>,
+    headers: HeaderMap,
+) -> Response {
+    let visibility = CatalogVisibility::for_user(user.as_ref());
+
+    match state.card_set_service.get_card_set_by_slug(&slug, visibility).await {
+        Ok(Some(card_set)) => {
+            let last_modified = Some(card_set.last_modified());
+            return conditional_response(&headers, card_set, last_modified, cache_control(&state.cache.card_set_detail, visibility));
+        },
+        Ok(None) => {},
+        Err(e) => return error_response::<()>(e.to_string(), 500).into_response(),
+    }
+
+    // Si el slug pertenecía a un conjunto renombrado, redirigimos al slug actual
+    match state.card_set_service.find_current_slug(&slug, visibility).await {
+        Ok(Some(current)) => Redirect::permanent(&format!("/api/v1/cards/sets/by-slug/{}", current)).into_response(),
+        Ok(None) => error_response::<()>(format!("Conjunto de cartas '{}' no encontrado", slug), 404).into_response(),
+        Err(e) => error_response::<()>(e.to_string(), 500).into_response(),
     }
 }
 
 async fn create_card_set(
     State(state): State<Arc<AppState>>,
+    user: AuthUser,
     ValidatedJson(payload): ValidatedJson<CreateCardSetDto>,
 ) -> ApiResponse<CardSet> {
+    if !user.is_staff() {
+        return ApiResponse::forbidden("Sólo el staff puede crear conjuntos".to_string());
+    }
+
     // Validamos los datos de entrada
     if let Err(e) = payload.validate() {
         return validation_error(format!("Error de validación: {}", e), None);
     }
     
-    // Verificamos si ya existe un conjunto con el mismo código
-    match check_unique_code(&state, &payload.code, None).await {
-        Ok(true) => {}, // Código único, continúa
-        Ok(false) => return validation_error(format!("El código '{}' ya está en uso", payload.code), None),
-        Err(e) => return error_response(e.to_string(), 500),
-    }
-    
     let card_set = payload.to_model();
     
+    // La unicidad del código la garantiza el índice único de la base de datos
     match state.card_set_service.create_card_set(card_set).await {
-        Ok(created) => {
-            let response = ApiResponse::success(created, StatusCode::CREATED);
-            response
-        },
-        Err(e) => error_response(e.to_string(), 500),
+        Ok(created) => ApiResponse::success(created, StatusCode::CREATED),
+        Err(e) => service_error_response(e),
     }
 }
 
 async fn update_card_set(
     State(state): State<Arc<AppState>>,
     Path(id): Path<Uuid>,
+    user: AuthUser,
     ValidatedJson(payload): ValidatedJson<UpdateCardSetDto>,
 ) -> ApiResponse<CardSet> {
+    if !user.is_staff() {
+        return ApiResponse::forbidden("Sólo el staff puede modificar conjuntos".to_string());
+    }
+
     // Validamos los datos de entrada
     if let Err(e) = payload.validate() {
         return validation_error(format!("Error de validación: {}", e), None);
     }
     
-    // Verificamos si ya existe un conjunto con el mismo código (excluyendo el actual)
-    match check_unique_code(&state, &payload.code, Some(id)).await {
-        Ok(true) => {}, // Código único, continúa
-        Ok(false) => return validation_error(format!("El código '{}' ya está en uso por otro conjunto", payload.code), None),
-        Err(e) => return error_response(e.to_string(), 500),
-    }
-    
     // Primero, verificamos si el conjunto de cartas existe
     match state.card_set_service.get_card_set_by_id(id).await {
         Ok(Some(existing)) => {
@@ -97,7 +177,7 @@ async fn update_card_set(
             let card_set = payload.to_model(id, existing.created_at);
             match state.card_set_service.update_card_set(card_set).await {
                 Ok(updated) => json_response(updated),
-                Err(e) => error_response(e.to_string(), 500),
+                Err(e) => service_error_response(e),
             }
         },
         Ok(None) => error_response(format!("Conjunto de cartas con ID {} no encontrado", id), 404),
@@ -109,22 +189,18 @@ async fn update_card_set(
 async fn patch_card_set(
     State(state): State<Arc<AppState>>,
     Path(id): Path<Uuid>,
+    user: AuthUser,
     ValidatedJson(payload): ValidatedJson<PatchCardSetDto>,
 ) -> ApiResponse<CardSet> {
+    if !user.is_staff() {
+        return ApiResponse::forbidden("Sólo el staff puede modificar conjuntos".to_string());
+    }
+
     // Validamos los datos de entrada
     if let Err(e) = payload.validate() {
         return validation_error(format!("Error de validación: {}", e), None);
     }
     
-    // Si estamos actualizando el código, verificamos que sea único
-    if let Some(code) = &payload.code {
-        match check_unique_code(&state, code, Some(id)).await {
-            Ok(true) => {}, // Código único, continúa
-            Ok(false) => return validation_error(format!("El código '{}' ya está en uso por otro conjunto", code), None),
-            Err(e) => return error_response(e.to_string(), 500),
-        }
-    }
-    
     // Primero, verificamos si el conjunto de cartas existe
     match state.card_set_service.get_card_set_by_id(id).await {
         Ok(Some(existing)) => {
@@ -134,7 +210,7 @@ async fn patch_card_set(
             // Guardamos los cambios
             match state.card_set_service.update_card_set(updated_card_set).await {
                 Ok(updated) => json_response(updated),
-                Err(e) => error_response(e.to_string(), 500),
+                Err(e) => service_error_response(e),
             }
         },
         Ok(None) => error_response(format!("Conjunto de cartas con ID {} no encontrado", id), 404),
@@ -145,7 +221,12 @@ async fn patch_card_set(
 async fn delete_card_set(
     State(state): State<Arc<AppState>>,
     Path(id): Path<Uuid>,
+    user: AuthUser,
 ) -> ApiResponse<String> {
+    if !user.is_staff() {
+        return ApiResponse::forbidden("Sólo el staff puede eliminar conjuntos".to_string());
+    }
+
     match state.card_set_service.delete_card_set(id).await {
         Ok(true) => json_response(format!("Conjunto de cartas con ID {} eliminado correctamente", id)),
         Ok(false) => error_response(format!("Conjunto de cartas con ID {} no encontrado", id), 404),
@@ -153,25 +234,50 @@ async fn delete_card_set(
     }
 }
 
-// Función auxiliar para verificar la unicidad del código
-async fn check_unique_code(state: &Arc<AppState>, code: &str, exclude_id: Option<Uuid>) -> Result<bool, String> {
-    let card_sets = match state.card_set_service.get_all_card_sets().await {
-        Ok(sets) => sets,
-        Err(e) => return Err(e.to_string()),
+// Importación masiva (CSV o NDJSON) con upsert por código en una sola transacción
+async fn import_card_sets(
+    State(state): State<Arc<AppState>>,
+    Query(query): Query<ImportQuery>,
+    user: AuthUser,
+    headers: HeaderMap,
+    body: Bytes,
+) -> Response {
+    // También con dry_run: el informe revela qué códigos existen, incluidos los no publicados
+    if !user.is_staff() {
+        return ApiResponse::<()>::forbidden("Sólo el staff puede importar conjuntos".to_string()).into_response();
+    }
+
+    let format = match resolve_import_format(query.format.as_deref(), &headers) {
+        Ok(format) => format,
+        Err(message) => return validation_error::<()>(message, None).into_response(),
+    };
+
+    let rows = match parse_rows::<CreateCardSetDto>(format, &body) {
+        Ok(rows) => rows,
+        Err(e) => return validation_error::<()>(e.to_string(), None).into_response(),
+    };
+
+    match state.card_set_service.import_card_sets(rows, query.dry_run).await {
+        Ok(report) => import_report_response(report),
+        Err(e) => service_error_response::<()>(e).into_response(),
+    }
+}
+
+async fn export_card_sets(
+    State(state): State<Arc<AppState>>,
+    Query(query): Query<ExportQuery>,
+    user: Option<AuthUser>,
+    headers: HeaderMap,
+) -> Response {
+    let format = match resolve_export_format(query.format.as_deref(), &headers) {
+        Ok(format) => format,
+        Err(message) => return validation_error::<()>(message, None).into_response(),
     };
-    
-    for set in card_sets {
-        if set.code == code {
-            // Si estamos excluyendo un ID (actualización) y ese ID es el mismo que el conjunto actual,
-            // entonces está bien que el código sea el mismo
-            if let Some(id) = exclude_id {
-                if set.id == id {
-                    continue;
-                }
-            }
-            return Ok(false); // Código ya existe
-        }
+
+    let visibility = CatalogVisibility::for_user(user.as_ref());
+
+    match state.card_set_service.export_card_sets(visibility).await {
+        Ok(card_sets) => export_response(format, export_rows(format, &card_sets), "card_sets"),
+        Err(e) => error_response::<()>(e.to_string(), 500).into_response(),
     }
-    
-    Ok(true) // Código es único
-} 
\ No newline at end of file
+}
@@ -0,0 +1,265 @@
+use axum::{
+    body::Bytes,
+    extract::{Path, Query, State},
+    http::HeaderMap,
+    response::{IntoResponse, Response},
+    routing::{get, post, put},
+    Router,
+};
+use std::collections::HashSet;
+use std::sync::Arc;
+use uuid::Uuid;
+
+use crate::api::bulk::{ExportQuery, ImportQuery, export_response, import_report_response, resolve_export_format, resolve_import_format};
+use crate::api::card_sets::cache_control;
+use crate::api::state::AppState;
+use crate::domain::cards::{
+    Card, CardPrice, CardPriceBatchDto, CardPriceDto, CatalogVisibility, CreateCardDto, RevealCardDto, Validable, export_cards,
+    parse_rows,
+};
+use crate::utils::cache::conditional_response;
+use crate::utils::extractors::{AuthUser, ValidatedJson};
+use crate::utils::response::{ApiResponse, error_response, json_response, service_error_response, validation_error};
+
+pub fn cards_routes(app_state: Arc<AppState>) -> Router {
+    Router::new()
+        .route("/cards/import", post(import_cards))
+        .route("/cards/export", get(export_all_cards))
+        .route("/cards/sets/:id/cards", get(get_cards_by_set))
+        .route("/cards/:id", get(get_card_by_id))
+        .route("/cards/:id/reveal", put(reveal_card).delete(hide_card))
+        .route("/cards/prices", post(save_card_prices))
+        .route("/cards/:id/price", get(get_card_price).put(save_card_price).delete(delete_card_price))
+        .with_state(app_state)
+}
+
+async fn get_card_by_id(
+    State(state): State<Arc<AppState>>,
+    Path(id): Path<Uuid>,
+    user: Option<AuthUser>,
+    headers: HeaderMap,
+) -> Response {
+    let visibility = CatalogVisibility::for_user(user.as_ref());
+
+    match state.card_service.get_card_by_id(id, visibility).await {
+        Ok(Some(card)) => {
+            let last_modified = Some(card.updated_at);
+            conditional_response(&headers, card, last_modified, cache_control(&state.cache.card_detail, visibility))
+        },
+        Ok(None) => error_response::<()>(format!("Carta con ID {} no encontrada", id), 404).into_response(),
+        Err(e) => error_response::<()>(e.to_string(), 500).into_response(),
+    }
+}
+
+async fn get_cards_by_set(
+    State(state): State<Arc<AppState>>,
+    Path(set_id): Path<Uuid>,
+    user: Option<AuthUser>,
+    headers: HeaderMap,
+) -> Response {
+    let visibility = CatalogVisibility::for_user(user.as_ref());
+
+    // Verificamos que el conjunto exista para distinguir un conjunto vacío de uno inexistente
+    match state.card_set_service.get_visible_card_set_by_id(set_id, visibility).await {
+        Ok(Some(_)) => {},
+        Ok(None) => return error_response::<()>(format!("Conjunto de cartas con ID {} no encontrado", set_id), 404).into_response(),
+        Err(e) => return error_response::<()>(e.to_string(), 500).into_response(),
+    }
+
+    // Antes del lanzamiento sólo se listan las cartas reveladas (salvo para el staff)
+    match state.card_service.get_cards_by_set(set_id, visibility).await {
+        // Las listas sólo se validan por ETag: una carta borrada no adelanta ninguna fecha
+        Ok(cards) => conditional_response(&headers, cards, None, cache_control(&state.cache.set_cards_list, visibility)),
+        Err(e) => error_response::<()>(e.to_string(), 500).into_response(),
+    }
+}
+
+// Importación masiva de cartas con upsert por conjunto y número de colección
+async fn import_cards(
+    State(state): State<Arc<AppState>>,
+    Query(query): Query<ImportQuery>,
+    user: AuthUser,
+    headers: HeaderMap,
+    body: Bytes,
+) -> Response {
+    if !user.is_staff() {
+        return ApiResponse::<()>::forbidden("Sólo el staff puede importar cartas".to_string()).into_response();
+    }
+
+    let format = match resolve_import_format(query.format.as_deref(), &headers) {
+        Ok(format) => format,
+        Err(message) => return validation_error::<()>(message, None).into_response(),
+    };
+
+    let rows = match parse_rows::<CreateCardDto>(format, &body) {
+        Ok(rows) => rows,
+        Err(e) => return validation_error::<()>(e.to_string(), None).into_response(),
+    };
+
+    match state.card_service.import_cards(rows, query.dry_run).await {
+        Ok(report) => import_report_response(report),
+        Err(e) => service_error_response::<()>(e).into_response(),
+    }
+}
+
+async fn export_all_cards(
+    State(state): State<Arc<AppState>>,
+    Query(query): Query<ExportQuery>,
+    user: Option<AuthUser>,
+    headers: HeaderMap,
+) -> Response {
+    let format = match resolve_export_format(query.format.as_deref(), &headers) {
+        Ok(format) => format,
+        Err(message) => return validation_error::<()>(message, None).into_response(),
+    };
+
+    let visibility = CatalogVisibility::for_user(user.as_ref());
+
+    match state.card_service.export_cards(visibility).await {
+        Ok(cards) => export_response(format, export_cards(format, &cards), "cards"),
+        Err(e) => error_response::<()>(e.to_string(), 500).into_response(),
+    }
+}
+
+// Revela una carta como spoiler antes del lanzamiento de su conjunto (sólo staff)
+async fn reveal_card(
+    State(state): State<Arc<AppState>>,
+    Path(id): Path<Uuid>,
+    user: AuthUser,
+    ValidatedJson(payload): ValidatedJson<RevealCardDto>,
+) -> ApiResponse<Card> {
+    if !user.is_staff() {
+        return ApiResponse::forbidden("Sólo el staff puede revelar cartas".to_string());
+    }
+
+    let revealed_at = payload.revealed_at.unwrap_or_else(chrono::Utc::now);
+
+    match state.card_service.set_card_revealed_at(id, Some(revealed_at)).await {
+        Ok(Some(card)) => json_response(card),
+        Ok(None) => error_response(format!("Carta con ID {} no encontrada", id), 404),
+        Err(e) => error_response(e.to_string(), 500),
+    }
+}
+
+async fn hide_card(
+    State(state): State<Arc<AppState>>,
+    Path(id): Path<Uuid>,
+    user: AuthUser,
+) -> ApiResponse<Card> {
+    if !user.is_staff() {
+        return ApiResponse::forbidden("Sólo el staff puede ocultar cartas".to_string());
+    }
+
+    match state.card_service.set_card_revealed_at(id, None).await {
+        Ok(Some(card)) => json_response(card),
+        Ok(None) => error_response(format!("Carta con ID {} no encontrada", id), 404),
+        Err(e) => error_response(e.to_string(), 500),
+    }
+}
+
+fn price_not_found<T>(id: Uuid) -> ApiResponse<T> {
+    error_response(format!("La carta con ID {} no tiene precio", id), 404)
+}
+
+async fn get_card_price(
+    State(state): State<Arc<AppState>>,
+    Path(id): Path<Uuid>,
+    user: Option<AuthUser>,
+) -> ApiResponse<CardPrice> {
+    let visibility = CatalogVisibility::for_user(user.as_ref());
+    match state.card_service.get_card_by_id(id, visibility).await {
+        Ok(Some(_)) => {},
+        Ok(None) => return error_response(format!("Carta con ID {} no encontrada", id), 404),
+        Err(e) => return error_response(e.to_string(), 500),
+    }
+
+    match state.card_service.get_prices(&[id]).await {
+        Ok(prices) => match prices.into_iter().next() {
+            Some(price) => json_response(price),
+            None => price_not_found(id),
+        },
+        Err(e) => error_response(e.to_string(), 500),
+    }
+}
+
+// Fija el precio de referencia de una carta (sólo staff)
+async fn save_card_price(
+    State(state): State<Arc<AppState>>,
+    Path(id): Path<Uuid>,
+    user: AuthUser,
+    ValidatedJson(payload): ValidatedJson<CardPriceDto>,
+) -> ApiResponse<CardPrice> {
+    if !user.is_staff() {
+        return ApiResponse::forbidden("Sólo el staff puede fijar precios".to_string());
+    }
+
+    if let Err(e) = payload.validate() {
+        return validation_error(format!("Error de validación: {}", e), None);
+    }
+
+    match state.card_service.get_card_by_id(id, CatalogVisibility::Staff).await {
+        Ok(Some(_)) => {},
+        Ok(None) => return error_response(format!("Carta con ID {} no encontrada", id), 404),
+        Err(e) => return error_response(e.to_string(), 500),
+    }
+
+    match state.card_service.save_prices(vec![(id, payload)]).await {
+        Ok(prices) => match prices.into_iter().next() {
+            Some(price) => json_response(price),
+            None => error_response("No se pudo guardar el precio".to_string(), 500),
+        },
+        Err(e) => error_response(e.to_string(), 500),
+    }
+}
+
+// Carga por lote, p. ej. desde la exportación de un marketplace; todo o nada
+async fn save_card_prices(
+    State(state): State<Arc<AppState>>,
+    user: AuthUser,
+    ValidatedJson(payload): ValidatedJson<CardPriceBatchDto>,
+) -> ApiResponse<Vec<CardPrice>> {
+    if !user.is_staff() {
+        return ApiResponse::forbidden("Sólo el staff puede fijar precios".to_string());
+    }
+
+    if let Err(e) = payload.validate() {
+        return validation_error(format!("Error de validación: {}", e), None);
+    }
+
+    let ids: Vec<Uuid> = payload.prices.iter().map(|entry| entry.card_id).collect();
+    let found: HashSet<Uuid> = match state.card_service.get_cards_by_ids(&ids, CatalogVisibility::Staff).await {
+        Ok(cards) => cards.into_iter().map(|card| card.id).collect(),
+        Err(e) => return error_response(e.to_string(), 500),
+    };
+    if let Some(missing) = ids.iter().find(|id| !found.contains(id)) {
+        return error_response(format!("Carta con ID {} no encontrada", missing), 404);
+    }
+
+    // Si una carta se repite, vale su último precio
+    let mut prices: Vec<(Uuid, CardPriceDto)> = Vec::with_capacity(payload.prices.len());
+    for entry in payload.prices {
+        prices.retain(|(card_id, _)| *card_id != entry.card_id);
+        prices.push((entry.card_id, entry.price));
+    }
+
+    match state.card_service.save_prices(prices).await {
+        Ok(prices) => json_response(prices),
+        Err(e) => error_response(e.to_string(), 500),
+    }
+}
+
+async fn delete_card_price(
+    State(state): State<Arc<AppState>>,
+    Path(id): Path<Uuid>,
+    user: AuthUser,
+) -> ApiResponse<()> {
+    if !user.is_staff() {
+        return ApiResponse::forbidden("Sólo el staff puede borrar precios".to_string());
+    }
+
+    match state.card_service.delete_price(id).await {
+        Ok(true) => json_response(()),
+        Ok(false) => price_not_found(id),
+        Err(e) => error_response(e.to_string(), 500),
+    }
+}
@@ -0,0 +1,298 @@
+use axum::{
+    body::Bytes,
+    extract::{Path, Query, State},
+    http::StatusCode,
+    routing::{get, post},
+    Router,
+};
+use serde::Deserialize;
+use std::sync::Arc;
+use uuid::Uuid;
+
+use crate::api::state::AppState;
+use crate::domain::cards::{CatalogVisibility, Validable};
+use crate::domain::collections::{
+    CollectionChangeDto, CollectionEntry, CollectionImportReport, CompletionSort, ImportMode, QuantityChange, SetCompletion,
+    SetCompletionDetail, SetQuantityDto, parse_collection_csv,
+};
+use crate::utils::extractors::{AuthUser, ValidatedJson};
+use crate::utils::response::{ApiResponse, error_response, json_response, validation_error};
+
+#[derive(Debug, Deserialize)]
+pub struct CompletionQuery {
+    pub sort: Option<String>,
+    pub order: Option<String>,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct CollectionImportQuery {
+    pub mode: Option<String>,
+    /// Explicit column mapping, e.g. `set_code:Edición,name:Carta`
+    pub columns: Option<String>,
+    #[serde(default)]
+    pub dry_run: bool,
+}
+
+pub fn collections_routes(app_state: Arc<AppState>) -> Router {
+    Router::new()
+        .route("/users/:user_id/collection", get(get_collection))
+        .route("/users/:user_id/collection/add", post(add_to_collection))
+        .route("/users/:user_id/collection/remove", post(remove_from_collection))
+        .route("/users/:user_id/collection/import", post(import_collection))
+        .route("/users/:user_id/collection/sets", get(get_set_completions))
+        .route("/users/:user_id/collection/sets/:set_id", get(get_set_completion))
+        .route(
+            "/users/:user_id/collection/:entry_id",
+            get(get_collection_entry).put(set_collection_quantity).delete(delete_collection_entry),
+        )
+        .with_state(app_state)
+}
+
+/// Resolves whose collection a request targets: `me` or the caller's own UID,
+/// or any user for admins (same semantics as the `user_collections` Firestore rule).
+pub(crate) fn resolve_owner(user: &AuthUser, user_id: &str) -> Result<String, String> {
+    if user_id == "me" || user_id == user.uid {
+        Ok(user.uid.clone())
+    } else if user.is_admin() {
+        Ok(user_id.to_string())
+    } else {
+        Err("No tienes permiso para acceder a la colección de otro usuario".to_string())
+    }
+}
+
+fn entry_not_found<T>(id: Uuid) -> ApiResponse<T> {
+    error_response(format!("Entrada de colección con ID {} no encontrada", id), 404)
+}
+
+async fn get_collection(
+    State(state): State<Arc<AppState>>,
+    Path(user_id): Path<String>,
+    user: AuthUser,
+) -> ApiResponse<Vec<CollectionEntry>> {
+    let owner_uid = match resolve_owner(&user, &user_id) {
+        Ok(owner_uid) => owner_uid,
+        Err(message) => return ApiResponse::forbidden(message),
+    };
+
+    match state.collection_service.get_entries(&owner_uid).await {
+        Ok(entries) => json_response(entries),
+        Err(e) => error_response(e.to_string(), 500),
+    }
+}
+
+async fn get_collection_entry(
+    State(state): State<Arc<AppState>>,
+    Path((user_id, entry_id)): Path<(String, Uuid)>,
+    user: AuthUser,
+) -> ApiResponse<CollectionEntry> {
+    let owner_uid = match resolve_owner(&user, &user_id) {
+        Ok(owner_uid) => owner_uid,
+        Err(message) => return ApiResponse::forbidden(message),
+    };
+
+    match state.collection_service.get_entry(&owner_uid, entry_id).await {
+        Ok(Some(entry)) => json_response(entry),
+        Ok(None) => entry_not_found(entry_id),
+        Err(e) => error_response(e.to_string(), 500),
+    }
+}
+
+// Añade copias de una impresión; si ya existía, se suman a las que había
+async fn add_to_collection(
+    State(state): State<Arc<AppState>>,
+    Path(user_id): Path<String>,
+    user: AuthUser,
+    ValidatedJson(payload): ValidatedJson<CollectionChangeDto>,
+) -> ApiResponse<CollectionEntry> {
+    let owner_uid = match resolve_owner(&user, &user_id) {
+        Ok(owner_uid) => owner_uid,
+        Err(message) => return ApiResponse::forbidden(message),
+    };
+
+    if let Err(e) = payload.validate() {
+        return validation_error(format!("Error de validación: {}", e), None);
+    }
+
+    // Sólo se pueden coleccionar cartas que el usuario puede ver
+    let visibility = CatalogVisibility::for_user(Some(&user));
+    match state.card_service.get_card_by_id(payload.card_id, visibility).await {
+        Ok(Some(_)) => {},
+        Ok(None) => return error_response(format!("Carta con ID {} no encontrada", payload.card_id), 404),
+        Err(e) => return error_response(e.to_string(), 500),
+    }
+
+    match state.collection_service.add_copies(&owner_uid, &payload.printing(), payload.quantity).await {
+        Ok(entry) => json_response(entry),
+        Err(e) => error_response(e.to_string(), 500),
+    }
+}
+
+// Retira copias de una impresión; la entrada desaparece al llegar a cero
+async fn remove_from_collection(
+    State(state): State<Arc<AppState>>,
+    Path(user_id): Path<String>,
+    user: AuthUser,
+    ValidatedJson(payload): ValidatedJson<CollectionChangeDto>,
+) -> ApiResponse<Option<CollectionEntry>> {
+    let owner_uid = match resolve_owner(&user, &user_id) {
+        Ok(owner_uid) => owner_uid,
+        Err(message) => return ApiResponse::forbidden(message),
+    };
+
+    if let Err(e) = payload.validate() {
+        return validation_error(format!("Error de validación: {}", e), None);
+    }
+
+    match state.collection_service.remove_copies(&owner_uid, &payload.printing(), payload.quantity).await {
+        Ok(QuantityChange::Updated(entry)) => json_response(Some(entry)),
+        Ok(QuantityChange::Deleted) => json_response(None),
+        Ok(QuantityChange::NotOwned) => error_response("La colección no contiene esa impresión".to_string(), 404),
+        Ok(QuantityChange::Insufficient { owned }) => validation_error(
+            format!("No se pueden retirar {} copias: sólo hay {}", payload.quantity, owned),
+            None,
+        ),
+        Err(e) => error_response(e.to_string(), 500),
+    }
+}
+
+// Ajusta la cantidad exacta de una entrada; cero la elimina
+async fn set_collection_quantity(
+    State(state): State<Arc<AppState>>,
+    Path((user_id, entry_id)): Path<(String, Uuid)>,
+    user: AuthUser,
+    ValidatedJson(payload): ValidatedJson<SetQuantityDto>,
+) -> ApiResponse<Option<CollectionEntry>> {
+    let owner_uid = match resolve_owner(&user, &user_id) {
+        Ok(owner_uid) => owner_uid,
+        Err(message) => return ApiResponse::forbidden(message),
+    };
+
+    if let Err(e) = payload.validate() {
+        return validation_error(format!("Error de validación: {}", e), None);
+    }
+
+    match state.collection_service.set_quantity(&owner_uid, entry_id, payload.quantity).await {
+        Ok(QuantityChange::Updated(entry)) => json_response(Some(entry)),
+        Ok(QuantityChange::Deleted) => json_response(None),
+        Ok(QuantityChange::NotOwned | QuantityChange::Insufficient { .. }) => entry_not_found(entry_id),
+        Err(e) => error_response(e.to_string(), 500),
+    }
+}
+
+async fn delete_collection_entry(
+    State(state): State<Arc<AppState>>,
+    Path((user_id, entry_id)): Path<(String, Uuid)>,
+    user: AuthUser,
+) -> ApiResponse<()> {
+    let owner_uid = match resolve_owner(&user, &user_id) {
+        Ok(owner_uid) => owner_uid,
+        Err(message) => return ApiResponse::forbidden(message),
+    };
+
+    match state.collection_service.delete_entry(&owner_uid, entry_id).await {
+        Ok(true) => json_response(()),
+        Ok(false) => entry_not_found(entry_id),
+        Err(e) => error_response(e.to_string(), 500),
+    }
+}
+
+// Progreso de la colección en cada conjunto; por defecto, los más completos primero
+async fn get_set_completions(
+    State(state): State<Arc<AppState>>,
+    Path(user_id): Path<String>,
+    Query(query): Query<CompletionQuery>,
+    user: AuthUser,
+) -> ApiResponse<Vec<SetCompletion>> {
+    let owner_uid = match resolve_owner(&user, &user_id) {
+        Ok(owner_uid) => owner_uid,
+        Err(message) => return ApiResponse::forbidden(message),
+    };
+
+    let sort = match query.sort.as_deref().map(CompletionSort::parse) {
+        None => CompletionSort::Completion,
+        Some(Some(sort)) => sort,
+        Some(None) => {
+            return validation_error(
+                "Orden no soportado: use completion, missing, name o release_date".to_string(),
+                None,
+            )
+        }
+    };
+
+    // Porcentaje y fecha de mayor a menor; faltantes y nombre de menor a mayor
+    let descending = match query.order.as_deref().map(|order| order.trim().to_lowercase()) {
+        None => matches!(sort, CompletionSort::Completion | CompletionSort::ReleaseDate),
+        Some(order) if order == "desc" => true,
+        Some(order) if order == "asc" => false,
+        Some(_) => return validation_error("Dirección no soportada: use asc o desc".to_string(), None),
+    };
+
+    let visibility = CatalogVisibility::for_user(Some(&user));
+    match state.collection_service.get_set_completions(&owner_uid, visibility, sort, descending).await {
+        Ok(completions) => json_response(completions),
+        Err(e) => error_response(e.to_string(), 500),
+    }
+}
+
+async fn get_set_completion(
+    State(state): State<Arc<AppState>>,
+    Path((user_id, set_id)): Path<(String, Uuid)>,
+    user: AuthUser,
+) -> ApiResponse<SetCompletionDetail> {
+    let owner_uid = match resolve_owner(&user, &user_id) {
+        Ok(owner_uid) => owner_uid,
+        Err(message) => return ApiResponse::forbidden(message),
+    };
+
+    let visibility = CatalogVisibility::for_user(Some(&user));
+    match state.collection_service.get_set_completion(&owner_uid, set_id, visibility).await {
+        Ok(Some(completion)) => json_response(completion),
+        Ok(None) => error_response(format!("Conjunto de cartas con ID {} no encontrado", set_id), 404),
+        Err(e) => error_response(e.to_string(), 500),
+    }
+}
+
+// Importación desde CSV (hojas de cálculo y exportaciones de otras aplicaciones)
+async fn import_collection(
+    State(state): State<Arc<AppState>>,
+    Path(user_id): Path<String>,
+    Query(query): Query<CollectionImportQuery>,
+    user: AuthUser,
+    body: Bytes,
+) -> ApiResponse<CollectionImportReport> {
+    let owner_uid = match resolve_owner(&user, &user_id) {
+        Ok(owner_uid) => owner_uid,
+        Err(message) => return ApiResponse::forbidden(message),
+    };
+
+    let mode = match query.mode.as_deref().map(ImportMode::parse) {
+        None => ImportMode::Merge,
+        Some(Some(mode)) => mode,
+        Some(None) => return validation_error("Modo no soportado: use merge o replace".to_string(), None),
+    };
+
+    let csv = match parse_collection_csv(&body, query.columns.as_deref()) {
+        Ok(csv) => csv,
+        Err(e) => return validation_error(e.to_string(), None),
+    };
+
+    // Sólo se resuelven cartas que el usuario puede ver
+    let visibility = CatalogVisibility::for_user(Some(&user));
+    let catalog = match state.card_service.get_catalog(visibility).await {
+        Ok(catalog) => catalog,
+        Err(e) => return error_response(e.to_string(), 500),
+    };
+
+    let report = CollectionImportReport::new(query.dry_run, mode, csv.columns, csv.rows.len());
+    match state.collection_service.import_collection(&owner_uid, report, csv.rows, &catalog).await {
+        Ok(report) if mode == ImportMode::Replace && !report.unmatched.is_empty() => {
+            let message = format!(
+                "{} fila(s) no se pudieron resolver; al reemplazar no se guardó ningún cambio",
+                report.unmatched.len()
+            );
+            ApiResponse::error_with_data(report, message, StatusCode::UNPROCESSABLE_ENTITY)
+        }
+        Ok(report) => json_response(report),
+        Err(e) => error_response(e.to_string(), 500),
+    }
+}
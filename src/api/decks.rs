@@ -0,0 +1,1096 @@
+use axum::{
+    body::Bytes,
+    extract::{Path, Query, State},
+    http::{header, StatusCode},
+    response::{IntoResponse, Response},
+    routing::{get, post, put},
+    Router,
+};
+use serde::Deserialize;
+use std::collections::HashSet;
+use std::sync::Arc;
+use uuid::Uuid;
+
+use crate::api::access::{Access, OwnedResource, authorize};
+use crate::api::state::AppState;
+use crate::api::collections::resolve_owner;
+use crate::domain::cards::{CatalogVisibility, Validable};
+use crate::domain::decks::{
+    BuildabilityReport, CardSuggestions, PlayedWith, DecodedDeck, Deck, DeckAnalysis, DeckAnalysisDto, DeckCard, DeckDetail, DeckDiff, DeckDto,
+    DeckFormat, DeckFormatDto, DeckReactions, DeckRevision, DeckRevisionDetail, DeckSort, DecklistFormat,
+    DecklistImport, EncodedDeck, ForkDeckDto, GalleryDeck, GalleryFilter, GallerySort, LegalityReport, SampleHand,
+    SampleHandDto, is_valid_format_code, printing_ids, validate_deck_cards,
+};
+use crate::utils::extractors::{AuthUser, ValidatedJson};
+use crate::utils::response::{ApiResponse, error_response, json_response, validation_error};
+
+const DEFAULT_DECK_LIMIT: i64 = 50;
+const MAX_DECK_LIMIT: i64 = 200;
+const DEFAULT_GALLERY_LIMIT: i64 = 24;
+const MAX_GALLERY_LIMIT: i64 = 100;
+const DEFAULT_SYNERGY_LIMIT: i64 = 20;
+const MAX_SYNERGY_LIMIT: i64 = 100;
+
+#[derive(Debug, Deserialize)]
+pub struct DeckListQuery {
+    pub sort: Option<String>,
+    pub order: Option<String>,
+    pub limit: Option<i64>,
+    pub offset: Option<i64>,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct LegalityQuery {
+    /// Format to check against; defaults to the deck's own format
+    pub format: Option<String>,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct DecklistExportQuery {
+    pub format: Option<String>,
+}
+
+/// Card list checked without saving a deck
+#[derive(Debug, Deserialize)]
+pub struct LegalityCheckDto {
+    pub cards: Vec<DeckCard>,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct GalleryQuery {
+    pub format: Option<String>,
+    pub energy: Option<String>,
+    /// Only decks including this card, in any printing
+    pub card: Option<Uuid>,
+    pub sort: Option<String>,
+    pub limit: Option<i64>,
+    pub offset: Option<i64>,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct SynergyQuery {
+    pub limit: Option<i64>,
+}
+
+/// Card list compared with the caller's collection without saving a deck
+#[derive(Debug, Deserialize)]
+pub struct BuildabilityCheckDto {
+    pub cards: Vec<DeckCard>,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct RevisionDiffQuery {
+    /// Defaults to the revision before `to`
+    pub from: Option<i32>,
+    /// Defaults to the latest revision
+    pub to: Option<i32>,
+}
+
+/// Card list encoded as a deck code without saving a deck
+#[derive(Debug, Deserialize)]
+pub struct DeckCodeDto {
+    pub cards: Vec<DeckCard>,
+    pub format_code: Option<String>,
+}
+
+pub fn decks_routes(app_state: Arc<AppState>) -> Router {
+    Router::new()
+        .route("/users/:user_id/decks", get(get_user_decks).post(create_deck))
+        .route("/decks/:id", get(get_deck).put(update_deck).delete(delete_deck))
+        .route("/decks/:id/legality", get(get_deck_legality))
+        .route("/decks/:id/decklist", get(export_deck_decklist).put(import_deck_decklist))
+        .route("/decks/:id/code", get(get_deck_code))
+        .route("/decks/:id/analysis", get(get_deck_analysis).post(analyze_deck))
+        .route("/decks/:id/sample-hand", get(get_sample_hand).post(deal_sample_hand))
+        .route("/decks/:id/suggestions", get(get_deck_suggestions))
+        .route("/cards/:id/played-with", get(get_played_with))
+        .route("/decks/:id/buildability", get(get_deck_buildability))
+        .route("/decks/buildability", post(check_buildability))
+        .route("/decks/:id/fork", post(fork_deck))
+        .route("/decks/:id/reactions", get(get_deck_reactions))
+        .route("/decks/:id/like", put(like_deck).delete(unlike_deck))
+        .route("/decks/:id/bookmark", put(bookmark_deck).delete(unbookmark_deck))
+        .route("/gallery/decks", get(get_gallery))
+        .route("/users/:user_id/bookmarks", get(get_bookmarks))
+        .route("/decks/:id/revisions", get(get_deck_revisions))
+        .route("/decks/:id/revisions/diff", get(diff_deck_revisions))
+        .route("/decks/:id/revisions/:revision", get(get_deck_revision))
+        .route("/decks/:id/revisions/:revision/restore", post(restore_deck_revision))
+        .route("/decklists/parse", post(parse_decklist))
+        .route("/deck-codes", post(encode_deck_code))
+        .route("/deck-codes/:code", get(decode_deck_code))
+        .route("/formats", get(get_formats))
+        .route("/formats/:code", get(get_format).put(save_format).delete(delete_format))
+        .route("/formats/:code/validate", post(validate_cards))
+        .with_state(app_state)
+}
+
+async fn find_deck<T>(state: &AppState, id: Uuid, user: &AuthUser, access: Access) -> Result<Deck, ApiResponse<T>> {
+    authorize(state.deck_service.get_deck_by_id(id).await, id, user, access)
+}
+
+fn deck_not_found<T>(id: Uuid) -> ApiResponse<T> {
+    error_response(Deck::not_found_message(id), 404)
+}
+
+// Las sesiones anónimas pueden leer mazos pero no crearlos ni modificarlos
+fn anonymous_forbidden<T>() -> ApiResponse<T> {
+    ApiResponse::forbidden("Inicia sesión con una cuenta para crear o modificar mazos".to_string())
+}
+
+// Todas las cartas del mazo deben existir y ser visibles para el usuario
+async fn check_deck_cards<T>(state: &AppState, user: &AuthUser, ids: Vec<Uuid>) -> Result<(), ApiResponse<T>> {
+    let visibility = CatalogVisibility::for_user(Some(user));
+    let found: HashSet<Uuid> = match state.card_service.get_cards_by_ids(&ids, visibility).await {
+        Ok(cards) => cards.into_iter().map(|card| card.id).collect(),
+        Err(e) => return Err(error_response(e.to_string(), 500)),
+    };
+
+    match ids.into_iter().find(|id| !found.contains(id)) {
+        Some(missing) => Err(error_response(format!("Carta con ID {} no encontrada", missing), 404)),
+        None => Ok(()),
+    }
+}
+
+fn format_not_found<T>(code: &str) -> ApiResponse<T> {
+    error_response(format!("Formato '{}' no encontrado", code), 404)
+}
+
+// El formato indicado al guardar un mazo debe existir
+async fn check_deck_format<T>(state: &AppState, payload: &DeckDto) -> Result<(), ApiResponse<T>> {
+    let Some(code) = payload.format_code() else {
+        return Ok(());
+    };
+
+    match state.deck_service.get_format(&code).await {
+        Ok(Some(_)) => Ok(()),
+        Ok(None) => Err(validation_error(format!("Formato '{}' no encontrado", code), None)),
+        Err(e) => Err(error_response(e.to_string(), 500)),
+    }
+}
+
+// Los mazos propios (o de cualquiera, para administradores) incluyen los privados
+async fn get_user_decks(
+    State(state): State<Arc<AppState>>,
+    Path(user_id): Path<String>,
+    Query(query): Query<DeckListQuery>,
+    user: AuthUser,
+) -> ApiResponse<Vec<Deck>> {
+    let (owner_uid, include_private) = match resolve_owner(&user, &user_id) {
+        Ok(owner_uid) => (owner_uid, true),
+        Err(_) => (user_id, false),
+    };
+
+    let sort = match query.sort.as_deref().map(DeckSort::parse) {
+        None => DeckSort::Updated,
+        Some(Some(sort)) => sort,
+        Some(None) => return validation_error("Orden no soportado: use created o updated".to_string(), None),
+    };
+
+    // Por defecto, los más recientes primero
+    let descending = match query.order.as_deref().map(|order| order.trim().to_lowercase()) {
+        None => true,
+        Some(order) if order == "desc" => true,
+        Some(order) if order == "asc" => false,
+        Some(_) => return validation_error("Dirección no soportada: use asc o desc".to_string(), None),
+    };
+
+    let limit = query.limit.unwrap_or(DEFAULT_DECK_LIMIT).clamp(1, MAX_DECK_LIMIT);
+    let offset = query.offset.unwrap_or(0).max(0);
+
+    match state.deck_service.get_decks_by_owner(&owner_uid, include_private, sort, descending, limit, offset).await {
+        Ok(decks) => json_response(decks),
+        Err(e) => error_response(e.to_string(), 500),
+    }
+}
+
+async fn create_deck(
+    State(state): State<Arc<AppState>>,
+    Path(user_id): Path<String>,
+    user: AuthUser,
+    ValidatedJson(payload): ValidatedJson<DeckDto>,
+) -> ApiResponse<DeckDetail> {
+    if user.is_anonymous() {
+        return anonymous_forbidden();
+    }
+
+    let owner_uid = match resolve_owner(&user, &user_id) {
+        Ok(owner_uid) => owner_uid,
+        Err(message) => return ApiResponse::forbidden(message),
+    };
+
+    if let Err(e) = payload.validate() {
+        return validation_error(format!("Error de validación: {}", e), None);
+    }
+
+    if let Err(response) = check_deck_cards(&state, &user, payload.card_ids()).await {
+        return response;
+    }
+
+    if let Err(response) = check_deck_format(&state, &payload).await {
+        return response;
+    }
+
+    match state.deck_service.create_deck(owner_uid, &payload, &user.uid).await {
+        Ok(deck) => ApiResponse::created(deck),
+        Err(e) => error_response(e.to_string(), 500),
+    }
+}
+
+async fn get_deck(
+    State(state): State<Arc<AppState>>,
+    Path(id): Path<Uuid>,
+    user: AuthUser,
+) -> ApiResponse<DeckDetail> {
+    let deck = match find_deck(&state, id, &user, Access::Read).await {
+        Ok(deck) => deck,
+        Err(response) => return response,
+    };
+
+    match state.deck_service.get_deck_detail(deck).await {
+        Ok(detail) => json_response(detail),
+        Err(e) => error_response(e.to_string(), 500),
+    }
+}
+
+// Guarda el mazo completo: nombre, visibilidad y lista de cartas
+async fn update_deck(
+    State(state): State<Arc<AppState>>,
+    Path(id): Path<Uuid>,
+    user: AuthUser,
+    ValidatedJson(payload): ValidatedJson<DeckDto>,
+) -> ApiResponse<DeckDetail> {
+    if user.is_anonymous() {
+        return anonymous_forbidden();
+    }
+
+    let deck = match find_deck(&state, id, &user, Access::Manage).await {
+        Ok(deck) => deck,
+        Err(response) => return response,
+    };
+
+    if let Err(e) = payload.validate() {
+        return validation_error(format!("Error de validación: {}", e), None);
+    }
+
+    if let Err(response) = check_deck_cards(&state, &user, payload.card_ids()).await {
+        return response;
+    }
+
+    if let Err(response) = check_deck_format(&state, &payload).await {
+        return response;
+    }
+
+    match state.deck_service.update_deck(&deck, &payload, &user.uid).await {
+        Ok(Some(deck)) => json_response(deck),
+        Ok(None) => deck_not_found(id),
+        Err(e) => error_response(e.to_string(), 500),
+    }
+}
+
+async fn delete_deck(
+    State(state): State<Arc<AppState>>,
+    Path(id): Path<Uuid>,
+    user: AuthUser,
+) -> ApiResponse<()> {
+    if user.is_anonymous() {
+        return anonymous_forbidden();
+    }
+
+    if let Err(response) = find_deck(&state, id, &user, Access::Manage).await {
+        return response;
+    }
+
+    match state.deck_service.delete_deck(id).await {
+        Ok(true) => json_response(()),
+        Ok(false) => deck_not_found(id),
+        Err(e) => error_response(e.to_string(), 500),
+    }
+}
+
+// Comprueba un mazo guardado contra su formato o contra el indicado en `format`
+async fn get_deck_legality(
+    State(state): State<Arc<AppState>>,
+    Path(id): Path<Uuid>,
+    Query(query): Query<LegalityQuery>,
+    user: AuthUser,
+) -> ApiResponse<LegalityReport> {
+    let deck = match find_deck(&state, id, &user, Access::Read).await {
+        Ok(deck) => deck,
+        Err(response) => return response,
+    };
+
+    let Some(code) = query.format.or_else(|| deck.format_code.clone()) else {
+        return validation_error("El mazo no tiene formato: indica uno con el parámetro 'format'".to_string(), None);
+    };
+
+    let format = match state.deck_service.get_format(code.trim()).await {
+        Ok(Some(format)) => format,
+        Ok(None) => return format_not_found(&code),
+        Err(e) => return error_response(e.to_string(), 500),
+    };
+
+    let detail = match state.deck_service.get_deck_detail(deck).await {
+        Ok(detail) => detail,
+        Err(e) => return error_response(e.to_string(), 500),
+    };
+
+    let visibility = CatalogVisibility::for_user(Some(&user));
+    match state.card_service.get_catalog(visibility).await {
+        Ok(catalog) => json_response(state.deck_service.check_legality(&detail.cards, &format, &catalog)),
+        Err(e) => error_response(e.to_string(), 500),
+    }
+}
+
+async fn get_formats(State(state): State<Arc<AppState>>) -> ApiResponse<Vec<DeckFormat>> {
+    match state.deck_service.get_formats().await {
+        Ok(formats) => json_response(formats),
+        Err(e) => error_response(e.to_string(), 500),
+    }
+}
+
+async fn get_format(
+    State(state): State<Arc<AppState>>,
+    Path(code): Path<String>,
+) -> ApiResponse<DeckFormat> {
+    match state.deck_service.get_format(&code).await {
+        Ok(Some(format)) => json_response(format),
+        Ok(None) => format_not_found(&code),
+        Err(e) => error_response(e.to_string(), 500),
+    }
+}
+
+// Crea o reemplaza un formato; sólo administradores
+async fn save_format(
+    State(state): State<Arc<AppState>>,
+    Path(code): Path<String>,
+    user: AuthUser,
+    ValidatedJson(payload): ValidatedJson<DeckFormatDto>,
+) -> ApiResponse<DeckFormat> {
+    if !user.is_admin() {
+        return ApiResponse::forbidden("Sólo los administradores pueden gestionar formatos".to_string());
+    }
+
+    if !is_valid_format_code(&code) {
+        return validation_error(
+            "El código del formato sólo admite minúsculas, dígitos, '-' y '_' (máximo 32)".to_string(),
+            None,
+        );
+    }
+
+    if let Err(e) = payload.validate() {
+        return validation_error(format!("Error de validación: {}", e), None);
+    }
+
+    match state.deck_service.save_format(&code, &payload).await {
+        Ok(format) => json_response(format),
+        Err(e) => error_response(e.to_string(), 500),
+    }
+}
+
+// Los mazos de un formato borrado se quedan sin formato
+async fn delete_format(
+    State(state): State<Arc<AppState>>,
+    Path(code): Path<String>,
+    user: AuthUser,
+) -> ApiResponse<()> {
+    if !user.is_admin() {
+        return ApiResponse::forbidden("Sólo los administradores pueden gestionar formatos".to_string());
+    }
+
+    match state.deck_service.delete_format(&code).await {
+        Ok(true) => json_response(()),
+        Ok(false) => format_not_found(&code),
+        Err(e) => error_response(e.to_string(), 500),
+    }
+}
+
+// Comprueba una lista de cartas sin guardarla, p. ej. mientras se construye el mazo
+async fn validate_cards(
+    State(state): State<Arc<AppState>>,
+    Path(code): Path<String>,
+    user: Option<AuthUser>,
+    ValidatedJson(payload): ValidatedJson<LegalityCheckDto>,
+) -> ApiResponse<LegalityReport> {
+    if let Err(e) = validate_deck_cards(&payload.cards) {
+        return validation_error(format!("Error de validación: {}", e), None);
+    }
+
+    let format = match state.deck_service.get_format(&code).await {
+        Ok(Some(format)) => format,
+        Ok(None) => return format_not_found(&code),
+        Err(e) => return error_response(e.to_string(), 500),
+    };
+
+    let visibility = CatalogVisibility::for_user(user.as_ref());
+    match state.card_service.get_catalog(visibility).await {
+        Ok(catalog) => json_response(state.deck_service.check_legality(&payload.cards, &format, &catalog)),
+        Err(e) => error_response(e.to_string(), 500),
+    }
+}
+
+// El texto de la lista se envía tal cual en el cuerpo, en UTF-8
+fn decklist_text<T>(body: &Bytes) -> Result<&str, ApiResponse<T>> {
+    std::str::from_utf8(body).map_err(|_| validation_error("La lista debe estar codificada en UTF-8".to_string(), None))
+}
+
+// Resuelve una lista en texto sin guardarla; la respuesta incluye las cartas listas para un mazo
+async fn parse_decklist(
+    State(state): State<Arc<AppState>>,
+    user: Option<AuthUser>,
+    body: Bytes,
+) -> ApiResponse<DecklistImport> {
+    let text = match decklist_text(&body) {
+        Ok(text) => text,
+        Err(response) => return response,
+    };
+
+    let visibility = CatalogVisibility::for_user(user.as_ref());
+    match state.card_service.get_catalog(visibility).await {
+        Ok(catalog) => json_response(state.deck_service.import_decklist(text, &catalog)),
+        Err(e) => error_response(e.to_string(), 500),
+    }
+}
+
+// Reemplaza las cartas del mazo por las de la lista; si alguna línea no se
+// resuelve no se guarda nada y se responde 422 con el informe
+async fn import_deck_decklist(
+    State(state): State<Arc<AppState>>,
+    Path(id): Path<Uuid>,
+    user: AuthUser,
+    body: Bytes,
+) -> ApiResponse<DecklistImport> {
+    if user.is_anonymous() {
+        return anonymous_forbidden();
+    }
+
+    let deck = match find_deck(&state, id, &user, Access::Manage).await {
+        Ok(deck) => deck,
+        Err(response) => return response,
+    };
+
+    let text = match decklist_text(&body) {
+        Ok(text) => text,
+        Err(response) => return response,
+    };
+
+    let visibility = CatalogVisibility::for_user(Some(&user));
+    let catalog = match state.card_service.get_catalog(visibility).await {
+        Ok(catalog) => catalog,
+        Err(e) => return error_response(e.to_string(), 500),
+    };
+
+    let report = state.deck_service.import_decklist(text, &catalog);
+    if !report.unresolved.is_empty() {
+        let message = format!(
+            "{} línea(s) no se pudieron resolver; no se guardó ningún cambio",
+            report.unresolved.len()
+        );
+        return ApiResponse::error_with_data(report, message, StatusCode::UNPROCESSABLE_ENTITY);
+    }
+
+    if let Err(e) = validate_deck_cards(&report.cards) {
+        return validation_error(format!("Error de validación: {}", e), None);
+    }
+
+    match state.deck_service.replace_cards(&deck, &report.cards, &user.uid).await {
+        Ok(Some(_)) => json_response(report),
+        Ok(None) => deck_not_found(id),
+        Err(e) => error_response(e.to_string(), 500),
+    }
+}
+
+// Descarga el mazo como lista en texto (`format=text`, por defecto) o markdown
+async fn export_deck_decklist(
+    State(state): State<Arc<AppState>>,
+    Path(id): Path<Uuid>,
+    Query(query): Query<DecklistExportQuery>,
+    user: AuthUser,
+) -> Response {
+    let format = match query.format.as_deref().map(DecklistFormat::parse) {
+        None => DecklistFormat::Text,
+        Some(Some(format)) => format,
+        Some(None) => {
+            return validation_error::<()>("Formato no soportado: use text o markdown".to_string(), None).into_response()
+        }
+    };
+
+    let deck = match find_deck::<()>(&state, id, &user, Access::Read).await {
+        Ok(deck) => deck,
+        Err(response) => return response.into_response(),
+    };
+
+    let detail = match state.deck_service.get_deck_detail(deck).await {
+        Ok(detail) => detail,
+        Err(e) => return error_response::<()>(e.to_string(), 500).into_response(),
+    };
+
+    let visibility = CatalogVisibility::for_user(Some(&user));
+    let catalog = match state.card_service.get_catalog(visibility).await {
+        Ok(catalog) => catalog,
+        Err(e) => return error_response::<()>(e.to_string(), 500).into_response(),
+    };
+
+    let body = state.deck_service.export_decklist(&detail, &catalog, format);
+    let disposition = format!("inline; filename=\"{}.{}\"", detail.deck.id, format.extension());
+
+    (
+        StatusCode::OK,
+        [
+            (header::CONTENT_TYPE, format.content_type().to_string()),
+            (header::CONTENT_DISPOSITION, disposition),
+        ],
+        body,
+    )
+        .into_response()
+}
+
+// Código compacto del mazo, para compartirlo o generar un QR
+async fn get_deck_code(
+    State(state): State<Arc<AppState>>,
+    Path(id): Path<Uuid>,
+    user: AuthUser,
+) -> ApiResponse<EncodedDeck> {
+    let deck = match find_deck(&state, id, &user, Access::Read).await {
+        Ok(deck) => deck,
+        Err(response) => return response,
+    };
+
+    let detail = match state.deck_service.get_deck_detail(deck).await {
+        Ok(detail) => detail,
+        Err(e) => return error_response(e.to_string(), 500),
+    };
+
+    let visibility = CatalogVisibility::for_user(Some(&user));
+    let catalog = match state.card_service.get_catalog(visibility).await {
+        Ok(catalog) => catalog,
+        Err(e) => return error_response(e.to_string(), 500),
+    };
+
+    let format_code = detail.deck.format_code.clone();
+    match state.deck_service.encode_deck_code(&detail.cards, format_code, &catalog) {
+        Ok(encoded) => json_response(encoded),
+        Err(e) => error_response(e.to_string(), 500),
+    }
+}
+
+// Codifica una lista de cartas sin guardarla
+async fn encode_deck_code(
+    State(state): State<Arc<AppState>>,
+    user: Option<AuthUser>,
+    ValidatedJson(payload): ValidatedJson<DeckCodeDto>,
+) -> ApiResponse<EncodedDeck> {
+    if let Err(e) = validate_deck_cards(&payload.cards) {
+        return validation_error(format!("Error de validación: {}", e), None);
+    }
+
+    let format_code = payload
+        .format_code
+        .as_deref()
+        .map(|code| code.trim().to_lowercase())
+        .filter(|code| !code.is_empty());
+    if format_code.as_deref().is_some_and(|code| !is_valid_format_code(code)) {
+        return validation_error("Código de formato no válido".to_string(), None);
+    }
+
+    let visibility = CatalogVisibility::for_user(user.as_ref());
+    let catalog = match state.card_service.get_catalog(visibility).await {
+        Ok(catalog) => catalog,
+        Err(e) => return error_response(e.to_string(), 500),
+    };
+
+    if let Some(missing) = payload.cards.iter().find(|card| catalog.by_id(card.card_id).is_none()) {
+        return error_response(format!("Carta con ID {} no encontrada", missing.card_id), 404);
+    }
+
+    match state.deck_service.encode_deck_code(&payload.cards, format_code, &catalog) {
+        Ok(encoded) => json_response(encoded),
+        Err(e) => error_response(e.to_string(), 500),
+    }
+}
+
+// Decodifica un código; las cartas de conjuntos que aún no conocemos se
+// devuelven aparte en lugar de rechazar el código entero
+async fn decode_deck_code(
+    State(state): State<Arc<AppState>>,
+    Path(code): Path<String>,
+    user: Option<AuthUser>,
+) -> ApiResponse<DecodedDeck> {
+    let visibility = CatalogVisibility::for_user(user.as_ref());
+    let catalog = match state.card_service.get_catalog(visibility).await {
+        Ok(catalog) => catalog,
+        Err(e) => return error_response(e.to_string(), 500),
+    };
+
+    match state.deck_service.decode_deck_code(&code, &catalog) {
+        Ok(decoded) => json_response(decoded),
+        Err(e) => validation_error(e.to_string(), None),
+    }
+}
+
+fn revision_not_found<T>(id: Uuid, revision: i32) -> ApiResponse<T> {
+    error_response(format!("El mazo {} no tiene la revisión {}", id, revision), 404)
+}
+
+// Historial del mazo, de la revisión más reciente a la más antigua
+async fn get_deck_revisions(
+    State(state): State<Arc<AppState>>,
+    Path(id): Path<Uuid>,
+    user: AuthUser,
+) -> ApiResponse<Vec<DeckRevision>> {
+    if let Err(response) = find_deck(&state, id, &user, Access::Read).await {
+        return response;
+    }
+
+    match state.deck_service.get_revisions(id).await {
+        Ok(revisions) => json_response(revisions),
+        Err(e) => error_response(e.to_string(), 500),
+    }
+}
+
+async fn get_deck_revision(
+    State(state): State<Arc<AppState>>,
+    Path((id, revision)): Path<(Uuid, i32)>,
+    user: AuthUser,
+) -> ApiResponse<DeckRevisionDetail> {
+    if let Err(response) = find_deck(&state, id, &user, Access::Read).await {
+        return response;
+    }
+
+    match state.deck_service.get_revision(id, revision).await {
+        Ok(Some(detail)) => json_response(detail),
+        Ok(None) => revision_not_found(id, revision),
+        Err(e) => error_response(e.to_string(), 500),
+    }
+}
+
+// Cartas añadidas y retiradas entre dos revisiones; por defecto, el último cambio
+async fn diff_deck_revisions(
+    State(state): State<Arc<AppState>>,
+    Path(id): Path<Uuid>,
+    Query(query): Query<RevisionDiffQuery>,
+    user: AuthUser,
+) -> ApiResponse<DeckDiff> {
+    if let Err(response) = find_deck(&state, id, &user, Access::Read).await {
+        return response;
+    }
+
+    match state.deck_service.diff_revisions(id, query.from, query.to).await {
+        Ok(Some(diff)) => json_response(diff),
+        Ok(None) => error_response(format!("El mazo {} no tiene alguna de las revisiones indicadas", id), 404),
+        Err(e) => error_response(e.to_string(), 500),
+    }
+}
+
+// Restaurar guarda el contenido de la revisión como una revisión nueva: el
+// historial nunca se reescribe
+async fn restore_deck_revision(
+    State(state): State<Arc<AppState>>,
+    Path((id, revision)): Path<(Uuid, i32)>,
+    user: AuthUser,
+) -> ApiResponse<DeckDetail> {
+    if user.is_anonymous() {
+        return anonymous_forbidden();
+    }
+
+    let deck = match find_deck(&state, id, &user, Access::Manage).await {
+        Ok(deck) => deck,
+        Err(response) => return response,
+    };
+
+    let detail = match state.deck_service.get_revision(id, revision).await {
+        Ok(Some(detail)) => detail,
+        Ok(None) => return revision_not_found(id, revision),
+        Err(e) => return error_response(e.to_string(), 500),
+    };
+
+    // Desde que se guardó la revisión pueden haberse borrado cartas o formatos
+    let ids: Vec<Uuid> = detail.cards.iter().map(|card| card.card_id).collect();
+    let visibility = CatalogVisibility::for_user(Some(&user));
+    let found: HashSet<Uuid> = match state.card_service.get_cards_by_ids(&ids, visibility).await {
+        Ok(cards) => cards.into_iter().map(|card| card.id).collect(),
+        Err(e) => return error_response(e.to_string(), 500),
+    };
+    if let Some(missing) = ids.iter().find(|id| !found.contains(id)) {
+        return validation_error(
+            format!("La revisión {} incluye la carta {}, que ya no está disponible", revision, missing),
+            None,
+        );
+    }
+
+    if let Some(code) = &detail.revision.format_code {
+        match state.deck_service.get_format(code).await {
+            Ok(Some(_)) => {},
+            Ok(None) => {
+                return validation_error(
+                    format!("La revisión {} usa el formato '{}', que ya no existe", revision, code),
+                    None,
+                )
+            }
+            Err(e) => return error_response(e.to_string(), 500),
+        }
+    }
+
+    match state.deck_service.restore_revision(&deck, &detail, &user.uid).await {
+        Ok(Some(deck)) => json_response(deck),
+        Ok(None) => deck_not_found(id),
+        Err(e) => error_response(e.to_string(), 500),
+    }
+}
+
+// Estadísticas con las reglas de robo por defecto
+async fn get_deck_analysis(
+    State(state): State<Arc<AppState>>,
+    Path(id): Path<Uuid>,
+    user: AuthUser,
+) -> ApiResponse<DeckAnalysis> {
+    deck_analysis(&state, id, &user, DeckAnalysisDto::default()).await
+}
+
+// Estadísticas con reglas de robo, mulligan y grupos de cartas a medida
+async fn analyze_deck(
+    State(state): State<Arc<AppState>>,
+    Path(id): Path<Uuid>,
+    user: AuthUser,
+    ValidatedJson(payload): ValidatedJson<DeckAnalysisDto>,
+) -> ApiResponse<DeckAnalysis> {
+    if let Err(e) = payload.validate() {
+        return validation_error(format!("Error de validación: {}", e), None);
+    }
+
+    deck_analysis(&state, id, &user, payload).await
+}
+
+async fn deck_analysis(state: &AppState, id: Uuid, user: &AuthUser, payload: DeckAnalysisDto) -> ApiResponse<DeckAnalysis> {
+    let deck = match find_deck(state, id, user, Access::Read).await {
+        Ok(deck) => deck,
+        Err(response) => return response,
+    };
+
+    let detail = match state.deck_service.get_deck_detail(deck).await {
+        Ok(detail) => detail,
+        Err(e) => return error_response(e.to_string(), 500),
+    };
+
+    let visibility = CatalogVisibility::for_user(Some(user));
+    match state.card_service.get_catalog(visibility).await {
+        Ok(catalog) => json_response(state.deck_service.analyze(&detail.cards, &catalog, payload)),
+        Err(e) => error_response(e.to_string(), 500),
+    }
+}
+
+// Mano de prueba con los parámetros en la URL, para poder compartirla
+async fn get_sample_hand(
+    State(state): State<Arc<AppState>>,
+    Path(id): Path<Uuid>,
+    Query(query): Query<SampleHandDto>,
+    user: AuthUser,
+) -> ApiResponse<SampleHand> {
+    sample_hand(&state, id, &user, query).await
+}
+
+// Igual, pero permite elegir qué cartas van al fondo con el mulligan london
+async fn deal_sample_hand(
+    State(state): State<Arc<AppState>>,
+    Path(id): Path<Uuid>,
+    user: AuthUser,
+    ValidatedJson(payload): ValidatedJson<SampleHandDto>,
+) -> ApiResponse<SampleHand> {
+    sample_hand(&state, id, &user, payload).await
+}
+
+async fn sample_hand(state: &AppState, id: Uuid, user: &AuthUser, payload: SampleHandDto) -> ApiResponse<SampleHand> {
+    if let Err(e) = payload.validate() {
+        return validation_error(format!("Error de validación: {}", e), None);
+    }
+
+    if let Err(response) = find_deck(state, id, user, Access::Read).await {
+        return response;
+    }
+
+    let revision = match payload.revision {
+        Some(revision) => state.deck_service.get_revision(id, revision).await,
+        None => state.deck_service.get_latest_revision(id).await,
+    };
+    let revision = match revision {
+        Ok(Some(revision)) => revision,
+        Ok(None) => return revision_not_found(id, payload.revision.unwrap_or(0)),
+        Err(e) => return error_response(e.to_string(), 500),
+    };
+
+    let visibility = CatalogVisibility::for_user(Some(user));
+    let catalog = match state.card_service.get_catalog(visibility).await {
+        Ok(catalog) => catalog,
+        Err(e) => return error_response(e.to_string(), 500),
+    };
+
+    // Sin semilla se elige una al azar; la respuesta la devuelve para repetir la tirada
+    let seed = payload.seed().ok().flatten().unwrap_or_else(rand::random);
+    match state.deck_service.sample_hand(&revision, &catalog, &payload, seed) {
+        Ok(hand) => json_response(hand),
+        Err(e) => validation_error(format!("Error de validación: {}", e), None),
+    }
+}
+
+// Qué le falta al usuario para montar uno de sus mazos o uno público
+async fn get_deck_buildability(
+    State(state): State<Arc<AppState>>,
+    Path(id): Path<Uuid>,
+    user: AuthUser,
+) -> ApiResponse<BuildabilityReport> {
+    let deck = match find_deck(&state, id, &user, Access::Read).await {
+        Ok(deck) => deck,
+        Err(response) => return response,
+    };
+
+    match state.deck_service.get_deck_detail(deck).await {
+        Ok(detail) => buildability(&state, &user, &detail.cards).await,
+        Err(e) => error_response(e.to_string(), 500),
+    }
+}
+
+// Lo mismo para una lista de cartas sin guardar, p. ej. un mazo decodificado
+async fn check_buildability(
+    State(state): State<Arc<AppState>>,
+    user: AuthUser,
+    ValidatedJson(payload): ValidatedJson<BuildabilityCheckDto>,
+) -> ApiResponse<BuildabilityReport> {
+    if let Err(e) = validate_deck_cards(&payload.cards) {
+        return validation_error(format!("Error de validación: {}", e), None);
+    }
+
+    buildability(&state, &user, &payload.cards).await
+}
+
+// Siempre se compara con la colección de quien hace la petición
+async fn buildability(state: &AppState, user: &AuthUser, cards: &[DeckCard]) -> ApiResponse<BuildabilityReport> {
+    let visibility = CatalogVisibility::for_user(Some(user));
+    let catalog = match state.card_service.get_catalog(visibility).await {
+        Ok(catalog) => catalog,
+        Err(e) => return error_response(e.to_string(), 500),
+    };
+
+    let collection = match state.collection_service.get_entries(&user.uid).await {
+        Ok(collection) => collection,
+        Err(e) => return error_response(e.to_string(), 500),
+    };
+
+    let prices = match state.card_service.get_prices(&printing_ids(cards, &catalog)).await {
+        Ok(prices) => prices,
+        Err(e) => return error_response(e.to_string(), 500),
+    };
+
+    json_response(state.deck_service.check_buildability(cards, &collection, &catalog, &prices))
+}
+
+fn gallery_filter<T>(query: GalleryQuery) -> Result<GalleryFilter, ApiResponse<T>> {
+    let sort = match query.sort.as_deref().map(GallerySort::parse) {
+        None => GallerySort::Trending,
+        Some(Some(sort)) => sort,
+        Some(None) => return Err(validation_error("Orden no soportado: use trending, new o likes".to_string(), None)),
+    };
+
+    let text = |value: Option<String>| value.map(|value| value.trim().to_string()).filter(|value| !value.is_empty());
+
+    Ok(GalleryFilter {
+        format_code: text(query.format).map(|code| code.to_lowercase()),
+        energy: text(query.energy),
+        card_id: query.card,
+        sort,
+        limit: query.limit.unwrap_or(DEFAULT_GALLERY_LIMIT).clamp(1, MAX_GALLERY_LIMIT),
+        offset: query.offset.unwrap_or(0).max(0),
+    })
+}
+
+// Mazos públicos de todos los usuarios; por defecto, los que son tendencia
+async fn get_gallery(
+    State(state): State<Arc<AppState>>,
+    Query(query): Query<GalleryQuery>,
+    user: AuthUser,
+) -> ApiResponse<Vec<GalleryDeck>> {
+    let filter = match gallery_filter(query) {
+        Ok(filter) => filter,
+        Err(response) => return response,
+    };
+
+    match state.deck_service.get_gallery(&filter, &user.uid).await {
+        Ok(decks) => json_response(decks),
+        Err(e) => error_response(e.to_string(), 500),
+    }
+}
+
+// Mazos guardados por el usuario, con los mismos filtros que la galería
+async fn get_bookmarks(
+    State(state): State<Arc<AppState>>,
+    Path(user_id): Path<String>,
+    Query(query): Query<GalleryQuery>,
+    user: AuthUser,
+) -> ApiResponse<Vec<GalleryDeck>> {
+    let owner_uid = match resolve_owner(&user, &user_id) {
+        Ok(owner_uid) => owner_uid,
+        Err(message) => return ApiResponse::forbidden(message),
+    };
+
+    let filter = match gallery_filter(query) {
+        Ok(filter) => filter,
+        Err(response) => return response,
+    };
+
+    match state.deck_service.get_bookmarks(&filter, &owner_uid).await {
+        Ok(decks) => json_response(decks),
+        Err(e) => error_response(e.to_string(), 500),
+    }
+}
+
+// Copia un mazo legible (público o propio) en la cuenta del usuario, con atribución
+async fn fork_deck(
+    State(state): State<Arc<AppState>>,
+    Path(id): Path<Uuid>,
+    user: AuthUser,
+    ValidatedJson(payload): ValidatedJson<ForkDeckDto>,
+) -> ApiResponse<DeckDetail> {
+    if user.is_anonymous() {
+        return anonymous_forbidden();
+    }
+
+    let source = match find_deck(&state, id, &user, Access::Read).await {
+        Ok(source) => source,
+        Err(response) => return response,
+    };
+
+    if let Err(e) = payload.validate() {
+        return validation_error(format!("Error de validación: {}", e), None);
+    }
+
+    // Un mazo público puede incluir cartas que quien lo copia aún no puede ver
+    let cards = match state.deck_service.get_cards(source.id).await {
+        Ok(cards) => cards,
+        Err(e) => return error_response(e.to_string(), 500),
+    };
+
+    if let Err(response) = check_deck_cards(&state, &user, cards.iter().map(|card| card.card_id).collect()).await {
+        return response;
+    }
+
+    match state.deck_service.fork_deck(&source, &cards, user.uid.clone(), &payload).await {
+        Ok(deck) => ApiResponse::created(deck),
+        Err(e) => error_response(e.to_string(), 500),
+    }
+}
+
+async fn get_deck_reactions(
+    State(state): State<Arc<AppState>>,
+    Path(id): Path<Uuid>,
+    user: AuthUser,
+) -> ApiResponse<DeckReactions> {
+    if let Err(response) = find_deck(&state, id, &user, Access::Read).await {
+        return response;
+    }
+
+    match state.deck_service.get_reactions(id, &user.uid).await {
+        Ok(reactions) => json_response(reactions),
+        Err(e) => error_response(e.to_string(), 500),
+    }
+}
+
+enum Reaction {
+    Like,
+    Bookmark,
+}
+
+// Las sesiones anónimas no reaccionan: inflarían la puntuación de tendencia
+async fn react(state: &AppState, id: Uuid, user: &AuthUser, reaction: Reaction, active: bool) -> ApiResponse<DeckReactions> {
+    if user.is_anonymous() {
+        return ApiResponse::forbidden("Inicia sesión con una cuenta para valorar o guardar mazos".to_string());
+    }
+
+    let deck = match find_deck(state, id, user, Access::Read).await {
+        Ok(deck) => deck,
+        Err(response) => return response,
+    };
+
+    // El propietario puede guardar su mazo para tenerlo a mano, pero no valorarlo
+    if matches!(reaction, Reaction::Like) && active && deck.owner_uid == user.uid {
+        return ApiResponse::forbidden("No puedes dar \"me gusta\" a tus propios mazos".to_string());
+    }
+
+    let result = match reaction {
+        Reaction::Like => state.deck_service.set_liked(id, &user.uid, active).await,
+        Reaction::Bookmark => state.deck_service.set_bookmarked(id, &user.uid, active).await,
+    };
+
+    match result {
+        Ok(reactions) => json_response(reactions),
+        Err(e) => error_response(e.to_string(), 500),
+    }
+}
+
+async fn like_deck(State(state): State<Arc<AppState>>, Path(id): Path<Uuid>, user: AuthUser) -> ApiResponse<DeckReactions> {
+    react(&state, id, &user, Reaction::Like, true).await
+}
+
+async fn unlike_deck(State(state): State<Arc<AppState>>, Path(id): Path<Uuid>, user: AuthUser) -> ApiResponse<DeckReactions> {
+    react(&state, id, &user, Reaction::Like, false).await
+}
+
+async fn bookmark_deck(State(state): State<Arc<AppState>>, Path(id): Path<Uuid>, user: AuthUser) -> ApiResponse<DeckReactions> {
+    react(&state, id, &user, Reaction::Bookmark, true).await
+}
+
+async fn unbookmark_deck(State(state): State<Arc<AppState>>, Path(id): Path<Uuid>, user: AuthUser) -> ApiResponse<DeckReactions> {
+    react(&state, id, &user, Reaction::Bookmark, false).await
+}
+
+// Cartas que más aparecen junto a esta en los mazos públicos
+async fn get_played_with(
+    State(state): State<Arc<AppState>>,
+    Path(id): Path<Uuid>,
+    Query(query): Query<SynergyQuery>,
+    user: AuthUser,
+) -> ApiResponse<PlayedWith> {
+    let visibility = CatalogVisibility::for_user(Some(&user));
+    let catalog = match state.card_service.get_catalog(visibility).await {
+        Ok(catalog) => catalog,
+        Err(e) => return error_response(e.to_string(), 500),
+    };
+
+    let Some(card) = catalog.by_id(id) else {
+        return error_response(format!("Carta con ID {} no encontrada", id), 404);
+    };
+
+    let limit = query.limit.unwrap_or(DEFAULT_SYNERGY_LIMIT).clamp(1, MAX_SYNERGY_LIMIT);
+    match state.deck_service.played_with(card, &catalog, limit).await {
+        Ok(played_with) => json_response(played_with),
+        Err(e) => error_response(e.to_string(), 500),
+    }
+}
+
+// Cartas que los mazos públicos juegan con las de este y que aún no tiene
+async fn get_deck_suggestions(
+    State(state): State<Arc<AppState>>,
+    Path(id): Path<Uuid>,
+    Query(query): Query<SynergyQuery>,
+    user: AuthUser,
+) -> ApiResponse<CardSuggestions> {
+    let deck = match find_deck(&state, id, &user, Access::Read).await {
+        Ok(deck) => deck,
+        Err(response) => return response,
+    };
+
+    let detail = match state.deck_service.get_deck_detail(deck).await {
+        Ok(detail) => detail,
+        Err(e) => return error_response(e.to_string(), 500),
+    };
+
+    let visibility = CatalogVisibility::for_user(Some(&user));
+    let catalog = match state.card_service.get_catalog(visibility).await {
+        Ok(catalog) => catalog,
+        Err(e) => return error_response(e.to_string(), 500),
+    };
+
+    let limit = query.limit.unwrap_or(DEFAULT_SYNERGY_LIMIT).clamp(1, MAX_SYNERGY_LIMIT);
+    match state.deck_service.suggest_cards(&detail.cards, &catalog, limit).await {
+        Ok(suggestions) => json_response(suggestions),
+        Err(e) => error_response(e.to_string(), 500),
+    }
+}
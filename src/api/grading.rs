@@ -0,0 +1,242 @@
+use axum::{
+    extract::{Path, Query, State},
+    routing::{get, post},
+    Router,
+};
+use serde::Deserialize;
+use std::sync::Arc;
+use uuid::Uuid;
+
+use crate::api::state::AppState;
+use crate::api::collections::resolve_owner;
+use crate::domain::cards::{CatalogVisibility, Validable};
+use crate::domain::grading::{
+    GradingCertificate, GradingChange, GradingSubmission, GradingSubmissionDto, Grades, STATUS_IN_REVIEW,
+};
+use crate::utils::extractors::{AuthUser, ValidatedJson};
+use crate::utils::response::{ApiResponse, error_response, json_response, validation_error};
+
+const DEFAULT_QUEUE_LIMIT: i64 = 50;
+const MAX_QUEUE_LIMIT: i64 = 200;
+
+#[derive(Debug, Deserialize)]
+pub struct GradingQueueQuery {
+    pub limit: Option<i64>,
+}
+
+pub fn grading_routes(app_state: Arc<AppState>) -> Router {
+    Router::new()
+        .route("/users/:user_id/gradings", get(get_user_submissions).post(submit_card))
+        .route("/gradings/queue", get(get_grading_queue))
+        .route("/gradings/assigned", get(get_assigned_submissions))
+        .route("/gradings/:id", get(get_submission).delete(withdraw_submission))
+        .route("/gradings/:id/claim", post(claim_submission))
+        .route("/gradings/:id/release", post(release_submission))
+        .route("/gradings/:id/grade", post(grade_submission))
+        .route("/certificates/:certificate_number", get(get_certificate))
+        .with_state(app_state)
+}
+
+fn not_found<T>(id: Uuid) -> ApiResponse<T> {
+    error_response(format!("Solicitud de gradación con ID {} no encontrada", id), 404)
+}
+
+fn grader_only<T>() -> ApiResponse<T> {
+    ApiResponse::forbidden("Sólo los graders pueden gestionar la cola de gradación".to_string())
+}
+
+// Nadie puede gradar su propia carta, ni siquiera un administrador. El dueño
+// de una solicitud no cambia, así que basta con comprobarlo antes de la transición.
+async fn check_not_own_submission<T>(state: &AppState, id: Uuid, user: &AuthUser) -> Result<(), ApiResponse<T>> {
+    match state.grading_service.get_submission_by_id(id).await {
+        Ok(Some(submission)) if submission.owner_uid == user.uid => Err(ApiResponse::forbidden(
+            "No puedes gradar tus propias solicitudes".to_string(),
+        )),
+        Ok(Some(_)) => Ok(()),
+        Ok(None) => Err(not_found(id)),
+        Err(e) => Err(error_response(e.to_string(), 500)),
+    }
+}
+
+// Respuesta común a las transiciones de estado de una solicitud
+fn change_response(id: Uuid, change: anyhow::Result<GradingChange>) -> ApiResponse<GradingSubmission> {
+    match change {
+        Ok(GradingChange::Applied(submission)) => json_response(*submission),
+        Ok(GradingChange::NotFound) => not_found(id),
+        Ok(GradingChange::Conflict { status }) if status == STATUS_IN_REVIEW => ApiResponse::conflict(
+            "La solicitud está asignada a otro grader".to_string(),
+        ),
+        Ok(GradingChange::Conflict { status }) => ApiResponse::conflict(
+            format!("No se puede realizar la acción: la solicitud está en estado '{}'", status),
+        ),
+        Err(e) => error_response(e.to_string(), 500),
+    }
+}
+
+async fn get_user_submissions(
+    State(state): State<Arc<AppState>>,
+    Path(user_id): Path<String>,
+    user: AuthUser,
+) -> ApiResponse<Vec<GradingSubmission>> {
+    let owner_uid = match resolve_owner(&user, &user_id) {
+        Ok(owner_uid) => owner_uid,
+        Err(message) => return ApiResponse::forbidden(message),
+    };
+
+    match state.grading_service.get_submissions_by_owner(&owner_uid).await {
+        Ok(submissions) => json_response(submissions),
+        Err(e) => error_response(e.to_string(), 500),
+    }
+}
+
+// Envía una carta a gradar; queda en la cola hasta que un grader la toma
+async fn submit_card(
+    State(state): State<Arc<AppState>>,
+    Path(user_id): Path<String>,
+    user: AuthUser,
+    ValidatedJson(payload): ValidatedJson<GradingSubmissionDto>,
+) -> ApiResponse<GradingSubmission> {
+    let owner_uid = match resolve_owner(&user, &user_id) {
+        Ok(owner_uid) => owner_uid,
+        Err(message) => return ApiResponse::forbidden(message),
+    };
+
+    if let Err(e) = payload.validate() {
+        return validation_error(format!("Error de validación: {}", e), None);
+    }
+
+    let visibility = CatalogVisibility::for_user(Some(&user));
+    match state.card_service.get_card_by_id(payload.card_id, visibility).await {
+        Ok(Some(_)) => {},
+        Ok(None) => return error_response(format!("Carta con ID {} no encontrada", payload.card_id), 404),
+        Err(e) => return error_response(e.to_string(), 500),
+    }
+
+    match state.grading_service.submit(owner_uid, &payload).await {
+        Ok(submission) => ApiResponse::created(submission),
+        Err(e) => error_response(e.to_string(), 500),
+    }
+}
+
+async fn get_grading_queue(
+    State(state): State<Arc<AppState>>,
+    Query(query): Query<GradingQueueQuery>,
+    user: AuthUser,
+) -> ApiResponse<Vec<GradingSubmission>> {
+    if !user.is_grader() {
+        return grader_only();
+    }
+
+    let limit = query.limit.unwrap_or(DEFAULT_QUEUE_LIMIT).clamp(1, MAX_QUEUE_LIMIT);
+
+    match state.grading_service.get_queue(limit).await {
+        Ok(submissions) => json_response(submissions),
+        Err(e) => error_response(e.to_string(), 500),
+    }
+}
+
+// Solicitudes que el grader actual tiene en revisión
+async fn get_assigned_submissions(
+    State(state): State<Arc<AppState>>,
+    user: AuthUser,
+) -> ApiResponse<Vec<GradingSubmission>> {
+    if !user.is_grader() {
+        return grader_only();
+    }
+
+    match state.grading_service.get_assigned(&user.uid).await {
+        Ok(submissions) => json_response(submissions),
+        Err(e) => error_response(e.to_string(), 500),
+    }
+}
+
+// Visible para el propietario y para los graders
+async fn get_submission(
+    State(state): State<Arc<AppState>>,
+    Path(id): Path<Uuid>,
+    user: AuthUser,
+) -> ApiResponse<GradingSubmission> {
+    match state.grading_service.get_submission_by_id(id).await {
+        Ok(Some(submission)) if submission.owner_uid == user.uid || user.is_grader() => json_response(submission),
+        Ok(_) => not_found(id),
+        Err(e) => error_response(e.to_string(), 500),
+    }
+}
+
+// El propietario puede retirar la solicitud mientras nadie la haya tomado
+async fn withdraw_submission(
+    State(state): State<Arc<AppState>>,
+    Path(id): Path<Uuid>,
+    user: AuthUser,
+) -> ApiResponse<GradingSubmission> {
+    match state.grading_service.get_submission_by_id(id).await {
+        Ok(Some(submission)) if submission.owner_uid == user.uid || user.is_admin() => {},
+        Ok(_) => return not_found(id),
+        Err(e) => return error_response(e.to_string(), 500),
+    }
+
+    change_response(id, state.grading_service.withdraw(id).await)
+}
+
+async fn claim_submission(
+    State(state): State<Arc<AppState>>,
+    Path(id): Path<Uuid>,
+    user: AuthUser,
+) -> ApiResponse<GradingSubmission> {
+    if !user.is_grader() {
+        return grader_only();
+    }
+
+    if let Err(response) = check_not_own_submission(&state, id, &user).await {
+        return response;
+    }
+
+    change_response(id, state.grading_service.claim(id, &user.uid).await)
+}
+
+// Devuelve a la cola una solicitud tomada; los administradores pueden liberar las de cualquiera
+async fn release_submission(
+    State(state): State<Arc<AppState>>,
+    Path(id): Path<Uuid>,
+    user: AuthUser,
+) -> ApiResponse<GradingSubmission> {
+    if !user.is_grader() {
+        return grader_only();
+    }
+
+    change_response(id, state.grading_service.release(id, &user.uid, user.is_admin()).await)
+}
+
+// Registra las subnotas y la nota final, y emite el número de certificado
+async fn grade_submission(
+    State(state): State<Arc<AppState>>,
+    Path(id): Path<Uuid>,
+    user: AuthUser,
+    ValidatedJson(payload): ValidatedJson<Grades>,
+) -> ApiResponse<GradingSubmission> {
+    if !user.is_grader() {
+        return grader_only();
+    }
+
+    if let Err(e) = payload.validate() {
+        return validation_error(format!("Error de validación: {}", e), None);
+    }
+
+    if let Err(response) = check_not_own_submission(&state, id, &user).await {
+        return response;
+    }
+
+    change_response(id, state.grading_service.grade(id, &user.uid, user.is_admin(), payload).await)
+}
+
+// Consulta pública de certificados: no requiere autenticación
+async fn get_certificate(
+    State(state): State<Arc<AppState>>,
+    Path(certificate_number): Path<String>,
+) -> ApiResponse<GradingCertificate> {
+    match state.grading_service.get_certificate(&certificate_number).await {
+        Ok(Some(certificate)) => json_response(certificate),
+        Ok(None) => error_response(format!("Certificado {} no encontrado", certificate_number), 404),
+        Err(e) => error_response(e.to_string(), 500),
+    }
+}
@@ -1,4 +1,15 @@
 pub mod routes;
+pub mod state;
+pub mod access;
 pub mod card_sets;
+pub mod cards;
+pub mod bulk;
+pub mod uploads;
+pub mod user_files;
+pub mod collections;
+pub mod trades;
+pub mod wishlists;
+pub mod grading;
+pub mod decks;
 
 pub use routes::*;
\ No newline at end of file
@@ -5,10 +5,30 @@ use axum::{
 use std::sync::Arc;
 use sqlx::PgPool;
 use tower_http::cors::CorsLayer;
+use tower_http::services::ServeDir;
 
+use crate::config::Config;
+use crate::config::firebase::FirebaseAuth;
 use crate::utils::response::{ApiResponse, json_response};
-use crate::domain::cards::{CardSetService, PgCardSetRepository};
-use crate::api::card_sets::{AppState, card_sets_routes};
+use crate::domain::cards::{CardSetService, CardService, ImagePipeline, PgCardSetRepository, PgCardRepository};
+use crate::api::card_sets::card_sets_routes;
+use crate::api::state::AppState;
+use crate::api::cards::cards_routes;
+use crate::api::uploads::uploads_routes;
+use crate::api::user_files::user_files_routes;
+use crate::api::collections::collections_routes;
+use crate::api::trades::trades_routes;
+use crate::api::wishlists::wishlists_routes;
+use crate::api::grading::grading_routes;
+use crate::api::decks::decks_routes;
+use crate::domain::files::{PgUserFileRepository, UserFileService};
+use crate::domain::collections::{CollectionService, PgCollectionRepository};
+use crate::domain::trades::{PgTradeRepository, TradeService};
+use crate::domain::wishlists::{PgWishlistRepository, WishlistService};
+use crate::domain::grading::{GradingService, PgGradingRepository};
+use crate::domain::decks::{DeckService, PgDeckRepository, start_cooccurrence_refresh, start_trending_refresh};
+use crate::infrastructure::storage::{private_storage_from_config, storage_from_config};
+use crate::utils::signed_url::UrlSigner;
 
 pub fn create_router() -> Router {
     // Sólo mantener la ruta de health check
@@ -17,21 +37,87 @@ pub fn create_router() -> Router {
         .layer(CorsLayer::permissive())
 }
 
-pub fn create_router_with_db(pool: PgPool) -> Router {
+pub fn create_router_with_db(pool: PgPool, config: &Config, firebase_auth: FirebaseAuth) -> Router {
     // Crear repositorio y servicio
-    let card_set_repository = PgCardSetRepository::new(pool);
+    let card_set_repository = PgCardSetRepository::new(pool.clone());
     let card_set_service = Arc::new(CardSetService::new(card_set_repository));
+    let card_repository = PgCardRepository::new(pool.clone());
+    let card_service = Arc::new(CardService::new(card_repository));
+    let user_file_repository = PgUserFileRepository::new(pool.clone());
+    let user_file_service = Arc::new(UserFileService::new(user_file_repository));
+    let collection_repository = PgCollectionRepository::new(pool.clone());
+    let collection_service = Arc::new(CollectionService::new(collection_repository));
+    let trade_repository = PgTradeRepository::new(pool.clone());
+    let trade_service = Arc::new(TradeService::new(trade_repository));
+    let wishlist_repository = PgWishlistRepository::new(pool.clone());
+    let wishlist_service = Arc::new(WishlistService::new(wishlist_repository));
+    let grading_repository = PgGradingRepository::new(pool.clone());
+    let grading_service = Arc::new(GradingService::new(grading_repository));
+    let deck_repository = PgDeckRepository::new(pool);
+    let deck_service = Arc::new(DeckService::new(deck_repository));
+
+    // Estadísticas de cartas jugadas juntas, al día con los cambios de los mazos
+    start_cooccurrence_refresh(deck_service.clone(), config.cooccurrence.clone());
+    // Puntuaciones de tendencia de la galería, que pierden peso con el tiempo
+    start_trending_refresh(deck_service.clone(), config.trending.clone());
     
+    // Almacenamiento de archivos subidos
+    let storage = storage_from_config(&config.storage).expect("Configuración de almacenamiento inválida");
+    let image_pipeline = ImagePipeline::start(
+        storage.clone(),
+        card_set_service.clone(),
+        card_service.clone(),
+        config.images.clone(),
+    );
+
+    // Archivos privados de los usuarios, servidos sólo con URLs firmadas
+    let private_storage = private_storage_from_config(&config.storage).expect("Configuración de almacenamiento inválida");
+    let url_signer = UrlSigner::new(&config.signed_urls.secret, config.signed_urls.ttl_secs);
+
     // Estado de la aplicación
     let app_state = Arc::new(AppState {
         card_set_service,
+        card_service,
+        cache: config.cache.clone(),
+        firebase_auth,
+        storage,
+        max_upload_bytes: config.storage.max_upload_bytes,
+        image_pipeline,
+        user_file_service,
+        private_storage,
+        url_signer,
+        collection_service,
+        trade_service,
+        wishlist_service,
+        grading_service,
+        deck_service,
     });
     
     // Router con rutas
-    Router::new()
+    let router = Router::new()
         .route("/health", get(health_check))
-        .nest("/api/v1", card_sets_routes(app_state.clone()))
-        .layer(CorsLayer::permissive())
+        .nest(
+            "/api/v1",
+            Router::new()
+                .merge(card_sets_routes(app_state.clone()))
+                .merge(cards_routes(app_state.clone()))
+                .merge(uploads_routes(app_state.clone()))
+                .merge(user_files_routes(app_state.clone()))
+                .merge(collections_routes(app_state.clone()))
+                .merge(trades_routes(app_state.clone()))
+                .merge(wishlists_routes(app_state.clone()))
+                .merge(grading_routes(app_state.clone()))
+                .merge(decks_routes(app_state.clone())),
+        );
+
+    // Con el backend local, el propio servidor sirve los archivos subidos
+    let router = if config.storage.backend == "local" {
+        router.nest_service("/media", ServeDir::new(&config.storage.local_path))
+    } else {
+        router
+    };
+
+    router.layer(CorsLayer::permissive())
 }
 
 async fn health_check() -> ApiResponse<&'static str> {
@@ -0,0 +1,39 @@
+use axum::extract::FromRef;
+use std::sync::Arc;
+
+use crate::config::CacheConfig;
+use crate::config::firebase::FirebaseAuth;
+use crate::domain::cards::{CardService, CardSetService, ImagePipeline, PgCardRepository, PgCardSetRepository};
+use crate::domain::collections::{CollectionService, PgCollectionRepository};
+use crate::domain::decks::{DeckService, PgDeckRepository};
+use crate::domain::files::{PgUserFileRepository, UserFileService};
+use crate::domain::grading::{GradingService, PgGradingRepository};
+use crate::domain::trades::{PgTradeRepository, TradeService};
+use crate::domain::wishlists::{PgWishlistRepository, WishlistService};
+use crate::infrastructure::storage::Storage;
+use crate::utils::signed_url::UrlSigner;
+
+/// State shared by every API router
+pub struct AppState {
+    pub card_set_service: Arc<CardSetService<PgCardSetRepository>>,
+    pub card_service: Arc<CardService<PgCardRepository>>,
+    pub cache: CacheConfig,
+    pub firebase_auth: FirebaseAuth,
+    pub storage: Arc<dyn Storage>,
+    pub max_upload_bytes: usize,
+    pub image_pipeline: ImagePipeline,
+    pub user_file_service: Arc<UserFileService<PgUserFileRepository>>,
+    pub private_storage: Arc<dyn Storage>,
+    pub url_signer: UrlSigner,
+    pub collection_service: Arc<CollectionService<PgCollectionRepository>>,
+    pub trade_service: Arc<TradeService<PgTradeRepository>>,
+    pub wishlist_service: Arc<WishlistService<PgWishlistRepository>>,
+    pub grading_service: Arc<GradingService<PgGradingRepository>>,
+    pub deck_service: Arc<DeckService<PgDeckRepository>>,
+}
+
+impl FromRef<Arc<AppState>> for FirebaseAuth {
+    fn from_ref(state: &Arc<AppState>) -> Self {
+        state.firebase_auth.clone()
+    }
+}
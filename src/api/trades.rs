@@ -0,0 +1,159 @@
+use axum::{
+    extract::{Path, Query, State},
+    routing::{delete, get, put},
+    Router,
+};
+use serde::Deserialize;
+use std::sync::Arc;
+use uuid::Uuid;
+
+use crate::api::state::AppState;
+use crate::api::collections::resolve_owner;
+use crate::domain::cards::{CatalogVisibility, Validable};
+use crate::domain::trades::{HaveChange, SetTradeHaveDto, TradeHave, TradeMatch, TradeWant, TradeWantDto};
+use crate::utils::extractors::{AuthUser, ValidatedJson};
+use crate::utils::response::{ApiResponse, error_response, json_response, validation_error};
+
+const DEFAULT_MATCH_LIMIT: usize = 20;
+const MAX_MATCH_LIMIT: usize = 100;
+
+#[derive(Debug, Deserialize)]
+pub struct TradeMatchQuery {
+    pub limit: Option<usize>,
+}
+
+pub fn trades_routes(app_state: Arc<AppState>) -> Router {
+    Router::new()
+        .route("/users/:user_id/trades/haves", get(get_trade_haves))
+        .route("/users/:user_id/trades/haves/:entry_id", put(set_trade_have))
+        .route("/users/:user_id/trades/wants", get(get_trade_wants).post(add_trade_want))
+        .route("/users/:user_id/trades/wants/:card_id", delete(delete_trade_want))
+        .route("/users/:user_id/trades/matches", get(get_trade_matches))
+        .with_state(app_state)
+}
+
+// Las listas de intercambio son visibles para cualquier usuario autenticado; sólo se
+// modifican con los mismos permisos que la colección
+fn list_owner(user: &AuthUser, user_id: &str) -> String {
+    if user_id == "me" {
+        user.uid.clone()
+    } else {
+        user_id.to_string()
+    }
+}
+
+async fn get_trade_haves(
+    State(state): State<Arc<AppState>>,
+    Path(user_id): Path<String>,
+    user: AuthUser,
+) -> ApiResponse<Vec<TradeHave>> {
+    match state.trade_service.get_haves(&list_owner(&user, &user_id)).await {
+        Ok(haves) => json_response(haves),
+        Err(e) => error_response(e.to_string(), 500),
+    }
+}
+
+// Ofrece copias de una entrada de la colección; cero retira la oferta
+async fn set_trade_have(
+    State(state): State<Arc<AppState>>,
+    Path((user_id, entry_id)): Path<(String, Uuid)>,
+    user: AuthUser,
+    ValidatedJson(payload): ValidatedJson<SetTradeHaveDto>,
+) -> ApiResponse<Option<TradeHave>> {
+    let owner_uid = match resolve_owner(&user, &user_id) {
+        Ok(owner_uid) => owner_uid,
+        Err(message) => return ApiResponse::forbidden(message),
+    };
+
+    if let Err(e) = payload.validate() {
+        return validation_error(format!("Error de validación: {}", e), None);
+    }
+
+    match state.trade_service.set_have(&owner_uid, entry_id, payload.quantity).await {
+        Ok(HaveChange::Updated(have)) => json_response(Some(have)),
+        Ok(HaveChange::Removed) => json_response(None),
+        Ok(HaveChange::NotOwned) => error_response(format!("Entrada de colección con ID {} no encontrada", entry_id), 404),
+        Ok(HaveChange::Insufficient { owned }) => validation_error(
+            format!("No se pueden ofrecer {} copias: sólo hay {}", payload.quantity, owned),
+            None,
+        ),
+        Err(e) => error_response(e.to_string(), 500),
+    }
+}
+
+async fn get_trade_wants(
+    State(state): State<Arc<AppState>>,
+    Path(user_id): Path<String>,
+    user: AuthUser,
+) -> ApiResponse<Vec<TradeWant>> {
+    match state.trade_service.get_wants(&list_owner(&user, &user_id)).await {
+        Ok(wants) => json_response(wants),
+        Err(e) => error_response(e.to_string(), 500),
+    }
+}
+
+// Añade una carta buscada o actualiza la cantidad si ya estaba en la lista
+async fn add_trade_want(
+    State(state): State<Arc<AppState>>,
+    Path(user_id): Path<String>,
+    user: AuthUser,
+    ValidatedJson(payload): ValidatedJson<TradeWantDto>,
+) -> ApiResponse<TradeWant> {
+    let owner_uid = match resolve_owner(&user, &user_id) {
+        Ok(owner_uid) => owner_uid,
+        Err(message) => return ApiResponse::forbidden(message),
+    };
+
+    if let Err(e) = payload.validate() {
+        return validation_error(format!("Error de validación: {}", e), None);
+    }
+
+    let visibility = CatalogVisibility::for_user(Some(&user));
+    match state.card_service.get_card_by_id(payload.card_id, visibility).await {
+        Ok(Some(_)) => {},
+        Ok(None) => return error_response(format!("Carta con ID {} no encontrada", payload.card_id), 404),
+        Err(e) => return error_response(e.to_string(), 500),
+    }
+
+    match state.trade_service.set_want(&owner_uid, payload.card_id, payload.quantity).await {
+        Ok(want) => json_response(want),
+        Err(e) => error_response(e.to_string(), 500),
+    }
+}
+
+async fn delete_trade_want(
+    State(state): State<Arc<AppState>>,
+    Path((user_id, card_id)): Path<(String, Uuid)>,
+    user: AuthUser,
+) -> ApiResponse<()> {
+    let owner_uid = match resolve_owner(&user, &user_id) {
+        Ok(owner_uid) => owner_uid,
+        Err(message) => return ApiResponse::forbidden(message),
+    };
+
+    match state.trade_service.delete_want(&owner_uid, card_id).await {
+        Ok(true) => json_response(()),
+        Ok(false) => error_response(format!("La carta con ID {} no está en la lista de buscadas", card_id), 404),
+        Err(e) => error_response(e.to_string(), 500),
+    }
+}
+
+// Usuarios con los que intercambiar, ordenados por intercambios posibles en ambos sentidos
+async fn get_trade_matches(
+    State(state): State<Arc<AppState>>,
+    Path(user_id): Path<String>,
+    Query(query): Query<TradeMatchQuery>,
+    user: AuthUser,
+) -> ApiResponse<Vec<TradeMatch>> {
+    let owner_uid = match resolve_owner(&user, &user_id) {
+        Ok(owner_uid) => owner_uid,
+        Err(message) => return ApiResponse::forbidden(message),
+    };
+
+    let limit = query.limit.unwrap_or(DEFAULT_MATCH_LIMIT).clamp(1, MAX_MATCH_LIMIT);
+
+    match state.trade_service.find_matches(&owner_uid, limit).await {
+        Ok(matches) => json_response(matches),
+        Err(e) => error_response(e.to_string(), 500),
+    }
+}
@@ -0,0 +1,289 @@
+use axum::{
+    extract::{DefaultBodyLimit, Multipart, Path, Query, State},
+    http::StatusCode,
+    routing::post,
+    Router,
+};
+use serde::Deserialize;
+use std::sync::Arc;
+use uuid::Uuid;
+
+use crate::api::state::AppState;
+use crate::domain::cards::{Card, CardMatch, CardSet, CatalogVisibility, ImageDetails, ImageJob, ImageTarget, delete_image_variants};
+use crate::infrastructure::images::{perceptual_hash, strip_metadata};
+use crate::utils::extractors::AuthUser;
+use crate::utils::response::{ApiResponse, error_response, json_response, validation_error};
+
+// Margen para las cabeceras y delimitadores del cuerpo multipart
+pub(crate) const MULTIPART_OVERHEAD_BYTES: usize = 64 * 1024;
+
+const DEFAULT_IDENTIFY_LIMIT: usize = 5;
+const MAX_IDENTIFY_LIMIT: usize = 20;
+
+#[derive(Debug, Deserialize)]
+pub struct IdentifyQuery {
+    pub limit: Option<usize>,
+}
+
+pub fn uploads_routes(app_state: Arc<AppState>) -> Router {
+    let body_limit = app_state.max_upload_bytes + MULTIPART_OVERHEAD_BYTES;
+
+    Router::new()
+        .route("/cards/sets/:id/icon", post(upload_card_set_icon))
+        .route("/cards/:id/image", post(upload_card_image))
+        .route("/cards/identify", post(identify_card))
+        .layer(DefaultBodyLimit::max(body_limit))
+        .with_state(app_state)
+}
+
+/// Image formats accepted for uploads
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ImageFormat {
+    Png,
+    Jpeg,
+    Webp,
+}
+
+impl ImageFormat {
+    // Se identifica el formato por su firma, sin confiar en el nombre del archivo
+    pub fn detect(data: &[u8]) -> Option<Self> {
+        if data.starts_with(b"\x89PNG\r\n\x1a\n") {
+            Some(Self::Png)
+        } else if data.starts_with(&[0xFF, 0xD8, 0xFF]) {
+            Some(Self::Jpeg)
+        } else if data.len() >= 12 && &data[0..4] == b"RIFF" && &data[8..12] == b"WEBP" {
+            Some(Self::Webp)
+        } else {
+            None
+        }
+    }
+
+    pub fn content_type(&self) -> &'static str {
+        match self {
+            Self::Png => "image/png",
+            Self::Jpeg => "image/jpeg",
+            Self::Webp => "image/webp",
+        }
+    }
+
+    pub fn extension(&self) -> &'static str {
+        match self {
+            Self::Png => "png",
+            Self::Jpeg => "jpg",
+            Self::Webp => "webp",
+        }
+    }
+}
+
+/// Validated image read from the `file` field of a multipart request
+pub struct UploadedImage {
+    pub format: ImageFormat,
+    pub file_name: Option<String>,
+    pub data: Vec<u8>,
+}
+
+// Lee el campo "file" y comprueba tamaño, tipo declarado y contenido real
+pub(crate) async fn read_image<T>(multipart: &mut Multipart, max_bytes: usize) -> Result<UploadedImage, ApiResponse<T>> {
+    loop {
+        let field = match multipart.next_field().await {
+            Ok(Some(field)) => field,
+            Ok(None) => return Err(validation_error("Falta el campo 'file' en el formulario".to_string(), None)),
+            Err(e) => return Err(upload_error(e.status(), e.body_text())),
+        };
+
+        if field.name() != Some("file") {
+            continue;
+        }
+
+        let file_name = field.file_name().map(str::to_string);
+        let declared_type = field.content_type().map(|content_type| content_type.to_ascii_lowercase());
+        let data = match field.bytes().await {
+            Ok(data) => data,
+            Err(e) => return Err(upload_error(e.status(), e.body_text())),
+        };
+
+        if data.is_empty() {
+            return Err(validation_error("El archivo está vacío".to_string(), None));
+        }
+
+        if data.len() > max_bytes {
+            return Err(upload_error(
+                StatusCode::PAYLOAD_TOO_LARGE,
+                format!("El archivo supera el tamaño máximo de {} bytes", max_bytes),
+            ));
+        }
+
+        let format = ImageFormat::detect(&data).ok_or_else(|| {
+            upload_error(
+                StatusCode::UNSUPPORTED_MEDIA_TYPE,
+                "Formato no soportado; se aceptan PNG, JPEG y WebP".to_string(),
+            )
+        })?;
+
+        // El tipo declarado por el cliente debe coincidir con el contenido
+        if let Some(declared_type) = declared_type {
+            let declared_type = if declared_type == "image/jpg" { "image/jpeg" } else { declared_type.as_str() };
+            if declared_type != format.content_type() && declared_type != "application/octet-stream" {
+                return Err(upload_error(
+                    StatusCode::UNSUPPORTED_MEDIA_TYPE,
+                    format!("El tipo declarado '{}' no coincide con el contenido ({})", declared_type, format.content_type()),
+                ));
+            }
+        }
+
+        return Ok(UploadedImage { format, file_name, data: data.to_vec() });
+    }
+}
+
+fn upload_error<T>(status: StatusCode, message: String) -> ApiResponse<T> {
+    ApiResponse::error(message, status, Some(status.as_u16()))
+}
+
+// Quita los metadatos EXIF y guarda el original; devuelve la clave y la URL pública
+async fn store_original(state: &AppState, key_prefix: String, image: UploadedImage) -> anyhow::Result<(String, String, Vec<u8>)> {
+    let data = tokio::task::spawn_blocking(move || strip_metadata(&image.data)).await??;
+    let key = format!("{}-{}.{}", key_prefix, Uuid::new_v4(), image.format.extension());
+    let url = state.storage.put(&key, image.format.content_type(), data.clone()).await?;
+
+    Ok((key, url, data))
+}
+
+// Encola la generación de variantes; si falla, la imagen queda marcada como fallida
+async fn enqueue_processing(state: &AppState, target: ImageTarget, key: String, url: String, data: Vec<u8>) {
+    let job = ImageJob { target, key, url: url.clone(), data };
+
+    if let Err(e) = state.image_pipeline.enqueue(job).await {
+        tracing::error!("No se pudo encolar el procesamiento de la imagen: {}", e);
+
+        let failed = ImageDetails::failed();
+        let result = match target {
+            ImageTarget::CardSetIcon(id) => state.card_set_service.set_card_set_icon_details(id, &url, &failed).await,
+            ImageTarget::CardImage(id) => state.card_service.set_card_image_details(id, &url, &failed).await,
+        };
+
+        if let Err(e) = result {
+            tracing::error!("No se pudo marcar la imagen como fallida: {}", e);
+        }
+    }
+}
+
+// Borra el archivo anterior y sus variantes si estaban en nuestro almacenamiento; un fallo no impide la subida
+async fn delete_previous(state: &AppState, previous_url: Option<&str>, previous_details: Option<&ImageDetails>) {
+    let prefix = state.storage.public_url("");
+    if let Some(key) = previous_url.and_then(|url| url.strip_prefix(prefix.as_str())) {
+        if let Err(e) = state.storage.delete(key).await {
+            tracing::warn!("No se pudo borrar el archivo anterior '{}': {}", key, e);
+        }
+    }
+
+    delete_image_variants(state.storage.as_ref(), previous_details).await;
+}
+
+// Sube el ícono de un conjunto y actualiza su icon_url (sólo staff)
+async fn upload_card_set_icon(
+    State(state): State<Arc<AppState>>,
+    Path(id): Path<Uuid>,
+    user: AuthUser,
+    mut multipart: Multipart,
+) -> ApiResponse<CardSet> {
+    if !user.is_staff() {
+        return ApiResponse::forbidden("Sólo el staff puede subir íconos de conjuntos".to_string());
+    }
+
+    let card_set = match state.card_set_service.get_card_set_by_id(id).await {
+        Ok(Some(card_set)) => card_set,
+        Ok(None) => return error_response(format!("Conjunto de cartas con ID {} no encontrado", id), 404),
+        Err(e) => return error_response(e.to_string(), 500),
+    };
+
+    let image = match read_image(&mut multipart, state.max_upload_bytes).await {
+        Ok(image) => image,
+        Err(error) => return error,
+    };
+
+    let (key, icon_url, data) = match store_original(&state, format!("card-sets/{}/icon", id), image).await {
+        Ok(stored) => stored,
+        Err(e) => return error_response(e.to_string(), 500),
+    };
+
+    match state.card_set_service.set_card_set_icon_url(id, &icon_url, &ImageDetails::pending()).await {
+        Ok(Some(updated)) => {
+            enqueue_processing(&state, ImageTarget::CardSetIcon(id), key, icon_url, data).await;
+            delete_previous(&state, card_set.icon_url.as_deref(), card_set.icon_details.as_ref()).await;
+            json_response(updated)
+        },
+        Ok(None) => error_response(format!("Conjunto de cartas con ID {} no encontrado", id), 404),
+        Err(e) => error_response(e.to_string(), 500),
+    }
+}
+
+// Sube la imagen de una carta y actualiza su image_url (sólo staff)
+async fn upload_card_image(
+    State(state): State<Arc<AppState>>,
+    Path(id): Path<Uuid>,
+    user: AuthUser,
+    mut multipart: Multipart,
+) -> ApiResponse<Card> {
+    if !user.is_staff() {
+        return ApiResponse::forbidden("Sólo el staff puede subir imágenes de cartas".to_string());
+    }
+
+    let card = match state.card_service.get_card_by_id(id, CatalogVisibility::Staff).await {
+        Ok(Some(card)) => card,
+        Ok(None) => return error_response(format!("Carta con ID {} no encontrada", id), 404),
+        Err(e) => return error_response(e.to_string(), 500),
+    };
+
+    let image = match read_image(&mut multipart, state.max_upload_bytes).await {
+        Ok(image) => image,
+        Err(error) => return error,
+    };
+
+    let (key, image_url, data) = match store_original(&state, format!("cards/{}/image", id), image).await {
+        Ok(stored) => stored,
+        Err(e) => return error_response(e.to_string(), 500),
+    };
+
+    match state.card_service.set_card_image_url(id, &image_url, &ImageDetails::pending()).await {
+        Ok(Some(updated)) => {
+            enqueue_processing(&state, ImageTarget::CardImage(id), key, image_url, data).await;
+            delete_previous(&state, card.image_url.as_deref(), card.image_details.as_ref()).await;
+            json_response(updated)
+        },
+        Ok(None) => error_response(format!("Carta con ID {} no encontrada", id), 404),
+        Err(e) => error_response(e.to_string(), 500),
+    }
+}
+
+// Identifica una carta a partir de una foto comparando hashes perceptuales
+async fn identify_card(
+    State(state): State<Arc<AppState>>,
+    Query(query): Query<IdentifyQuery>,
+    user: AuthUser,
+    mut multipart: Multipart,
+) -> ApiResponse<Vec<CardMatch>> {
+    // Cada foto se decodifica y se compara con todo el catálogo: sólo para cuentas
+    if user.is_anonymous() {
+        return ApiResponse::forbidden("Inicia sesión con una cuenta para identificar cartas".to_string());
+    }
+
+    let limit = query.limit.unwrap_or(DEFAULT_IDENTIFY_LIMIT).clamp(1, MAX_IDENTIFY_LIMIT);
+
+    let image = match read_image(&mut multipart, state.max_upload_bytes).await {
+        Ok(image) => image,
+        Err(error) => return error,
+    };
+
+    let hash = match tokio::task::spawn_blocking(move || perceptual_hash(&image.data)).await {
+        Ok(Ok(hash)) => hash,
+        Ok(Err(e)) => return validation_error(format!("No se pudo leer la imagen: {}", e), None),
+        Err(e) => return error_response(e.to_string(), 500),
+    };
+
+    let visibility = CatalogVisibility::for_user(Some(&user));
+
+    match state.card_service.identify_card(hash, limit, visibility).await {
+        Ok(matches) => json_response(matches),
+        Err(e) => error_response(e.to_string(), 500),
+    }
+}
@@ -0,0 +1,164 @@
+use axum::{
+    body::Body,
+    extract::{DefaultBodyLimit, Multipart, Path, Query, State},
+    http::{header, StatusCode},
+    response::{IntoResponse, Response},
+    routing::{get, post},
+    Router,
+};
+use chrono::Utc;
+use serde::Deserialize;
+use std::sync::Arc;
+use uuid::Uuid;
+
+use crate::api::state::AppState;
+use crate::api::uploads::{MULTIPART_OVERHEAD_BYTES, read_image};
+use crate::domain::files::{UserFile, UserFileResponse};
+use crate::infrastructure::images::strip_metadata;
+use crate::utils::extractors::AuthUser;
+use crate::utils::response::{ApiResponse, error_response, json_response};
+
+// Ruta pública (bajo /api/v1) desde la que se descargan los archivos con URL firmada
+const SIGNED_FILES_PATH: &str = "/api/v1/files";
+
+#[derive(Debug, Deserialize)]
+pub struct SignedFileQuery {
+    pub expires: i64,
+    pub signature: String,
+}
+
+pub fn user_files_routes(app_state: Arc<AppState>) -> Router {
+    let body_limit = app_state.max_upload_bytes + MULTIPART_OVERHEAD_BYTES;
+
+    Router::new()
+        .route("/users/me/files", post(upload_user_file).layer(DefaultBodyLimit::max(body_limit)))
+        .route("/users/me/files", get(get_user_files))
+        .route("/users/me/files/:id", get(get_user_file).delete(delete_user_file))
+        .route("/files/:id", get(download_signed_file))
+        .with_state(app_state)
+}
+
+fn with_signed_url(state: &AppState, file: UserFile) -> UserFileResponse {
+    let signed = state.url_signer.sign(&format!("{}/{}", SIGNED_FILES_PATH, file.id));
+
+    UserFileResponse {
+        file,
+        url: signed.url,
+        url_expires_at: signed.expires_at,
+    }
+}
+
+// Sube un archivo privado a la carpeta del usuario
+async fn upload_user_file(
+    State(state): State<Arc<AppState>>,
+    user: AuthUser,
+    mut multipart: Multipart,
+) -> ApiResponse<UserFileResponse> {
+    let image = match read_image(&mut multipart, state.max_upload_bytes).await {
+        Ok(image) => image,
+        Err(error) => return error,
+    };
+
+    // Las fotos de móvil suelen incluir la ubicación en los metadatos EXIF
+    let format = image.format;
+    let file_name = image.file_name.clone();
+    let data = match tokio::task::spawn_blocking(move || strip_metadata(&image.data)).await {
+        Ok(Ok(data)) => data,
+        Ok(Err(e)) => return error_response(format!("No se pudo leer la imagen: {}", e), 400),
+        Err(e) => return error_response(e.to_string(), 500),
+    };
+
+    let key = format!("users/{}/{}.{}", user.uid, Uuid::new_v4(), format.extension());
+    let size_bytes = data.len() as i64;
+
+    if let Err(e) = state.private_storage.put(&key, format.content_type(), data).await {
+        return error_response(e.to_string(), 500);
+    }
+
+    let file = UserFile::new(user.uid, key.clone(), file_name, format.content_type().to_string(), size_bytes);
+
+    match state.user_file_service.create_file(file).await {
+        Ok(file) => ApiResponse::created(with_signed_url(&state, file)),
+        Err(e) => {
+            // Sin registro el archivo quedaría huérfano
+            if let Err(e) = state.private_storage.delete(&key).await {
+                tracing::warn!("No se pudo borrar el archivo huérfano '{}': {}", key, e);
+            }
+            error_response(e.to_string(), 500)
+        }
+    }
+}
+
+async fn get_user_files(
+    State(state): State<Arc<AppState>>,
+    user: AuthUser,
+) -> ApiResponse<Vec<UserFileResponse>> {
+    match state.user_file_service.get_files_by_owner(&user.uid).await {
+        Ok(files) => json_response(files.into_iter().map(|file| with_signed_url(&state, file)).collect()),
+        Err(e) => error_response(e.to_string(), 500),
+    }
+}
+
+async fn get_user_file(
+    State(state): State<Arc<AppState>>,
+    Path(id): Path<Uuid>,
+    user: AuthUser,
+) -> ApiResponse<UserFileResponse> {
+    match state.user_file_service.get_owned_file(id, &user.uid).await {
+        Ok(Some(file)) => json_response(with_signed_url(&state, file)),
+        Ok(None) => error_response(format!("Archivo con ID {} no encontrado", id), 404),
+        Err(e) => error_response(e.to_string(), 500),
+    }
+}
+
+async fn delete_user_file(
+    State(state): State<Arc<AppState>>,
+    Path(id): Path<Uuid>,
+    user: AuthUser,
+) -> ApiResponse<()> {
+    match state.user_file_service.delete_file(id, &user.uid).await {
+        Ok(Some(file)) => {
+            if let Err(e) = state.private_storage.delete(&file.storage_key).await {
+                tracing::warn!("No se pudo borrar el archivo '{}': {}", file.storage_key, e);
+            }
+            json_response(())
+        },
+        Ok(None) => error_response(format!("Archivo con ID {} no encontrado", id), 404),
+        Err(e) => error_response(e.to_string(), 500),
+    }
+}
+
+// Descarga con URL firmada: no requiere autenticación, la firma es la autorización
+async fn download_signed_file(
+    State(state): State<Arc<AppState>>,
+    Path(id): Path<Uuid>,
+    Query(query): Query<SignedFileQuery>,
+) -> Response {
+    let path = format!("{}/{}", SIGNED_FILES_PATH, id);
+    if !state.url_signer.verify(&path, query.expires, &query.signature) {
+        return ApiResponse::<()>::forbidden("URL inválida o caducada".to_string()).into_response();
+    }
+
+    let file = match state.user_file_service.get_file_by_id(id).await {
+        Ok(Some(file)) => file,
+        Ok(None) => return error_response::<()>(format!("Archivo con ID {} no encontrado", id), 404).into_response(),
+        Err(e) => return error_response::<()>(e.to_string(), 500).into_response(),
+    };
+
+    let data = match state.private_storage.get(&file.storage_key).await {
+        Ok(Some(data)) => data,
+        Ok(None) => return error_response::<()>(format!("Archivo con ID {} no encontrado", id), 404).into_response(),
+        Err(e) => return error_response::<()>(e.to_string(), 500).into_response(),
+    };
+
+    // La respuesta sólo puede guardarse en el navegador y no más allá de la caducidad de la URL
+    let max_age = (query.expires - Utc::now().timestamp()).max(0);
+
+    Response::builder()
+        .status(StatusCode::OK)
+        .header(header::CONTENT_TYPE, &file.content_type)
+        .header(header::CACHE_CONTROL, format!("private, max-age={}", max_age))
+        .header(header::X_CONTENT_TYPE_OPTIONS, "nosniff")
+        .body(Body::from(data))
+        .unwrap_or_else(|e| error_response::<()>(e.to_string(), 500).into_response())
+}
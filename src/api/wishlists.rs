@@ -0,0 +1,247 @@
+use axum::{
+    extract::{Path, State},
+    routing::{get, put},
+    Router,
+};
+use std::sync::Arc;
+use uuid::Uuid;
+
+use crate::api::access::{Access, OwnedResource, authorize};
+use crate::api::state::AppState;
+use crate::api::collections::resolve_owner;
+use crate::domain::cards::{CatalogVisibility, Validable};
+use crate::domain::wishlists::{Wishlist, WishlistDetail, WishlistDto, WishlistItem, WishlistItemDto, WishlistShareLink};
+use crate::utils::extractors::{AuthUser, ValidatedJson};
+use crate::utils::response::{ApiResponse, error_response, json_response, validation_error};
+
+// Ruta pública (bajo /api/v1) de las listas compartidas por enlace
+const SHARED_WISHLISTS_PATH: &str = "/api/v1/wishlists/shared";
+
+pub fn wishlists_routes(app_state: Arc<AppState>) -> Router {
+    Router::new()
+        .route("/users/:user_id/wishlists", get(get_user_wishlists).post(create_wishlist))
+        .route("/wishlists/shared/:token", get(get_shared_wishlist))
+        .route("/wishlists/:id", get(get_wishlist).put(update_wishlist).delete(delete_wishlist))
+        .route("/wishlists/:id/items/:card_id", put(set_wishlist_item).delete(delete_wishlist_item))
+        .route("/wishlists/:id/share", get(get_share_link).post(rotate_share_link).delete(revoke_share_link))
+        .with_state(app_state)
+}
+
+async fn find_wishlist<T>(state: &AppState, id: Uuid, user: &AuthUser, access: Access) -> Result<Wishlist, ApiResponse<T>> {
+    authorize(state.wishlist_service.get_wishlist_by_id(id).await, id, user, access)
+}
+
+fn not_found<T>(id: Uuid) -> ApiResponse<T> {
+    error_response(Wishlist::not_found_message(id), 404)
+}
+
+fn share_link(wishlist: &Wishlist) -> Option<WishlistShareLink> {
+    wishlist.share_token.as_ref().map(|token| WishlistShareLink {
+        share_token: token.clone(),
+        url: format!("{}/{}", SHARED_WISHLISTS_PATH, token),
+    })
+}
+
+// Las listas propias (o de cualquiera, para administradores) incluyen las privadas
+async fn get_user_wishlists(
+    State(state): State<Arc<AppState>>,
+    Path(user_id): Path<String>,
+    user: AuthUser,
+) -> ApiResponse<Vec<Wishlist>> {
+    let (owner_uid, include_private) = match resolve_owner(&user, &user_id) {
+        Ok(owner_uid) => (owner_uid, true),
+        Err(_) => (user_id, false),
+    };
+
+    match state.wishlist_service.get_wishlists_by_owner(&owner_uid, include_private).await {
+        Ok(mut wishlists) => {
+            if !include_private {
+                wishlists.iter_mut().for_each(|wishlist| wishlist.share_token = None);
+            }
+            json_response(wishlists)
+        }
+        Err(e) => error_response(e.to_string(), 500),
+    }
+}
+
+async fn create_wishlist(
+    State(state): State<Arc<AppState>>,
+    Path(user_id): Path<String>,
+    user: AuthUser,
+    ValidatedJson(payload): ValidatedJson<WishlistDto>,
+) -> ApiResponse<Wishlist> {
+    let owner_uid = match resolve_owner(&user, &user_id) {
+        Ok(owner_uid) => owner_uid,
+        Err(message) => return ApiResponse::forbidden(message),
+    };
+
+    if let Err(e) = payload.validate() {
+        return validation_error(format!("Error de validación: {}", e), None);
+    }
+
+    match state.wishlist_service.create_wishlist(owner_uid, &payload).await {
+        Ok(wishlist) => ApiResponse::created(wishlist),
+        Err(e) => error_response(e.to_string(), 500),
+    }
+}
+
+async fn get_wishlist(
+    State(state): State<Arc<AppState>>,
+    Path(id): Path<Uuid>,
+    user: AuthUser,
+) -> ApiResponse<WishlistDetail> {
+    let wishlist = match find_wishlist(&state, id, &user, Access::Read).await {
+        Ok(wishlist) => wishlist,
+        Err(response) => return response,
+    };
+
+    match state.wishlist_service.get_wishlist_detail(wishlist).await {
+        Ok(detail) => json_response(detail),
+        Err(e) => error_response(e.to_string(), 500),
+    }
+}
+
+// Lectura por enlace: no requiere autenticación, el token es la autorización
+async fn get_shared_wishlist(
+    State(state): State<Arc<AppState>>,
+    Path(token): Path<String>,
+) -> ApiResponse<WishlistDetail> {
+    match state.wishlist_service.get_shared_wishlist(&token).await {
+        Ok(Some(detail)) => json_response(detail),
+        Ok(None) => error_response("El enlace no es válido o ha sido revocado".to_string(), 404),
+        Err(e) => error_response(e.to_string(), 500),
+    }
+}
+
+async fn update_wishlist(
+    State(state): State<Arc<AppState>>,
+    Path(id): Path<Uuid>,
+    user: AuthUser,
+    ValidatedJson(payload): ValidatedJson<WishlistDto>,
+) -> ApiResponse<Wishlist> {
+    if let Err(response) = find_wishlist(&state, id, &user, Access::Manage).await {
+        return response;
+    }
+
+    if let Err(e) = payload.validate() {
+        return validation_error(format!("Error de validación: {}", e), None);
+    }
+
+    match state.wishlist_service.update_wishlist(id, &payload).await {
+        Ok(Some(wishlist)) => json_response(wishlist),
+        Ok(None) => not_found(id),
+        Err(e) => error_response(e.to_string(), 500),
+    }
+}
+
+async fn delete_wishlist(
+    State(state): State<Arc<AppState>>,
+    Path(id): Path<Uuid>,
+    user: AuthUser,
+) -> ApiResponse<()> {
+    if let Err(response) = find_wishlist(&state, id, &user, Access::Manage).await {
+        return response;
+    }
+
+    match state.wishlist_service.delete_wishlist(id).await {
+        Ok(true) => json_response(()),
+        Ok(false) => not_found(id),
+        Err(e) => error_response(e.to_string(), 500),
+    }
+}
+
+// Añade una carta o actualiza su cantidad y prioridad
+async fn set_wishlist_item(
+    State(state): State<Arc<AppState>>,
+    Path((id, card_id)): Path<(Uuid, Uuid)>,
+    user: AuthUser,
+    ValidatedJson(payload): ValidatedJson<WishlistItemDto>,
+) -> ApiResponse<WishlistItem> {
+    if let Err(response) = find_wishlist(&state, id, &user, Access::Manage).await {
+        return response;
+    }
+
+    if let Err(e) = payload.validate() {
+        return validation_error(format!("Error de validación: {}", e), None);
+    }
+
+    let visibility = CatalogVisibility::for_user(Some(&user));
+    match state.card_service.get_card_by_id(card_id, visibility).await {
+        Ok(Some(_)) => {},
+        Ok(None) => return error_response(format!("Carta con ID {} no encontrada", card_id), 404),
+        Err(e) => return error_response(e.to_string(), 500),
+    }
+
+    match state.wishlist_service.set_item(id, card_id, &payload).await {
+        Ok(item) => json_response(item),
+        Err(e) => error_response(e.to_string(), 500),
+    }
+}
+
+async fn delete_wishlist_item(
+    State(state): State<Arc<AppState>>,
+    Path((id, card_id)): Path<(Uuid, Uuid)>,
+    user: AuthUser,
+) -> ApiResponse<()> {
+    if let Err(response) = find_wishlist(&state, id, &user, Access::Manage).await {
+        return response;
+    }
+
+    match state.wishlist_service.delete_item(id, card_id).await {
+        Ok(true) => json_response(()),
+        Ok(false) => error_response(format!("La carta con ID {} no está en la lista de deseos", card_id), 404),
+        Err(e) => error_response(e.to_string(), 500),
+    }
+}
+
+async fn get_share_link(
+    State(state): State<Arc<AppState>>,
+    Path(id): Path<Uuid>,
+    user: AuthUser,
+) -> ApiResponse<WishlistShareLink> {
+    let wishlist = match find_wishlist(&state, id, &user, Access::Manage).await {
+        Ok(wishlist) => wishlist,
+        Err(response) => return response,
+    };
+
+    match share_link(&wishlist) {
+        Some(link) => json_response(link),
+        None => error_response("La lista de deseos no se ha compartido".to_string(), 404),
+    }
+}
+
+// Genera un enlace nuevo; el anterior deja de funcionar
+async fn rotate_share_link(
+    State(state): State<Arc<AppState>>,
+    Path(id): Path<Uuid>,
+    user: AuthUser,
+) -> ApiResponse<WishlistShareLink> {
+    if let Err(response) = find_wishlist(&state, id, &user, Access::Manage).await {
+        return response;
+    }
+
+    match state.wishlist_service.rotate_share_token(id).await {
+        Ok(Some(wishlist)) => match share_link(&wishlist) {
+            Some(link) => ApiResponse::created(link),
+            None => error_response("No se pudo compartir la lista de deseos".to_string(), 500),
+        },
+        Ok(None) => not_found(id),
+        Err(e) => error_response(e.to_string(), 500),
+    }
+}
+
+async fn revoke_share_link(
+    State(state): State<Arc<AppState>>,
+    Path(id): Path<Uuid>,
+    user: AuthUser,
+) -> ApiResponse<()> {
+    if let Err(response) = find_wishlist(&state, id, &user, Access::Manage).await {
+        return response;
+    }
+
+    match state.wishlist_service.revoke_share_token(id).await {
+        Ok(Some(_)) => json_response(()),
+        Ok(None) => not_found(id),
+        Err(e) => error_response(e.to_string(), 500),
+    }
+}
@@ -0,0 +1,196 @@
+use std::error::Error;
+use std::path::Path;
+
+use crate::config::CooccurrenceConfig;
+use crate::domain::cards::{
+    BulkFormat, CardService, CardSetService, CatalogVisibility, CreateCardDto, CreateCardSetDto, PgCardRepository,
+    PgCardSetRepository, export_cards, export_rows, parse_rows,
+};
+use crate::domain::decks::{DeckService, PgDeckRepository};
+use crate::infrastructure::database::init_database;
+use crate::infrastructure::images::perceptual_hash;
+
+const USAGE: &str = "Uso:
+  kodem_cards_backend import <sets|cards> <archivo> [--format csv|ndjson] [--dry-run]
+  kodem_cards_backend export <sets|cards> [--format csv|ndjson] [--output archivo]
+  kodem_cards_backend hash-images
+  kodem_cards_backend cooccurrence [--rebuild]";
+
+#[derive(Debug, Clone, Copy)]
+enum Entity {
+    Sets,
+    Cards,
+}
+
+struct Options {
+    format: Option<BulkFormat>,
+    dry_run: bool,
+    output: Option<String>,
+    positional: Vec<String>,
+}
+
+/// Runs a catalog import/export command with the same validation and
+/// transaction semantics as the HTTP endpoints.
+pub async fn run(args: Vec<String>) -> Result<(), Box<dyn Error>> {
+    let (command, rest) = args.split_first().ok_or(USAGE)?;
+
+    if command == "hash-images" {
+        return hash_images().await;
+    }
+
+    if command == "cooccurrence" {
+        return refresh_cooccurrence(rest).await;
+    }
+
+    let options = parse_options(rest)?;
+
+    let entity = match options.positional.first().map(String::as_str) {
+        Some("sets") => Entity::Sets,
+        Some("cards") => Entity::Cards,
+        _ => return Err(USAGE.into()),
+    };
+
+    let database_url = std::env::var("DATABASE_URL")?;
+    let pool = init_database(&database_url).await?;
+    let card_set_service = CardSetService::new(PgCardSetRepository::new(pool.clone()));
+    let card_service = CardService::new(PgCardRepository::new(pool));
+
+    match command.as_str() {
+        "import" => {
+            let path = options.positional.get(1).ok_or(USAGE)?;
+            let format = match options.format {
+                Some(format) => format,
+                None => format_from_extension(path)?,
+            };
+            let data = std::fs::read(path)?;
+
+            let report = match entity {
+                Entity::Sets => {
+                    let rows = parse_rows::<CreateCardSetDto>(format, &data)?;
+                    card_set_service.import_card_sets(rows, options.dry_run).await?
+                }
+                Entity::Cards => {
+                    let rows = parse_rows::<CreateCardDto>(format, &data)?;
+                    card_service.import_cards(rows, options.dry_run).await?
+                }
+            };
+
+            println!("{}", serde_json::to_string_pretty(&report)?);
+
+            if report.has_errors() {
+                return Err(format!("{} fila(s) con errores; no se guardó ningún cambio", report.errors.len()).into());
+            }
+        }
+        "export" => {
+            let format = match (options.format, &options.output) {
+                (Some(format), _) => format,
+                (None, Some(output)) => format_from_extension(output)?,
+                (None, None) => BulkFormat::Csv,
+            };
+
+            let data = match entity {
+                // Quien ejecuta la CLI tiene acceso directo a la base de datos: se exporta todo el catálogo
+                Entity::Sets => export_rows(format, &card_set_service.export_card_sets(CatalogVisibility::Staff).await?)?,
+                Entity::Cards => export_cards(format, &card_service.export_cards(CatalogVisibility::Staff).await?)?,
+            };
+
+            match &options.output {
+                Some(output) => std::fs::write(output, data)?,
+                None => print!("{}", String::from_utf8(data)?),
+            }
+        }
+        _ => return Err(USAGE.into()),
+    }
+
+    Ok(())
+}
+
+/// Computes the perceptual hash of every card image that does not have one
+/// for its current URL, including images imported as external URLs.
+async fn hash_images() -> Result<(), Box<dyn Error>> {
+    let database_url = std::env::var("DATABASE_URL")?;
+    let pool = init_database(&database_url).await?;
+    let card_service = CardService::new(PgCardRepository::new(pool));
+    let client = reqwest::Client::builder().use_rustls_tls().build()?;
+
+    let cards = card_service.get_cards_missing_image_hash().await?;
+    let (mut hashed, mut failed) = (0, 0);
+
+    for card in cards {
+        let Some(image_url) = card.image_url else { continue };
+
+        let result = async {
+            let data = client.get(&image_url).send().await?.error_for_status()?.bytes().await?;
+            let hash = tokio::task::spawn_blocking(move || perceptual_hash(&data)).await??;
+            card_service.save_card_image_hash(card.id, &image_url, hash).await?;
+            Ok::<_, Box<dyn Error>>(())
+        }
+        .await;
+
+        match result {
+            Ok(()) => hashed += 1,
+            Err(e) => {
+                failed += 1;
+                eprintln!("{} ({}): {}", card.name, image_url, e);
+            }
+        }
+    }
+
+    println!("{} imagen(es) procesada(s), {} con errores", hashed, failed);
+    Ok(())
+}
+
+// Indexa los mazos públicos que cambiaron desde la última vez; con --rebuild, todos
+async fn refresh_cooccurrence(args: &[String]) -> Result<(), Box<dyn Error>> {
+    let rebuild = match args {
+        [] => false,
+        [flag] if flag == "--rebuild" => true,
+        _ => return Err(USAGE.into()),
+    };
+
+    let database_url = std::env::var("DATABASE_URL")?;
+    let pool = init_database(&database_url).await?;
+    let deck_service = DeckService::new(PgDeckRepository::new(pool));
+    let batch_size = CooccurrenceConfig::from_env().batch_size;
+
+    let refresh = if rebuild {
+        deck_service.rebuild_cooccurrence(batch_size).await?
+    } else {
+        deck_service.refresh_cooccurrence(batch_size).await?
+    };
+
+    println!("{} mazo(s) indexado(s), {} retirado(s)", refresh.indexed, refresh.removed);
+    Ok(())
+}
+
+fn parse_options(args: &[String]) -> Result<Options, Box<dyn Error>> {
+    let mut options = Options {
+        format: None,
+        dry_run: false,
+        output: None,
+        positional: Vec::new(),
+    };
+
+    let mut iter = args.iter();
+    while let Some(arg) = iter.next() {
+        match arg.as_str() {
+            "--dry-run" => options.dry_run = true,
+            "--format" => {
+                let value = iter.next().ok_or(USAGE)?;
+                options.format = Some(BulkFormat::parse(value).ok_or_else(|| format!("Formato '{}' no soportado", value))?);
+            }
+            "--output" => options.output = Some(iter.next().ok_or(USAGE)?.clone()),
+            _ => options.positional.push(arg.clone()),
+        }
+    }
+
+    Ok(options)
+}
+
+fn format_from_extension(path: &str) -> Result<BulkFormat, Box<dyn Error>> {
+    Path::new(path)
+        .extension()
+        .and_then(|extension| extension.to_str())
+        .and_then(BulkFormat::parse)
+        .ok_or_else(|| format!("No se pudo deducir el formato de '{}'; usa --format", path).into())
+}
@@ -1,7 +1,6 @@
 use jsonwebtoken::{decode, decode_header, DecodingKey, Validation, Algorithm, jwk::JwkSet};
 use reqwest::Client;
 use serde::{Deserialize, Serialize};
-use std::collections::HashMap;
 use std::sync::Arc;
 use std::time::{Duration, SystemTime, UNIX_EPOCH};
 use tokio::sync::RwLock;
@@ -25,6 +24,14 @@ pub struct FirebaseClaims {
     pub name: Option<String>,
     pub picture: Option<String>,
     pub user_id: Option<String>,
+    pub role: Option<String>,        // Custom claim asignado por el script de seed
+    pub firebase: Option<FirebaseSignIn>,
+}
+
+// Claim `firebase` que añade Firebase Auth con el método de inicio de sesión
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct FirebaseSignIn {
+    pub sign_in_provider: Option<String>,
 }
 
 #[derive(Clone)]
@@ -9,6 +9,12 @@ pub struct Config {
     pub server_port: u16,
     pub environment: String,
     pub firebase: FirebaseConfig,
+    pub cache: CacheConfig,
+    pub storage: StorageConfig,
+    pub images: ImageConfig,
+    pub signed_urls: SignedUrlConfig,
+    pub cooccurrence: CooccurrenceConfig,
+    pub trending: TrendingConfig,
 }
 
 #[derive(Debug, Deserialize, Clone)]
@@ -21,6 +27,195 @@ pub struct FirebaseConfig {
     pub emulator_port: Option<u16>,
 }
 
+// Políticas de Cache-Control por ruta del catálogo
+#[derive(Debug, Deserialize, Clone)]
+pub struct CacheConfig {
+    pub card_sets_list: String,
+    pub card_set_detail: String,
+    pub set_cards_list: String,
+    pub card_detail: String,
+}
+
+impl CacheConfig {
+    pub fn from_env() -> Self {
+        Self {
+            card_sets_list: env::var("CACHE_CONTROL_CARD_SETS_LIST")
+                .unwrap_or_else(|_| "public, max-age=60, must-revalidate".to_string()),
+            card_set_detail: env::var("CACHE_CONTROL_CARD_SET_DETAIL")
+                .unwrap_or_else(|_| "public, max-age=300, must-revalidate".to_string()),
+            set_cards_list: env::var("CACHE_CONTROL_SET_CARDS_LIST")
+                .unwrap_or_else(|_| "public, max-age=300, must-revalidate".to_string()),
+            card_detail: env::var("CACHE_CONTROL_CARD_DETAIL")
+                .unwrap_or_else(|_| "public, max-age=300, must-revalidate".to_string()),
+        }
+    }
+}
+
+// Almacenamiento de archivos subidos (íconos de conjuntos e imágenes de cartas)
+#[derive(Debug, Deserialize, Clone)]
+pub struct StorageConfig {
+    pub backend: String,
+    pub local_path: String,
+    pub private_local_path: String,
+    pub public_url: String,
+    pub max_upload_bytes: usize,
+    pub s3: Option<S3Config>,
+}
+
+#[derive(Debug, Deserialize, Clone)]
+pub struct S3Config {
+    pub endpoint: String,
+    pub bucket: String,
+    pub private_bucket: String,
+    pub region: String,
+    pub access_key_id: String,
+    pub secret_access_key: String,
+}
+
+impl StorageConfig {
+    pub fn from_env() -> Result<Self, env::VarError> {
+        let backend = env::var("STORAGE_BACKEND").unwrap_or_else(|_| "local".to_string());
+
+        // Las credenciales de S3 sólo son obligatorias si se usa ese backend
+        let s3 = if backend == "s3" {
+            Some(S3Config {
+                endpoint: env::var("S3_ENDPOINT")?,
+                bucket: env::var("S3_BUCKET")?,
+                private_bucket: env::var("S3_PRIVATE_BUCKET")?,
+                region: env::var("S3_REGION").unwrap_or_else(|_| "us-east-1".to_string()),
+                access_key_id: env::var("S3_ACCESS_KEY_ID")?,
+                secret_access_key: env::var("S3_SECRET_ACCESS_KEY")?,
+            })
+        } else {
+            None
+        };
+
+        Ok(Self {
+            backend,
+            local_path: env::var("STORAGE_LOCAL_PATH").unwrap_or_else(|_| "./uploads".to_string()),
+            private_local_path: env::var("STORAGE_PRIVATE_PATH").unwrap_or_else(|_| "./private-uploads".to_string()),
+            public_url: env::var("STORAGE_PUBLIC_URL").unwrap_or_else(|_| "http://localhost:3001/media".to_string()),
+            max_upload_bytes: env::var("UPLOAD_MAX_BYTES")
+                .unwrap_or_else(|_| "5242880".to_string())
+                .parse()
+                .unwrap_or(5 * 1024 * 1024),
+            s3,
+        })
+    }
+}
+
+// Variantes que genera el pipeline de imágenes a partir de cada imagen subida
+#[derive(Debug, Deserialize, Clone)]
+pub struct ImageConfig {
+    pub thumbnail_width: u32,
+    pub variant_widths: Vec<u32>,
+    pub variant_formats: Vec<String>,
+    pub quality: u8,
+    pub avif_speed: u8,
+}
+
+impl ImageConfig {
+    pub fn from_env() -> Self {
+        let variant_widths = env::var("IMAGE_VARIANT_WIDTHS")
+            .unwrap_or_else(|_| "320,640,1024".to_string())
+            .split(',')
+            .filter_map(|width| width.trim().parse().ok())
+            .filter(|width| *width > 0)
+            .collect();
+
+        // Formatos soportados: webp y avif
+        let variant_formats = env::var("IMAGE_VARIANT_FORMATS")
+            .unwrap_or_else(|_| "webp,avif".to_string())
+            .split(',')
+            .map(|format| format.trim().to_ascii_lowercase())
+            .filter(|format| format == "webp" || format == "avif")
+            .collect();
+
+        Self {
+            thumbnail_width: env::var("IMAGE_THUMBNAIL_WIDTH")
+                .ok()
+                .and_then(|width| width.parse().ok())
+                .unwrap_or(160),
+            variant_widths,
+            variant_formats,
+            quality: env::var("IMAGE_QUALITY")
+                .ok()
+                .and_then(|quality| quality.parse().ok())
+                .filter(|quality| (1..=100).contains(quality))
+                .unwrap_or(80),
+            avif_speed: env::var("IMAGE_AVIF_SPEED")
+                .ok()
+                .and_then(|speed| speed.parse().ok())
+                .filter(|speed| (1..=10).contains(speed))
+                .unwrap_or(8),
+        }
+    }
+}
+
+// Firma de las URLs temporales de los archivos privados de cada usuario
+#[derive(Debug, Deserialize, Clone)]
+pub struct SignedUrlConfig {
+    pub secret: String,
+    pub ttl_secs: i64,
+}
+
+impl SignedUrlConfig {
+    pub fn from_env() -> Result<Self, env::VarError> {
+        Ok(Self {
+            // Si no hay un secreto propio se reutiliza JWT_SECRET
+            secret: env::var("SIGNED_URL_SECRET").or_else(|_| env::var("JWT_SECRET"))?,
+            ttl_secs: env::var("SIGNED_URL_TTL_SECS")
+                .ok()
+                .and_then(|ttl| ttl.parse().ok())
+                .filter(|ttl| *ttl > 0)
+                .unwrap_or(900),
+        })
+    }
+}
+
+// Refresco en segundo plano de las estadísticas de cartas jugadas juntas
+#[derive(Debug, Deserialize, Clone)]
+pub struct CooccurrenceConfig {
+    /// 0 desactiva el refresco automático
+    pub refresh_secs: u64,
+    /// Mazos que se leen de cada vez al buscar los que cambiaron
+    pub batch_size: i64,
+}
+
+impl CooccurrenceConfig {
+    pub fn from_env() -> Self {
+        Self {
+            refresh_secs: env::var("COOCCURRENCE_REFRESH_SECS")
+                .ok()
+                .and_then(|secs| secs.parse().ok())
+                .unwrap_or(300),
+            batch_size: env::var("COOCCURRENCE_BATCH_SIZE")
+                .ok()
+                .and_then(|size| size.parse().ok())
+                .filter(|size| *size > 0)
+                .unwrap_or(500),
+        }
+    }
+}
+
+// Envejecimiento en segundo plano de las puntuaciones de tendencia de la galería
+#[derive(Debug, Deserialize, Clone)]
+pub struct TrendingConfig {
+    /// 0 desactiva el refresco automático
+    pub refresh_secs: u64,
+}
+
+impl TrendingConfig {
+    pub fn from_env() -> Self {
+        Self {
+            refresh_secs: env::var("TRENDING_REFRESH_SECS")
+                .ok()
+                .and_then(|secs| secs.parse().ok())
+                .unwrap_or(600),
+        }
+    }
+}
+
 impl Config {
     pub fn from_env() -> Result<Self, env::VarError> {
         // Determinar si se debe usar el emulador de Firebase
@@ -61,6 +256,12 @@ impl Config {
                 emulator_host,
                 emulator_port,
             },
+            cache: CacheConfig::from_env(),
+            storage: StorageConfig::from_env()?,
+            images: ImageConfig::from_env(),
+            signed_urls: SignedUrlConfig::from_env()?,
+            cooccurrence: CooccurrenceConfig::from_env(),
+            trending: TrendingConfig::from_env(),
         })
     }
 }
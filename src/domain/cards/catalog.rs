@@ -0,0 +1,181 @@
+use serde::Serialize;
+use std::collections::HashMap;
+use uuid::Uuid;
+
+use super::model::Card;
+
+// Similitud mínima (Levenshtein normalizado) para aceptar un nombre aproximado
+pub const FUZZY_MATCH_THRESHOLD: f64 = 0.85;
+
+// Sugerencias que se ofrecen cuando un nombre no se puede resolver
+const MAX_SUGGESTIONS: usize = 3;
+const MIN_SUGGESTION_SCORE: f64 = 0.5;
+
+/// Snapshot of the visible catalog, indexed to resolve user-typed card
+/// references (collection imports, decklists) without a query per line.
+pub struct CardCatalog {
+    cards: Vec<Card>,
+    set_codes: HashMap<Uuid, String>,
+    by_id: HashMap<Uuid, usize>,
+    by_number: HashMap<(String, i32), usize>,
+    by_name: HashMap<String, Vec<usize>>,
+}
+
+/// Outcome of resolving a card name
+pub enum NameResolution<'a> {
+    Exact(&'a Card),
+    /// Closest name above the similarity threshold
+    Fuzzy { card: &'a Card, score: f64 },
+    /// The name is printed in several sets; a set code is needed to pick one
+    Ambiguous(Vec<&'a Card>),
+    NotFound { suggestions: Vec<&'a Card> },
+}
+
+/// Short reference to a card, used in reports
+#[derive(Debug, Clone, Serialize)]
+pub struct CardReference {
+    pub card_id: Uuid,
+    pub name: String,
+    pub set_code: String,
+    pub collector_number: i32,
+}
+
+impl CardCatalog {
+    pub fn new(cards: Vec<Card>, set_codes: HashMap<Uuid, String>) -> Self {
+        let mut by_id = HashMap::with_capacity(cards.len());
+        let mut by_number = HashMap::with_capacity(cards.len());
+        let mut by_name: HashMap<String, Vec<usize>> = HashMap::new();
+
+        for (index, card) in cards.iter().enumerate() {
+            by_id.insert(card.id, index);
+            if let Some(code) = set_codes.get(&card.set_id) {
+                by_number.insert((code.to_uppercase(), card.collector_number), index);
+            }
+            by_name.entry(normalize_name(&card.name)).or_default().push(index);
+        }
+
+        Self { cards, set_codes, by_id, by_number, by_name }
+    }
+
+    pub fn set_code(&self, card: &Card) -> Option<&str> {
+        self.set_codes.get(&card.set_id).map(String::as_str)
+    }
+
+    pub fn has_set(&self, code: &str) -> bool {
+        self.set_codes.values().any(|set_code| set_code.eq_ignore_ascii_case(code.trim()))
+    }
+
+    pub fn reference(&self, card: &Card) -> CardReference {
+        CardReference {
+            card_id: card.id,
+            name: card.name.clone(),
+            set_code: self.set_code(card).unwrap_or_default().to_string(),
+            collector_number: card.collector_number,
+        }
+    }
+
+    pub fn by_id(&self, id: Uuid) -> Option<&Card> {
+        self.by_id.get(&id).map(|&index| &self.cards[index])
+    }
+
+    /// Every printing of a card's name, the card itself included
+    pub fn printings(&self, card: &Card) -> Vec<&Card> {
+        self.by_name
+            .get(&normalize_name(&card.name))
+            .map(|indexes| indexes.iter().map(|&index| &self.cards[index]).collect())
+            .unwrap_or_default()
+    }
+
+    /// Every printing of a name, compared after `normalize_name`
+    pub fn named(&self, name: &str) -> Vec<&Card> {
+        self.by_name
+            .get(&normalize_name(name))
+            .map(|indexes| indexes.iter().map(|&index| &self.cards[index]).collect())
+            .unwrap_or_default()
+    }
+
+    pub fn by_number(&self, set_code: &str, collector_number: i32) -> Option<&Card> {
+        self.by_number
+            .get(&(set_code.trim().to_uppercase(), collector_number))
+            .map(|&index| &self.cards[index])
+    }
+
+    /// Resolves a name, optionally restricted to one set. Exact matches ignore
+    /// case, accents and punctuation; otherwise the closest name is accepted
+    /// when it is similar enough and no other name is equally close.
+    pub fn resolve_name(&self, name: &str, set_code: Option<&str>) -> NameResolution<'_> {
+        let normalized = normalize_name(name);
+        let in_set = |card: &&Card| match set_code {
+            Some(code) => self.set_code(card).is_some_and(|card_code| card_code.eq_ignore_ascii_case(code.trim())),
+            None => true,
+        };
+
+        let exact: Vec<&Card> = self
+            .by_name
+            .get(&normalized)
+            .map(|indexes| indexes.iter().map(|&index| &self.cards[index]).filter(in_set).collect())
+            .unwrap_or_default();
+
+        match exact.len() {
+            0 => {}
+            1 => return NameResolution::Exact(exact[0]),
+            _ => return NameResolution::Ambiguous(exact),
+        }
+
+        // Similitud por nombre distinto: las reimpresiones comparten nombre
+        let mut scored: Vec<(f64, &str)> = self
+            .by_name
+            .iter()
+            .filter(|(_, indexes)| indexes.iter().any(|&index| in_set(&&self.cards[index])))
+            .map(|(candidate, _)| (strsim::normalized_levenshtein(&normalized, candidate), candidate.as_str()))
+            .collect();
+        scored.sort_by(|a, b| b.0.total_cmp(&a.0).then_with(|| a.1.cmp(b.1)));
+
+        let candidates = |name: &str| -> Vec<&Card> {
+            self.by_name[name].iter().map(|&index| &self.cards[index]).filter(in_set).collect()
+        };
+
+        match scored.as_slice() {
+            [(score, best), rest @ ..]
+                if *score >= FUZZY_MATCH_THRESHOLD && rest.first().is_none_or(|(second, _)| second < score) =>
+            {
+                let cards = candidates(best);
+                if cards.len() == 1 {
+                    NameResolution::Fuzzy { card: cards[0], score: *score }
+                } else {
+                    NameResolution::Ambiguous(cards)
+                }
+            }
+            _ => NameResolution::NotFound {
+                suggestions: scored
+                    .iter()
+                    .filter(|(score, _)| *score >= MIN_SUGGESTION_SCORE)
+                    .take(MAX_SUGGESTIONS)
+                    .flat_map(|(_, name)| candidates(name))
+                    .collect(),
+            },
+        }
+    }
+}
+
+/// Lowercases, removes accents and collapses punctuation and whitespace, so
+/// "Guerra  Roja!" and "guerra roja" compare equal
+pub fn normalize_name(name: &str) -> String {
+    let folded: String = name
+        .chars()
+        .flat_map(char::to_lowercase)
+        .map(|c| match c {
+            'á' | 'à' | 'ä' | 'â' | 'ã' => 'a',
+            'é' | 'è' | 'ë' | 'ê' => 'e',
+            'í' | 'ì' | 'ï' | 'î' => 'i',
+            'ó' | 'ò' | 'ö' | 'ô' | 'õ' => 'o',
+            'ú' | 'ù' | 'ü' | 'û' => 'u',
+            'ñ' => 'n',
+            'ç' => 'c',
+            c if c.is_alphanumeric() => c,
+            _ => ' ',
+        })
+        .collect();
+
+    folded.split_whitespace().collect::<Vec<_>>().join(" ")
+}
@@ -3,7 +3,8 @@ use serde::{Deserialize, Serialize, Deserializer};
 use uuid::Uuid;
 use anyhow::{Result, anyhow};
 
-use super::model::CardSet;
+use super::model::{Card, CardSet};
+use crate::utils::slug::slugify;
 
 pub trait Validable {
     fn validate(&self) -> Result<()>;
@@ -69,12 +70,22 @@ where
     }
 }
 
+// Para PATCH: distingue entre campo ausente (None) y null explícito (Some(None))
+fn flexible_date_format_patch<'de, D>(deserializer: D) -> std::result::Result<Option<Option<DateTime<Utc>>>, D::Error>
+where
+    D: Deserializer<'de>,
+{
+    flexible_date_format_optional(deserializer).map(Some)
+}
+
 #[derive(Debug, Serialize, Deserialize)]
 pub struct CreateCardSetDto {
     pub name: String,
     pub code: String,
     #[serde(deserialize_with = "flexible_date_format")]
     pub release_date: DateTime<Utc>,
+    #[serde(default, deserialize_with = "flexible_date_format_optional")]
+    pub publish_at: Option<DateTime<Utc>>,
     pub icon_url: Option<String>,
     pub total_cards: i32,
 }
@@ -85,12 +96,26 @@ impl CreateCardSetDto {
             self.name.clone(),
             self.code.clone(),
             self.release_date,
+            self.publish_at,
             self.icon_url.clone(),
             self.total_cards,
         )
     }
 }
 
+impl From<&CardSet> for CreateCardSetDto {
+    fn from(card_set: &CardSet) -> Self {
+        Self {
+            name: card_set.name.clone(),
+            code: card_set.code.clone(),
+            release_date: card_set.release_date,
+            publish_at: card_set.publish_at,
+            icon_url: card_set.icon_url.clone(),
+            total_cards: card_set.total_cards,
+        }
+    }
+}
+
 impl Validable for CreateCardSetDto {
     fn validate(&self) -> Result<()> {
         // Validar nombre (no vacío y longitud mínima)
@@ -130,6 +155,13 @@ impl Validable for CreateCardSetDto {
         if self.release_date > now && (self.release_date - now).num_days() > 365 {
             return Err(anyhow!("La fecha de lanzamiento no puede ser más de un año en el futuro"));
         }
+
+        // Validar la fecha de publicación si está presente
+        if let Some(publish_at) = self.publish_at {
+            if publish_at > now && (publish_at - now).num_days() > 365 {
+                return Err(anyhow!("La fecha de publicación no puede ser más de un año en el futuro"));
+            }
+        }
         
         // Validar URL del ícono si está presente
         if let Some(url) = &self.icon_url {
@@ -152,6 +184,8 @@ pub struct UpdateCardSetDto {
     pub code: String,
     #[serde(deserialize_with = "flexible_date_format")]
     pub release_date: DateTime<Utc>,
+    #[serde(default, deserialize_with = "flexible_date_format_optional")]
+    pub publish_at: Option<DateTime<Utc>>,
     pub icon_url: Option<String>,
     pub total_cards: i32,
 }
@@ -162,8 +196,12 @@ impl UpdateCardSetDto {
             id,
             name: self.name.clone(),
             code: self.code.clone(),
+            // El servicio decide si el slug cambia según el nombre actual
+            slug: slugify(&self.name),
             release_date: self.release_date,
+            publish_at: self.publish_at,
             icon_url: self.icon_url.clone(),
+            icon_details: None,
             total_cards: self.total_cards,
             created_at,
             updated_at: Utc::now(),
@@ -210,6 +248,13 @@ impl Validable for UpdateCardSetDto {
         if self.release_date > now && (self.release_date - now).num_days() > 365 {
             return Err(anyhow!("La fecha de lanzamiento no puede ser más de un año en el futuro"));
         }
+
+        // Validar la fecha de publicación si está presente
+        if let Some(publish_at) = self.publish_at {
+            if publish_at > now && (publish_at - now).num_days() > 365 {
+                return Err(anyhow!("La fecha de publicación no puede ser más de un año en el futuro"));
+            }
+        }
         
         // Validar URL del ícono si está presente
         if let Some(url) = &self.icon_url {
@@ -233,6 +278,8 @@ pub struct PatchCardSetDto {
     pub code: Option<String>,
     #[serde(default, deserialize_with = "flexible_date_format_optional")]
     pub release_date: Option<DateTime<Utc>>,
+    #[serde(default, deserialize_with = "flexible_date_format_patch")]
+    pub publish_at: Option<Option<DateTime<Utc>>>, // null elimina la fecha de publicación explícita
     pub icon_url: Option<Option<String>>, // Option<Option<>> para permitir eliminar el valor (null) o no incluirlo
     pub total_cards: Option<i32>,
 }
@@ -251,6 +298,10 @@ impl PatchCardSetDto {
         if let Some(release_date) = self.release_date {
             card_set.release_date = release_date;
         }
+
+        if let Some(publish_at) = self.publish_at {
+            card_set.publish_at = publish_at;
+        }
         
         // Manejo especial para icon_url, que es Option<Option<String>>
         // Esto permite distinguir entre "no actualizar" y "establecer en null"
@@ -315,6 +366,13 @@ impl Validable for PatchCardSetDto {
             }
         }
         
+        if let Some(Some(publish_at)) = self.publish_at {
+            let now = Utc::now();
+            if publish_at > now && (publish_at - now).num_days() > 365 {
+                return Err(anyhow!("La fecha de publicación no puede ser más de un año en el futuro"));
+            }
+        }
+        
         // Validar URL del ícono si está presente y no es None
         if let Some(Some(url)) = &self.icon_url {
             if url.trim().is_empty() {
@@ -328,4 +386,211 @@ impl Validable for PatchCardSetDto {
         
         Ok(())
     }
-} 
\ No newline at end of file
+}
+
+// Acepta la lista de artistas como arreglo JSON o como texto separado por '|' (CSV)
+fn flexible_string_list<'de, D>(deserializer: D) -> std::result::Result<Vec<String>, D::Error>
+where
+    D: Deserializer<'de>,
+{
+    #[derive(Deserialize)]
+    #[serde(untagged)]
+    enum StringOrList {
+        List(Vec<String>),
+        Text(String),
+    }
+
+    let values = match Option::<StringOrList>::deserialize(deserializer)? {
+        None => Vec::new(),
+        Some(StringOrList::List(values)) => values,
+        Some(StringOrList::Text(text)) => text.split('|').map(str::to_string).collect(),
+    };
+
+    Ok(values
+        .into_iter()
+        .map(|value| value.trim().to_string())
+        .filter(|value| !value.is_empty())
+        .collect())
+}
+
+// DTO para crear cartas; el conjunto se referencia por su código
+#[derive(Debug, Serialize, Deserialize)]
+pub struct CreateCardDto {
+    pub set_code: String,
+    pub collector_number: i32,
+    pub name: String,
+    pub card_type: String,
+    pub card_energy: Option<String>,
+    pub rarity: String,
+    pub cost: Option<i32>,
+    #[serde(default, deserialize_with = "flexible_string_list")]
+    pub artists: Vec<String>,
+    pub image_url: Option<String>,
+    #[serde(default, deserialize_with = "flexible_date_format_optional")]
+    pub revealed_at: Option<DateTime<Utc>>,
+}
+
+impl CreateCardDto {
+    pub fn to_model(&self, set_id: Uuid) -> Card {
+        Card {
+            id: Uuid::new_v4(),
+            set_id,
+            collector_number: self.collector_number,
+            name: self.name.trim().to_string(),
+            card_type: self.card_type.trim().to_string(),
+            card_energy: self.card_energy.clone(),
+            rarity: self.rarity.trim().to_string(),
+            cost: self.cost,
+            artists: self.artists.clone(),
+            image_url: self.image_url.clone(),
+            image_details: None,
+            revealed_at: self.revealed_at,
+            created_at: Utc::now(),
+            updated_at: Utc::now(),
+        }
+    }
+
+    pub fn from_model(card: &Card, set_code: String) -> Self {
+        Self {
+            set_code,
+            collector_number: card.collector_number,
+            name: card.name.clone(),
+            card_type: card.card_type.clone(),
+            card_energy: card.card_energy.clone(),
+            rarity: card.rarity.clone(),
+            cost: card.cost,
+            artists: card.artists.clone(),
+            image_url: card.image_url.clone(),
+            revealed_at: card.revealed_at,
+        }
+    }
+}
+
+impl Validable for CreateCardDto {
+    fn validate(&self) -> Result<()> {
+        // Validar código del conjunto
+        if self.set_code.trim().is_empty() {
+            return Err(anyhow!("El código del conjunto no puede estar vacío"));
+        }
+
+        if self.set_code != self.set_code.to_uppercase() {
+            return Err(anyhow!("El código del conjunto debe estar en mayúsculas"));
+        }
+
+        // Validar número de colección
+        if self.collector_number <= 0 {
+            return Err(anyhow!("El número de colección debe ser mayor que cero"));
+        }
+
+        // Validar nombre
+        if self.name.trim().is_empty() {
+            return Err(anyhow!("El nombre no puede estar vacío"));
+        }
+
+        if self.name.len() > 100 {
+            return Err(anyhow!("El nombre no puede exceder los 100 caracteres"));
+        }
+
+        // Validar tipo y rareza
+        if self.card_type.trim().is_empty() {
+            return Err(anyhow!("El tipo de carta no puede estar vacío"));
+        }
+
+        if self.rarity.trim().is_empty() {
+            return Err(anyhow!("La rareza no puede estar vacía"));
+        }
+
+        if let Some(cost) = self.cost {
+            if cost < 0 {
+                return Err(anyhow!("El coste no puede ser negativo"));
+            }
+        }
+
+        // Validar URL de la imagen si está presente
+        if let Some(url) = &self.image_url {
+            if !url.starts_with("http://") && !url.starts_with("https://") {
+                return Err(anyhow!("La URL de la imagen debe comenzar con http:// o https://"));
+            }
+        }
+
+        Ok(())
+    }
+}
+
+// DTO para revelar una carta antes del lanzamiento de su conjunto
+#[derive(Debug, Serialize, Deserialize)]
+pub struct RevealCardDto {
+    #[serde(default, deserialize_with = "flexible_date_format_optional")]
+    pub revealed_at: Option<DateTime<Utc>>,
+}
+
+// Precios cargados por lote en una sola petición
+pub const MAX_PRICES_PER_REQUEST: usize = 1000;
+
+// DTO para fijar el precio de referencia de una carta
+#[derive(Debug, Serialize, Deserialize)]
+pub struct CardPriceDto {
+    pub price_cents: i64,
+    pub currency: String,
+    pub source: Option<String>,
+}
+
+impl CardPriceDto {
+    pub fn currency(&self) -> String {
+        self.currency.trim().to_uppercase()
+    }
+
+    pub fn source(&self) -> Option<String> {
+        self.source
+            .as_deref()
+            .map(str::trim)
+            .filter(|source| !source.is_empty())
+            .map(str::to_string)
+    }
+}
+
+impl Validable for CardPriceDto {
+    fn validate(&self) -> Result<()> {
+        if self.price_cents < 0 {
+            return Err(anyhow!("El precio no puede ser negativo"));
+        }
+
+        let currency = self.currency();
+        if currency.len() != 3 || !currency.chars().all(|c| c.is_ascii_uppercase()) {
+            return Err(anyhow!("La moneda debe ser un código ISO 4217 de tres letras (p. ej. EUR)"));
+        }
+
+        if self.source().is_some_and(|source| source.len() > 100) {
+            return Err(anyhow!("La fuente no puede exceder los 100 caracteres"));
+        }
+
+        Ok(())
+    }
+}
+
+// DTO para cargar precios de varias cartas a la vez
+#[derive(Debug, Serialize, Deserialize)]
+pub struct CardPriceBatchDto {
+    pub prices: Vec<CardPriceEntryDto>,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct CardPriceEntryDto {
+    pub card_id: Uuid,
+    #[serde(flatten)]
+    pub price: CardPriceDto,
+}
+
+impl Validable for CardPriceBatchDto {
+    fn validate(&self) -> Result<()> {
+        if self.prices.is_empty() || self.prices.len() > MAX_PRICES_PER_REQUEST {
+            return Err(anyhow!("Se pueden cargar entre 1 y {} precios por petición", MAX_PRICES_PER_REQUEST));
+        }
+
+        for entry in &self.prices {
+            entry.price.validate().map_err(|e| anyhow!("Carta {}: {}", entry.card_id, e))?;
+        }
+
+        Ok(())
+    }
+}
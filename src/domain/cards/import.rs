@@ -0,0 +1,187 @@
+use anyhow::{Result, anyhow};
+use chrono::{DateTime, Utc};
+use serde::{de::DeserializeOwned, Deserialize, Serialize};
+
+use super::dto::CreateCardDto;
+
+// Formatos soportados para importación y exportación masiva
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum BulkFormat {
+    Csv,
+    Ndjson,
+}
+
+impl BulkFormat {
+    pub fn parse(value: &str) -> Option<Self> {
+        match value.trim().to_lowercase().as_str() {
+            "csv" | "text/csv" => Some(Self::Csv),
+            "ndjson" | "jsonl" | "application/x-ndjson" | "application/ndjson" | "application/jsonl" => Some(Self::Ndjson),
+            _ => None,
+        }
+    }
+
+    pub fn content_type(&self) -> &'static str {
+        match self {
+            Self::Csv => "text/csv; charset=utf-8",
+            Self::Ndjson => "application/x-ndjson",
+        }
+    }
+
+    pub fn extension(&self) -> &'static str {
+        match self {
+            Self::Csv => "csv",
+            Self::Ndjson => "ndjson",
+        }
+    }
+}
+
+// Fila leída del archivo: el número de línea se conserva para el reporte de errores
+pub struct ImportRow<T> {
+    pub line: usize,
+    pub value: std::result::Result<T, String>,
+}
+
+#[derive(Debug, Serialize)]
+pub struct ImportRowError {
+    pub line: usize,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub key: Option<String>,
+    pub message: String,
+}
+
+#[derive(Debug, Serialize)]
+pub struct ImportReport {
+    pub dry_run: bool,
+    pub total_rows: usize,
+    pub created: usize,
+    pub updated: usize,
+    pub errors: Vec<ImportRowError>,
+}
+
+impl ImportReport {
+    pub fn new(dry_run: bool, total_rows: usize) -> Self {
+        Self {
+            dry_run,
+            total_rows,
+            created: 0,
+            updated: 0,
+            errors: Vec::new(),
+        }
+    }
+
+    pub fn has_errors(&self) -> bool {
+        !self.errors.is_empty()
+    }
+}
+
+// Registro CSV de una carta: los artistas se aplanan en una sola columna separada por '|'
+#[derive(Debug, Serialize)]
+struct CardCsvRecord<'a> {
+    set_code: &'a str,
+    collector_number: i32,
+    name: &'a str,
+    card_type: &'a str,
+    card_energy: Option<&'a str>,
+    rarity: &'a str,
+    cost: Option<i32>,
+    artists: String,
+    image_url: Option<&'a str>,
+    revealed_at: Option<DateTime<Utc>>,
+}
+
+impl<'a> From<&'a CreateCardDto> for CardCsvRecord<'a> {
+    fn from(card: &'a CreateCardDto) -> Self {
+        Self {
+            set_code: &card.set_code,
+            collector_number: card.collector_number,
+            name: &card.name,
+            card_type: &card.card_type,
+            card_energy: card.card_energy.as_deref(),
+            rarity: &card.rarity,
+            cost: card.cost,
+            artists: card.artists.join("|"),
+            image_url: card.image_url.as_deref(),
+            revealed_at: card.revealed_at,
+        }
+    }
+}
+
+/// Parses every row of the file; rows that fail to deserialize are kept as errors
+/// so the report can point at them instead of aborting the whole import.
+pub fn parse_rows<T: DeserializeOwned>(format: BulkFormat, data: &[u8]) -> Result<Vec<ImportRow<T>>> {
+    match format {
+        BulkFormat::Csv => {
+            let mut reader = csv::ReaderBuilder::new()
+                .trim(csv::Trim::All)
+                .from_reader(data);
+
+            // Validamos la cabecera antes de procesar filas
+            let headers = reader
+                .headers()
+                .map_err(|e| anyhow!("La cabecera del CSV no es válida: {}", e))?
+                .clone();
+
+            // La línea la da el lector: un campo entre comillas puede ocupar varias
+            let line = |position: Option<&csv::Position>| position.map_or(0, |position| position.line() as usize);
+
+            Ok(reader
+                .records()
+                .map(|record| match record {
+                    Ok(record) => ImportRow {
+                        line: line(record.position()),
+                        value: record.deserialize::<T>(Some(&headers)).map_err(|e| e.to_string()),
+                    },
+                    Err(e) => ImportRow {
+                        line: line(e.position()),
+                        value: Err(e.to_string()),
+                    },
+                })
+                .collect())
+        }
+        BulkFormat::Ndjson => {
+            let text = std::str::from_utf8(data)
+                .map_err(|_| anyhow!("El archivo NDJSON debe estar codificado en UTF-8"))?;
+
+            Ok(text
+                .lines()
+                .enumerate()
+                .filter(|(_, line)| !line.trim().is_empty())
+                .map(|(index, line)| ImportRow {
+                    line: index + 1,
+                    value: serde_json::from_str::<T>(line).map_err(|e| e.to_string()),
+                })
+                .collect())
+        }
+    }
+}
+
+pub fn export_rows<T: Serialize>(format: BulkFormat, rows: &[T]) -> Result<Vec<u8>> {
+    match format {
+        BulkFormat::Csv => {
+            let mut writer = csv::Writer::from_writer(Vec::new());
+            for row in rows {
+                writer.serialize(row)?;
+            }
+            Ok(writer.into_inner()?)
+        }
+        BulkFormat::Ndjson => {
+            let mut output = Vec::new();
+            for row in rows {
+                serde_json::to_writer(&mut output, row)?;
+                output.push(b'\n');
+            }
+            Ok(output)
+        }
+    }
+}
+
+pub fn export_cards(format: BulkFormat, cards: &[CreateCardDto]) -> Result<Vec<u8>> {
+    match format {
+        BulkFormat::Csv => {
+            let records: Vec<CardCsvRecord> = cards.iter().map(CardCsvRecord::from).collect();
+            export_rows(format, &records)
+        }
+        BulkFormat::Ndjson => export_rows(format, cards),
+    }
+}
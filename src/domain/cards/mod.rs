@@ -2,8 +2,14 @@ mod model;
 mod repository;
 mod service;
 mod dto;
+mod import;
+mod pipeline;
+mod catalog;
 
 pub use model::*;
 pub use repository::*;
 pub use service::*;
-pub use dto::*; 
\ No newline at end of file
+pub use dto::*;
+pub use import::*;
+pub use pipeline::*;
+pub use catalog::*; 
\ No newline at end of file
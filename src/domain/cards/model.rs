@@ -1,16 +1,100 @@
 use serde::{Deserialize, Serialize};
 use sqlx::postgres::PgRow;
+use sqlx::types::Json;
 use sqlx::Row;
 use uuid::Uuid;
 use chrono::{DateTime, Utc};
 
+use crate::utils::extractors::AuthUser;
+use crate::utils::slug::slugify;
+
+// Qué parte del catálogo puede ver quien hace la petición
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum CatalogVisibility {
+    Public,
+    Staff,
+}
+
+impl CatalogVisibility {
+    pub fn for_user(user: Option<&AuthUser>) -> Self {
+        match user {
+            Some(user) if user.is_staff() => Self::Staff,
+            _ => Self::Public,
+        }
+    }
+
+    pub fn includes_unreleased(&self) -> bool {
+        *self == Self::Staff
+    }
+}
+
+// Estado del procesamiento en segundo plano de una imagen subida
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ImageStatus {
+    Pending,
+    Ready,
+    Failed,
+}
+
+/// Resized copy of an uploaded image
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ImageVariant {
+    /// `thumbnail` or `resized`
+    pub kind: String,
+    pub format: String,
+    pub width: u32,
+    pub height: u32,
+    pub url: String,
+}
+
+/// Dimensions, placeholder and derivatives generated for an uploaded image
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ImageDetails {
+    pub status: ImageStatus,
+    pub width: Option<u32>,
+    pub height: Option<u32>,
+    pub blurhash: Option<String>,
+    #[serde(default)]
+    pub variants: Vec<ImageVariant>,
+}
+
+impl ImageDetails {
+    pub fn pending() -> Self {
+        Self::with_status(ImageStatus::Pending)
+    }
+
+    pub fn failed() -> Self {
+        Self::with_status(ImageStatus::Failed)
+    }
+
+    fn with_status(status: ImageStatus) -> Self {
+        Self {
+            status,
+            width: None,
+            height: None,
+            blurhash: None,
+            variants: Vec::new(),
+        }
+    }
+}
+
+// Las columnas JSONB de detalles de imagen pueden ser NULL (imágenes externas o anteriores al pipeline)
+fn image_details_from_row(row: &PgRow, column: &str) -> Result<Option<ImageDetails>, sqlx::Error> {
+    let details: Option<Json<ImageDetails>> = row.try_get(column)?;
+    Ok(details.map(|Json(details)| details))
+}
+
 #[derive(Debug, Serialize, Deserialize)]
 pub struct CardSet {
     pub id: Uuid,
     pub name: String,
     pub code: String,
+    pub slug: String,
     pub release_date: DateTime<Utc>,
+    pub publish_at: Option<DateTime<Utc>>,
     pub icon_url: Option<String>,
+    pub icon_details: Option<ImageDetails>,
     pub total_cards: i32,
     pub created_at: DateTime<Utc>,
     pub updated_at: DateTime<Utc>,
@@ -21,20 +105,42 @@ impl CardSet {
         name: String,
         code: String,
         release_date: DateTime<Utc>,
+        publish_at: Option<DateTime<Utc>>,
         icon_url: Option<String>,
         total_cards: i32,
     ) -> Self {
         Self {
             id: Uuid::new_v4(),
+            slug: slugify(&name),
             name,
             code,
             release_date,
+            publish_at,
             icon_url,
+            icon_details: None,
             total_cards,
             created_at: Utc::now(),
             updated_at: Utc::now(),
         }
     }
+
+    /// Moment from which non-staff users can see the set
+    pub fn visible_from(&self) -> DateTime<Utc> {
+        self.publish_at.unwrap_or(self.release_date)
+    }
+
+    pub fn is_released(&self) -> bool {
+        self.visible_from() <= Utc::now()
+    }
+
+    /// Last visible change: the latest edit or, once released, the publication moment
+    pub fn last_modified(&self) -> DateTime<Utc> {
+        if self.is_released() {
+            self.updated_at.max(self.visible_from())
+        } else {
+            self.updated_at
+        }
+    }
 }
 
 impl<'r> sqlx::FromRow<'r, PgRow> for CardSet {
@@ -43,11 +149,86 @@ impl<'r> sqlx::FromRow<'r, PgRow> for CardSet {
             id: row.try_get("id")?,
             name: row.try_get("name")?,
             code: row.try_get("code")?,
+            slug: row.try_get("slug")?,
             release_date: row.try_get("release_date")?,
+            publish_at: row.try_get("publish_at")?,
             icon_url: row.try_get("icon_url")?,
+            icon_details: image_details_from_row(row, "icon_details")?,
             total_cards: row.try_get("total_cards")?,
             created_at: row.try_get("created_at")?,
             updated_at: row.try_get("updated_at")?,
         })
     }
-} 
\ No newline at end of file
+} 
+#[derive(Debug, Serialize, Deserialize)]
+pub struct Card {
+    pub id: Uuid,
+    pub set_id: Uuid,
+    pub collector_number: i32,
+    pub name: String,
+    pub card_type: String,
+    pub card_energy: Option<String>,
+    pub rarity: String,
+    pub cost: Option<i32>,
+    pub artists: Vec<String>,
+    pub image_url: Option<String>,
+    pub image_details: Option<ImageDetails>,
+    pub revealed_at: Option<DateTime<Utc>>,
+    pub created_at: DateTime<Utc>,
+    pub updated_at: DateTime<Utc>,
+}
+
+impl<'r> sqlx::FromRow<'r, PgRow> for Card {
+    fn from_row(row: &'r PgRow) -> Result<Self, sqlx::Error> {
+        Ok(Self {
+            id: row.try_get("id")?,
+            set_id: row.try_get("set_id")?,
+            collector_number: row.try_get("collector_number")?,
+            name: row.try_get("name")?,
+            card_type: row.try_get("card_type")?,
+            card_energy: row.try_get("card_energy")?,
+            rarity: row.try_get("rarity")?,
+            cost: row.try_get("cost")?,
+            artists: row.try_get("artists")?,
+            image_url: row.try_get("image_url")?,
+            image_details: image_details_from_row(row, "image_details")?,
+            revealed_at: row.try_get("revealed_at")?,
+            created_at: row.try_get("created_at")?,
+            updated_at: row.try_get("updated_at")?,
+        })
+    }
+}
+
+/// Catalog card that resembles an identified photo
+#[derive(Debug, Serialize)]
+pub struct CardMatch {
+    pub card: Card,
+    /// Hamming distance between the perceptual hashes (0 = identical)
+    pub distance: u32,
+    /// 1.0 for identical hashes, decreasing to 0.0 at the maximum accepted distance
+    pub confidence: f64,
+}
+
+/// Reference price of a printing, in the currency's minor unit (cents)
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct CardPrice {
+    pub card_id: Uuid,
+    pub price_cents: i64,
+    /// ISO 4217 code, e.g. `EUR`
+    pub currency: String,
+    /// Where the price comes from, e.g. a marketplace
+    pub source: Option<String>,
+    pub updated_at: DateTime<Utc>,
+}
+
+impl<'r> sqlx::FromRow<'r, PgRow> for CardPrice {
+    fn from_row(row: &'r PgRow) -> Result<Self, sqlx::Error> {
+        Ok(Self {
+            card_id: row.try_get("card_id")?,
+            price_cents: row.try_get("price_cents")?,
+            currency: row.try_get("currency")?,
+            source: row.try_get("source")?,
+            updated_at: row.try_get("updated_at")?,
+        })
+    }
+}
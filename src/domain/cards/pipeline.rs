@@ -0,0 +1,150 @@
+use anyhow::{Result, anyhow};
+use std::sync::Arc;
+use tokio::sync::mpsc;
+use uuid::Uuid;
+
+use super::model::{ImageDetails, ImageStatus, ImageVariant};
+use super::repository::{PgCardRepository, PgCardSetRepository};
+use super::service::{CardService, CardSetService};
+use crate::config::ImageConfig;
+use crate::infrastructure::images::process_image;
+use crate::infrastructure::storage::Storage;
+
+// Subidas en cola antes de que las nuevas tengan que esperar
+const QUEUE_CAPACITY: usize = 64;
+
+/// Entity whose image is being processed
+#[derive(Debug, Clone, Copy)]
+pub enum ImageTarget {
+    CardSetIcon(Uuid),
+    CardImage(Uuid),
+}
+
+/// Uploaded image waiting for its derivatives
+pub struct ImageJob {
+    pub target: ImageTarget,
+    /// Storage key of the original; derivatives are stored next to it
+    pub key: String,
+    /// Public URL of the original, used to detect replacements during processing
+    pub url: String,
+    pub data: Vec<u8>,
+}
+
+/// Background worker that generates thumbnails, WebP/AVIF variants,
+/// dimensions and blurhash for uploaded images.
+#[derive(Clone)]
+pub struct ImagePipeline {
+    sender: mpsc::Sender<ImageJob>,
+}
+
+struct Worker {
+    storage: Arc<dyn Storage>,
+    card_set_service: Arc<CardSetService<PgCardSetRepository>>,
+    card_service: Arc<CardService<PgCardRepository>>,
+    config: Arc<ImageConfig>,
+}
+
+impl ImagePipeline {
+    pub fn start(
+        storage: Arc<dyn Storage>,
+        card_set_service: Arc<CardSetService<PgCardSetRepository>>,
+        card_service: Arc<CardService<PgCardRepository>>,
+        config: ImageConfig,
+    ) -> Self {
+        let (sender, mut receiver) = mpsc::channel::<ImageJob>(QUEUE_CAPACITY);
+
+        let worker = Worker {
+            storage,
+            card_set_service,
+            card_service,
+            config: Arc::new(config),
+        };
+
+        tokio::spawn(async move {
+            while let Some(job) = receiver.recv().await {
+                worker.run(job).await;
+            }
+        });
+
+        Self { sender }
+    }
+
+    pub async fn enqueue(&self, job: ImageJob) -> Result<()> {
+        self.sender
+            .send(job)
+            .await
+            .map_err(|_| anyhow!("El pipeline de imágenes no está disponible"))
+    }
+}
+
+impl Worker {
+    async fn run(&self, job: ImageJob) {
+        let details = match self.process(&job).await {
+            Ok(details) => details,
+            Err(e) => {
+                tracing::error!("Error procesando la imagen '{}': {}", job.key, e);
+                ImageDetails::failed()
+            }
+        };
+
+        let result = match job.target {
+            ImageTarget::CardSetIcon(id) => self.card_set_service.set_card_set_icon_details(id, &job.url, &details).await,
+            ImageTarget::CardImage(id) => self.card_service.set_card_image_details(id, &job.url, &details).await,
+        };
+
+        match result {
+            Ok(true) => {}
+            // La imagen se reemplazó mientras se procesaba: las variantes ya no sirven
+            Ok(false) => delete_image_variants(self.storage.as_ref(), Some(&details)).await,
+            Err(e) => tracing::error!("No se pudieron guardar los detalles de '{}': {}", job.key, e),
+        }
+    }
+
+    async fn process(&self, job: &ImageJob) -> Result<ImageDetails> {
+        let data = job.data.clone();
+        let config = self.config.clone();
+        let processed = tokio::task::spawn_blocking(move || process_image(&data, &config)).await??;
+
+        // El hash perceptual de las imágenes de cartas permite identificarlas por foto
+        if let ImageTarget::CardImage(id) = job.target {
+            self.card_service.save_card_image_hash(id, &job.url, processed.perceptual_hash).await?;
+        }
+
+        let stem = job.key.rsplit_once('.').map(|(stem, _)| stem).unwrap_or(&job.key);
+        let mut variants = Vec::with_capacity(processed.derivatives.len());
+
+        for derivative in processed.derivatives {
+            let key = format!("{}-{}-{}.{}", stem, derivative.kind, derivative.width, derivative.extension);
+            let url = self.storage.put(&key, derivative.content_type, derivative.data).await?;
+
+            variants.push(ImageVariant {
+                kind: derivative.kind.to_string(),
+                format: derivative.format.to_string(),
+                width: derivative.width,
+                height: derivative.height,
+                url,
+            });
+        }
+
+        Ok(ImageDetails {
+            status: ImageStatus::Ready,
+            width: Some(processed.width),
+            height: Some(processed.height),
+            blurhash: Some(processed.blurhash),
+            variants,
+        })
+    }
+}
+
+/// Deletes the stored derivatives of an image; failures are only logged
+pub async fn delete_image_variants(storage: &dyn Storage, details: Option<&ImageDetails>) {
+    let prefix = storage.public_url("");
+
+    for variant in details.map(|details| details.variants.as_slice()).unwrap_or_default() {
+        if let Some(key) = variant.url.strip_prefix(prefix.as_str()) {
+            if let Err(e) = storage.delete(key).await {
+                tracing::warn!("No se pudo borrar la variante '{}': {}", key, e);
+            }
+        }
+    }
+}
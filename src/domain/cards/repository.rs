@@ -1,9 +1,13 @@
 use async_trait::async_trait;
-use sqlx::PgPool;
+use sqlx::{PgPool, Row};
 use anyhow::Result;
+use chrono::{DateTime, Utc};
+use std::collections::{HashMap, HashSet};
 use uuid::Uuid;
 
-use super::model::CardSet;
+use sqlx::types::Json;
+
+use super::model::{Card, CardPrice, CardSet, ImageDetails};
 
 #[async_trait]
 pub trait CardSetRepository {
@@ -12,6 +16,22 @@ pub trait CardSetRepository {
     async fn create_card_set(&self, card_set: CardSet) -> Result<CardSet>;
     async fn update_card_set(&self, card_set: CardSet) -> Result<CardSet>;
     async fn delete_card_set(&self, id: Uuid) -> Result<bool>;
+    async fn get_card_set_by_code(&self, code: &str) -> Result<Option<CardSet>>;
+    async fn get_card_set_by_slug(&self, slug: &str) -> Result<Option<CardSet>>;
+    /// The set that used `old_slug` before being renamed
+    async fn get_card_set_by_old_slug(&self, old_slug: &str) -> Result<Option<CardSet>>;
+    async fn is_slug_taken(&self, slug: &str, exclude_id: Option<Uuid>) -> Result<bool>;
+    async fn get_set_ids_with_revealed_cards(&self, ids: &[Uuid]) -> Result<HashSet<Uuid>>;
+    async fn upsert_card_sets(&self, card_sets: Vec<CardSet>, dry_run: bool) -> Result<UpsertCounts>;
+    async fn update_icon_url(&self, id: Uuid, icon_url: &str, details: &ImageDetails) -> Result<Option<CardSet>>;
+    async fn update_icon_details(&self, id: Uuid, icon_url: &str, details: &ImageDetails) -> Result<bool>;
+}
+
+// Resultado de un upsert masivo
+#[derive(Debug, Default, Clone, Copy)]
+pub struct UpsertCounts {
+    pub created: usize,
+    pub updated: usize,
 }
 
 pub struct PgCardSetRepository {
@@ -29,7 +49,7 @@ impl CardSetRepository for PgCardSetRepository {
     async fn get_all_card_sets(&self) -> Result<Vec<CardSet>> {
         let card_sets = sqlx::query_as::<_, CardSet>(
             r#"
-            SELECT id, name, code, release_date, icon_url, total_cards, created_at, updated_at
+            SELECT id, name, code, slug, release_date, publish_at, icon_url, icon_details, total_cards, created_at, updated_at
             FROM card_sets
             ORDER BY release_date DESC
             "#
@@ -43,7 +63,7 @@ impl CardSetRepository for PgCardSetRepository {
     async fn get_card_set_by_id(&self, id: Uuid) -> Result<Option<CardSet>> {
         let card_set = sqlx::query_as::<_, CardSet>(
             r#"
-            SELECT id, name, code, release_date, icon_url, total_cards, created_at, updated_at
+            SELECT id, name, code, slug, release_date, publish_at, icon_url, icon_details, total_cards, created_at, updated_at
             FROM card_sets
             WHERE id = $1
             "#
@@ -58,15 +78,17 @@ impl CardSetRepository for PgCardSetRepository {
     async fn create_card_set(&self, card_set: CardSet) -> Result<CardSet> {
         let created = sqlx::query_as::<_, CardSet>(
             r#"
-            INSERT INTO card_sets (id, name, code, release_date, icon_url, total_cards, created_at, updated_at)
-            VALUES ($1, $2, $3, $4, $5, $6, $7, $8)
-            RETURNING id, name, code, release_date, icon_url, total_cards, created_at, updated_at
+            INSERT INTO card_sets (id, name, code, slug, release_date, publish_at, icon_url, total_cards, created_at, updated_at)
+            VALUES ($1, $2, $3, $4, $5, $6, $7, $8, $9, $10)
+            RETURNING id, name, code, slug, release_date, publish_at, icon_url, icon_details, total_cards, created_at, updated_at
             "#
         )
         .bind(card_set.id)
         .bind(card_set.name)
         .bind(card_set.code)
+        .bind(card_set.slug)
         .bind(card_set.release_date)
+        .bind(card_set.publish_at)
         .bind(card_set.icon_url)
         .bind(card_set.total_cards)
         .bind(card_set.created_at)
@@ -79,31 +101,58 @@ impl CardSetRepository for PgCardSetRepository {
 
     async fn update_card_set(&self, card_set: CardSet) -> Result<CardSet> {
         let now = chrono::Utc::now();
-        
+        let mut tx = self.pool.begin().await?;
+
+        // Si el slug cambia, el anterior se conserva para poder redirigir
+        sqlx::query(
+            r#"
+            INSERT INTO card_set_slug_history (slug, card_set_id)
+            SELECT slug, id FROM card_sets WHERE id = $1 AND slug <> $2
+            ON CONFLICT (slug) DO UPDATE SET card_set_id = EXCLUDED.card_set_id, created_at = NOW()
+            "#
+        )
+        .bind(card_set.id)
+        .bind(&card_set.slug)
+        .execute(&mut *tx)
+        .await?;
+
+        // Un slug recuperado deja de ser histórico
+        sqlx::query("DELETE FROM card_set_slug_history WHERE slug = $1")
+            .bind(&card_set.slug)
+            .execute(&mut *tx)
+            .await?;
+
         let updated = sqlx::query_as::<_, CardSet>(
             r#"
             UPDATE card_sets
             SET 
                 name = $1,
                 code = $2,
-                release_date = $3,
-                icon_url = $4,
-                total_cards = $5,
-                updated_at = $6
-            WHERE id = $7
-            RETURNING id, name, code, release_date, icon_url, total_cards, created_at, updated_at
+                slug = $3,
+                release_date = $4,
+                publish_at = $5,
+                icon_url = $6,
+                icon_details = CASE WHEN icon_url IS DISTINCT FROM $6 THEN NULL ELSE icon_details END,
+                total_cards = $7,
+                updated_at = $8
+            WHERE id = $9
+            RETURNING id, name, code, slug, release_date, publish_at, icon_url, icon_details, total_cards, created_at, updated_at
             "#
         )
         .bind(card_set.name)
         .bind(card_set.code)
+        .bind(card_set.slug)
         .bind(card_set.release_date)
+        .bind(card_set.publish_at)
         .bind(card_set.icon_url)
         .bind(card_set.total_cards)
         .bind(now)
         .bind(card_set.id)
-        .fetch_one(&self.pool)
+        .fetch_one(&mut *tx)
         .await?;
 
+        tx.commit().await?;
+
         Ok(updated)
     }
 
@@ -120,4 +169,531 @@ impl CardSetRepository for PgCardSetRepository {
 
         Ok(result.rows_affected() > 0)
     }
-} 
\ No newline at end of file
+
+    async fn get_card_set_by_code(&self, code: &str) -> Result<Option<CardSet>> {
+        let card_set = sqlx::query_as::<_, CardSet>(
+            r#"
+            SELECT id, name, code, slug, release_date, publish_at, icon_url, icon_details, total_cards, created_at, updated_at
+            FROM card_sets
+            WHERE UPPER(code) = UPPER($1)
+            "#
+        )
+        .bind(code)
+        .fetch_optional(&self.pool)
+        .await?;
+
+        Ok(card_set)
+    }
+
+    async fn get_card_set_by_slug(&self, slug: &str) -> Result<Option<CardSet>> {
+        let card_set = sqlx::query_as::<_, CardSet>(
+            r#"
+            SELECT id, name, code, slug, release_date, publish_at, icon_url, icon_details, total_cards, created_at, updated_at
+            FROM card_sets
+            WHERE slug = $1
+            "#
+        )
+        .bind(slug)
+        .fetch_optional(&self.pool)
+        .await?;
+
+        Ok(card_set)
+    }
+
+    async fn get_card_set_by_old_slug(&self, old_slug: &str) -> Result<Option<CardSet>> {
+        let card_set = sqlx::query_as::<_, CardSet>(
+            r#"
+            SELECT s.id, s.name, s.code, s.slug, s.release_date, s.publish_at, s.icon_url, s.icon_details, s.total_cards, s.created_at, s.updated_at
+            FROM card_set_slug_history h
+            JOIN card_sets s ON s.id = h.card_set_id
+            WHERE h.slug = $1
+            "#
+        )
+        .bind(old_slug)
+        .fetch_optional(&self.pool)
+        .await?;
+
+        Ok(card_set)
+    }
+
+    async fn is_slug_taken(&self, slug: &str, exclude_id: Option<Uuid>) -> Result<bool> {
+        // Un slug histórico de otro conjunto tampoco se reutiliza, para no romper sus redirecciones
+        let taken = sqlx::query_scalar::<_, bool>(
+            r#"
+            SELECT EXISTS (
+                SELECT 1 FROM card_sets WHERE slug = $1 AND ($2::uuid IS NULL OR id <> $2)
+                UNION ALL
+                SELECT 1 FROM card_set_slug_history WHERE slug = $1 AND ($2::uuid IS NULL OR card_set_id <> $2)
+            )
+            "#
+        )
+        .bind(slug)
+        .bind(exclude_id)
+        .fetch_one(&self.pool)
+        .await?;
+
+        Ok(taken)
+    }
+
+    async fn get_set_ids_with_revealed_cards(&self, ids: &[Uuid]) -> Result<HashSet<Uuid>> {
+        let revealed = sqlx::query_scalar::<_, Uuid>(
+            r#"
+            SELECT s.id
+            FROM UNNEST($1::uuid[]) AS s(id)
+            WHERE EXISTS (SELECT 1 FROM cards WHERE set_id = s.id AND revealed_at <= NOW())
+            "#
+        )
+        .bind(ids)
+        .fetch_all(&self.pool)
+        .await?;
+
+        Ok(revealed.into_iter().collect())
+    }
+
+    async fn upsert_card_sets(&self, card_sets: Vec<CardSet>, dry_run: bool) -> Result<UpsertCounts> {
+        let mut tx = self.pool.begin().await?;
+        let mut counts = UpsertCounts::default();
+
+        for card_set in card_sets {
+            // Un renombrado conserva el slug anterior para redirigir, como en update_card_set
+            sqlx::query(
+                r#"
+                INSERT INTO card_set_slug_history (slug, card_set_id)
+                SELECT slug, id FROM card_sets WHERE UPPER(code) = UPPER($1) AND slug <> $2
+                ON CONFLICT (slug) DO UPDATE SET card_set_id = EXCLUDED.card_set_id, created_at = NOW()
+                "#
+            )
+            .bind(&card_set.code)
+            .bind(&card_set.slug)
+            .execute(&mut *tx)
+            .await?;
+
+            sqlx::query("DELETE FROM card_set_slug_history WHERE slug = $1")
+                .bind(&card_set.slug)
+                .execute(&mut *tx)
+                .await?;
+
+            // xmax = 0 indica que la fila fue insertada y no actualizada
+            let inserted: bool = sqlx::query(
+                r#"
+                INSERT INTO card_sets (id, name, code, slug, release_date, publish_at, icon_url, total_cards, created_at, updated_at)
+                VALUES ($1, $2, $3, $4, $5, $6, $7, $8, $9, $10)
+                ON CONFLICT ((UPPER(code))) DO UPDATE
+                SET
+                    name = EXCLUDED.name,
+                    slug = EXCLUDED.slug,
+                    release_date = EXCLUDED.release_date,
+                    publish_at = EXCLUDED.publish_at,
+                    icon_details = CASE WHEN card_sets.icon_url IS DISTINCT FROM EXCLUDED.icon_url THEN NULL ELSE card_sets.icon_details END,
+                    icon_url = EXCLUDED.icon_url,
+                    total_cards = EXCLUDED.total_cards,
+                    updated_at = EXCLUDED.updated_at
+                RETURNING (xmax = 0) AS inserted
+                "#
+            )
+            .bind(card_set.id)
+            .bind(card_set.name)
+            .bind(card_set.code)
+            .bind(card_set.slug)
+            .bind(card_set.release_date)
+            .bind(card_set.publish_at)
+            .bind(card_set.icon_url)
+            .bind(card_set.total_cards)
+            .bind(card_set.created_at)
+            .bind(card_set.updated_at)
+            .fetch_one(&mut *tx)
+            .await?
+            .try_get("inserted")?;
+
+            if inserted {
+                counts.created += 1;
+            } else {
+                counts.updated += 1;
+            }
+        }
+
+        // En modo de prueba se descartan los cambios
+        if dry_run {
+            tx.rollback().await?;
+        } else {
+            tx.commit().await?;
+        }
+
+        Ok(counts)
+    }
+
+    async fn update_icon_url(&self, id: Uuid, icon_url: &str, details: &ImageDetails) -> Result<Option<CardSet>> {
+        let card_set = sqlx::query_as::<_, CardSet>(
+            r#"
+            UPDATE card_sets
+            SET icon_url = $1, icon_details = $2, updated_at = NOW()
+            WHERE id = $3
+            RETURNING id, name, code, slug, release_date, publish_at, icon_url, icon_details, total_cards, created_at, updated_at
+            "#
+        )
+        .bind(icon_url)
+        .bind(Json(details))
+        .bind(id)
+        .fetch_optional(&self.pool)
+        .await?;
+
+        Ok(card_set)
+    }
+
+    // Sólo se guardan los detalles si el ícono no se reemplazó mientras se procesaba
+    async fn update_icon_details(&self, id: Uuid, icon_url: &str, details: &ImageDetails) -> Result<bool> {
+        let result = sqlx::query(
+            r#"
+            UPDATE card_sets
+            SET icon_details = $1, updated_at = NOW()
+            WHERE id = $2 AND icon_url = $3
+            "#
+        )
+        .bind(Json(details))
+        .bind(id)
+        .bind(icon_url)
+        .execute(&self.pool)
+        .await?;
+
+        Ok(result.rows_affected() > 0)
+    }
+}
+
+#[async_trait]
+pub trait CardRepository {
+    async fn get_card_by_id(&self, id: Uuid, include_unreleased: bool) -> Result<Option<Card>>;
+    async fn get_cards_by_set(&self, set_id: Uuid, include_unreleased: bool) -> Result<Vec<Card>>;
+    async fn get_all_cards(&self, include_unreleased: bool) -> Result<Vec<Card>>;
+    async fn get_set_codes(&self) -> Result<HashMap<String, Uuid>>;
+    async fn upsert_cards(&self, cards: Vec<Card>, dry_run: bool) -> Result<UpsertCounts>;
+    async fn set_revealed_at(&self, id: Uuid, revealed_at: Option<DateTime<Utc>>) -> Result<Option<Card>>;
+    async fn update_image_url(&self, id: Uuid, image_url: &str, details: &ImageDetails) -> Result<Option<Card>>;
+    async fn update_image_details(&self, id: Uuid, image_url: &str, details: &ImageDetails) -> Result<bool>;
+    async fn get_cards_by_ids(&self, ids: &[Uuid], include_unreleased: bool) -> Result<Vec<Card>>;
+    /// Hashes of the current image of every card, released or not
+    async fn get_image_hashes(&self) -> Result<Vec<(Uuid, i64)>>;
+    async fn save_image_hash(&self, card_id: Uuid, image_url: &str, hash: i64) -> Result<bool>;
+    async fn get_cards_missing_image_hash(&self) -> Result<Vec<Card>>;
+    async fn get_prices(&self, card_ids: &[Uuid]) -> Result<Vec<CardPrice>>;
+    /// Creates or replaces the prices of several cards at once
+    async fn save_prices(&self, prices: &[CardPrice]) -> Result<Vec<CardPrice>>;
+    async fn delete_price(&self, card_id: Uuid) -> Result<bool>;
+}
+
+pub struct PgCardRepository {
+    pool: PgPool,
+}
+
+impl PgCardRepository {
+    pub fn new(pool: PgPool) -> Self {
+        Self { pool }
+    }
+}
+
+// Una carta es pública si su conjunto ya se publicó o si fue revelada como spoiler
+#[async_trait]
+impl CardRepository for PgCardRepository {
+    async fn get_card_by_id(&self, id: Uuid, include_unreleased: bool) -> Result<Option<Card>> {
+        let card = sqlx::query_as::<_, Card>(
+            r#"
+            SELECT c.id, c.set_id, c.collector_number, c.name, c.card_type, c.card_energy, c.rarity, c.cost, c.artists, c.image_url, c.image_details, c.revealed_at, c.created_at, c.updated_at
+            FROM cards c
+            JOIN card_sets s ON s.id = c.set_id
+            WHERE c.id = $1
+              AND ($2 OR COALESCE(s.publish_at, s.release_date) <= NOW() OR c.revealed_at <= NOW())
+            "#
+        )
+        .bind(id)
+        .bind(include_unreleased)
+        .fetch_optional(&self.pool)
+        .await?;
+
+        Ok(card)
+    }
+
+    async fn get_cards_by_set(&self, set_id: Uuid, include_unreleased: bool) -> Result<Vec<Card>> {
+        let cards = sqlx::query_as::<_, Card>(
+            r#"
+            SELECT c.id, c.set_id, c.collector_number, c.name, c.card_type, c.card_energy, c.rarity, c.cost, c.artists, c.image_url, c.image_details, c.revealed_at, c.created_at, c.updated_at
+            FROM cards c
+            JOIN card_sets s ON s.id = c.set_id
+            WHERE c.set_id = $1
+              AND ($2 OR COALESCE(s.publish_at, s.release_date) <= NOW() OR c.revealed_at <= NOW())
+            ORDER BY c.collector_number
+            "#
+        )
+        .bind(set_id)
+        .bind(include_unreleased)
+        .fetch_all(&self.pool)
+        .await?;
+
+        Ok(cards)
+    }
+
+    async fn get_all_cards(&self, include_unreleased: bool) -> Result<Vec<Card>> {
+        let cards = sqlx::query_as::<_, Card>(
+            r#"
+            SELECT c.id, c.set_id, c.collector_number, c.name, c.card_type, c.card_energy, c.rarity, c.cost, c.artists, c.image_url, c.image_details, c.revealed_at, c.created_at, c.updated_at
+            FROM cards c
+            JOIN card_sets s ON s.id = c.set_id
+            WHERE $1 OR COALESCE(s.publish_at, s.release_date) <= NOW() OR c.revealed_at <= NOW()
+            ORDER BY s.release_date, c.collector_number
+            "#
+        )
+        .bind(include_unreleased)
+        .fetch_all(&self.pool)
+        .await?;
+
+        Ok(cards)
+    }
+
+    async fn get_set_codes(&self) -> Result<HashMap<String, Uuid>> {
+        let rows = sqlx::query("SELECT id, code FROM card_sets")
+            .fetch_all(&self.pool)
+            .await?;
+
+        let mut codes = HashMap::with_capacity(rows.len());
+        for row in rows {
+            codes.insert(row.try_get("code")?, row.try_get("id")?);
+        }
+
+        Ok(codes)
+    }
+
+    // Un archivo sin imagen o sin fecha de revelación (una hoja de cálculo sin
+    // esas columnas) conserva las que ya tenía la carta en vez de borrarlas
+    async fn upsert_cards(&self, cards: Vec<Card>, dry_run: bool) -> Result<UpsertCounts> {
+        let mut tx = self.pool.begin().await?;
+        let mut counts = UpsertCounts::default();
+
+        for card in cards {
+            let inserted: bool = sqlx::query(
+                r#"
+                INSERT INTO cards (id, set_id, collector_number, name, card_type, card_energy, rarity, cost, artists, image_url, revealed_at, created_at, updated_at)
+                VALUES ($1, $2, $3, $4, $5, $6, $7, $8, $9, $10, $11, $12, $13)
+                ON CONFLICT (set_id, collector_number) DO UPDATE
+                SET
+                    name = EXCLUDED.name,
+                    card_type = EXCLUDED.card_type,
+                    card_energy = EXCLUDED.card_energy,
+                    rarity = EXCLUDED.rarity,
+                    cost = EXCLUDED.cost,
+                    artists = EXCLUDED.artists,
+                    image_details = CASE
+                        WHEN EXCLUDED.image_url IS NOT NULL AND cards.image_url IS DISTINCT FROM EXCLUDED.image_url THEN NULL
+                        ELSE cards.image_details
+                    END,
+                    image_url = COALESCE(EXCLUDED.image_url, cards.image_url),
+                    revealed_at = COALESCE(EXCLUDED.revealed_at, cards.revealed_at),
+                    updated_at = EXCLUDED.updated_at
+                RETURNING (xmax = 0) AS inserted
+                "#
+            )
+            .bind(card.id)
+            .bind(card.set_id)
+            .bind(card.collector_number)
+            .bind(card.name)
+            .bind(card.card_type)
+            .bind(card.card_energy)
+            .bind(card.rarity)
+            .bind(card.cost)
+            .bind(card.artists)
+            .bind(card.image_url)
+            .bind(card.revealed_at)
+            .bind(card.created_at)
+            .bind(card.updated_at)
+            .fetch_one(&mut *tx)
+            .await?
+            .try_get("inserted")?;
+
+            if inserted {
+                counts.created += 1;
+            } else {
+                counts.updated += 1;
+            }
+        }
+
+        if dry_run {
+            tx.rollback().await?;
+        } else {
+            tx.commit().await?;
+        }
+
+        Ok(counts)
+    }
+
+    async fn set_revealed_at(&self, id: Uuid, revealed_at: Option<DateTime<Utc>>) -> Result<Option<Card>> {
+        let card = sqlx::query_as::<_, Card>(
+            r#"
+            UPDATE cards
+            SET revealed_at = $1, updated_at = NOW()
+            WHERE id = $2
+            RETURNING id, set_id, collector_number, name, card_type, card_energy, rarity, cost, artists, image_url, image_details, revealed_at, created_at, updated_at
+            "#
+        )
+        .bind(revealed_at)
+        .bind(id)
+        .fetch_optional(&self.pool)
+        .await?;
+
+        Ok(card)
+    }
+
+    async fn update_image_url(&self, id: Uuid, image_url: &str, details: &ImageDetails) -> Result<Option<Card>> {
+        let card = sqlx::query_as::<_, Card>(
+            r#"
+            UPDATE cards
+            SET image_url = $1, image_details = $2, updated_at = NOW()
+            WHERE id = $3
+            RETURNING id, set_id, collector_number, name, card_type, card_energy, rarity, cost, artists, image_url, image_details, revealed_at, created_at, updated_at
+            "#
+        )
+        .bind(image_url)
+        .bind(Json(details))
+        .bind(id)
+        .fetch_optional(&self.pool)
+        .await?;
+
+        Ok(card)
+    }
+
+    async fn update_image_details(&self, id: Uuid, image_url: &str, details: &ImageDetails) -> Result<bool> {
+        let result = sqlx::query(
+            r#"
+            UPDATE cards
+            SET image_details = $1, updated_at = NOW()
+            WHERE id = $2 AND image_url = $3
+            "#
+        )
+        .bind(Json(details))
+        .bind(id)
+        .bind(image_url)
+        .execute(&self.pool)
+        .await?;
+
+        Ok(result.rows_affected() > 0)
+    }
+
+    async fn get_cards_by_ids(&self, ids: &[Uuid], include_unreleased: bool) -> Result<Vec<Card>> {
+        let cards = sqlx::query_as::<_, Card>(
+            r#"
+            SELECT c.id, c.set_id, c.collector_number, c.name, c.card_type, c.card_energy, c.rarity, c.cost, c.artists, c.image_url, c.image_details, c.revealed_at, c.created_at, c.updated_at
+            FROM cards c
+            JOIN card_sets s ON s.id = c.set_id
+            WHERE c.id = ANY($1)
+              AND ($2 OR COALESCE(s.publish_at, s.release_date) <= NOW() OR c.revealed_at <= NOW())
+            "#
+        )
+        .bind(ids)
+        .bind(include_unreleased)
+        .fetch_all(&self.pool)
+        .await?;
+
+        Ok(cards)
+    }
+
+    // Sólo cuentan los hashes calculados sobre la imagen actual de cada carta
+    async fn get_image_hashes(&self) -> Result<Vec<(Uuid, i64)>> {
+        let hashes = sqlx::query_as::<_, (Uuid, i64)>(
+            r#"
+            SELECT h.card_id, h.phash
+            FROM card_image_hashes h
+            JOIN cards c ON c.id = h.card_id AND c.image_url = h.image_url
+            "#
+        )
+        .fetch_all(&self.pool)
+        .await?;
+
+        Ok(hashes)
+    }
+
+    // No se guarda nada si la imagen de la carta cambió mientras se calculaba el hash
+    async fn save_image_hash(&self, card_id: Uuid, image_url: &str, hash: i64) -> Result<bool> {
+        let result = sqlx::query(
+            r#"
+            INSERT INTO card_image_hashes (card_id, image_url, phash, computed_at)
+            SELECT id, image_url, $3, NOW()
+            FROM cards
+            WHERE id = $1 AND image_url = $2
+            ON CONFLICT (card_id) DO UPDATE
+            SET image_url = EXCLUDED.image_url, phash = EXCLUDED.phash, computed_at = EXCLUDED.computed_at
+            "#
+        )
+        .bind(card_id)
+        .bind(image_url)
+        .bind(hash)
+        .execute(&self.pool)
+        .await?;
+
+        Ok(result.rows_affected() > 0)
+    }
+
+    async fn get_cards_missing_image_hash(&self) -> Result<Vec<Card>> {
+        let cards = sqlx::query_as::<_, Card>(
+            r#"
+            SELECT c.id, c.set_id, c.collector_number, c.name, c.card_type, c.card_energy, c.rarity, c.cost, c.artists, c.image_url, c.image_details, c.revealed_at, c.created_at, c.updated_at
+            FROM cards c
+            LEFT JOIN card_image_hashes h ON h.card_id = c.id AND h.image_url = c.image_url
+            WHERE c.image_url IS NOT NULL AND h.card_id IS NULL
+            ORDER BY c.set_id, c.collector_number
+            "#
+        )
+        .fetch_all(&self.pool)
+        .await?;
+
+        Ok(cards)
+    }
+
+    async fn get_prices(&self, card_ids: &[Uuid]) -> Result<Vec<CardPrice>> {
+        let prices = sqlx::query_as::<_, CardPrice>(
+            r#"
+            SELECT card_id, price_cents, currency, source, updated_at
+            FROM card_prices
+            WHERE card_id = ANY($1)
+            "#
+        )
+        .bind(card_ids)
+        .fetch_all(&self.pool)
+        .await?;
+
+        Ok(prices)
+    }
+
+    async fn save_prices(&self, prices: &[CardPrice]) -> Result<Vec<CardPrice>> {
+        let mut tx = self.pool.begin().await?;
+        let mut saved = Vec::with_capacity(prices.len());
+
+        for price in prices {
+            let price = sqlx::query_as::<_, CardPrice>(
+                r#"
+                INSERT INTO card_prices (card_id, price_cents, currency, source, updated_at)
+                VALUES ($1, $2, $3, $4, $5)
+                ON CONFLICT (card_id) DO UPDATE
+                SET price_cents = EXCLUDED.price_cents, currency = EXCLUDED.currency,
+                    source = EXCLUDED.source, updated_at = EXCLUDED.updated_at
+                RETURNING card_id, price_cents, currency, source, updated_at
+                "#
+            )
+            .bind(price.card_id)
+            .bind(price.price_cents)
+            .bind(&price.currency)
+            .bind(&price.source)
+            .bind(price.updated_at)
+            .fetch_one(&mut *tx)
+            .await?;
+            saved.push(price);
+        }
+
+        tx.commit().await?;
+        Ok(saved)
+    }
+
+    async fn delete_price(&self, card_id: Uuid) -> Result<bool> {
+        let result = sqlx::query("DELETE FROM card_prices WHERE card_id = $1")
+            .bind(card_id)
+            .execute(&self.pool)
+            .await?;
+
+        Ok(result.rows_affected() > 0)
+    }
+}
@@ -1,8 +1,23 @@
 use anyhow::Result;
+use chrono::{DateTime, Utc};
+use std::collections::{HashMap, HashSet};
+use std::sync::Arc;
+use std::time::{Duration, Instant};
+use tokio::sync::RwLock;
 use uuid::Uuid;
 
-use super::model::CardSet;
-use super::repository::CardSetRepository;
+use super::catalog::CardCatalog;
+use super::dto::{CardPriceDto, CreateCardDto, CreateCardSetDto, Validable};
+use super::import::{ImportReport, ImportRow, ImportRowError};
+use super::model::{Card, CardMatch, CardPrice, CardSet, CatalogVisibility, ImageDetails};
+use super::repository::{CardRepository, CardSetRepository};
+use crate::infrastructure::images::hamming_distance;
+use crate::utils::slug::slugify;
+
+// Distancia de Hamming máxima (de 64 bits) para considerar que una foto corresponde a una carta
+const MAX_MATCH_DISTANCE: u32 = 16;
+// Tiempo que se conservan en memoria los hashes de imagen; guardar uno nuevo los invalida antes
+const IMAGE_HASHES_TTL: Duration = Duration::from_secs(300);
 
 pub struct CardSetService<R: CardSetRepository> {
     repository: R,
@@ -13,23 +28,417 @@ impl<R: CardSetRepository> CardSetService<R> {
         Self { repository }
     }
 
-    pub async fn get_all_card_sets(&self) -> Result<Vec<CardSet>> {
-        self.repository.get_all_card_sets().await
+    pub async fn get_all_card_sets(&self, visibility: CatalogVisibility) -> Result<Vec<CardSet>> {
+        let card_sets = self.repository.get_all_card_sets().await?;
+
+        Ok(card_sets
+            .into_iter()
+            .filter(|card_set| visibility.includes_unreleased() || card_set.is_released())
+            .collect())
     }
 
+    // Sin filtro de visibilidad: uso interno de las operaciones de escritura
     pub async fn get_card_set_by_id(&self, id: Uuid) -> Result<Option<CardSet>> {
         self.repository.get_card_set_by_id(id).await
     }
 
-    pub async fn create_card_set(&self, card_set: CardSet) -> Result<CardSet> {
+    pub async fn get_visible_card_set_by_id(&self, id: Uuid, visibility: CatalogVisibility) -> Result<Option<CardSet>> {
+        let card_set = self.repository.get_card_set_by_id(id).await?;
+        self.filter_visible(card_set, visibility).await
+    }
+
+    pub async fn get_card_set_by_code(&self, code: &str, visibility: CatalogVisibility) -> Result<Option<CardSet>> {
+        let card_set = self.repository.get_card_set_by_code(code).await?;
+        self.filter_visible(card_set, visibility).await
+    }
+
+    pub async fn get_card_set_by_slug(&self, slug: &str, visibility: CatalogVisibility) -> Result<Option<CardSet>> {
+        let card_set = self.repository.get_card_set_by_slug(slug).await?;
+        self.filter_visible(card_set, visibility).await
+    }
+
+    /// Unreleased sets ordered by the date they become public. Non-staff users
+    /// only see the ones already in preview (with at least one revealed card).
+    pub async fn get_upcoming_card_sets(&self, visibility: CatalogVisibility) -> Result<Vec<CardSet>> {
+        let mut upcoming: Vec<CardSet> = self
+            .repository
+            .get_all_card_sets()
+            .await?
+            .into_iter()
+            .filter(|card_set| !card_set.is_released())
+            .collect();
+
+        if !visibility.includes_unreleased() {
+            // Una sola consulta para todos los conjuntos en lugar de una por conjunto
+            let ids: Vec<Uuid> = upcoming.iter().map(|card_set| card_set.id).collect();
+            let revealed = self.repository.get_set_ids_with_revealed_cards(&ids).await?;
+            upcoming.retain(|card_set| revealed.contains(&card_set.id));
+        }
+
+        upcoming.sort_by_key(|card_set| card_set.visible_from());
+
+        Ok(upcoming)
+    }
+
+    // Un conjunto no publicado sólo es visible para el staff o en modo preview
+    async fn filter_visible(&self, card_set: Option<CardSet>, visibility: CatalogVisibility) -> Result<Option<CardSet>> {
+        let Some(card_set) = card_set else {
+            return Ok(None);
+        };
+
+        if visibility.includes_unreleased()
+            || card_set.is_released()
+            || !self.repository.get_set_ids_with_revealed_cards(&[card_set.id]).await?.is_empty()
+        {
+            Ok(Some(card_set))
+        } else {
+            Ok(None)
+        }
+    }
+
+    /// Current slug of the set that used to be published under `old_slug`, if
+    /// the user can see it: redirecting to a hidden set would reveal its new name
+    pub async fn find_current_slug(&self, old_slug: &str, visibility: CatalogVisibility) -> Result<Option<String>> {
+        let card_set = self.repository.get_card_set_by_old_slug(old_slug).await?;
+        Ok(self.filter_visible(card_set, visibility).await?.map(|card_set| card_set.slug))
+    }
+
+    pub async fn create_card_set(&self, mut card_set: CardSet) -> Result<CardSet> {
+        card_set.slug = self.unique_slug(&card_set.name, None, &HashSet::new()).await?;
         self.repository.create_card_set(card_set).await
     }
 
-    pub async fn update_card_set(&self, card_set: CardSet) -> Result<CardSet> {
+    pub async fn update_card_set(&self, mut card_set: CardSet) -> Result<CardSet> {
+        // El slug es estable: sólo se regenera cuando cambia el nombre
+        if let Some(current) = self.repository.get_card_set_by_id(card_set.id).await? {
+            card_set.slug = if current.name == card_set.name {
+                current.slug
+            } else {
+                self.unique_slug(&card_set.name, Some(card_set.id), &HashSet::new()).await?
+            };
+        }
+
         self.repository.update_card_set(card_set).await
     }
 
+    pub async fn set_card_set_icon_url(&self, id: Uuid, icon_url: &str, details: &ImageDetails) -> Result<Option<CardSet>> {
+        self.repository.update_icon_url(id, icon_url, details).await
+    }
+
+    pub async fn set_card_set_icon_details(&self, id: Uuid, icon_url: &str, details: &ImageDetails) -> Result<bool> {
+        self.repository.update_icon_details(id, icon_url, details).await
+    }
+
     pub async fn delete_card_set(&self, id: Uuid) -> Result<bool> {
         self.repository.delete_card_set(id).await
     }
-} 
\ No newline at end of file
+
+    /// Validates every row and upserts the card sets by code in a single transaction.
+    /// Nothing is written if any row fails; in dry-run mode the transaction is rolled back.
+    pub async fn import_card_sets(&self, rows: Vec<ImportRow<CreateCardSetDto>>, dry_run: bool) -> Result<ImportReport> {
+        let mut report = ImportReport::new(dry_run, rows.len());
+        let mut seen_codes: HashMap<String, usize> = HashMap::new();
+        let mut card_sets = Vec::with_capacity(rows.len());
+
+        for row in rows {
+            let dto = match row.value {
+                Ok(dto) => dto,
+                Err(message) => {
+                    report.errors.push(ImportRowError { line: row.line, key: None, message });
+                    continue;
+                }
+            };
+
+            if let Err(e) = dto.validate() {
+                report.errors.push(ImportRowError { line: row.line, key: Some(dto.code.clone()), message: e.to_string() });
+                continue;
+            }
+
+            // Los códigos no distinguen mayúsculas: "lgro" y "LGRO" son el mismo conjunto
+            if let Some(first_line) = seen_codes.insert(dto.code.to_uppercase(), row.line) {
+                report.errors.push(ImportRowError {
+                    line: row.line,
+                    key: Some(dto.code.clone()),
+                    message: format!("El código '{}' ya aparece en la línea {}", dto.code, first_line),
+                });
+                continue;
+            }
+
+            card_sets.push(dto.to_model());
+        }
+
+        if report.has_errors() {
+            return Ok(report);
+        }
+
+        // Igual que al editar: un conjunto existente conserva su slug salvo que cambie de nombre
+        let mut assigned_slugs = HashSet::new();
+        for card_set in card_sets.iter_mut() {
+            let current = self.repository.get_card_set_by_code(&card_set.code).await?;
+            card_set.slug = match current {
+                Some(current) if current.name == card_set.name => current.slug,
+                current => self.unique_slug(&card_set.name, current.map(|c| c.id), &assigned_slugs).await?,
+            };
+            assigned_slugs.insert(card_set.slug.clone());
+        }
+
+        let counts = self.repository.upsert_card_sets(card_sets, dry_run).await?;
+        report.created = counts.created;
+        report.updated = counts.updated;
+
+        Ok(report)
+    }
+
+    pub async fn export_card_sets(&self, visibility: CatalogVisibility) -> Result<Vec<CreateCardSetDto>> {
+        let card_sets = self.get_all_card_sets(visibility).await?;
+        Ok(card_sets.iter().map(CreateCardSetDto::from).collect())
+    }
+
+    // Agrega un sufijo numérico cuando el slug base ya pertenece a otro conjunto
+    async fn unique_slug(&self, name: &str, exclude_id: Option<Uuid>, reserved: &HashSet<String>) -> Result<String> {
+        let base = match slugify(name) {
+            slug if slug.is_empty() => "conjunto".to_string(),
+            slug => slug,
+        };
+
+        let mut candidate = base.clone();
+        let mut suffix = 2;
+        while reserved.contains(&candidate) || self.repository.is_slug_taken(&candidate, exclude_id).await? {
+            candidate = format!("{}-{}", base, suffix);
+            suffix += 1;
+        }
+
+        Ok(candidate)
+    }
+}
+
+pub struct CardService<R: CardRepository> {
+    repository: R,
+    image_hashes: RwLock<Option<CachedImageHashes>>,
+}
+
+struct CachedImageHashes {
+    hashes: Arc<Vec<(Uuid, i64)>>,
+    expiry: Instant,
+}
+
+impl<R: CardRepository> CardService<R> {
+    pub fn new(repository: R) -> Self {
+        Self { repository, image_hashes: RwLock::new(None) }
+    }
+
+    pub async fn get_card_by_id(&self, id: Uuid, visibility: CatalogVisibility) -> Result<Option<Card>> {
+        self.repository.get_card_by_id(id, visibility.includes_unreleased()).await
+    }
+
+    pub async fn get_cards_by_set(&self, set_id: Uuid, visibility: CatalogVisibility) -> Result<Vec<Card>> {
+        self.repository.get_cards_by_set(set_id, visibility.includes_unreleased()).await
+    }
+
+    /// Cards among `ids` the caller can see; unknown or hidden ids are left out
+    pub async fn get_cards_by_ids(&self, ids: &[Uuid], visibility: CatalogVisibility) -> Result<Vec<Card>> {
+        self.repository.get_cards_by_ids(ids, visibility.includes_unreleased()).await
+    }
+
+    /// Reveals a card before its set is released; `None` hides it again
+    pub async fn set_card_revealed_at(&self, id: Uuid, revealed_at: Option<DateTime<Utc>>) -> Result<Option<Card>> {
+        self.repository.set_revealed_at(id, revealed_at).await
+    }
+
+    pub async fn set_card_image_url(&self, id: Uuid, image_url: &str, details: &ImageDetails) -> Result<Option<Card>> {
+        self.repository.update_image_url(id, image_url, details).await
+    }
+
+    pub async fn set_card_image_details(&self, id: Uuid, image_url: &str, details: &ImageDetails) -> Result<bool> {
+        self.repository.update_image_details(id, image_url, details).await
+    }
+
+    pub async fn save_card_image_hash(&self, id: Uuid, image_url: &str, hash: u64) -> Result<bool> {
+        // Postgres no tiene enteros sin signo: se guardan los mismos 64 bits como BIGINT
+        let saved = self.repository.save_image_hash(id, image_url, hash as i64).await?;
+        if saved {
+            *self.image_hashes.write().await = None;
+        }
+        Ok(saved)
+    }
+
+    pub async fn get_cards_missing_image_hash(&self) -> Result<Vec<Card>> {
+        self.repository.get_cards_missing_image_hash().await
+    }
+
+    pub async fn get_prices(&self, card_ids: &[Uuid]) -> Result<Vec<CardPrice>> {
+        self.repository.get_prices(card_ids).await
+    }
+
+    pub async fn save_prices(&self, prices: Vec<(Uuid, CardPriceDto)>) -> Result<Vec<CardPrice>> {
+        let prices: Vec<CardPrice> = prices
+            .into_iter()
+            .map(|(card_id, dto)| CardPrice {
+                card_id,
+                price_cents: dto.price_cents,
+                currency: dto.currency(),
+                source: dto.source(),
+                updated_at: Utc::now(),
+            })
+            .collect();
+
+        self.repository.save_prices(&prices).await
+    }
+
+    pub async fn delete_price(&self, card_id: Uuid) -> Result<bool> {
+        self.repository.delete_price(card_id).await
+    }
+
+    /// Cards whose image hash is closest to `hash`, best matches first
+    pub async fn identify_card(&self, hash: u64, limit: usize, visibility: CatalogVisibility) -> Result<Vec<CardMatch>> {
+        let mut nearest: Vec<(Uuid, u32)> = self
+            .image_hashes()
+            .await?
+            .iter()
+            .map(|&(id, card_hash)| (id, hamming_distance(hash, card_hash as u64)))
+            .filter(|(_, distance)| *distance <= MAX_MATCH_DISTANCE)
+            .collect();
+        nearest.sort_by_key(|(_, distance)| *distance);
+
+        // Los hashes incluyen las cartas sin publicar: la visibilidad se aplica
+        // al leer las cartas, antes de quedarse con las `limit` primeras
+        let ids: Vec<Uuid> = nearest.iter().map(|(id, _)| *id).collect();
+        let mut cards: HashMap<Uuid, Card> = self
+            .repository
+            .get_cards_by_ids(&ids, visibility.includes_unreleased())
+            .await?
+            .into_iter()
+            .map(|card| (card.id, card))
+            .collect();
+
+        Ok(nearest
+            .into_iter()
+            .filter_map(|(id, distance)| {
+                cards.remove(&id).map(|card| CardMatch {
+                    card,
+                    distance,
+                    confidence: 1.0 - distance as f64 / (MAX_MATCH_DISTANCE + 1) as f64,
+                })
+            })
+            .take(limit)
+            .collect())
+    }
+
+    // Hashes de todas las cartas, leídos de la base de datos como mucho cada IMAGE_HASHES_TTL
+    async fn image_hashes(&self) -> Result<Arc<Vec<(Uuid, i64)>>> {
+        if let Some(cached) = self.image_hashes.read().await.as_ref() {
+            if cached.expiry > Instant::now() {
+                return Ok(cached.hashes.clone());
+            }
+        }
+
+        let mut cache = self.image_hashes.write().await;
+        if let Some(cached) = cache.as_ref() {
+            if cached.expiry > Instant::now() {
+                return Ok(cached.hashes.clone());
+            }
+        }
+
+        let hashes = Arc::new(self.repository.get_image_hashes().await?);
+        *cache = Some(CachedImageHashes { hashes: hashes.clone(), expiry: Instant::now() + IMAGE_HASHES_TTL });
+        Ok(hashes)
+    }
+
+    /// Validates every row, resolves the set codes and upserts the cards by
+    /// set and collector number in a single transaction.
+    pub async fn import_cards(&self, rows: Vec<ImportRow<CreateCardDto>>, dry_run: bool) -> Result<ImportReport> {
+        let mut report = ImportReport::new(dry_run, rows.len());
+        // Los códigos de conjunto no distinguen mayúsculas, igual que en la base de datos
+        let set_codes: HashMap<String, Uuid> = self
+            .repository
+            .get_set_codes()
+            .await?
+            .into_iter()
+            .map(|(code, id)| (code.to_uppercase(), id))
+            .collect();
+        let mut seen_keys: HashMap<(String, i32), usize> = HashMap::new();
+        let mut cards = Vec::with_capacity(rows.len());
+
+        for row in rows {
+            let dto = match row.value {
+                Ok(dto) => dto,
+                Err(message) => {
+                    report.errors.push(ImportRowError { line: row.line, key: None, message });
+                    continue;
+                }
+            };
+
+            let key = format!("{}-{}", dto.set_code, dto.collector_number);
+
+            if let Err(e) = dto.validate() {
+                report.errors.push(ImportRowError { line: row.line, key: Some(key), message: e.to_string() });
+                continue;
+            }
+
+            let set_code = dto.set_code.to_uppercase();
+            let Some(set_id) = set_codes.get(&set_code) else {
+                report.errors.push(ImportRowError {
+                    line: row.line,
+                    key: Some(key),
+                    message: format!("No existe un conjunto con el código '{}'", dto.set_code),
+                });
+                continue;
+            };
+
+            if let Some(first_line) = seen_keys.insert((set_code, dto.collector_number), row.line) {
+                report.errors.push(ImportRowError {
+                    line: row.line,
+                    key: Some(key),
+                    message: format!("La carta ya aparece en la línea {}", first_line),
+                });
+                continue;
+            }
+
+            cards.push(dto.to_model(*set_id));
+        }
+
+        if report.has_errors() {
+            return Ok(report);
+        }
+
+        let counts = self.repository.upsert_cards(cards, dry_run).await?;
+        report.created = counts.created;
+        report.updated = counts.updated;
+
+        Ok(report)
+    }
+
+    /// Visible cards indexed by set code, collector number and name
+    pub async fn get_catalog(&self, visibility: CatalogVisibility) -> Result<CardCatalog> {
+        let set_codes: HashMap<Uuid, String> = self
+            .repository
+            .get_set_codes()
+            .await?
+            .into_iter()
+            .map(|(code, id)| (id, code))
+            .collect();
+        let cards = self.repository.get_all_cards(visibility.includes_unreleased()).await?;
+
+        Ok(CardCatalog::new(cards, set_codes))
+    }
+
+    pub async fn export_cards(&self, visibility: CatalogVisibility) -> Result<Vec<CreateCardDto>> {
+        let set_codes: HashMap<Uuid, String> = self
+            .repository
+            .get_set_codes()
+            .await?
+            .into_iter()
+            .map(|(code, id)| (id, code))
+            .collect();
+        let cards = self.repository.get_all_cards(visibility.includes_unreleased()).await?;
+
+        Ok(cards
+            .iter()
+            .filter_map(|card| {
+                set_codes
+                    .get(&card.set_id)
+                    .map(|code| CreateCardDto::from_model(card, code.clone()))
+            })
+            .collect())
+    }
+}
@@ -0,0 +1,86 @@
+use anyhow::{anyhow, Result};
+use serde::Deserialize;
+use uuid::Uuid;
+
+use super::model::{CARD_CONDITIONS, CARD_FINISHES, DEFAULT_CONDITION, DEFAULT_FINISH, DEFAULT_LANGUAGE, Printing};
+use crate::domain::cards::Validable;
+
+// Cantidad máxima de copias por operación y por impresión
+pub const MAX_QUANTITY: i32 = 9999;
+
+#[derive(Debug, Deserialize)]
+pub struct CollectionChangeDto {
+    pub card_id: Uuid,
+    pub condition: Option<String>,
+    pub language: Option<String>,
+    pub finish: Option<String>,
+    #[serde(default = "default_quantity")]
+    pub quantity: i32,
+}
+
+fn default_quantity() -> i32 {
+    1
+}
+
+impl CollectionChangeDto {
+    // Normaliza los valores opcionales a los predeterminados
+    pub fn printing(&self) -> Printing {
+        Printing {
+            card_id: self.card_id,
+            condition: normalize(self.condition.as_deref(), DEFAULT_CONDITION),
+            language: normalize(self.language.as_deref(), DEFAULT_LANGUAGE),
+            finish: normalize(self.finish.as_deref(), DEFAULT_FINISH),
+        }
+    }
+}
+
+fn normalize(value: Option<&str>, default: &str) -> String {
+    value
+        .map(|value| value.trim().to_lowercase())
+        .filter(|value| !value.is_empty())
+        .unwrap_or_else(|| default.to_string())
+}
+
+/// Checks condition, language and finish of a printing
+pub fn validate_printing(printing: &Printing) -> Result<()> {
+    if !CARD_CONDITIONS.contains(&printing.condition.as_str()) {
+        return Err(anyhow!("Estado '{}' no válido; valores admitidos: {}", printing.condition, CARD_CONDITIONS.join(", ")));
+    }
+
+    if !CARD_FINISHES.contains(&printing.finish.as_str()) {
+        return Err(anyhow!("Acabado '{}' no válido; valores admitidos: {}", printing.finish, CARD_FINISHES.join(", ")));
+    }
+
+    // Código de idioma ISO 639-1 (por ejemplo "es" o "en")
+    if printing.language.len() != 2 || !printing.language.chars().all(|c| c.is_ascii_lowercase()) {
+        return Err(anyhow!("El idioma debe ser un código ISO 639-1 de dos letras"));
+    }
+
+    Ok(())
+}
+
+impl Validable for CollectionChangeDto {
+    fn validate(&self) -> Result<()> {
+        if self.quantity <= 0 || self.quantity > MAX_QUANTITY {
+            return Err(anyhow!("La cantidad debe estar entre 1 y {}", MAX_QUANTITY));
+        }
+
+        validate_printing(&self.printing())
+    }
+}
+
+#[derive(Debug, Deserialize)]
+pub struct SetQuantityDto {
+    pub quantity: i32,
+}
+
+impl Validable for SetQuantityDto {
+    fn validate(&self) -> Result<()> {
+        // Cero elimina la entrada
+        if self.quantity < 0 || self.quantity > MAX_QUANTITY {
+            return Err(anyhow!("La cantidad debe estar entre 0 y {}", MAX_QUANTITY));
+        }
+
+        Ok(())
+    }
+}
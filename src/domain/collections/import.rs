@@ -0,0 +1,362 @@
+use anyhow::{Result, anyhow};
+use serde::Serialize;
+use std::collections::BTreeMap;
+
+use super::dto::MAX_QUANTITY;
+use super::model::{CARD_CONDITIONS, DEFAULT_CONDITION, DEFAULT_FINISH, DEFAULT_LANGUAGE};
+use crate::domain::cards::{CardReference, normalize_name};
+
+/// What happens to the existing collection when a file is imported
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "lowercase")]
+pub enum ImportMode {
+    /// Imported copies are added to the ones already owned
+    Merge,
+    /// The collection ends up containing exactly the imported printings
+    Replace,
+}
+
+impl ImportMode {
+    pub fn parse(value: &str) -> Option<Self> {
+        match value.trim().to_lowercase().as_str() {
+            "merge" => Some(Self::Merge),
+            "replace" => Some(Self::Replace),
+            _ => None,
+        }
+    }
+}
+
+/// Field of the collection that a CSV column can be mapped to
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum CollectionColumn {
+    SetCode,
+    CollectorNumber,
+    Name,
+    Quantity,
+    Condition,
+    Language,
+    Finish,
+}
+
+impl CollectionColumn {
+    const ALL: [Self; 7] = [
+        Self::SetCode,
+        Self::CollectorNumber,
+        Self::Name,
+        Self::Quantity,
+        Self::Condition,
+        Self::Language,
+        Self::Finish,
+    ];
+
+    pub fn parse(value: &str) -> Option<Self> {
+        match value.trim().to_lowercase().as_str() {
+            "set_code" => Some(Self::SetCode),
+            "collector_number" => Some(Self::CollectorNumber),
+            "name" => Some(Self::Name),
+            "quantity" => Some(Self::Quantity),
+            "condition" => Some(Self::Condition),
+            "language" => Some(Self::Language),
+            "finish" => Some(Self::Finish),
+            _ => None,
+        }
+    }
+
+    // Cabeceras habituales en hojas de cálculo y exportaciones de otras aplicaciones, ya normalizadas
+    fn aliases(&self) -> &'static [&'static str] {
+        match self {
+            Self::SetCode => &["set code", "set", "set id", "edition", "expansion", "expansion code", "codigo", "codigo set", "edicion", "expansion set"],
+            Self::CollectorNumber => &["collector number", "number", "card number", "no", "nº", "nr", "num", "numero", "numero de carta", "numero coleccionista"],
+            Self::Name => &["name", "card name", "card", "nombre", "carta", "nombre carta", "nombre de la carta"],
+            Self::Quantity => &["quantity", "qty", "count", "amount", "copies", "owned", "have", "cantidad", "copias", "cant"],
+            Self::Condition => &["condition", "cond", "estado", "conservacion"],
+            Self::Language => &["language", "lang", "idioma"],
+            Self::Finish => &["finish", "foil", "printing", "acabado", "foil holo"],
+        }
+    }
+}
+
+/// Raw values of one CSV row, taken from the mapped columns
+#[derive(Debug, Default)]
+pub struct CollectionImportRow {
+    pub line: usize,
+    pub set_code: Option<String>,
+    pub collector_number: Option<String>,
+    pub name: Option<String>,
+    pub quantity: Option<String>,
+    pub condition: Option<String>,
+    pub language: Option<String>,
+    pub finish: Option<String>,
+}
+
+impl CollectionImportRow {
+    /// Row as the user wrote it, for the report
+    pub fn describe(&self) -> String {
+        [&self.set_code, &self.collector_number, &self.name]
+            .iter()
+            .filter_map(|value| value.as_deref())
+            .collect::<Vec<_>>()
+            .join(" ")
+    }
+}
+
+/// Parsed file: the column used for each field and the rows
+pub struct CollectionCsv {
+    pub columns: BTreeMap<CollectionColumn, String>,
+    pub rows: Vec<CollectionImportRow>,
+}
+
+/// A name that was resolved approximately; listed so the user can check it
+#[derive(Debug, Serialize)]
+pub struct FuzzyMatch {
+    pub line: usize,
+    pub input: String,
+    pub card: CardReference,
+    pub score: f64,
+}
+
+/// A row that was not imported and why
+#[derive(Debug, Serialize)]
+pub struct UnmatchedRow {
+    pub line: usize,
+    pub input: String,
+    pub reason: String,
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub suggestions: Vec<CardReference>,
+}
+
+#[derive(Debug, Serialize)]
+pub struct CollectionImportReport {
+    pub dry_run: bool,
+    pub mode: ImportMode,
+    pub columns: BTreeMap<CollectionColumn, String>,
+    pub total_rows: usize,
+    pub imported_rows: usize,
+    /// Rows with a quantity of zero
+    pub skipped_rows: usize,
+    pub created: usize,
+    pub updated: usize,
+    pub removed: usize,
+    pub fuzzy_matches: Vec<FuzzyMatch>,
+    pub unmatched: Vec<UnmatchedRow>,
+}
+
+impl CollectionImportReport {
+    pub fn new(dry_run: bool, mode: ImportMode, columns: BTreeMap<CollectionColumn, String>, total_rows: usize) -> Self {
+        Self {
+            dry_run,
+            mode,
+            columns,
+            total_rows,
+            imported_rows: 0,
+            skipped_rows: 0,
+            created: 0,
+            updated: 0,
+            removed: 0,
+            fuzzy_matches: Vec::new(),
+            unmatched: Vec::new(),
+        }
+    }
+}
+
+/// Reads a collection CSV. The delimiter (comma, semicolon or tab) is detected
+/// from the header, and columns are mapped by their usual names unless
+/// `overrides` (e.g. `set_code:Edición,name:Carta`) says otherwise.
+pub fn parse_collection_csv(data: &[u8], overrides: Option<&str>) -> Result<CollectionCsv> {
+    // Excel añade una marca BOM al guardar en UTF-8
+    let data = data.strip_prefix(b"\xEF\xBB\xBF").unwrap_or(data);
+
+    let mut reader = csv::ReaderBuilder::new()
+        .delimiter(detect_delimiter(data))
+        .flexible(true)
+        .trim(csv::Trim::All)
+        .from_reader(data);
+
+    let headers = reader
+        .headers()
+        .map_err(|e| anyhow!("La cabecera del CSV no es válida: {}", e))?
+        .clone();
+    let columns = map_columns(&headers, overrides)?;
+
+    let indexes: BTreeMap<CollectionColumn, usize> = columns
+        .iter()
+        .filter_map(|(column, header)| headers.iter().position(|h| h == header).map(|index| (*column, index)))
+        .collect();
+
+    let mut rows = Vec::new();
+    for record in reader.records() {
+        let record = record.map_err(|e| anyhow!("Error al leer el CSV: {}", e))?;
+        if record.iter().all(str::is_empty) {
+            continue;
+        }
+
+        let value = |column: CollectionColumn| {
+            indexes
+                .get(&column)
+                .and_then(|&index| record.get(index))
+                .filter(|value| !value.is_empty())
+                .map(str::to_string)
+        };
+
+        rows.push(CollectionImportRow {
+            line: record.position().map_or(0, |position| position.line() as usize),
+            set_code: value(CollectionColumn::SetCode),
+            collector_number: value(CollectionColumn::CollectorNumber),
+            name: value(CollectionColumn::Name),
+            quantity: value(CollectionColumn::Quantity),
+            condition: value(CollectionColumn::Condition),
+            language: value(CollectionColumn::Language),
+            finish: value(CollectionColumn::Finish),
+        });
+    }
+
+    Ok(CollectionCsv { columns, rows })
+}
+
+// El separador más frecuente en la primera línea; las hojas en español suelen usar ';'
+fn detect_delimiter(data: &[u8]) -> u8 {
+    let first_line = data.split(|&byte| byte == b'\n').next().unwrap_or_default();
+
+    [b',', b';', b'\t']
+        .into_iter()
+        .max_by_key(|delimiter| first_line.iter().filter(|byte| *byte == delimiter).count())
+        .filter(|delimiter| first_line.contains(delimiter))
+        .unwrap_or(b',')
+}
+
+fn map_columns(headers: &csv::StringRecord, overrides: Option<&str>) -> Result<BTreeMap<CollectionColumn, String>> {
+    let normalized: Vec<String> = headers
+        .iter()
+        .map(|header| if header == "#" { "number".to_string() } else { normalize_name(header) })
+        .collect();
+
+    let mut columns = BTreeMap::new();
+
+    for pair in overrides.unwrap_or_default().split(',').filter(|pair| !pair.trim().is_empty()) {
+        let (field, header) = pair
+            .split_once(':')
+            .ok_or_else(|| anyhow!("Mapeo de columna '{}' no válido; usa campo:columna", pair.trim()))?;
+        let column = CollectionColumn::parse(field)
+            .ok_or_else(|| anyhow!("Campo '{}' desconocido en el mapeo de columnas", field.trim()))?;
+        let index = normalized
+            .iter()
+            .position(|candidate| *candidate == normalize_name(header))
+            .ok_or_else(|| anyhow!("El CSV no tiene la columna '{}'", header.trim()))?;
+        columns.insert(column, headers[index].to_string());
+    }
+
+    for column in CollectionColumn::ALL {
+        if columns.contains_key(&column) {
+            continue;
+        }
+        let found = normalized
+            .iter()
+            .position(|candidate| column.aliases().contains(&candidate.as_str()));
+        if let Some(index) = found {
+            columns.insert(column, headers[index].to_string());
+        }
+    }
+
+    let by_number = columns.contains_key(&CollectionColumn::SetCode) && columns.contains_key(&CollectionColumn::CollectorNumber);
+    if !by_number && !columns.contains_key(&CollectionColumn::Name) {
+        return Err(anyhow!(
+            "No se reconocen las columnas de la carta: se necesitan código de conjunto y número, o el nombre. Columnas del archivo: {}",
+            headers.iter().collect::<Vec<_>>().join(", ")
+        ));
+    }
+
+    Ok(columns)
+}
+
+/// Copies on the row; an empty cell counts as one
+pub fn parse_quantity(value: Option<&str>) -> std::result::Result<i32, String> {
+    let Some(value) = value else {
+        return Ok(1);
+    };
+
+    match value.parse::<i32>() {
+        Ok(quantity) if (0..=MAX_QUANTITY).contains(&quantity) => Ok(quantity),
+        _ => Err(format!("Cantidad '{}' no válida; debe estar entre 0 y {}", value, MAX_QUANTITY)),
+    }
+}
+
+/// Collector number as trackers write it: "042", "42/120", "LGRO-042"
+pub fn parse_collector_number(value: &str) -> Option<i32> {
+    let number = value.split('/').next().unwrap_or_default();
+    let digits: String = number
+        .chars()
+        .rev()
+        .skip_while(|c| !c.is_ascii_digit())
+        .take_while(char::is_ascii_digit)
+        .collect::<Vec<_>>()
+        .into_iter()
+        .rev()
+        .collect();
+
+    digits.parse().ok()
+}
+
+/// Maps the abbreviations used by graders and marketplaces (NM, LP...) to a condition
+pub fn parse_condition(value: Option<&str>) -> std::result::Result<String, String> {
+    let Some(value) = value else {
+        return Ok(DEFAULT_CONDITION.to_string());
+    };
+
+    let normalized = normalize_name(value).replace(' ', "_");
+    let condition = match normalized.as_str() {
+        "m" | "mt" | "mint" | "gem_mint" => "mint",
+        "nm" | "near_mint" | "casi_nueva" | "nm_m" => "near_mint",
+        "ex" | "excellent" | "excelente" | "slightly_played" | "sp" => "excellent",
+        "gd" | "g" | "good" | "buena" | "bueno" => "good",
+        "lp" | "light_played" | "lightly_played" => "light_played",
+        "pl" | "pld" | "played" | "mp" | "moderately_played" | "jugada" => "played",
+        "po" | "p" | "hp" | "poor" | "heavily_played" | "damaged" | "dmg" | "danada" => "poor",
+        other if CARD_CONDITIONS.contains(&other) => other,
+        _ => return Err(format!("Estado '{}' no reconocido", value)),
+    };
+
+    Ok(condition.to_string())
+}
+
+/// Accepts ISO 639-1 codes and the language names most exports use
+pub fn parse_language(value: Option<&str>) -> std::result::Result<String, String> {
+    let Some(value) = value else {
+        return Ok(DEFAULT_LANGUAGE.to_string());
+    };
+
+    let normalized = normalize_name(value);
+    let language = match normalized.as_str() {
+        "spanish" | "espanol" | "castellano" => "es",
+        "english" | "ingles" => "en",
+        "french" | "frances" => "fr",
+        "german" | "aleman" => "de",
+        "italian" | "italiano" => "it",
+        "portuguese" | "portugues" => "pt",
+        "japanese" | "japones" => "ja",
+        "korean" | "coreano" => "ko",
+        "chinese" | "chino" => "zh",
+        code if code.len() == 2 && code.chars().all(|c| c.is_ascii_lowercase()) => code,
+        _ => return Err(format!("Idioma '{}' no reconocido", value)),
+    };
+
+    Ok(language.to_string())
+}
+
+/// Accepts finish names and the yes/no values of a "Foil" column
+pub fn parse_finish(value: Option<&str>) -> std::result::Result<String, String> {
+    let Some(value) = value else {
+        return Ok(DEFAULT_FINISH.to_string());
+    };
+
+    let normalized = normalize_name(value).replace(' ', "_");
+    let finish = match normalized.as_str() {
+        "normal" | "non_foil" | "nonfoil" | "regular" | "no" | "false" | "0" => "normal",
+        "foil" | "yes" | "si" | "true" | "1" | "x" => "foil",
+        "holo" | "holofoil" | "holografica" => "holo",
+        "reverse_holo" | "reverse" | "reverse_holofoil" | "reverse_foil" => "reverse_holo",
+        _ => return Err(format!("Acabado '{}' no reconocido", value)),
+    };
+
+    Ok(finish.to_string())
+}
@@ -0,0 +1,11 @@
+mod model;
+mod repository;
+mod service;
+mod dto;
+mod import;
+
+pub use model::*;
+pub use repository::*;
+pub use service::*;
+pub use dto::*;
+pub use import::*;
@@ -0,0 +1,169 @@
+use serde::{Deserialize, Serialize};
+use sqlx::postgres::PgRow;
+use sqlx::Row;
+use uuid::Uuid;
+use chrono::{DateTime, Utc};
+
+use crate::domain::cards::Card;
+
+// Estados de conservación admitidos, de mejor a peor
+pub const CARD_CONDITIONS: &[&str] = &["mint", "near_mint", "excellent", "good", "light_played", "played", "poor"];
+
+// Acabados de impresión admitidos
+pub const CARD_FINISHES: &[&str] = &["normal", "foil", "holo", "reverse_holo"];
+
+pub const DEFAULT_CONDITION: &str = "near_mint";
+pub const DEFAULT_LANGUAGE: &str = "es";
+pub const DEFAULT_FINISH: &str = "normal";
+
+/// Copies of one printing owned by a user. A printing is a card in a given
+/// condition, language and finish.
+#[derive(Debug, Serialize, Deserialize)]
+pub struct CollectionEntry {
+    pub id: Uuid,
+    pub owner_uid: String,
+    pub card_id: Uuid,
+    pub condition: String,
+    pub language: String,
+    pub finish: String,
+    pub quantity: i32,
+    pub created_at: DateTime<Utc>,
+    pub updated_at: DateTime<Utc>,
+}
+
+impl<'r> sqlx::FromRow<'r, PgRow> for CollectionEntry {
+    fn from_row(row: &'r PgRow) -> Result<Self, sqlx::Error> {
+        Ok(Self {
+            id: row.try_get("id")?,
+            owner_uid: row.try_get("owner_uid")?,
+            card_id: row.try_get("card_id")?,
+            condition: row.try_get("condition")?,
+            language: row.try_get("language")?,
+            finish: row.try_get("finish")?,
+            quantity: row.try_get("quantity")?,
+            created_at: row.try_get("created_at")?,
+            updated_at: row.try_get("updated_at")?,
+        })
+    }
+}
+
+/// Identifies a printing within a user's collection
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct Printing {
+    pub card_id: Uuid,
+    pub condition: String,
+    pub language: String,
+    pub finish: String,
+}
+
+/// Result of removing copies from a collection
+#[derive(Debug)]
+pub enum QuantityChange {
+    Updated(CollectionEntry),
+    /// The last copies were removed and the entry no longer exists
+    Deleted,
+    NotOwned,
+    Insufficient { owned: i32 },
+}
+
+/// How much of a set a user owns. Counts are distinct cards, regardless of
+/// printing; the official `total_cards` of the set is the target.
+#[derive(Debug, Serialize, Deserialize)]
+pub struct SetCompletion {
+    pub set_id: Uuid,
+    pub name: String,
+    pub code: String,
+    pub slug: String,
+    pub release_date: DateTime<Utc>,
+    pub total_cards: i32,
+    pub owned_cards: i64,
+    pub missing_cards: i64,
+    /// Copies of the set's cards in every printing, duplicates included
+    pub owned_copies: i64,
+    pub completion_percent: f64,
+}
+
+impl<'r> sqlx::FromRow<'r, PgRow> for SetCompletion {
+    fn from_row(row: &'r PgRow) -> Result<Self, sqlx::Error> {
+        let total_cards: i32 = row.try_get("total_cards")?;
+        let owned_cards: i64 = row.try_get("owned_cards")?;
+
+        Ok(Self {
+            set_id: row.try_get("set_id")?,
+            name: row.try_get("name")?,
+            code: row.try_get("code")?,
+            slug: row.try_get("slug")?,
+            release_date: row.try_get("release_date")?,
+            total_cards,
+            owned_cards,
+            missing_cards: (total_cards as i64 - owned_cards).max(0),
+            owned_copies: row.try_get("owned_copies")?,
+            completion_percent: completion_percent(owned_cards, total_cards as i64),
+        })
+    }
+}
+
+/// Owned and missing cards of one rarity within a set. The target is the
+/// number of cards of that rarity in the catalog.
+#[derive(Debug, Serialize, Deserialize)]
+pub struct RarityCompletion {
+    pub rarity: String,
+    pub total_cards: i64,
+    pub owned_cards: i64,
+    pub missing_cards: i64,
+    pub completion_percent: f64,
+}
+
+impl<'r> sqlx::FromRow<'r, PgRow> for RarityCompletion {
+    fn from_row(row: &'r PgRow) -> Result<Self, sqlx::Error> {
+        let total_cards: i64 = row.try_get("total_cards")?;
+        let owned_cards: i64 = row.try_get("owned_cards")?;
+
+        Ok(Self {
+            rarity: row.try_get("rarity")?,
+            total_cards,
+            owned_cards,
+            missing_cards: (total_cards - owned_cards).max(0),
+            completion_percent: completion_percent(owned_cards, total_cards),
+        })
+    }
+}
+
+/// Completion of a single set, broken down by rarity, with the cards still missing
+#[derive(Debug, Serialize)]
+pub struct SetCompletionDetail {
+    #[serde(flatten)]
+    pub completion: SetCompletion,
+    pub by_rarity: Vec<RarityCompletion>,
+    pub missing: Vec<Card>,
+}
+
+// Porcentaje con un decimal; un conjunto sin cartas cuenta como vacío
+fn completion_percent(owned: i64, total: i64) -> f64 {
+    if total <= 0 {
+        return 0.0;
+    }
+
+    ((owned.min(total) as f64 / total as f64) * 1000.0).round() / 10.0
+}
+
+/// Field the set completion list is sorted by
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum CompletionSort {
+    Completion,
+    Missing,
+    Name,
+    ReleaseDate,
+}
+
+impl CompletionSort {
+    pub fn parse(value: &str) -> Option<Self> {
+        match value.trim().to_lowercase().as_str() {
+            "completion" => Some(Self::Completion),
+            "missing" => Some(Self::Missing),
+            "name" => Some(Self::Name),
+            "release_date" => Some(Self::ReleaseDate),
+            _ => None,
+        }
+    }
+}
@@ -0,0 +1,320 @@
+use async_trait::async_trait;
+use sqlx::PgPool;
+use anyhow::Result;
+use sqlx::Row;
+use uuid::Uuid;
+
+use super::dto::MAX_QUANTITY;
+use super::model::{CollectionEntry, Printing, QuantityChange, RarityCompletion, SetCompletion};
+use crate::domain::cards::Card;
+
+#[async_trait]
+pub trait CollectionRepository {
+    async fn get_entries(&self, owner_uid: &str) -> Result<Vec<CollectionEntry>>;
+    async fn get_entry(&self, owner_uid: &str, id: Uuid) -> Result<Option<CollectionEntry>>;
+    async fn add_copies(&self, owner_uid: &str, printing: &Printing, quantity: i32) -> Result<CollectionEntry>;
+    async fn remove_copies(&self, owner_uid: &str, printing: &Printing, quantity: i32) -> Result<QuantityChange>;
+    async fn set_quantity(&self, owner_uid: &str, id: Uuid, quantity: i32) -> Result<Option<CollectionEntry>>;
+    async fn delete_entry(&self, owner_uid: &str, id: Uuid) -> Result<bool>;
+    /// Adds the copies (or, with `replace`, sets the exact collection) in a single
+    /// transaction; in dry-run mode it is rolled back
+    async fn import_entries(&self, owner_uid: &str, entries: Vec<(Printing, i32)>, replace: bool, dry_run: bool) -> Result<ImportCounts>;
+    /// Completion of every visible set, or only of `set_id` when given
+    async fn get_set_completions(&self, owner_uid: &str, set_id: Option<Uuid>, include_unreleased: bool) -> Result<Vec<SetCompletion>>;
+    async fn get_rarity_completions(&self, owner_uid: &str, set_id: Uuid, include_unreleased: bool) -> Result<Vec<RarityCompletion>>;
+    async fn get_missing_cards(&self, owner_uid: &str, set_id: Uuid, include_unreleased: bool) -> Result<Vec<Card>>;
+}
+
+// Resultado de una importación de colección
+#[derive(Debug, Default)]
+pub struct ImportCounts {
+    pub created: usize,
+    pub updated: usize,
+    pub removed: usize,
+}
+
+pub struct PgCollectionRepository {
+    pool: PgPool,
+}
+
+impl PgCollectionRepository {
+    pub fn new(pool: PgPool) -> Self {
+        Self { pool }
+    }
+}
+
+#[async_trait]
+impl CollectionRepository for PgCollectionRepository {
+    async fn get_entries(&self, owner_uid: &str) -> Result<Vec<CollectionEntry>> {
+        let entries = sqlx::query_as::<_, CollectionEntry>(
+            r#"
+            SELECT e.id, e.owner_uid, e.card_id, e.condition, e.language, e.finish, e.quantity, e.created_at, e.updated_at
+            FROM collection_entries e
+            JOIN cards c ON c.id = e.card_id
+            JOIN card_sets s ON s.id = c.set_id
+            WHERE e.owner_uid = $1
+            ORDER BY s.release_date, s.code, c.collector_number, e.condition, e.language, e.finish
+            "#
+        )
+        .bind(owner_uid)
+        .fetch_all(&self.pool)
+        .await?;
+
+        Ok(entries)
+    }
+
+    async fn get_entry(&self, owner_uid: &str, id: Uuid) -> Result<Option<CollectionEntry>> {
+        let entry = sqlx::query_as::<_, CollectionEntry>(
+            r#"
+            SELECT id, owner_uid, card_id, condition, language, finish, quantity, created_at, updated_at
+            FROM collection_entries
+            WHERE owner_uid = $1 AND id = $2
+            "#
+        )
+        .bind(owner_uid)
+        .bind(id)
+        .fetch_optional(&self.pool)
+        .await?;
+
+        Ok(entry)
+    }
+
+    async fn add_copies(&self, owner_uid: &str, printing: &Printing, quantity: i32) -> Result<CollectionEntry> {
+        let entry = sqlx::query_as::<_, CollectionEntry>(
+            r#"
+            INSERT INTO collection_entries (id, owner_uid, card_id, condition, language, finish, quantity, created_at, updated_at)
+            VALUES ($1, $2, $3, $4, $5, $6, $7, NOW(), NOW())
+            ON CONFLICT (owner_uid, card_id, condition, language, finish) DO UPDATE
+            SET quantity = collection_entries.quantity + EXCLUDED.quantity, updated_at = NOW()
+            RETURNING id, owner_uid, card_id, condition, language, finish, quantity, created_at, updated_at
+            "#
+        )
+        .bind(Uuid::new_v4())
+        .bind(owner_uid)
+        .bind(printing.card_id)
+        .bind(&printing.condition)
+        .bind(&printing.language)
+        .bind(&printing.finish)
+        .bind(quantity)
+        .fetch_one(&self.pool)
+        .await?;
+
+        Ok(entry)
+    }
+
+    async fn remove_copies(&self, owner_uid: &str, printing: &Printing, quantity: i32) -> Result<QuantityChange> {
+        let mut tx = self.pool.begin().await?;
+
+        // Se bloquea la fila para que dos retiradas simultáneas no dejen cantidades negativas
+        let entry = sqlx::query_as::<_, CollectionEntry>(
+            r#"
+            SELECT id, owner_uid, card_id, condition, language, finish, quantity, created_at, updated_at
+            FROM collection_entries
+            WHERE owner_uid = $1 AND card_id = $2 AND condition = $3 AND language = $4 AND finish = $5
+            FOR UPDATE
+            "#
+        )
+        .bind(owner_uid)
+        .bind(printing.card_id)
+        .bind(&printing.condition)
+        .bind(&printing.language)
+        .bind(&printing.finish)
+        .fetch_optional(&mut *tx)
+        .await?;
+
+        let Some(entry) = entry else {
+            return Ok(QuantityChange::NotOwned);
+        };
+
+        let change = if quantity > entry.quantity {
+            QuantityChange::Insufficient { owned: entry.quantity }
+        } else if quantity == entry.quantity {
+            sqlx::query("DELETE FROM collection_entries WHERE id = $1")
+                .bind(entry.id)
+                .execute(&mut *tx)
+                .await?;
+            QuantityChange::Deleted
+        } else {
+            let updated = sqlx::query_as::<_, CollectionEntry>(
+                r#"
+                UPDATE collection_entries
+                SET quantity = quantity - $1, updated_at = NOW()
+                WHERE id = $2
+                RETURNING id, owner_uid, card_id, condition, language, finish, quantity, created_at, updated_at
+                "#
+            )
+            .bind(quantity)
+            .bind(entry.id)
+            .fetch_one(&mut *tx)
+            .await?;
+            QuantityChange::Updated(updated)
+        };
+
+        tx.commit().await?;
+
+        Ok(change)
+    }
+
+    async fn set_quantity(&self, owner_uid: &str, id: Uuid, quantity: i32) -> Result<Option<CollectionEntry>> {
+        let entry = sqlx::query_as::<_, CollectionEntry>(
+            r#"
+            UPDATE collection_entries
+            SET quantity = $1, updated_at = NOW()
+            WHERE owner_uid = $2 AND id = $3
+            RETURNING id, owner_uid, card_id, condition, language, finish, quantity, created_at, updated_at
+            "#
+        )
+        .bind(quantity)
+        .bind(owner_uid)
+        .bind(id)
+        .fetch_optional(&self.pool)
+        .await?;
+
+        Ok(entry)
+    }
+
+    async fn delete_entry(&self, owner_uid: &str, id: Uuid) -> Result<bool> {
+        let result = sqlx::query(
+            r#"
+            DELETE FROM collection_entries
+            WHERE owner_uid = $1 AND id = $2
+            "#
+        )
+        .bind(owner_uid)
+        .bind(id)
+        .execute(&self.pool)
+        .await?;
+
+        Ok(result.rows_affected() > 0)
+    }
+
+    async fn import_entries(&self, owner_uid: &str, entries: Vec<(Printing, i32)>, replace: bool, dry_run: bool) -> Result<ImportCounts> {
+        let mut tx = self.pool.begin().await?;
+        let mut counts = ImportCounts::default();
+        let mut imported_ids = Vec::with_capacity(entries.len());
+
+        for (printing, quantity) in entries {
+            // Al sumar a lo que ya había, la cantidad no pasa del máximo permitido.
+            // xmax = 0 indica que la fila fue insertada y no actualizada
+            let row = sqlx::query(
+                r#"
+                INSERT INTO collection_entries (id, owner_uid, card_id, condition, language, finish, quantity, created_at, updated_at)
+                VALUES ($1, $2, $3, $4, $5, $6, $7, NOW(), NOW())
+                ON CONFLICT (owner_uid, card_id, condition, language, finish) DO UPDATE
+                SET quantity = CASE WHEN $8 THEN EXCLUDED.quantity ELSE LEAST(collection_entries.quantity + EXCLUDED.quantity, $9) END,
+                    updated_at = NOW()
+                RETURNING id, (xmax = 0) AS inserted
+                "#
+            )
+            .bind(Uuid::new_v4())
+            .bind(owner_uid)
+            .bind(printing.card_id)
+            .bind(&printing.condition)
+            .bind(&printing.language)
+            .bind(&printing.finish)
+            .bind(quantity)
+            .bind(replace)
+            .bind(MAX_QUANTITY)
+            .fetch_one(&mut *tx)
+            .await?;
+
+            if row.try_get::<bool, _>("inserted")? {
+                counts.created += 1;
+            } else {
+                counts.updated += 1;
+            }
+            imported_ids.push(row.try_get::<Uuid, _>("id")?);
+        }
+
+        // Al reemplazar, desaparece todo lo que no venía en el archivo
+        if replace {
+            let result = sqlx::query("DELETE FROM collection_entries WHERE owner_uid = $1 AND NOT (id = ANY($2))")
+                .bind(owner_uid)
+                .bind(&imported_ids)
+                .execute(&mut *tx)
+                .await?;
+            counts.removed = result.rows_affected() as usize;
+        }
+
+        if dry_run {
+            tx.rollback().await?;
+        } else {
+            tx.commit().await?;
+        }
+
+        Ok(counts)
+    }
+
+    async fn get_set_completions(&self, owner_uid: &str, set_id: Option<Uuid>, include_unreleased: bool) -> Result<Vec<SetCompletion>> {
+        // Un conjunto no publicado es visible si tiene cartas reveladas, pero sólo cuentan esas cartas
+        let completions = sqlx::query_as::<_, SetCompletion>(
+            r#"
+            SELECT s.id AS set_id, s.name, s.code, s.slug, s.release_date, s.total_cards,
+                   COUNT(DISTINCT e.card_id) AS owned_cards,
+                   COALESCE(SUM(e.quantity), 0)::BIGINT AS owned_copies
+            FROM card_sets s
+            LEFT JOIN cards c ON c.set_id = s.id
+              AND ($3 OR COALESCE(s.publish_at, s.release_date) <= NOW() OR c.revealed_at <= NOW())
+            LEFT JOIN collection_entries e ON e.card_id = c.id AND e.owner_uid = $1
+            WHERE ($2::UUID IS NULL OR s.id = $2)
+              AND ($3 OR COALESCE(s.publish_at, s.release_date) <= NOW()
+                   OR EXISTS (SELECT 1 FROM cards r WHERE r.set_id = s.id AND r.revealed_at <= NOW()))
+            GROUP BY s.id
+            ORDER BY s.release_date DESC
+            "#
+        )
+        .bind(owner_uid)
+        .bind(set_id)
+        .bind(include_unreleased)
+        .fetch_all(&self.pool)
+        .await?;
+
+        Ok(completions)
+    }
+
+    async fn get_rarity_completions(&self, owner_uid: &str, set_id: Uuid, include_unreleased: bool) -> Result<Vec<RarityCompletion>> {
+        let completions = sqlx::query_as::<_, RarityCompletion>(
+            r#"
+            SELECT c.rarity,
+                   COUNT(*) AS total_cards,
+                   COUNT(*) FILTER (
+                       WHERE EXISTS (SELECT 1 FROM collection_entries e WHERE e.card_id = c.id AND e.owner_uid = $1)
+                   ) AS owned_cards
+            FROM cards c
+            JOIN card_sets s ON s.id = c.set_id
+            WHERE c.set_id = $2
+              AND ($3 OR COALESCE(s.publish_at, s.release_date) <= NOW() OR c.revealed_at <= NOW())
+            GROUP BY c.rarity
+            ORDER BY c.rarity
+            "#
+        )
+        .bind(owner_uid)
+        .bind(set_id)
+        .bind(include_unreleased)
+        .fetch_all(&self.pool)
+        .await?;
+
+        Ok(completions)
+    }
+
+    async fn get_missing_cards(&self, owner_uid: &str, set_id: Uuid, include_unreleased: bool) -> Result<Vec<Card>> {
+        let cards = sqlx::query_as::<_, Card>(
+            r#"
+            SELECT c.id, c.set_id, c.collector_number, c.name, c.card_type, c.card_energy, c.rarity, c.cost, c.artists, c.image_url, c.image_details, c.revealed_at, c.created_at, c.updated_at
+            FROM cards c
+            JOIN card_sets s ON s.id = c.set_id
+            WHERE c.set_id = $2
+              AND ($3 OR COALESCE(s.publish_at, s.release_date) <= NOW() OR c.revealed_at <= NOW())
+              AND NOT EXISTS (SELECT 1 FROM collection_entries e WHERE e.card_id = c.id AND e.owner_uid = $1)
+            ORDER BY c.collector_number
+            "#
+        )
+        .bind(owner_uid)
+        .bind(set_id)
+        .bind(include_unreleased)
+        .fetch_all(&self.pool)
+        .await?;
+
+        Ok(cards)
+    }
+}
@@ -0,0 +1,273 @@
+use anyhow::Result;
+use std::collections::HashMap;
+use uuid::Uuid;
+
+use super::dto::{validate_printing, MAX_QUANTITY};
+use super::import::{
+    CollectionImportReport, CollectionImportRow, FuzzyMatch, ImportMode, UnmatchedRow, parse_collector_number, parse_condition,
+    parse_finish, parse_language, parse_quantity,
+};
+use super::model::{CollectionEntry, CompletionSort, Printing, QuantityChange, SetCompletion, SetCompletionDetail};
+use super::repository::CollectionRepository;
+use crate::domain::cards::{Card, CardCatalog, CatalogVisibility, NameResolution};
+
+pub struct CollectionService<R: CollectionRepository> {
+    repository: R,
+}
+
+impl<R: CollectionRepository> CollectionService<R> {
+    pub fn new(repository: R) -> Self {
+        Self { repository }
+    }
+
+    pub async fn get_entries(&self, owner_uid: &str) -> Result<Vec<CollectionEntry>> {
+        self.repository.get_entries(owner_uid).await
+    }
+
+    pub async fn get_entry(&self, owner_uid: &str, id: Uuid) -> Result<Option<CollectionEntry>> {
+        self.repository.get_entry(owner_uid, id).await
+    }
+
+    pub async fn add_copies(&self, owner_uid: &str, printing: &Printing, quantity: i32) -> Result<CollectionEntry> {
+        self.repository.add_copies(owner_uid, printing, quantity).await
+    }
+
+    pub async fn remove_copies(&self, owner_uid: &str, printing: &Printing, quantity: i32) -> Result<QuantityChange> {
+        self.repository.remove_copies(owner_uid, printing, quantity).await
+    }
+
+    /// Sets the exact number of copies; zero removes the entry
+    pub async fn set_quantity(&self, owner_uid: &str, id: Uuid, quantity: i32) -> Result<QuantityChange> {
+        if quantity == 0 {
+            return Ok(match self.repository.delete_entry(owner_uid, id).await? {
+                true => QuantityChange::Deleted,
+                false => QuantityChange::NotOwned,
+            });
+        }
+
+        Ok(match self.repository.set_quantity(owner_uid, id, quantity).await? {
+            Some(entry) => QuantityChange::Updated(entry),
+            None => QuantityChange::NotOwned,
+        })
+    }
+
+    pub async fn delete_entry(&self, owner_uid: &str, id: Uuid) -> Result<bool> {
+        self.repository.delete_entry(owner_uid, id).await
+    }
+
+    /// Resolves every row against the catalog and imports the matched ones.
+    /// Rows that cannot be resolved are reported; in replace mode any of them
+    /// cancels the import, since the collection would silently lose those cards.
+    pub async fn import_collection(
+        &self,
+        owner_uid: &str,
+        mut report: CollectionImportReport,
+        rows: Vec<CollectionImportRow>,
+        catalog: &CardCatalog,
+    ) -> Result<CollectionImportReport> {
+        // Las filas repetidas de una misma impresión se suman
+        let mut entries: HashMap<Printing, i32> = HashMap::new();
+        let mut order = Vec::new();
+
+        for row in rows {
+            let quantity = match parse_quantity(row.quantity.as_deref()) {
+                Ok(0) => {
+                    report.skipped_rows += 1;
+                    continue;
+                }
+                Ok(quantity) => quantity,
+                Err(reason) => {
+                    report.unmatched.push(unmatched(&row, reason, Vec::new(), catalog));
+                    continue;
+                }
+            };
+
+            let card = match resolve_card(&row, catalog) {
+                Ok((card, score)) => {
+                    if let Some(score) = score {
+                        report.fuzzy_matches.push(FuzzyMatch {
+                            line: row.line,
+                            input: row.describe(),
+                            card: catalog.reference(card),
+                            score: (score * 100.0).round() / 100.0,
+                        });
+                    }
+                    card
+                }
+                Err(unresolved) => {
+                    report.unmatched.push(unmatched(&row, unresolved.reason, unresolved.suggestions, catalog));
+                    continue;
+                }
+            };
+
+            let printing = match printing_for(&row, card) {
+                Ok(printing) => printing,
+                Err(reason) => {
+                    report.unmatched.push(unmatched(&row, reason, Vec::new(), catalog));
+                    continue;
+                }
+            };
+
+            let total = entries.entry(printing.clone()).or_insert_with(|| {
+                order.push(printing);
+                0
+            });
+            if *total + quantity > MAX_QUANTITY {
+                let reason = format!("Las filas de esta impresión suman más de {} copias", MAX_QUANTITY);
+                report.unmatched.push(unmatched(&row, reason, Vec::new(), catalog));
+                continue;
+            }
+            *total += quantity;
+            report.imported_rows += 1;
+        }
+
+        if report.mode == ImportMode::Replace && !report.unmatched.is_empty() {
+            report.imported_rows = 0;
+            return Ok(report);
+        }
+
+        let entries = order
+            .into_iter()
+            .map(|printing| {
+                let quantity = entries[&printing];
+                (printing, quantity)
+            })
+            .collect();
+
+        let counts = self
+            .repository
+            .import_entries(owner_uid, entries, report.mode == ImportMode::Replace, report.dry_run)
+            .await?;
+        report.created = counts.created;
+        report.updated = counts.updated;
+        report.removed = counts.removed;
+
+        Ok(report)
+    }
+
+    /// Completion of every visible set, sorted by `sort`; ties keep the newest set first
+    pub async fn get_set_completions(
+        &self,
+        owner_uid: &str,
+        visibility: CatalogVisibility,
+        sort: CompletionSort,
+        descending: bool,
+    ) -> Result<Vec<SetCompletion>> {
+        let mut completions = self
+            .repository
+            .get_set_completions(owner_uid, None, visibility.includes_unreleased())
+            .await?;
+
+        completions.sort_by(|a, b| {
+            let ordering = match sort {
+                CompletionSort::Completion => a.completion_percent.total_cmp(&b.completion_percent),
+                CompletionSort::Missing => a.missing_cards.cmp(&b.missing_cards),
+                CompletionSort::Name => a.name.to_lowercase().cmp(&b.name.to_lowercase()),
+                CompletionSort::ReleaseDate => a.release_date.cmp(&b.release_date),
+            };
+            if descending { ordering.reverse() } else { ordering }
+        });
+
+        Ok(completions)
+    }
+
+    pub async fn get_set_completion(
+        &self,
+        owner_uid: &str,
+        set_id: Uuid,
+        visibility: CatalogVisibility,
+    ) -> Result<Option<SetCompletionDetail>> {
+        let include_unreleased = visibility.includes_unreleased();
+
+        let Some(completion) = self
+            .repository
+            .get_set_completions(owner_uid, Some(set_id), include_unreleased)
+            .await?
+            .into_iter()
+            .next()
+        else {
+            return Ok(None);
+        };
+
+        Ok(Some(SetCompletionDetail {
+            completion,
+            by_rarity: self.repository.get_rarity_completions(owner_uid, set_id, include_unreleased).await?,
+            missing: self.repository.get_missing_cards(owner_uid, set_id, include_unreleased).await?,
+        }))
+    }
+}
+
+// Motivo por el que una fila no corresponde a ninguna carta, con las más parecidas
+struct Unresolved<'a> {
+    reason: String,
+    suggestions: Vec<&'a Card>,
+}
+
+impl<'a> Unresolved<'a> {
+    fn new(reason: String, suggestions: Vec<&'a Card>) -> Self {
+        Self { reason, suggestions }
+    }
+}
+
+// Primero por código de conjunto y número; si no hay o no coincide, por nombre.
+// Devuelve la similitud cuando el nombre se resolvió de forma aproximada.
+fn resolve_card<'a>(
+    row: &CollectionImportRow,
+    catalog: &'a CardCatalog,
+) -> std::result::Result<(&'a Card, Option<f64>), Unresolved<'a>> {
+    let set_code = row.set_code.as_deref();
+
+    if let (Some(set_code), Some(number)) = (set_code, row.collector_number.as_deref()) {
+        let card = parse_collector_number(number).and_then(|number| catalog.by_number(set_code, number));
+        match (card, row.name.as_deref()) {
+            (Some(card), _) => return Ok((card, None)),
+            (None, None) if !catalog.has_set(set_code) => {
+                return Err(Unresolved::new(format!("No existe un conjunto con el código '{}'", set_code), Vec::new()))
+            }
+            (None, None) => {
+                return Err(Unresolved::new(format!("El conjunto '{}' no tiene la carta número {}", set_code, number), Vec::new()))
+            }
+            (None, Some(_)) => {}
+        }
+    }
+
+    let Some(name) = row.name.as_deref() else {
+        return Err(Unresolved::new("La fila no indica el conjunto y número ni el nombre de la carta".to_string(), Vec::new()));
+    };
+
+    // Un código de conjunto desconocido no impide buscar por nombre en todo el catálogo
+    let set_code = set_code.filter(|code| catalog.has_set(code));
+
+    match catalog.resolve_name(name, set_code) {
+        NameResolution::Exact(card) => Ok((card, None)),
+        NameResolution::Fuzzy { card, score } => Ok((card, Some(score))),
+        NameResolution::Ambiguous(cards) => Err(Unresolved::new(
+            format!("'{}' existe en varios conjuntos; indica el código de conjunto", name),
+            cards,
+        )),
+        NameResolution::NotFound { suggestions } => {
+            Err(Unresolved::new(format!("No se encontró ninguna carta llamada '{}'", name), suggestions))
+        }
+    }
+}
+
+fn printing_for(row: &CollectionImportRow, card: &Card) -> std::result::Result<Printing, String> {
+    let printing = Printing {
+        card_id: card.id,
+        condition: parse_condition(row.condition.as_deref())?,
+        language: parse_language(row.language.as_deref())?,
+        finish: parse_finish(row.finish.as_deref())?,
+    };
+
+    validate_printing(&printing).map_err(|e| e.to_string())?;
+    Ok(printing)
+}
+
+fn unmatched(row: &CollectionImportRow, reason: String, suggestions: Vec<&Card>, catalog: &CardCatalog) -> UnmatchedRow {
+    UnmatchedRow {
+        line: row.line,
+        input: row.describe(),
+        reason,
+        suggestions: suggestions.into_iter().map(|card| catalog.reference(card)).collect(),
+    }
+}
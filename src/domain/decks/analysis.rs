@@ -0,0 +1,377 @@
+use serde::{Deserialize, Serialize};
+use std::collections::{BTreeMap, HashSet};
+use uuid::Uuid;
+
+use crate::domain::cards::{normalize_name, Card};
+
+/// How a player may replace an opening hand
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum MulliganRule {
+    /// Opening hands are final
+    #[default]
+    None,
+    /// Shuffle back and draw a full hand again
+    Redraw,
+    /// Draw a full hand again, then put one card per mulligan on the bottom
+    London,
+    /// Draw one card fewer per mulligan
+    Reduced,
+}
+
+/// Opening hand and draw rules the probabilities are computed with
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct DrawRules {
+    pub hand_size: u32,
+    /// Last turn probabilities are computed for
+    pub turns: u32,
+    pub draws_per_turn: u32,
+    /// The player going first skips the draw of their first turn
+    pub on_the_play: bool,
+    pub mulligan: MulliganRule,
+    pub max_mulligans: u32,
+}
+
+impl Default for DrawRules {
+    fn default() -> Self {
+        Self {
+            hand_size: 7,
+            turns: 5,
+            draws_per_turn: 1,
+            on_the_play: true,
+            mulligan: MulliganRule::None,
+            max_mulligans: 0,
+        }
+    }
+}
+
+/// A group of cards to compute draw odds for, e.g. every copy of a combo piece
+#[derive(Debug, Clone, Deserialize)]
+pub struct DrawTarget {
+    pub label: Option<String>,
+    pub card_ids: Vec<Uuid>,
+}
+
+#[derive(Debug, Serialize)]
+pub struct CostBucket {
+    /// `null` groups the cards without cost
+    pub cost: Option<i32>,
+    pub count: i32,
+}
+
+#[derive(Debug, Serialize)]
+pub struct DistributionEntry {
+    /// `null` groups the cards without a value (e.g. no energy)
+    pub value: Option<String>,
+    pub count: i32,
+    pub percentage: f64,
+}
+
+#[derive(Debug, Serialize)]
+pub struct TurnOdds {
+    pub turn: u32,
+    /// Cards seen by the end of the turn's draw, counting the hand the odds
+    /// assume is kept: after every allowed mulligan, which with `reduced`
+    /// is smaller than `hand_size`
+    pub cards_seen: u32,
+    pub probability: f64,
+}
+
+/// Chance of drawing at least one card of a target
+#[derive(Debug, Serialize)]
+pub struct TargetOdds {
+    pub label: String,
+    pub card_ids: Vec<Uuid>,
+    /// Copies of the target in the deck
+    pub copies: i32,
+    pub opening_hand: f64,
+    /// Mulliganing every opening hand without the target
+    pub opening_hand_with_mulligans: f64,
+    pub by_turn: Vec<TurnOdds>,
+}
+
+#[derive(Debug, Serialize)]
+pub struct DeckAnalysis {
+    pub card_count: i32,
+    pub distinct_cards: usize,
+    /// Average cost of the cards that have one
+    pub average_cost: Option<f64>,
+    pub cost_curve: Vec<CostBucket>,
+    pub energies: Vec<DistributionEntry>,
+    pub card_types: Vec<DistributionEntry>,
+    pub rarities: Vec<DistributionEntry>,
+    pub rules: DrawRules,
+    pub draw_odds: Vec<TargetOdds>,
+    /// Cards the catalog does not include (not visible to the caller); they
+    /// count towards the deck size but not the distributions
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub unknown_cards: Vec<Uuid>,
+}
+
+/// A card of the deck being analysed
+pub struct AnalysisEntry<'a> {
+    pub card: &'a Card,
+    pub quantity: i32,
+}
+
+/// Computes a deck's statistics and draw odds. Without `targets`, odds are
+/// given for every card of the deck, grouping printings by name.
+pub fn analyze_deck(
+    entries: &[AnalysisEntry],
+    unknown: &[(Uuid, i32)],
+    rules: DrawRules,
+    targets: &[DrawTarget],
+) -> DeckAnalysis {
+    let known_count: i32 = entries.iter().map(|entry| entry.quantity).sum();
+    let card_count = known_count + unknown.iter().map(|(_, quantity)| quantity).sum::<i32>();
+
+    let mut curve: BTreeMap<Option<i32>, i32> = BTreeMap::new();
+    for entry in entries {
+        *curve.entry(entry.card.cost).or_default() += entry.quantity;
+    }
+    let (cost_total, costed) = entries
+        .iter()
+        .filter_map(|entry| entry.card.cost.map(|cost| (i64::from(cost) * i64::from(entry.quantity), entry.quantity)))
+        .fold((0i64, 0i32), |(total, count), (cost, quantity)| (total + cost, count + quantity));
+
+    // Sin coste al final de la curva
+    let mut cost_curve: Vec<CostBucket> = curve.into_iter().map(|(cost, count)| CostBucket { cost, count }).collect();
+    cost_curve.sort_by_key(|bucket| (bucket.cost.is_none(), bucket.cost));
+
+    let targets: Vec<DrawTarget> = if targets.is_empty() { card_targets(entries) } else { targets.to_vec() };
+    let draw_odds = targets
+        .iter()
+        .map(|target| target_odds(target, entries, unknown, card_count, &rules))
+        .collect();
+
+    DeckAnalysis {
+        card_count,
+        distinct_cards: entries.len() + unknown.len(),
+        average_cost: (costed > 0).then(|| cost_total as f64 / f64::from(costed)),
+        cost_curve,
+        energies: distribution(entries, known_count, |card| card.card_energy.as_deref()),
+        card_types: distribution(entries, known_count, |card| Some(card.card_type.as_str())),
+        rarities: distribution(entries, known_count, |card| Some(card.rarity.as_str())),
+        rules,
+        draw_odds,
+        unknown_cards: unknown.iter().map(|(id, _)| *id).collect(),
+    }
+}
+
+// Los valores se agrupan sin distinguir mayúsculas ni acentos; de más a menos cartas
+fn distribution<'a>(
+    entries: &[AnalysisEntry<'a>],
+    total: i32,
+    value: impl Fn(&'a Card) -> Option<&'a str>,
+) -> Vec<DistributionEntry> {
+    let mut groups: BTreeMap<Option<String>, (Option<&str>, i32)> = BTreeMap::new();
+    for entry in entries {
+        let value = value(entry.card).map(str::trim).filter(|value| !value.is_empty());
+        groups.entry(value.map(normalize_name)).or_insert((value, 0)).1 += entry.quantity;
+    }
+
+    let mut distribution: Vec<DistributionEntry> = groups
+        .into_values()
+        .map(|(value, count)| DistributionEntry {
+            value: value.map(str::to_string),
+            count,
+            percentage: if total > 0 { f64::from(count) * 100.0 / f64::from(total) } else { 0.0 },
+        })
+        .collect();
+    distribution.sort_by(|a, b| b.count.cmp(&a.count).then_with(|| a.value.cmp(&b.value)));
+    distribution
+}
+
+// Un objetivo por carta, juntando sus impresiones, en el orden del mazo
+fn card_targets(entries: &[AnalysisEntry]) -> Vec<DrawTarget> {
+    let mut targets: Vec<DrawTarget> = Vec::new();
+    let mut names: Vec<String> = Vec::new();
+
+    for entry in entries {
+        let name = normalize_name(&entry.card.name);
+        match names.iter().position(|existing| *existing == name) {
+            Some(index) => targets[index].card_ids.push(entry.card.id),
+            None => {
+                names.push(name);
+                targets.push(DrawTarget { label: Some(entry.card.name.clone()), card_ids: vec![entry.card.id] });
+            }
+        }
+    }
+
+    targets
+}
+
+fn target_odds(
+    target: &DrawTarget,
+    entries: &[AnalysisEntry],
+    unknown: &[(Uuid, i32)],
+    card_count: i32,
+    rules: &DrawRules,
+) -> TargetOdds {
+    let ids: HashSet<Uuid> = target.card_ids.iter().copied().collect();
+    let copies: i32 = entries
+        .iter()
+        .map(|entry| (entry.card.id, entry.quantity))
+        .chain(unknown.iter().copied())
+        .filter(|(id, _)| ids.contains(id))
+        .map(|(_, quantity)| quantity)
+        .sum();
+
+    let label = target.label.clone().unwrap_or_else(|| {
+        entries
+            .iter()
+            .filter(|entry| ids.contains(&entry.card.id))
+            .map(|entry| entry.card.name.as_str())
+            .collect::<Vec<_>>()
+            .join(" / ")
+    });
+
+    let deck = card_count.max(0) as u32;
+    let copies_in_deck = copies.max(0) as u32;
+
+    // Manos iniciales que se ven: la primera y una por cada mulligan
+    let mulligans = if rules.mulligan == MulliganRule::None { 0 } else { rules.max_mulligans };
+    let hand_drawn = |mulligan: u32| match rules.mulligan {
+        MulliganRule::Reduced => rules.hand_size.saturating_sub(mulligan),
+        _ => rules.hand_size,
+    };
+
+    let first_miss = miss_probability(deck, copies_in_deck, rules.hand_size);
+    let opening_miss: f64 = (0..=mulligans)
+        .map(|mulligan| miss_probability(deck, copies_in_deck, hand_drawn(mulligan)))
+        .product();
+
+    // Si ninguna mano tuvo el objetivo, las copias siguen entre las cartas por robar
+    let library = deck.saturating_sub(hand_drawn(mulligans));
+    let by_turn = (1..=rules.turns)
+        .map(|turn| {
+            let draws = rules.draws_per_turn * (turn - u32::from(rules.on_the_play));
+            TurnOdds {
+                turn,
+                cards_seen: hand_drawn(mulligans) + draws,
+                probability: 1.0 - opening_miss * miss_probability(library, copies_in_deck, draws),
+            }
+        })
+        .collect();
+
+    TargetOdds {
+        label,
+        card_ids: target.card_ids.clone(),
+        copies,
+        opening_hand: 1.0 - first_miss,
+        opening_hand_with_mulligans: 1.0 - opening_miss,
+        by_turn,
+    }
+}
+
+/// Hypergeometric probability of drawing none of `successes` cards when
+/// drawing `draws` cards from `population`
+fn miss_probability(population: u32, successes: u32, draws: u32) -> f64 {
+    if successes == 0 {
+        return 1.0;
+    }
+
+    let misses = population.saturating_sub(successes);
+    if draws > misses {
+        return 0.0;
+    }
+
+    (0..draws).map(|i| f64::from(misses - i) / f64::from(population - i)).product()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn assert_close(actual: f64, expected: f64) {
+        assert!((actual - expected).abs() < 1e-4, "expected {expected}, got {actual}");
+    }
+
+    fn rules(mulligan: MulliganRule, max_mulligans: u32) -> DrawRules {
+        DrawRules { turns: 3, mulligan, max_mulligans, ..DrawRules::default() }
+    }
+
+    // Tres copias en un mazo de 40; no hacen falta cartas del catálogo
+    fn three_of(rules: &DrawRules) -> TargetOdds {
+        let id = Uuid::new_v4();
+        let target = DrawTarget { label: Some("Tokar".to_string()), card_ids: vec![id] };
+        target_odds(&target, &[], &[(id, 3)], 40, rules)
+    }
+
+    #[test]
+    fn miss_probability_matches_the_hypergeometric_distribution() {
+        // C(37,7) / C(40,7): al menos una de 3 copias en 7 cartas de 40 es 0.4478
+        assert_close(miss_probability(40, 3, 7), 0.552227);
+        assert_close(1.0 - miss_probability(40, 3, 7), 0.447773);
+        // C(56,7) / C(60,7)
+        assert_close(miss_probability(60, 4, 7), 0.600500);
+    }
+
+    #[test]
+    fn miss_probability_edge_cases() {
+        assert_eq!(miss_probability(40, 0, 7), 1.0);
+        assert_eq!(miss_probability(40, 3, 0), 1.0);
+        // Más cartas robadas que cartas que no son el objetivo
+        assert_eq!(miss_probability(10, 4, 7), 0.0);
+        assert_eq!(miss_probability(0, 0, 7), 1.0);
+    }
+
+    #[test]
+    fn target_without_copies_is_never_drawn() {
+        let target = DrawTarget { label: None, card_ids: vec![Uuid::new_v4()] };
+        let odds = target_odds(&target, &[], &[], 40, &rules(MulliganRule::London, 2));
+
+        assert_eq!(odds.copies, 0);
+        assert_eq!(odds.opening_hand, 0.0);
+        assert_eq!(odds.opening_hand_with_mulligans, 0.0);
+        assert!(odds.by_turn.iter().all(|turn| turn.probability == 0.0));
+    }
+
+    #[test]
+    fn odds_without_mulligans() {
+        let odds = three_of(&rules(MulliganRule::None, 2));
+
+        assert_close(odds.opening_hand, 0.447773);
+        assert_eq!(odds.opening_hand, odds.opening_hand_with_mulligans);
+        // En el primer turno no se roba yendo primero
+        let seen: Vec<u32> = odds.by_turn.iter().map(|turn| turn.cards_seen).collect();
+        assert_eq!(seen, vec![7, 8, 9]);
+        assert_close(odds.by_turn[0].probability, 0.447773);
+        // C(37,9) / C(40,9)
+        assert_close(odds.by_turn[2].probability, 0.545040);
+    }
+
+    #[test]
+    fn odds_with_london_mulligan() {
+        let odds = three_of(&rules(MulliganRule::London, 1));
+
+        assert_close(odds.opening_hand, 0.447773);
+        // Dos manos de 7: 1 - 0.552227²
+        assert_close(odds.opening_hand_with_mulligans, 0.695046);
+        // Se ven 7 cartas por mano aunque luego se pongan al fondo
+        assert_eq!(odds.by_turn[2].cards_seen, 9);
+        assert_close(odds.by_turn[2].probability, 1.0 - 0.552227 * 0.552227 * (30.0 * 29.0) / (33.0 * 32.0));
+    }
+
+    #[test]
+    fn odds_with_reduced_mulligan() {
+        let odds = three_of(&rules(MulliganRule::Reduced, 1));
+
+        // Una mano de 7 y otra de 6: 1 - C(37,7)/C(40,7) · C(37,6)/C(40,6)
+        assert_close(odds.opening_hand_with_mulligans, 0.665534);
+        // La mano que se queda es de 6
+        let seen: Vec<u32> = odds.by_turn.iter().map(|turn| turn.cards_seen).collect();
+        assert_eq!(seen, vec![6, 7, 8]);
+        assert_close(odds.by_turn[2].probability, 0.722769);
+    }
+
+    #[test]
+    fn mulligans_only_help() {
+        for mulligan in [MulliganRule::Redraw, MulliganRule::London, MulliganRule::Reduced] {
+            let odds = three_of(&rules(mulligan, 2));
+            assert!(odds.opening_hand_with_mulligans > odds.opening_hand, "{:?}", mulligan);
+            assert!(odds.by_turn.windows(2).all(|pair| pair[1].probability >= pair[0].probability));
+        }
+    }
+}
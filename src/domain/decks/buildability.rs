@@ -0,0 +1,210 @@
+use serde::Serialize;
+use std::collections::{BTreeMap, HashMap};
+use uuid::Uuid;
+
+use super::model::DeckCard;
+use crate::domain::cards::{CardCatalog, CardPrice, CardReference};
+use crate::domain::collections::CollectionEntry;
+
+/// Another printing of a deck card the user owns and can play instead
+#[derive(Debug, Serialize)]
+pub struct AlternativePrinting {
+    #[serde(flatten)]
+    pub card: CardReference,
+    /// Copies not already used by other cards of the deck
+    pub available: i32,
+    /// Missing copies of the slot this printing covers
+    pub covers: i32,
+}
+
+/// Price of the missing copies of a card, using its cheapest priced printing
+/// in one currency: the deck printing's own, or the most common among its
+/// printings when it has no price
+#[derive(Debug, Serialize)]
+pub struct PriceEstimate {
+    pub card_id: Uuid,
+    pub unit_price_cents: i64,
+    pub currency: String,
+    pub total_cents: i64,
+}
+
+#[derive(Debug, Serialize)]
+pub struct BuildabilitySlot {
+    #[serde(flatten)]
+    pub card: CardReference,
+    pub quantity: i32,
+    /// Copies of this exact printing in the collection, up to `quantity`
+    pub owned: i32,
+    pub missing: i32,
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub alternatives: Vec<AlternativePrinting>,
+    /// Copies still missing after using the alternatives
+    pub still_missing: i32,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub price: Option<PriceEstimate>,
+}
+
+#[derive(Debug, Serialize)]
+pub struct CostEstimate {
+    pub currency: String,
+    pub amount_cents: i64,
+}
+
+/// What a user already owns of a deck and what they would need to buy
+#[derive(Debug, Serialize)]
+pub struct BuildabilityReport {
+    pub card_count: i32,
+    pub owned_cards: i32,
+    pub covered_by_alternatives: i32,
+    pub missing_cards: i32,
+    /// The collection has every card, counting alternative printings
+    pub buildable: bool,
+    /// The collection has every card in the exact printings of the deck
+    pub buildable_as_listed: bool,
+    /// Cost of the cards still missing, one total per currency
+    pub estimated_cost: Vec<CostEstimate>,
+    /// Missing copies with no known price, left out of `estimated_cost`
+    pub unpriced_cards: i32,
+    pub cards: Vec<BuildabilitySlot>,
+    /// Cards the catalog does not include (not visible to the caller)
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub unknown_cards: Vec<Uuid>,
+}
+
+/// Every printing of the deck's cards, to look up their prices
+pub fn printing_ids(cards: &[DeckCard], catalog: &CardCatalog) -> Vec<Uuid> {
+    let mut ids: Vec<Uuid> = cards
+        .iter()
+        .filter_map(|deck_card| catalog.by_id(deck_card.card_id))
+        .flat_map(|card| catalog.printings(card))
+        .map(|card| card.id)
+        .collect();
+    ids.sort();
+    ids.dedup();
+    ids
+}
+
+/// Compares a deck with a collection. Exact printings are used first, for
+/// every card; only then are the remaining copies of other printings of the
+/// same name shared out, in deck order, so no copy is counted twice.
+pub fn check_buildability(
+    cards: &[DeckCard],
+    collection: &[CollectionEntry],
+    catalog: &CardCatalog,
+    prices: &[CardPrice],
+) -> BuildabilityReport {
+    // Copias por impresión, sumando estados, idiomas y acabados
+    let mut available: HashMap<Uuid, i32> = HashMap::new();
+    for entry in collection {
+        *available.entry(entry.card_id).or_default() += entry.quantity;
+    }
+    let prices: HashMap<Uuid, &CardPrice> = prices.iter().map(|price| (price.card_id, price)).collect();
+
+    let mut unknown_cards = Vec::new();
+    let mut slots = Vec::with_capacity(cards.len());
+    for deck_card in cards {
+        let Some(card) = catalog.by_id(deck_card.card_id) else {
+            unknown_cards.push(deck_card.card_id);
+            continue;
+        };
+
+        let copies = available.entry(card.id).or_default();
+        let owned = deck_card.quantity.min(*copies);
+        *copies -= owned;
+
+        slots.push((card, BuildabilitySlot {
+            card: catalog.reference(card),
+            quantity: deck_card.quantity,
+            owned,
+            missing: deck_card.quantity - owned,
+            alternatives: Vec::new(),
+            still_missing: deck_card.quantity - owned,
+            price: None,
+        }));
+    }
+
+    for (card, slot) in slots.iter_mut().filter(|(_, slot)| slot.missing > 0) {
+        for printing in catalog.printings(card).into_iter().filter(|printing| printing.id != card.id) {
+            if slot.still_missing == 0 {
+                break;
+            }
+
+            let copies = available.entry(printing.id).or_default();
+            let covers = slot.still_missing.min(*copies);
+            if covers == 0 {
+                continue;
+            }
+
+            slot.alternatives.push(AlternativePrinting { card: catalog.reference(printing), available: *copies, covers });
+            *copies -= covers;
+            slot.still_missing -= covers;
+        }
+
+        // Cualquier impresión vale para el hueco: se estima con la más barata
+        if slot.still_missing > 0 {
+            let priced: Vec<&CardPrice> = catalog
+                .printings(card)
+                .into_iter()
+                .filter_map(|printing| prices.get(&printing.id).copied())
+                .collect();
+
+            slot.price = estimate_currency(card.id, &priced)
+                .and_then(|currency| {
+                    priced
+                        .iter()
+                        .filter(|price| price.currency == currency)
+                        .min_by_key(|price| (price.price_cents, price.card_id != card.id))
+                })
+                .map(|price| PriceEstimate {
+                    card_id: price.card_id,
+                    unit_price_cents: price.price_cents,
+                    currency: price.currency.clone(),
+                    total_cents: price.price_cents * i64::from(slot.still_missing),
+                });
+        }
+    }
+
+    let slots: Vec<BuildabilitySlot> = slots.into_iter().map(|(_, slot)| slot).collect();
+    let mut costs: BTreeMap<&str, i64> = BTreeMap::new();
+    for price in slots.iter().filter_map(|slot| slot.price.as_ref()) {
+        *costs.entry(price.currency.as_str()).or_default() += price.total_cents;
+    }
+
+    let card_count: i32 = slots.iter().map(|slot| slot.quantity).sum();
+    let owned_cards: i32 = slots.iter().map(|slot| slot.owned).sum();
+    let missing_cards: i32 = slots.iter().map(|slot| slot.still_missing).sum();
+
+    BuildabilityReport {
+        card_count,
+        owned_cards,
+        covered_by_alternatives: card_count - owned_cards - missing_cards,
+        missing_cards,
+        buildable: missing_cards == 0 && unknown_cards.is_empty(),
+        buildable_as_listed: owned_cards == card_count && unknown_cards.is_empty(),
+        estimated_cost: costs
+            .into_iter()
+            .map(|(currency, amount_cents)| CostEstimate { currency: currency.to_string(), amount_cents })
+            .collect(),
+        unpriced_cards: slots.iter().filter(|slot| slot.price.is_none()).map(|slot| slot.still_missing).sum(),
+        cards: slots,
+        unknown_cards,
+    }
+}
+
+// Los céntimos sólo se comparan dentro de una misma moneda
+fn estimate_currency<'a>(card_id: Uuid, priced: &[&'a CardPrice]) -> Option<&'a str> {
+    if let Some(own) = priced.iter().find(|price| price.card_id == card_id) {
+        return Some(own.currency.as_str());
+    }
+
+    let mut counts: BTreeMap<&str, usize> = BTreeMap::new();
+    for price in priced {
+        *counts.entry(price.currency.as_str()).or_default() += 1;
+    }
+
+    // Con empate, la primera en orden alfabético
+    counts
+        .into_iter()
+        .max_by(|a, b| a.1.cmp(&b.1).then_with(|| b.0.cmp(a.0)))
+        .map(|(currency, _)| currency)
+}
@@ -0,0 +1,330 @@
+use serde::Serialize;
+use sqlx::postgres::PgRow;
+use sqlx::Row;
+use std::collections::{BTreeMap, BTreeSet};
+use std::sync::Arc;
+use std::time::Duration;
+use tokio::time::MissedTickBehavior;
+
+use super::repository::PgDeckRepository;
+use super::service::DeckService;
+use crate::config::CooccurrenceConfig;
+use crate::domain::cards::{normalize_name, CardReference};
+
+/// A card seen together with another in the public decks, as stored
+#[derive(Debug)]
+pub struct CardPairCount {
+    pub other_name: String,
+    /// Decks including both cards
+    pub deck_count: i64,
+    /// Decks including the other card
+    pub other_deck_count: i64,
+}
+
+impl<'r> sqlx::FromRow<'r, PgRow> for CardPairCount {
+    fn from_row(row: &'r PgRow) -> Result<Self, sqlx::Error> {
+        Ok(Self {
+            other_name: row.try_get("other_name")?,
+            deck_count: row.try_get("deck_count")?,
+            other_deck_count: row.try_get("other_deck_count")?,
+        })
+    }
+}
+
+/// A card not in a deck, scored by how often it is played with the deck's cards
+#[derive(Debug)]
+pub struct CardSuggestionCount {
+    pub card_name: String,
+    pub score: f64,
+    pub played_with: i64,
+    pub deck_count: i64,
+}
+
+impl<'r> sqlx::FromRow<'r, PgRow> for CardSuggestionCount {
+    fn from_row(row: &'r PgRow) -> Result<Self, sqlx::Error> {
+        Ok(Self {
+            card_name: row.try_get("card_name")?,
+            score: row.try_get("score")?,
+            played_with: row.try_get("played_with")?,
+            deck_count: row.try_get("deck_count")?,
+        })
+    }
+}
+
+#[derive(Debug, Serialize)]
+pub struct PlayedWithCard {
+    #[serde(flatten)]
+    pub card: CardReference,
+    /// Public decks including both cards
+    pub decks: i64,
+    /// Share of the decks with the requested card that also play this one
+    pub rate: f64,
+    /// How many times more often both cards appear together than if decks
+    /// picked them independently; 1 means no relation
+    pub lift: f64,
+}
+
+/// Cards most often played with a given card, grouping printings by name
+#[derive(Debug, Serialize)]
+pub struct PlayedWith {
+    #[serde(flatten)]
+    pub card: CardReference,
+    /// Public decks including the card
+    pub deck_count: i64,
+    /// Public decks the statistics are computed from
+    pub total_decks: i64,
+    pub cards: Vec<PlayedWithCard>,
+}
+
+#[derive(Debug, Serialize)]
+pub struct CardSuggestion {
+    #[serde(flatten)]
+    pub card: CardReference,
+    /// Average, over the deck's cards, of the share of their decks that also
+    /// play this card
+    pub score: f64,
+    /// Cards of the deck it has been played with
+    pub played_with: i64,
+    /// Public decks including it
+    pub deck_count: i64,
+}
+
+/// Cards to add to a deck, best first
+#[derive(Debug, Serialize)]
+pub struct CardSuggestions {
+    pub total_decks: i64,
+    pub cards: Vec<CardSuggestion>,
+}
+
+/// Outcome of a refresh of the statistics
+#[derive(Debug, Default, Serialize)]
+pub struct CooccurrenceRefresh {
+    /// Public decks added or updated
+    pub indexed: usize,
+    /// Decks deleted or made private since they were indexed
+    pub removed: usize,
+}
+
+/// Changes to apply to the counts when a deck's cards go from one set of
+/// names to another
+#[derive(Debug, Default)]
+pub struct CooccurrenceDelta {
+    pub cards: Vec<(String, i32)>,
+    pub pairs: Vec<(String, String, i32)>,
+}
+
+/// Keys the statistics are stored by: normalized names, without repeats
+pub fn cooccurrence_names<'a>(names: impl IntoIterator<Item = &'a str>) -> Vec<String> {
+    names
+        .into_iter()
+        .map(normalize_name)
+        .filter(|name| !name.is_empty())
+        .collect::<BTreeSet<_>>()
+        .into_iter()
+        .collect()
+}
+
+/// Count changes between the names a deck contributed and the ones it has
+/// now. Each pair is returned once, with the names in byte order as in
+/// `card_pair_counts`; unchanged ones are left out.
+pub fn cooccurrence_delta(old: &[String], new: &[String]) -> CooccurrenceDelta {
+    let old: BTreeSet<&str> = old.iter().map(String::as_str).collect();
+    let new: BTreeSet<&str> = new.iter().map(String::as_str).collect();
+
+    let cards = old
+        .symmetric_difference(&new)
+        .map(|name| (name.to_string(), if new.contains(name) { 1 } else { -1 }))
+        .collect();
+
+    // Las parejas que siguen en el mazo se restan y se suman: se descartan al final
+    let mut pairs: BTreeMap<(&str, &str), i32> = BTreeMap::new();
+    for (names, delta) in [(&old, -1), (&new, 1)] {
+        for &name in names {
+            for &other in names.iter().filter(|&&other| other > name) {
+                *pairs.entry((name, other)).or_default() += delta;
+            }
+        }
+    }
+
+    CooccurrenceDelta {
+        cards,
+        pairs: pairs
+            .into_iter()
+            .filter(|(_, delta)| *delta != 0)
+            .map(|((name, other), delta)| (name.to_string(), other.to_string(), delta))
+            .collect(),
+    }
+}
+
+/// Keeps the statistics up to date in the background, reindexing every
+/// `refresh_secs` the public decks changed since the last run. With
+/// `refresh_secs` at 0 they are only refreshed from the command line.
+pub fn start_cooccurrence_refresh(deck_service: Arc<DeckService<PgDeckRepository>>, config: CooccurrenceConfig) {
+    if config.refresh_secs == 0 {
+        return;
+    }
+
+    tokio::spawn(async move {
+        let mut interval = tokio::time::interval(Duration::from_secs(config.refresh_secs));
+        interval.set_missed_tick_behavior(MissedTickBehavior::Delay);
+
+        loop {
+            interval.tick().await;
+            match deck_service.refresh_cooccurrence(config.batch_size).await {
+                Ok(refresh) if refresh.indexed + refresh.removed > 0 => tracing::info!(
+                    "Estadísticas de cartas jugadas juntas: {} mazo(s) indexado(s), {} retirado(s)",
+                    refresh.indexed, refresh.removed
+                ),
+                Ok(_) => {}
+                Err(e) => tracing::error!("Error actualizando las estadísticas de cartas jugadas juntas: {}", e),
+            }
+        }
+    });
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::collections::HashMap;
+
+    fn names(names: &[&str]) -> Vec<String> {
+        names.iter().map(|name| name.to_string()).collect()
+    }
+
+    // Cuenta las cartas y parejas como las tablas, sin guardar las que llegan a 0
+    #[derive(Debug, Default, PartialEq)]
+    struct Counts {
+        cards: BTreeMap<String, i32>,
+        pairs: BTreeMap<(String, String), i32>,
+    }
+
+    impl Counts {
+        fn apply(&mut self, delta: CooccurrenceDelta) {
+            for (name, count) in delta.cards {
+                let entry = self.cards.entry(name.clone()).or_default();
+                *entry += count;
+                assert!(*entry >= 0, "{} quedaría en negativo", name);
+                if *entry == 0 {
+                    self.cards.remove(&name);
+                }
+            }
+            for (name, other, count) in delta.pairs {
+                assert!(name < other, "pareja {} / {} fuera de orden", name, other);
+                let key = (name, other);
+                let entry = self.pairs.entry(key.clone()).or_default();
+                *entry += count;
+                assert!(*entry >= 0, "{:?} quedaría en negativo", key);
+                if *entry == 0 {
+                    self.pairs.remove(&key);
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn delta_of_a_new_deck_adds_every_card_and_each_pair_once() {
+        let delta = cooccurrence_delta(&[], &names(&["c", "a", "b"]));
+
+        assert_eq!(delta.cards, vec![("a".to_string(), 1), ("b".to_string(), 1), ("c".to_string(), 1)]);
+        assert_eq!(
+            delta.pairs,
+            vec![
+                ("a".to_string(), "b".to_string(), 1),
+                ("a".to_string(), "c".to_string(), 1),
+                ("b".to_string(), "c".to_string(), 1),
+            ]
+        );
+    }
+
+    #[test]
+    fn delta_of_a_removed_deck_subtracts_everything() {
+        let delta = cooccurrence_delta(&names(&["a", "b", "c"]), &[]);
+
+        assert_eq!(delta.cards, vec![("a".to_string(), -1), ("b".to_string(), -1), ("c".to_string(), -1)]);
+        assert_eq!(
+            delta.pairs,
+            vec![
+                ("a".to_string(), "b".to_string(), -1),
+                ("a".to_string(), "c".to_string(), -1),
+                ("b".to_string(), "c".to_string(), -1),
+            ]
+        );
+    }
+
+    #[test]
+    fn delta_of_a_renamed_card_only_touches_its_pairs() {
+        // "b" pasa a llamarse "d": las parejas entre "a" y "c" no cambian
+        let delta = cooccurrence_delta(&names(&["a", "b", "c"]), &names(&["a", "c", "d"]));
+
+        assert_eq!(delta.cards, vec![("b".to_string(), -1), ("d".to_string(), 1)]);
+        assert_eq!(
+            delta.pairs,
+            vec![
+                ("a".to_string(), "b".to_string(), -1),
+                ("a".to_string(), "d".to_string(), 1),
+                ("b".to_string(), "c".to_string(), -1),
+                ("c".to_string(), "d".to_string(), 1),
+            ]
+        );
+    }
+
+    #[test]
+    fn delta_of_an_unchanged_deck_is_empty() {
+        let delta = cooccurrence_delta(&names(&["a", "b", "c"]), &names(&["c", "b", "a", "b"]));
+
+        assert!(delta.cards.is_empty());
+        assert!(delta.pairs.is_empty());
+        assert!(cooccurrence_delta(&[], &[]).pairs.is_empty());
+    }
+
+    #[test]
+    fn pairs_are_ordered_by_bytes_like_the_database() {
+        // En orden de bytes los dígitos van antes que las letras y "z" antes que "ñ"
+        let delta = cooccurrence_delta(&[], &names(&["ñu", "zorro", "1up"]));
+
+        for (name, other, _) in &delta.pairs {
+            assert!(name.as_bytes() < other.as_bytes());
+        }
+        assert_eq!(delta.pairs[0].0, "1up");
+        assert_eq!(delta.pairs[2], ("zorro".to_string(), "ñu".to_string(), 1));
+    }
+
+    #[test]
+    fn sequential_deltas_match_a_full_rebuild() {
+        // Historial de cada mazo; `None` es cuando deja de ser público o se borra
+        let histories: Vec<Vec<Option<Vec<String>>>> = vec![
+            vec![Some(names(&["a", "b"])), Some(names(&["a", "b", "c"])), Some(names(&["b", "c", "d"]))],
+            vec![Some(names(&["a", "c"])), None, Some(names(&["a", "c", "e"]))],
+            vec![Some(names(&["a", "b", "c", "d"])), Some(names(&["a", "b", "c", "d"])), None],
+            vec![Some(names(&[])), Some(names(&["e"])), Some(names(&["d", "e"]))],
+        ];
+
+        let mut incremental = Counts::default();
+        let mut indexed: HashMap<usize, Vec<String>> = HashMap::new();
+        let steps = histories.iter().map(Vec::len).max().unwrap();
+        for step in 0..steps {
+            for (deck, history) in histories.iter().enumerate() {
+                let Some(version) = history.get(step) else { continue };
+                let old = indexed.remove(&deck).unwrap_or_default();
+                let new = version.clone().unwrap_or_default();
+                incremental.apply(cooccurrence_delta(&old, &new));
+                if version.is_some() {
+                    indexed.insert(deck, new);
+                }
+            }
+        }
+
+        let mut rebuilt = Counts::default();
+        for history in &histories {
+            if let Some(Some(last)) = history.last() {
+                rebuilt.apply(cooccurrence_delta(&[], last));
+            }
+        }
+
+        assert_eq!(incremental, rebuilt);
+        assert_eq!(rebuilt.cards.get("c"), Some(&2));
+        assert_eq!(rebuilt.pairs.get(&("c".to_string(), "d".to_string())), Some(&1));
+        assert_eq!(rebuilt.pairs.get(&("d".to_string(), "e".to_string())), Some(&1));
+        assert!(!rebuilt.pairs.contains_key(&("a".to_string(), "b".to_string())));
+    }
+}
@@ -0,0 +1,415 @@
+use anyhow::{anyhow, bail, Result};
+use ring::digest::{digest, SHA256};
+use serde::Serialize;
+use std::collections::{BTreeMap, HashMap};
+use uuid::Uuid;
+
+use super::model::DeckCard;
+use crate::domain::cards::CardCatalog;
+
+/// Newest deck code version this server writes. Every older version must
+/// keep decoding.
+pub const DECK_CODE_VERSION: u8 = 1;
+
+const CHECKSUM_BYTES: usize = 2;
+// Un código no puede describir mazos absurdos: protege al decodificador
+const MAX_CODE_LENGTH: usize = 4096;
+
+// Secciones opcionales tras la lista de cartas. Los decodificadores ignoran las
+// etiquetas que no conocen, así que añadir una nueva no rompe códigos antiguos.
+const SECTION_FORMAT: u64 = 1;
+
+/// A card as a deck code stores it: by set code and collector number, never
+/// by database id, so codes survive re-imports and new sets
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct DeckCodeCard {
+    pub set_code: String,
+    pub collector_number: u32,
+    pub quantity: u32,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct DeckCodeContents {
+    pub version: u8,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub format_code: Option<String>,
+    pub cards: Vec<DeckCodeCard>,
+}
+
+/// A shareable code generated for a deck
+#[derive(Debug, Serialize)]
+pub struct EncodedDeck {
+    pub code: String,
+    pub version: u8,
+    pub card_count: i32,
+}
+
+/// A deck code resolved against the catalog, ready to be saved as a deck
+#[derive(Debug, Serialize)]
+pub struct DecodedDeck {
+    pub version: u8,
+    pub format_code: Option<String>,
+    pub cards: Vec<DeckCard>,
+    pub card_count: i32,
+    /// Cards of sets this server does not know yet, or the caller cannot see
+    pub unresolved: Vec<DeckCodeCard>,
+}
+
+/// Encodes a deck as a compact base64url code (version 1):
+///
+/// ```text
+/// version: u8
+/// varint set_count
+///   varint code_len, code bytes (uppercase ASCII)
+///   varint card_count
+///     varint collector_number delta (from the previous card of the set), varint quantity
+/// section*: varint tag, varint len, bytes    (tag 1 = format code)
+/// checksum: first 2 bytes of SHA-256 over everything above
+/// ```
+pub fn encode_deck_code(contents: &DeckCodeContents) -> Result<String> {
+    let mut groups: BTreeMap<String, BTreeMap<u32, u32>> = BTreeMap::new();
+    for card in &contents.cards {
+        let code = card.set_code.trim().to_uppercase();
+        if code.is_empty() || code.len() > u8::MAX as usize || !code.is_ascii() {
+            bail!("Código de conjunto '{}' no válido para un código de mazo", card.set_code);
+        }
+        if card.quantity == 0 {
+            continue;
+        }
+        *groups.entry(code).or_default().entry(card.collector_number).or_default() += card.quantity;
+    }
+
+    let mut bytes = vec![DECK_CODE_VERSION];
+    write_varint(&mut bytes, groups.len() as u64);
+    for (code, cards) in &groups {
+        write_varint(&mut bytes, code.len() as u64);
+        bytes.extend_from_slice(code.as_bytes());
+        write_varint(&mut bytes, cards.len() as u64);
+
+        let mut previous = 0;
+        for (&number, &quantity) in cards {
+            write_varint(&mut bytes, u64::from(number - previous));
+            write_varint(&mut bytes, u64::from(quantity));
+            previous = number;
+        }
+    }
+
+    if let Some(format_code) = &contents.format_code {
+        write_varint(&mut bytes, SECTION_FORMAT);
+        write_varint(&mut bytes, format_code.len() as u64);
+        bytes.extend_from_slice(format_code.as_bytes());
+    }
+
+    let checksum = checksum(&bytes);
+    bytes.extend_from_slice(&checksum);
+
+    Ok(base64::encode_config(bytes, base64::URL_SAFE_NO_PAD))
+}
+
+/// Decodes a deck code of any version up to `DECK_CODE_VERSION`
+pub fn decode_deck_code(code: &str) -> Result<DeckCodeContents> {
+    let code = code.trim();
+    if code.is_empty() || code.len() > MAX_CODE_LENGTH {
+        bail!("El código de mazo está vacío o es demasiado largo");
+    }
+
+    let bytes = base64::decode_config(code, base64::URL_SAFE_NO_PAD)
+        .map_err(|_| anyhow!("El código de mazo no es base64url válido"))?;
+    if bytes.len() < 1 + CHECKSUM_BYTES {
+        bail!("El código de mazo está incompleto");
+    }
+
+    let (payload, expected) = bytes.split_at(bytes.len() - CHECKSUM_BYTES);
+    if checksum(payload) != expected {
+        bail!("El código de mazo está dañado: la suma de control no coincide");
+    }
+
+    match payload[0] {
+        1 => decode_v1(&payload[1..]),
+        0 => bail!("Versión de código de mazo no válida"),
+        version => bail!(
+            "El código de mazo usa la versión {}, más nueva que la soportada ({})",
+            version, DECK_CODE_VERSION
+        ),
+    }
+}
+
+fn decode_v1(payload: &[u8]) -> Result<DeckCodeContents> {
+    let mut reader = Reader { bytes: payload, position: 0 };
+    let mut cards = Vec::new();
+
+    let set_count = reader.varint()?;
+    for _ in 0..set_count {
+        let code_len = reader.varint()? as usize;
+        let set_code = String::from_utf8(reader.take(code_len)?.to_vec())
+            .map_err(|_| anyhow!("El código de mazo contiene un código de conjunto no válido"))?;
+
+        let card_count = reader.varint()?;
+        let mut number: u32 = 0;
+        for _ in 0..card_count {
+            number = number
+                .checked_add(to_u32(reader.varint()?)?)
+                .ok_or_else(|| anyhow!("El código de mazo contiene un número de colección no válido"))?;
+            let quantity = to_u32(reader.varint()?)?;
+            cards.push(DeckCodeCard { set_code: set_code.clone(), collector_number: number, quantity });
+        }
+    }
+
+    let mut format_code = None;
+    while !reader.is_done() {
+        let tag = reader.varint()?;
+        let len = reader.varint()? as usize;
+        let data = reader.take(len)?;
+
+        if tag == SECTION_FORMAT {
+            format_code = String::from_utf8(data.to_vec()).ok();
+        }
+    }
+
+    Ok(DeckCodeContents { version: 1, format_code, cards })
+}
+
+fn checksum(bytes: &[u8]) -> [u8; CHECKSUM_BYTES] {
+    let hash = digest(&SHA256, bytes);
+    let mut checksum = [0u8; CHECKSUM_BYTES];
+    checksum.copy_from_slice(&hash.as_ref()[..CHECKSUM_BYTES]);
+    checksum
+}
+
+fn to_u32(value: u64) -> Result<u32> {
+    u32::try_from(value).map_err(|_| anyhow!("El código de mazo contiene un valor fuera de rango"))
+}
+
+// Entero sin signo LEB128: 7 bits por byte, el bit alto indica que sigue otro byte
+fn write_varint(bytes: &mut Vec<u8>, mut value: u64) {
+    loop {
+        let byte = (value & 0x7f) as u8;
+        value >>= 7;
+        if value == 0 {
+            bytes.push(byte);
+            return;
+        }
+        bytes.push(byte | 0x80);
+    }
+}
+
+struct Reader<'a> {
+    bytes: &'a [u8],
+    position: usize,
+}
+
+impl<'a> Reader<'a> {
+    fn is_done(&self) -> bool {
+        self.position >= self.bytes.len()
+    }
+
+    fn take(&mut self, len: usize) -> Result<&'a [u8]> {
+        let end = self
+            .position
+            .checked_add(len)
+            .filter(|&end| end <= self.bytes.len())
+            .ok_or_else(|| anyhow!("El código de mazo está incompleto"))?;
+        let slice = &self.bytes[self.position..end];
+        self.position = end;
+        Ok(slice)
+    }
+
+    fn varint(&mut self) -> Result<u64> {
+        let mut value: u64 = 0;
+        for shift in (0..64).step_by(7) {
+            let byte = self.take(1)?[0];
+            value |= u64::from(byte & 0x7f) << shift;
+            if byte & 0x80 == 0 {
+                return Ok(value);
+            }
+        }
+        bail!("El código de mazo contiene un número demasiado largo")
+    }
+}
+
+/// Maps the cards of a decoded code to catalog printings, merging repeated
+/// references. Unknown cards are reported rather than rejected, so a code
+/// made on a newer catalog still yields the part of the deck this one knows.
+pub fn resolve_deck_code(contents: DeckCodeContents, catalog: &CardCatalog) -> DecodedDeck {
+    let mut quantities: HashMap<Uuid, i32> = HashMap::new();
+    let mut order = Vec::new();
+    let mut unresolved = Vec::new();
+
+    for card in contents.cards {
+        let found = i32::try_from(card.collector_number)
+            .ok()
+            .and_then(|number| catalog.by_number(&card.set_code, number));
+        let (Some(found), Ok(quantity)) = (found, i32::try_from(card.quantity)) else {
+            unresolved.push(card);
+            continue;
+        };
+
+        let total = quantities.entry(found.id).or_insert_with(|| {
+            order.push(found.id);
+            0
+        });
+        *total = total.saturating_add(quantity);
+    }
+
+    let cards: Vec<DeckCard> = order
+        .into_iter()
+        .map(|card_id| DeckCard { card_id, quantity: quantities[&card_id] })
+        .collect();
+
+    DecodedDeck {
+        version: contents.version,
+        format_code: contents.format_code,
+        card_count: cards.iter().map(|card| card.quantity).sum(),
+        cards,
+        unresolved,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    // Generado a mano a partir del formato documentado, no con encode_deck_code:
+    // KD01 #7 x1, LGRO #1 x3 y #42 x2, formato "standard". No debe cambiar nunca.
+    const GOLDEN_V1: &str = "AQIES0QwMQEHAQRMR1JPAgEDKQIBCHN0YW5kYXJkVY4";
+
+    fn card(set_code: &str, collector_number: u32, quantity: u32) -> DeckCodeCard {
+        DeckCodeCard { set_code: set_code.to_string(), collector_number, quantity }
+    }
+
+    fn golden_contents() -> DeckCodeContents {
+        DeckCodeContents {
+            version: DECK_CODE_VERSION,
+            format_code: Some("standard".to_string()),
+            cards: vec![card("KD01", 7, 1), card("LGRO", 1, 3), card("LGRO", 42, 2)],
+        }
+    }
+
+    // Un código con la suma de control correcta para bytes arbitrarios
+    fn seal(mut payload: Vec<u8>) -> String {
+        let checksum = checksum(&payload);
+        payload.extend_from_slice(&checksum);
+        base64::encode_config(payload, base64::URL_SAFE_NO_PAD)
+    }
+
+    fn error(code: &str) -> String {
+        decode_deck_code(code).unwrap_err().to_string()
+    }
+
+    #[test]
+    fn encode_then_decode_round_trips() {
+        let contents = DeckCodeContents {
+            version: DECK_CODE_VERSION,
+            format_code: Some("extendido".to_string()),
+            cards: vec![card("LGRO", 120, 1), card("kd01", 3, 4), card("LGRO", 5, 2), card("LGRO", 300, 1)],
+        };
+
+        let decoded = decode_deck_code(&encode_deck_code(&contents).unwrap()).unwrap();
+
+        assert_eq!(decoded.version, DECK_CODE_VERSION);
+        assert_eq!(decoded.format_code.as_deref(), Some("extendido"));
+        // Agrupadas por conjunto y ordenadas por número; los códigos, en mayúsculas
+        assert_eq!(
+            decoded.cards,
+            vec![card("KD01", 3, 4), card("LGRO", 5, 2), card("LGRO", 120, 1), card("LGRO", 300, 1)]
+        );
+    }
+
+    #[test]
+    fn encode_merges_repeated_cards_and_drops_empty_ones() {
+        let contents = DeckCodeContents {
+            version: DECK_CODE_VERSION,
+            format_code: None,
+            cards: vec![card("LGRO", 1, 2), card("lgro", 1, 1), card("LGRO", 2, 0)],
+        };
+
+        let decoded = decode_deck_code(&encode_deck_code(&contents).unwrap()).unwrap();
+
+        assert_eq!(decoded.format_code, None);
+        assert_eq!(decoded.cards, vec![card("LGRO", 1, 3)]);
+    }
+
+    #[test]
+    fn golden_v1_code_keeps_decoding() {
+        let decoded = decode_deck_code(GOLDEN_V1).unwrap();
+
+        assert_eq!(decoded.version, 1);
+        assert_eq!(decoded.format_code.as_deref(), Some("standard"));
+        assert_eq!(decoded.cards, golden_contents().cards);
+    }
+
+    #[test]
+    fn encoding_is_stable_for_version_1() {
+        assert_eq!(DECK_CODE_VERSION, 1, "a new version needs its own golden code");
+        assert_eq!(encode_deck_code(&golden_contents()).unwrap(), GOLDEN_V1);
+    }
+
+    #[test]
+    fn checksum_mismatch_is_rejected() {
+        let mut bytes = base64::decode_config(GOLDEN_V1, base64::URL_SAFE_NO_PAD).unwrap();
+        bytes[5] ^= 0x01;
+        let code = base64::encode_config(bytes, base64::URL_SAFE_NO_PAD);
+
+        assert!(error(&code).contains("suma de control"));
+    }
+
+    #[test]
+    fn truncated_varint_is_rejected() {
+        // El número de conjuntos anuncia otro byte que no llega
+        assert!(error(&seal(vec![1, 0x80])).contains("incompleto"));
+        // Un conjunto anunciado que no está
+        assert!(error(&seal(vec![1, 1])).contains("incompleto"));
+    }
+
+    #[test]
+    fn overlong_varint_is_rejected() {
+        let mut payload = vec![1];
+        payload.extend_from_slice(&[0xff; 10]);
+        payload.push(0x01);
+
+        assert!(error(&seal(payload)).contains("demasiado largo"));
+    }
+
+    #[test]
+    fn out_of_range_collector_number_is_rejected() {
+        let mut payload = vec![1, 1, 4];
+        payload.extend_from_slice(b"LGRO");
+        payload.push(1);
+        write_varint(&mut payload, u64::from(u32::MAX) + 1);
+        payload.push(1);
+
+        assert!(error(&seal(payload)).contains("fuera de rango"));
+    }
+
+    #[test]
+    fn unknown_sections_are_skipped() {
+        let mut payload = vec![1, 1, 4];
+        payload.extend_from_slice(b"LGRO");
+        payload.extend_from_slice(&[1, 42, 2]);
+        // Sección de una versión futura del codificador, antes del formato
+        payload.extend_from_slice(&[9, 3]);
+        payload.extend_from_slice(b"abc");
+        payload.extend_from_slice(&[1, 8]);
+        payload.extend_from_slice(b"standard");
+
+        let decoded = decode_deck_code(&seal(payload)).unwrap();
+
+        assert_eq!(decoded.format_code.as_deref(), Some("standard"));
+        assert_eq!(decoded.cards, vec![card("LGRO", 42, 2)]);
+    }
+
+    #[test]
+    fn newer_version_is_rejected() {
+        let message = error(&seal(vec![DECK_CODE_VERSION + 1, 0]));
+
+        assert!(message.contains("más nueva"), "{}", message);
+    }
+
+    #[test]
+    fn malformed_codes_are_rejected() {
+        assert!(error("").contains("vacío"));
+        assert!(error("no es base64!").contains("base64url"));
+        assert!(error("AQ").contains("incompleto"));
+        assert!(error(&seal(vec![0])).contains("no válida"));
+    }
+}
@@ -0,0 +1,500 @@
+use serde::Serialize;
+use std::collections::{BTreeMap, HashMap};
+use uuid::Uuid;
+
+use super::model::DeckCard;
+use crate::domain::cards::{Card, CardCatalog, CardReference, NameResolution};
+use crate::domain::collections::{parse_collector_number, FuzzyMatch, UnmatchedRow, MAX_QUANTITY};
+
+/// A card line of a text decklist, before resolving it against the catalog
+#[derive(Debug, Clone)]
+pub struct DecklistLine {
+    pub line: usize,
+    pub input: String,
+    pub quantity: i32,
+    pub name: Option<String>,
+    pub set_code: Option<String>,
+    pub collector_number: Option<i32>,
+}
+
+/// Text decklist resolved to deck cards
+#[derive(Debug, Serialize)]
+pub struct DecklistImport {
+    pub cards: Vec<DeckCard>,
+    pub card_count: i32,
+    pub fuzzy_matches: Vec<FuzzyMatch>,
+    /// Names printed in several sets, given without a set code; the first
+    /// printing was used
+    pub assumed_printings: Vec<AssumedPrinting>,
+    pub unresolved: Vec<UnmatchedRow>,
+}
+
+#[derive(Debug, Serialize)]
+pub struct AssumedPrinting {
+    pub line: usize,
+    pub input: String,
+    pub card: CardReference,
+}
+
+/// Output flavours of a decklist export
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum DecklistFormat {
+    /// One "3 Card Name (LGRO) 042" line per card; parses back as is
+    Text,
+    Markdown,
+}
+
+impl DecklistFormat {
+    pub fn parse(value: &str) -> Option<Self> {
+        match value.trim().to_lowercase().as_str() {
+            "text" | "txt" | "plain" => Some(Self::Text),
+            "markdown" | "md" => Some(Self::Markdown),
+            _ => None,
+        }
+    }
+
+    pub fn content_type(&self) -> &'static str {
+        match self {
+            Self::Text => "text/plain; charset=utf-8",
+            Self::Markdown => "text/markdown; charset=utf-8",
+        }
+    }
+
+    pub fn extension(&self) -> &'static str {
+        match self {
+            Self::Text => "txt",
+            Self::Markdown => "md",
+        }
+    }
+}
+
+/// A card of the deck being exported
+pub struct DecklistEntry<'a> {
+    pub card: &'a Card,
+    pub set_code: &'a str,
+    pub quantity: i32,
+}
+
+/// Parses a text decklist. Lines look like "3 Card Name (LGRO) 042" and may
+/// also be written as "3x Card Name", "Card Name x3", "Card Name [LGRO-042]"
+/// or just "Card Name" (one copy). Blank lines, comments (`//`, `#`) and
+/// section headers ending in `:` are skipped. Lines that cannot be read are
+/// returned as unresolved.
+pub fn parse_decklist(text: &str) -> (Vec<DecklistLine>, Vec<UnmatchedRow>) {
+    let mut lines = Vec::new();
+    let mut invalid = Vec::new();
+
+    for (index, raw) in text.trim_start_matches('\u{feff}').lines().enumerate() {
+        let line = index + 1;
+        let input = raw.trim();
+
+        // Comentarios al final de la línea: "3 Tokar // por probar"
+        let content = input.split(" //").next().unwrap_or_default().trim();
+        if content.is_empty() || content.starts_with("//") || content.starts_with('#') || content.ends_with(':') {
+            continue;
+        }
+
+        match parse_line(content) {
+            Ok((quantity, name, set_code, collector_number)) => lines.push(DecklistLine {
+                line,
+                input: input.to_string(),
+                quantity,
+                name,
+                set_code,
+                collector_number,
+            }),
+            Err(reason) => invalid.push(UnmatchedRow {
+                line,
+                input: input.to_string(),
+                reason,
+                suggestions: Vec::new(),
+            }),
+        }
+    }
+
+    (lines, invalid)
+}
+
+type ParsedLine = (i32, Option<String>, Option<String>, Option<i32>);
+
+fn parse_line(content: &str) -> Result<ParsedLine, String> {
+    let content = content.trim_start_matches(['-', '*', '•']).trim();
+    let (quantity, rest) = split_quantity(content)?;
+    let (name, set_code, collector_number) = split_printing(rest);
+
+    if name.is_none() && (set_code.is_none() || collector_number.is_none()) {
+        return Err("La línea no indica el nombre de la carta ni su conjunto y número".to_string());
+    }
+
+    Ok((quantity, name, set_code, collector_number))
+}
+
+// Cantidad al principio ("3", "3x", "x3") o al final ("x3"); sin ella, una copia
+fn split_quantity(content: &str) -> Result<(i32, &str), String> {
+    let is_times = |c: char| matches!(c, 'x' | 'X' | '×');
+
+    let leading = content.strip_prefix(is_times).unwrap_or(content);
+    let digits = leading.len() - leading.trim_start_matches(|c: char| c.is_ascii_digit()).len();
+    if digits > 0 {
+        let after = &leading[digits..];
+        let after = after.strip_prefix(is_times).unwrap_or(after);
+        if after.starts_with(char::is_whitespace) {
+            // "3 x Card Name"
+            let name = after.trim_start();
+            let name = name.strip_prefix(is_times).filter(|rest| rest.starts_with(char::is_whitespace)).unwrap_or(name);
+            return Ok((parse_line_quantity(&leading[..digits])?, name.trim()));
+        }
+    }
+
+    if let Some((head, last)) = content.rsplit_once(char::is_whitespace) {
+        if let Some(number) = last.strip_prefix(is_times).filter(|n| !n.is_empty() && n.chars().all(|c| c.is_ascii_digit())) {
+            return Ok((parse_line_quantity(number)?, head.trim()));
+        }
+    }
+
+    Ok((1, content))
+}
+
+fn parse_line_quantity(value: &str) -> Result<i32, String> {
+    match value.parse::<i32>() {
+        Ok(quantity) if (1..=MAX_QUANTITY).contains(&quantity) => Ok(quantity),
+        _ => Err(format!("Cantidad '{}' no válida; debe estar entre 1 y {}", value, MAX_QUANTITY)),
+    }
+}
+
+// Conjunto y número al final: "(LGRO) 042", "[LGRO-042]", "(LGRO 042/120)" o "LGRO-042"
+fn split_printing(rest: &str) -> (Option<String>, Option<String>, Option<i32>) {
+    let mut rest = rest.trim();
+    let mut number = None;
+
+    if let Some((head, last)) = rest.rsplit_once(char::is_whitespace) {
+        let head = head.trim_end();
+        if last.starts_with(|c: char| c.is_ascii_digit()) && (head.ends_with(')') || head.ends_with(']')) {
+            number = parse_collector_number(last);
+            rest = head;
+        }
+    }
+
+    let mut set_code = None;
+    let closing = rest.chars().last().filter(|c| matches!(c, ')' | ']'));
+    if let Some(closing) = closing {
+        let opening = if closing == ')' { '(' } else { '[' };
+        if let Some(start) = rest.rfind(opening) {
+            let inner = &rest[start + 1..rest.len() - 1];
+            let mut parts = inner.split(|c: char| c == '-' || c == '#' || c.is_whitespace()).filter(|part| !part.is_empty());
+            if let Some(code) = parts.next().filter(|code| is_set_code(code)) {
+                set_code = Some(code.to_uppercase());
+                number = parts.next().and_then(parse_collector_number).or(number);
+                rest = rest[..start].trim_end();
+            }
+        }
+    } else {
+        let (head, last) = rest.rsplit_once(char::is_whitespace).unwrap_or(("", rest));
+        if let Some((code, digits)) = last.split_once('-') {
+            if is_set_code(code) && digits.starts_with(|c: char| c.is_ascii_digit()) {
+                set_code = Some(code.to_uppercase());
+                number = parse_collector_number(digits);
+                rest = head.trim_end();
+            }
+        }
+    }
+
+    let name = Some(rest.to_string()).filter(|name| !name.is_empty());
+    (name, set_code, number)
+}
+
+// Códigos de conjunto: letras y dígitos, con al menos una letra ("LGRO", "KD01")
+fn is_set_code(value: &str) -> bool {
+    (2..=8).contains(&value.len())
+        && value.chars().all(|c| c.is_ascii_alphanumeric())
+        && value.chars().any(|c| c.is_ascii_alphabetic())
+}
+
+// Resultado de resolver una línea contra el catálogo
+enum LineResolution<'a> {
+    Exact(&'a Card),
+    Fuzzy(&'a Card, f64),
+    /// Same name in several sets and no set code given
+    Assumed(&'a Card),
+    Unresolved(String, Vec<&'a Card>),
+}
+
+/// Resolves parsed lines to deck cards: by set code and number first, then
+/// by name. Repeated cards are merged, keeping the order of first appearance.
+pub fn resolve_decklist(lines: Vec<DecklistLine>, invalid: Vec<UnmatchedRow>, catalog: &CardCatalog) -> DecklistImport {
+    let mut quantities: HashMap<Uuid, i32> = HashMap::new();
+    let mut order = Vec::new();
+    let mut report = DecklistImport {
+        cards: Vec::new(),
+        card_count: 0,
+        fuzzy_matches: Vec::new(),
+        assumed_printings: Vec::new(),
+        unresolved: invalid,
+    };
+
+    for line in lines {
+        let card = match resolve_line(&line, catalog) {
+            LineResolution::Exact(card) => card,
+            LineResolution::Fuzzy(card, score) => {
+                report.fuzzy_matches.push(FuzzyMatch {
+                    line: line.line,
+                    input: line.input.clone(),
+                    card: catalog.reference(card),
+                    score: (score * 100.0).round() / 100.0,
+                });
+                card
+            }
+            LineResolution::Assumed(card) => {
+                report.assumed_printings.push(AssumedPrinting {
+                    line: line.line,
+                    input: line.input.clone(),
+                    card: catalog.reference(card),
+                });
+                card
+            }
+            LineResolution::Unresolved(reason, suggestions) => {
+                report.unresolved.push(UnmatchedRow {
+                    line: line.line,
+                    input: line.input,
+                    reason,
+                    suggestions: suggestions.into_iter().map(|card| catalog.reference(card)).collect(),
+                });
+                continue;
+            }
+        };
+
+        let total = quantities.entry(card.id).or_insert_with(|| {
+            order.push(card.id);
+            0
+        });
+        *total = total.saturating_add(line.quantity).min(MAX_QUANTITY);
+    }
+
+    report.unresolved.sort_by_key(|row| row.line);
+    report.cards = order
+        .into_iter()
+        .map(|card_id| DeckCard { card_id, quantity: quantities[&card_id] })
+        .collect();
+    report.card_count = report.cards.iter().map(|card| card.quantity).sum();
+    report
+}
+
+fn resolve_line<'a>(line: &DecklistLine, catalog: &'a CardCatalog) -> LineResolution<'a> {
+    let set_code = line.set_code.as_deref();
+
+    if let (Some(set_code), Some(number)) = (set_code, line.collector_number) {
+        match (catalog.by_number(set_code, number), line.name.as_deref()) {
+            (Some(card), _) => return LineResolution::Exact(card),
+            (None, None) => {
+                return LineResolution::Unresolved(
+                    format!("No existe la carta número {} del conjunto '{}'", number, set_code),
+                    Vec::new(),
+                )
+            }
+            (None, Some(_)) => {}
+        }
+    }
+
+    let Some(name) = line.name.as_deref() else {
+        return LineResolution::Unresolved("La línea no indica el nombre de la carta".to_string(), Vec::new());
+    };
+
+    // Un código de conjunto desconocido no impide buscar por nombre en todo el catálogo
+    let set_code = set_code.filter(|code| catalog.has_set(code));
+
+    match catalog.resolve_name(name, set_code) {
+        NameResolution::Exact(card) => LineResolution::Exact(card),
+        NameResolution::Fuzzy { card, score } => LineResolution::Fuzzy(card, score),
+        // Para un mazo cualquier impresión vale: se usa la primera publicada
+        NameResolution::Ambiguous(cards) => LineResolution::Assumed(cards[0]),
+        NameResolution::NotFound { suggestions } => {
+            LineResolution::Unresolved(format!("No se encontró ninguna carta llamada '{}'", name), suggestions)
+        }
+    }
+}
+
+/// A decklist line as "3 Card Name (LGRO) 042"
+pub fn decklist_line(entry: &DecklistEntry) -> String {
+    format!(
+        "{} {} ({}) {:03}",
+        entry.quantity, entry.card.name, entry.set_code, entry.card.collector_number
+    )
+}
+
+/// Exports a deck grouped by card type. The text variant uses `//` comments
+/// for headings so it can be imported again unchanged.
+pub fn export_decklist(name: &str, entries: &[DecklistEntry], format: DecklistFormat) -> String {
+    let mut by_type: BTreeMap<&str, Vec<&DecklistEntry>> = BTreeMap::new();
+    for entry in entries {
+        by_type.entry(entry.card.card_type.as_str()).or_default().push(entry);
+    }
+
+    let total: i32 = entries.iter().map(|entry| entry.quantity).sum();
+    let mut out = String::new();
+
+    match format {
+        DecklistFormat::Text => out.push_str(&format!("// {} ({} cartas)\n", name, total)),
+        DecklistFormat::Markdown => out.push_str(&format!("# {}\n\n{} cartas\n", name, total)),
+    }
+
+    for (card_type, mut group) in by_type {
+        group.sort_by(|a, b| a.card.name.cmp(&b.card.name).then(a.set_code.cmp(b.set_code)));
+        let count: i32 = group.iter().map(|entry| entry.quantity).sum();
+
+        match format {
+            DecklistFormat::Text => out.push_str(&format!("\n// {} ({})\n", card_type, count)),
+            DecklistFormat::Markdown => out.push_str(&format!("\n## {} ({})\n\n", card_type, count)),
+        }
+
+        for entry in group {
+            let line = match format {
+                DecklistFormat::Text => decklist_line(entry),
+                DecklistFormat::Markdown => format!(
+                    "- {} **{}** ({}) {:03}",
+                    entry.quantity, entry.card.name, entry.set_code, entry.card.collector_number
+                ),
+            };
+            out.push_str(&line);
+            out.push('\n');
+        }
+    }
+
+    out
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::Utc;
+
+    fn parse_one(text: &str) -> DecklistLine {
+        let (mut lines, invalid) = parse_decklist(text);
+        assert!(invalid.is_empty(), "unexpected invalid lines for {:?}: {:?}", text, invalid);
+        assert_eq!(lines.len(), 1, "expected one line for {:?}", text);
+        lines.remove(0)
+    }
+
+    fn printing(line: &DecklistLine) -> (i32, Option<&str>, Option<&str>, Option<i32>) {
+        (line.quantity, line.name.as_deref(), line.set_code.as_deref(), line.collector_number)
+    }
+
+    fn card(set_id: Uuid, collector_number: i32, name: &str, card_type: &str) -> Card {
+        Card {
+            id: Uuid::new_v4(),
+            set_id,
+            collector_number,
+            name: name.to_string(),
+            card_type: card_type.to_string(),
+            card_energy: None,
+            rarity: "Común".to_string(),
+            cost: None,
+            artists: Vec::new(),
+            image_url: None,
+            image_details: None,
+            revealed_at: None,
+            created_at: Utc::now(),
+            updated_at: Utc::now(),
+        }
+    }
+
+    #[test]
+    fn split_quantity_reads_every_documented_form() {
+        assert_eq!(split_quantity("3 Tokar"), Ok((3, "Tokar")));
+        assert_eq!(split_quantity("3x Tokar"), Ok((3, "Tokar")));
+        assert_eq!(split_quantity("x3 Tokar"), Ok((3, "Tokar")));
+        assert_eq!(split_quantity("3 x Tokar"), Ok((3, "Tokar")));
+        assert_eq!(split_quantity("Tokar x3"), Ok((3, "Tokar")));
+        assert_eq!(split_quantity("Tokar"), Ok((1, "Tokar")));
+    }
+
+    #[test]
+    fn split_quantity_keeps_names_that_start_with_x() {
+        assert_eq!(split_quantity("Xólotl"), Ok((1, "Xólotl")));
+        assert_eq!(split_quantity("2 Xólotl"), Ok((2, "Xólotl")));
+    }
+
+    #[test]
+    fn split_quantity_rejects_out_of_range_quantities() {
+        assert!(split_quantity("0 Tokar").is_err());
+        assert!(split_quantity(&format!("{} Tokar", MAX_QUANTITY + 1)).is_err());
+    }
+
+    #[test]
+    fn split_printing_reads_every_documented_form() {
+        let expected = (Some("Tokar".to_string()), Some("LGRO".to_string()), Some(42));
+        assert_eq!(split_printing("Tokar (LGRO) 042"), expected);
+        assert_eq!(split_printing("Tokar [LGRO-042]"), expected);
+        assert_eq!(split_printing("Tokar (LGRO 042/120)"), expected);
+        assert_eq!(split_printing("Tokar LGRO-042"), expected);
+        assert_eq!(split_printing("Tokar (lgro) 042"), expected);
+    }
+
+    #[test]
+    fn split_printing_without_printing_is_just_a_name() {
+        assert_eq!(split_printing("Tokar"), (Some("Tokar".to_string()), None, None));
+        // Un paréntesis que no es un código de conjunto forma parte del nombre
+        assert_eq!(split_printing("Tokar (promo!)"), (Some("Tokar (promo!)".to_string()), None, None));
+    }
+
+    #[test]
+    fn split_printing_allows_a_printing_without_name() {
+        assert_eq!(split_printing("[LGRO-042]"), (None, Some("LGRO".to_string()), Some(42)));
+    }
+
+    #[test]
+    fn parse_decklist_reads_quantity_and_printing_together() {
+        assert_eq!(printing(&parse_one("3x Tokar [LGRO-042]")), (3, Some("Tokar"), Some("LGRO"), Some(42)));
+        assert_eq!(printing(&parse_one("Tokar (LGRO 042/120) x2")), (2, Some("Tokar"), Some("LGRO"), Some(42)));
+        assert_eq!(printing(&parse_one("- 4 Tokar")), (4, Some("Tokar"), None, None));
+    }
+
+    #[test]
+    fn parse_decklist_skips_comments_headers_and_blank_lines() {
+        let text = "\u{feff}// Mazo rojo\n# Notas\nCriaturas:\n\n2 Tokar // por probar\n   \nAcecho\n";
+        let (lines, invalid) = parse_decklist(text);
+
+        assert!(invalid.is_empty());
+        assert_eq!(lines.len(), 2);
+        assert_eq!((lines[0].line, printing(&lines[0])), (5, (2, Some("Tokar"), None, None)));
+        assert_eq!(lines[0].input, "2 Tokar // por probar");
+        assert_eq!((lines[1].line, printing(&lines[1])), (7, (1, Some("Acecho"), None, None)));
+    }
+
+    #[test]
+    fn parse_decklist_reports_unreadable_lines() {
+        let (lines, invalid) = parse_decklist("1 Tokar\n0 Acecho\n");
+
+        assert_eq!(lines.len(), 1);
+        assert_eq!(invalid.len(), 1);
+        assert_eq!((invalid[0].line, invalid[0].input.as_str()), (2, "0 Acecho"));
+    }
+
+    #[test]
+    fn exported_text_parses_back_to_the_same_cards() {
+        let set_id = Uuid::new_v4();
+        let cards = vec![
+            card(set_id, 42, "Tokar", "Criatura"),
+            card(set_id, 7, "Acecho (Versión 2)", "Hechizo"),
+            card(set_id, 120, "Guerra Roja", "Criatura"),
+        ];
+        let quantities: Vec<(Uuid, i32)> = cards.iter().map(|card| card.id).zip([3, 1, 2]).collect();
+        let catalog = CardCatalog::new(cards, HashMap::from([(set_id, "LGRO".to_string())]));
+        let entries: Vec<DecklistEntry> = quantities
+            .iter()
+            .map(|&(id, quantity)| DecklistEntry { card: catalog.by_id(id).unwrap(), set_code: "LGRO", quantity })
+            .collect();
+
+        let text = export_decklist("Rojo (agresivo)", &entries, DecklistFormat::Text);
+        let (lines, invalid) = parse_decklist(&text);
+        let import = resolve_decklist(lines, invalid, &catalog);
+
+        assert!(import.unresolved.is_empty(), "{}", text);
+        assert!(import.fuzzy_matches.is_empty() && import.assumed_printings.is_empty());
+        let mut imported: Vec<(Uuid, i32)> = import.cards.iter().map(|card| (card.card_id, card.quantity)).collect();
+        let mut exported: Vec<(Uuid, i32)> = entries.iter().map(|entry| (entry.card.id, entry.quantity)).collect();
+        imported.sort();
+        exported.sort();
+        assert_eq!(imported, exported);
+        assert_eq!(import.card_count, 6);
+    }
+}
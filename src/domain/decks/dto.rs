@@ -0,0 +1,321 @@
+use anyhow::{anyhow, Result};
+use serde::Deserialize;
+use std::collections::HashSet;
+use uuid::Uuid;
+
+use super::analysis::{DrawRules, DrawTarget, MulliganRule};
+use super::legality::FormatRules;
+use super::model::DeckCard;
+use crate::domain::cards::Validable;
+use crate::domain::collections::MAX_QUANTITY;
+
+const MAX_NAME_LENGTH: usize = 100;
+const MAX_DESCRIPTION_LENGTH: usize = 2000;
+// Límites de las simulaciones de robo
+const MAX_HAND_SIZE: u32 = 20;
+const MAX_TURNS: u32 = 30;
+const MAX_DRAWS_PER_TURN: u32 = 10;
+const MAX_DRAW_TARGETS: usize = 50;
+// Cartas distintas por mazo; los límites reales de cada formato se validan aparte
+pub const MAX_DECK_ENTRIES: usize = 500;
+
+#[derive(Debug, Deserialize)]
+pub struct DeckDto {
+    pub name: String,
+    pub description: Option<String>,
+    pub format_code: Option<String>,
+    #[serde(default)]
+    pub is_public: bool,
+    #[serde(default)]
+    pub cards: Vec<DeckCard>,
+}
+
+impl DeckDto {
+    pub fn name(&self) -> String {
+        self.name.trim().to_string()
+    }
+
+    // Una descripción vacía se guarda como ausente
+    pub fn description(&self) -> Option<String> {
+        self.description
+            .as_deref()
+            .map(str::trim)
+            .filter(|description| !description.is_empty())
+            .map(str::to_string)
+    }
+
+    pub fn format_code(&self) -> Option<String> {
+        self.format_code
+            .as_deref()
+            .map(|code| code.trim().to_lowercase())
+            .filter(|code| !code.is_empty())
+    }
+
+    pub fn card_ids(&self) -> Vec<Uuid> {
+        self.cards.iter().map(|card| card.card_id).collect()
+    }
+}
+
+impl Validable for DeckDto {
+    fn validate(&self) -> Result<()> {
+        let name = self.name();
+        if name.is_empty() || name.chars().count() > MAX_NAME_LENGTH {
+            return Err(anyhow!("El nombre debe tener entre 1 y {} caracteres", MAX_NAME_LENGTH));
+        }
+
+        if self.description().is_some_and(|description| description.chars().count() > MAX_DESCRIPTION_LENGTH) {
+            return Err(anyhow!("La descripción no puede superar los {} caracteres", MAX_DESCRIPTION_LENGTH));
+        }
+
+        validate_deck_cards(&self.cards)
+    }
+}
+
+/// Checks a card list: no repeated cards and sensible quantities
+pub fn validate_deck_cards(cards: &[DeckCard]) -> Result<()> {
+    if cards.len() > MAX_DECK_ENTRIES {
+        return Err(anyhow!("Un mazo no puede tener más de {} cartas distintas", MAX_DECK_ENTRIES));
+    }
+
+    let mut seen = HashSet::new();
+    for card in cards {
+        if card.quantity <= 0 || card.quantity > MAX_QUANTITY {
+            return Err(anyhow!("La cantidad de la carta {} debe estar entre 1 y {}", card.card_id, MAX_QUANTITY));
+        }
+
+        if !seen.insert(card.card_id) {
+            return Err(anyhow!("La carta {} aparece más de una vez", card.card_id));
+        }
+    }
+
+    Ok(())
+}
+
+/// Options of a fork; by default the copy keeps the original's name and is private
+#[derive(Debug, Deserialize)]
+pub struct ForkDeckDto {
+    pub name: Option<String>,
+    #[serde(default)]
+    pub is_public: bool,
+}
+
+impl ForkDeckDto {
+    pub fn name(&self) -> Option<String> {
+        self.name
+            .as_deref()
+            .map(str::trim)
+            .filter(|name| !name.is_empty())
+            .map(str::to_string)
+    }
+}
+
+impl Validable for ForkDeckDto {
+    fn validate(&self) -> Result<()> {
+        if self.name().is_some_and(|name| name.chars().count() > MAX_NAME_LENGTH) {
+            return Err(anyhow!("El nombre no puede superar los {} caracteres", MAX_NAME_LENGTH));
+        }
+
+        Ok(())
+    }
+}
+
+#[derive(Debug, Deserialize)]
+pub struct DeckFormatDto {
+    pub name: String,
+    pub description: Option<String>,
+    #[serde(default)]
+    pub rules: FormatRules,
+}
+
+impl DeckFormatDto {
+    pub fn name(&self) -> String {
+        self.name.trim().to_string()
+    }
+
+    pub fn description(&self) -> Option<String> {
+        self.description
+            .as_deref()
+            .map(str::trim)
+            .filter(|description| !description.is_empty())
+            .map(str::to_string)
+    }
+}
+
+/// Format codes are short lowercase slugs, e.g. `standard`
+pub fn is_valid_format_code(code: &str) -> bool {
+    !code.is_empty()
+        && code.len() <= 32
+        && code.chars().all(|c| c.is_ascii_lowercase() || c.is_ascii_digit() || c == '-' || c == '_')
+}
+
+fn check_bounds(field: &str, min: Option<i32>, max: Option<i32>) -> Result<()> {
+    if min.is_some_and(|min| min < 0) || max.is_some_and(|max| max < 0) {
+        return Err(anyhow!("Los límites de '{}' no pueden ser negativos", field));
+    }
+
+    if let (Some(min), Some(max)) = (min, max) {
+        if min > max {
+            return Err(anyhow!("El mínimo de '{}' no puede superar al máximo", field));
+        }
+    }
+
+    Ok(())
+}
+
+impl Validable for DeckFormatDto {
+    fn validate(&self) -> Result<()> {
+        let name = self.name();
+        if name.is_empty() || name.chars().count() > MAX_NAME_LENGTH {
+            return Err(anyhow!("El nombre debe tener entre 1 y {} caracteres", MAX_NAME_LENGTH));
+        }
+
+        if self.description().is_some_and(|description| description.chars().count() > MAX_DESCRIPTION_LENGTH) {
+            return Err(anyhow!("La descripción no puede superar los {} caracteres", MAX_DESCRIPTION_LENGTH));
+        }
+
+        let rules = &self.rules;
+        check_bounds("cards", rules.min_cards, rules.max_cards)?;
+        check_bounds("max_copies", None, rules.max_copies)?;
+
+        if let Some((name, _)) = rules.copy_limits.iter().find(|(_, &limit)| limit < 0) {
+            return Err(anyhow!("El límite de copias de '{}' no puede ser negativo", name));
+        }
+
+        for quota in &rules.type_quotas {
+            if quota.card_type.trim().is_empty() {
+                return Err(anyhow!("Cada cuota de tipo necesita un 'card_type'"));
+            }
+            check_bounds(&quota.card_type, quota.min, quota.max)?;
+        }
+
+        Ok(())
+    }
+}
+
+/// Draw rules and optional card groups for a deck analysis; every field
+/// has a default, so `{}` is a valid body
+#[derive(Debug, Default, Deserialize)]
+pub struct DeckAnalysisDto {
+    #[serde(flatten)]
+    pub rules: DrawRules,
+    #[serde(default)]
+    pub targets: Vec<DrawTarget>,
+}
+
+impl Validable for DeckAnalysisDto {
+    fn validate(&self) -> Result<()> {
+        let rules = &self.rules;
+        if rules.hand_size == 0 || rules.hand_size > MAX_HAND_SIZE {
+            return Err(anyhow!("El tamaño de la mano debe estar entre 1 y {}", MAX_HAND_SIZE));
+        }
+
+        if rules.turns > MAX_TURNS {
+            return Err(anyhow!("Se pueden calcular como mucho {} turnos", MAX_TURNS));
+        }
+
+        if rules.draws_per_turn > MAX_DRAWS_PER_TURN {
+            return Err(anyhow!("Se pueden robar como mucho {} cartas por turno", MAX_DRAWS_PER_TURN));
+        }
+
+        if rules.max_mulligans >= rules.hand_size {
+            return Err(anyhow!("Los mulligans deben ser menos que el tamaño de la mano"));
+        }
+
+        if self.targets.len() > MAX_DRAW_TARGETS {
+            return Err(anyhow!("Se pueden indicar como mucho {} grupos de cartas", MAX_DRAW_TARGETS));
+        }
+
+        if self.targets.iter().any(|target| target.card_ids.is_empty() || target.card_ids.len() > MAX_DECK_ENTRIES) {
+            return Err(anyhow!("Cada grupo de cartas debe tener entre 1 y {} cartas", MAX_DECK_ENTRIES));
+        }
+
+        Ok(())
+    }
+}
+
+/// Sample hand to deal from a deck. Works both as a JSON body and as query
+/// parameters (except `bottom`); every field has a default.
+#[derive(Debug, Deserialize)]
+#[serde(default)]
+pub struct SampleHandDto {
+    /// Seed of a previous sample hand, to replay it; a random one otherwise
+    pub seed: Option<String>,
+    /// Deck revision to deal from; the latest one by default
+    pub revision: Option<i32>,
+    pub hand_size: u32,
+    pub mulligan: MulliganRule,
+    /// Mulligans to take before keeping a hand
+    pub mulligans: u32,
+    /// London mulligan: cards of the kept hand to put on the bottom, one per mulligan
+    pub bottom: Vec<Uuid>,
+    /// Turns to draw after the opening hand
+    pub turns: u32,
+    pub draws_per_turn: u32,
+    pub on_the_play: bool,
+}
+
+impl Default for SampleHandDto {
+    fn default() -> Self {
+        Self {
+            seed: None,
+            revision: None,
+            hand_size: 7,
+            mulligan: MulliganRule::London,
+            mulligans: 0,
+            bottom: Vec::new(),
+            turns: 0,
+            draws_per_turn: 1,
+            on_the_play: true,
+        }
+    }
+}
+
+impl SampleHandDto {
+    pub fn seed(&self) -> Result<Option<u64>> {
+        self.seed
+            .as_deref()
+            .map(|seed| {
+                seed.trim()
+                    .parse::<u64>()
+                    .map_err(|_| anyhow!("La semilla debe ser un número entre 0 y {}", u64::MAX))
+            })
+            .transpose()
+    }
+}
+
+impl Validable for SampleHandDto {
+    fn validate(&self) -> Result<()> {
+        self.seed()?;
+
+        if self.hand_size == 0 || self.hand_size > MAX_HAND_SIZE {
+            return Err(anyhow!("El tamaño de la mano debe estar entre 1 y {}", MAX_HAND_SIZE));
+        }
+
+        if self.turns > MAX_TURNS {
+            return Err(anyhow!("Se pueden robar como mucho {} turnos", MAX_TURNS));
+        }
+
+        if self.draws_per_turn > MAX_DRAWS_PER_TURN {
+            return Err(anyhow!("Se pueden robar como mucho {} cartas por turno", MAX_DRAWS_PER_TURN));
+        }
+
+        if self.mulligans >= self.hand_size {
+            return Err(anyhow!("Los mulligans deben ser menos que el tamaño de la mano"));
+        }
+
+        if self.mulligans > 0 && self.mulligan == MulliganRule::None {
+            return Err(anyhow!("Indica una regla de mulligan (redraw, london o reduced) para hacer mulligans"));
+        }
+
+        if !self.bottom.is_empty() {
+            if self.mulligan != MulliganRule::London {
+                return Err(anyhow!("Sólo el mulligan london pone cartas al fondo"));
+            }
+            if self.bottom.len() != self.mulligans as usize {
+                return Err(anyhow!("Hay que poner al fondo una carta por mulligan ({})", self.mulligans));
+            }
+        }
+
+        Ok(())
+    }
+}
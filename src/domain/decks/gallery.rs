@@ -0,0 +1,168 @@
+use chrono::{DateTime, Utc};
+use serde::Serialize;
+use sqlx::postgres::PgRow;
+use sqlx::Row;
+use std::sync::Arc;
+use std::time::Duration;
+use tokio::time::MissedTickBehavior;
+use uuid::Uuid;
+
+use super::model::Deck;
+use super::repository::PgDeckRepository;
+use super::service::DeckService;
+use crate::config::TrendingConfig;
+
+/// Hours after which a like, bookmark or fork counts half as much towards
+/// the trending score
+pub const TRENDING_HALF_LIFE_HOURS: f64 = 72.0;
+// Peso de cada interacción: copiar un mazo dice más que marcarlo, y esto más que un "me gusta"
+pub const TRENDING_LIKE_WEIGHT: f64 = 1.0;
+pub const TRENDING_BOOKMARK_WEIGHT: f64 = 2.0;
+pub const TRENDING_FORK_WEIGHT: f64 = 3.0;
+/// Interactions older than this many half-lives no longer count: they would
+/// weigh less than a millionth
+pub const TRENDING_MAX_AGE_HALF_LIVES: f64 = 20.0;
+
+/// Where a forked deck was copied from. The original's name and owner are
+/// kept so attribution survives if it is deleted (`source_deck_id` is then null).
+#[derive(Debug, Clone, Serialize)]
+pub struct DeckFork {
+    pub source_deck_id: Option<Uuid>,
+    pub source_owner_uid: String,
+    pub source_name: String,
+    /// Revision of the original that was copied
+    pub source_revision: Option<i32>,
+    pub created_at: DateTime<Utc>,
+}
+
+impl<'r> sqlx::FromRow<'r, PgRow> for DeckFork {
+    fn from_row(row: &'r PgRow) -> Result<Self, sqlx::Error> {
+        Ok(Self {
+            source_deck_id: row.try_get("source_deck_id")?,
+            source_owner_uid: row.try_get("source_owner_uid")?,
+            source_name: row.try_get("source_name")?,
+            source_revision: row.try_get("source_revision")?,
+            created_at: row.try_get("created_at")?,
+        })
+    }
+}
+
+/// Order of the gallery
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum GallerySort {
+    /// Likes, bookmarks and forks, each decaying with its age
+    Trending,
+    New,
+    Likes,
+}
+
+impl GallerySort {
+    pub fn parse(value: &str) -> Option<Self> {
+        match value.trim().to_lowercase().as_str() {
+            "trending" => Some(Self::Trending),
+            "new" | "newest" => Some(Self::New),
+            "likes" => Some(Self::Likes),
+            _ => None,
+        }
+    }
+
+    /// `ORDER BY` of the gallery query; trending follows the index on
+    /// `decks (trending_score DESC, created_at DESC, id)`
+    pub fn order_by(&self) -> &'static str {
+        match self {
+            Self::Trending => "g.trending_score DESC, g.created_at DESC, g.id",
+            Self::New => "g.created_at DESC, g.id",
+            Self::Likes => "g.like_count DESC, g.created_at DESC, g.id",
+        }
+    }
+}
+
+/// Filters of a gallery page
+#[derive(Debug, Clone)]
+pub struct GalleryFilter {
+    pub format_code: Option<String>,
+    pub energy: Option<String>,
+    /// Decks including this card, in any printing
+    pub card_id: Option<Uuid>,
+    pub sort: GallerySort,
+    pub limit: i64,
+    pub offset: i64,
+}
+
+/// A deck as listed in the gallery, with its popularity
+#[derive(Debug, Serialize)]
+pub struct GalleryDeck {
+    #[serde(flatten)]
+    pub deck: Deck,
+    pub card_count: i32,
+    pub energies: Vec<String>,
+    pub like_count: i64,
+    pub bookmark_count: i64,
+    pub fork_count: i64,
+    /// As of the last refresh, see [`start_trending_refresh`]
+    pub trending_score: f64,
+    /// Whether the current user liked or bookmarked it
+    pub liked: bool,
+    pub bookmarked: bool,
+}
+
+impl<'r> sqlx::FromRow<'r, PgRow> for GalleryDeck {
+    fn from_row(row: &'r PgRow) -> Result<Self, sqlx::Error> {
+        Ok(Self {
+            deck: Deck::from_row(row)?,
+            card_count: row.try_get("card_count")?,
+            energies: row.try_get("energies")?,
+            like_count: row.try_get("like_count")?,
+            bookmark_count: row.try_get("bookmark_count")?,
+            fork_count: row.try_get("fork_count")?,
+            trending_score: row.try_get("trending_score")?,
+            liked: row.try_get("liked")?,
+            bookmarked: row.try_get("bookmarked")?,
+        })
+    }
+}
+
+/// Likes, bookmarks and forks of a deck, and the current user's own
+#[derive(Debug, Serialize)]
+pub struct DeckReactions {
+    pub deck_id: Uuid,
+    pub like_count: i64,
+    pub bookmark_count: i64,
+    pub fork_count: i64,
+    pub liked: bool,
+    pub bookmarked: bool,
+}
+
+impl<'r> sqlx::FromRow<'r, PgRow> for DeckReactions {
+    fn from_row(row: &'r PgRow) -> Result<Self, sqlx::Error> {
+        Ok(Self {
+            deck_id: row.try_get("deck_id")?,
+            like_count: row.try_get("like_count")?,
+            bookmark_count: row.try_get("bookmark_count")?,
+            fork_count: row.try_get("fork_count")?,
+            liked: row.try_get("liked")?,
+            bookmarked: row.try_get("bookmarked")?,
+        })
+    }
+}
+
+/// Lets the stored trending scores decay, recomputing every `refresh_secs` those
+/// of the decks with recent interactions. Reactions and forks already refresh
+/// their deck at once; with `refresh_secs` at 0 scores only change then.
+pub fn start_trending_refresh(deck_service: Arc<DeckService<PgDeckRepository>>, config: TrendingConfig) {
+    if config.refresh_secs == 0 {
+        return;
+    }
+
+    tokio::spawn(async move {
+        let mut interval = tokio::time::interval(Duration::from_secs(config.refresh_secs));
+        interval.set_missed_tick_behavior(MissedTickBehavior::Delay);
+
+        loop {
+            interval.tick().await;
+            if let Err(e) = deck_service.refresh_trending_scores().await {
+                tracing::error!("Error actualizando las puntuaciones de tendencia: {}", e);
+            }
+        }
+    });
+}
@@ -0,0 +1,558 @@
+use serde::{Deserialize, Serialize};
+use std::collections::{BTreeMap, HashMap, HashSet};
+use uuid::Uuid;
+
+use crate::domain::cards::{normalize_name, Card};
+
+/// Deck construction rules of a format, stored as JSONB. Every rule is
+/// optional; an empty object allows any deck.
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(default)]
+pub struct FormatRules {
+    pub min_cards: Option<i32>,
+    pub max_cards: Option<i32>,
+    /// Copies allowed of each card, counted by name across printings
+    pub max_copies: Option<i32>,
+    /// Per-card limits by name that override `max_copies` (restricted list)
+    pub copy_limits: BTreeMap<String, i32>,
+    /// Card types exempt from the copy limits
+    pub unlimited_types: Vec<String>,
+    pub type_quotas: Vec<TypeQuota>,
+    /// If present, only cards with these energies (or none) are allowed
+    pub allowed_energies: Option<Vec<String>>,
+    /// Maximum number of different energies in a deck
+    pub max_energies: Option<usize>,
+    /// Banned cards, by name
+    pub banned_cards: Vec<String>,
+    /// If present, only cards printed in these sets are allowed
+    pub legal_sets: Option<Vec<String>>,
+}
+
+/// Number of cards of a type a deck must (or may) include
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct TypeQuota {
+    pub card_type: String,
+    pub min: Option<i32>,
+    pub max: Option<i32>,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ViolationCode {
+    DeckTooSmall,
+    DeckTooLarge,
+    TooManyCopies,
+    CardBanned,
+    TypeQuotaNotMet,
+    TypeQuotaExceeded,
+    EnergyNotAllowed,
+    TooManyEnergies,
+    SetNotLegal,
+    /// The card does not exist or the caller cannot see it yet
+    UnknownCard,
+}
+
+/// A broken rule. `subject` is the card name, type, energy or set code the
+/// violation is about; `limit` and `actual` are the counts compared.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Violation {
+    pub code: ViolationCode,
+    pub message: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub subject: Option<String>,
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub card_ids: Vec<Uuid>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub limit: Option<i64>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub actual: Option<i64>,
+}
+
+impl Violation {
+    fn new(code: ViolationCode, message: String) -> Self {
+        Self { code, message, subject: None, card_ids: Vec::new(), limit: None, actual: None }
+    }
+
+    fn subject(mut self, subject: &str) -> Self {
+        self.subject = Some(subject.to_string());
+        self
+    }
+
+    fn cards(mut self, card_ids: Vec<Uuid>) -> Self {
+        self.card_ids = card_ids;
+        self
+    }
+
+    fn counts(mut self, limit: i64, actual: i64) -> Self {
+        self.limit = Some(limit);
+        self.actual = Some(actual);
+        self
+    }
+}
+
+/// Every rule a deck breaks in a format; `legal` when there are none
+#[derive(Debug, Serialize)]
+pub struct LegalityReport {
+    pub format: String,
+    pub legal: bool,
+    pub card_count: i64,
+    pub violations: Vec<Violation>,
+}
+
+/// A card of the deck being checked
+pub struct LegalityEntry<'a> {
+    pub card: &'a Card,
+    pub set_code: &'a str,
+    pub quantity: i32,
+}
+
+// Copias de una misma carta, sumando todas sus impresiones
+struct NameGroup<'a> {
+    name: &'a str,
+    card_type: &'a str,
+    copies: i64,
+    card_ids: Vec<Uuid>,
+}
+
+/// Checks a deck against a format's rules, collecting every violation
+/// instead of stopping at the first one. `unknown` are the deck's card ids
+/// that could not be resolved against the catalog.
+pub fn check_legality(format: &str, rules: &FormatRules, entries: &[LegalityEntry], unknown: &[Uuid]) -> LegalityReport {
+    let mut violations = Vec::new();
+    let card_count: i64 = entries.iter().map(|entry| i64::from(entry.quantity)).sum();
+
+    for id in unknown {
+        violations.push(
+            Violation::new(ViolationCode::UnknownCard, format!("La carta {} no existe o no está disponible", id))
+                .cards(vec![*id]),
+        );
+    }
+
+    check_size(rules, card_count, &mut violations);
+    check_copies(rules, entries, &mut violations);
+    check_type_quotas(rules, entries, &mut violations);
+    check_energies(rules, entries, &mut violations);
+    check_sets(rules, entries, &mut violations);
+
+    LegalityReport {
+        format: format.to_string(),
+        legal: violations.is_empty(),
+        card_count,
+        violations,
+    }
+}
+
+fn check_size(rules: &FormatRules, card_count: i64, violations: &mut Vec<Violation>) {
+    if let Some(min) = rules.min_cards.filter(|&min| card_count < i64::from(min)) {
+        violations.push(
+            Violation::new(ViolationCode::DeckTooSmall, format!("El mazo tiene {} cartas y el mínimo es {}", card_count, min))
+                .counts(i64::from(min), card_count),
+        );
+    }
+
+    if let Some(max) = rules.max_cards.filter(|&max| card_count > i64::from(max)) {
+        violations.push(
+            Violation::new(ViolationCode::DeckTooLarge, format!("El mazo tiene {} cartas y el máximo es {}", card_count, max))
+                .counts(i64::from(max), card_count),
+        );
+    }
+}
+
+fn check_copies(rules: &FormatRules, entries: &[LegalityEntry], violations: &mut Vec<Violation>) {
+    let banned: HashSet<String> = rules.banned_cards.iter().map(|name| normalize_name(name)).collect();
+    let limits: HashMap<String, i32> = rules
+        .copy_limits
+        .iter()
+        .map(|(name, limit)| (normalize_name(name), *limit))
+        .collect();
+    let unlimited: HashSet<String> = rules.unlimited_types.iter().map(|card_type| normalize_name(card_type)).collect();
+
+    // Agrupado por nombre normalizado y ordenado para que el informe sea estable
+    let mut groups: BTreeMap<String, NameGroup> = BTreeMap::new();
+    for entry in entries {
+        let group = groups.entry(normalize_name(&entry.card.name)).or_insert_with(|| NameGroup {
+            name: &entry.card.name,
+            card_type: &entry.card.card_type,
+            copies: 0,
+            card_ids: Vec::new(),
+        });
+        group.copies += i64::from(entry.quantity);
+        group.card_ids.push(entry.card.id);
+    }
+
+    for (key, group) in groups {
+        if banned.contains(&key) {
+            violations.push(
+                Violation::new(ViolationCode::CardBanned, format!("'{}' está prohibida en este formato", group.name))
+                    .subject(group.name)
+                    .cards(group.card_ids),
+            );
+            continue;
+        }
+
+        let limit = match limits.get(&key) {
+            Some(&limit) => Some(limit),
+            None if unlimited.contains(&normalize_name(group.card_type)) => None,
+            None => rules.max_copies,
+        };
+
+        if let Some(limit) = limit.filter(|&limit| group.copies > i64::from(limit)) {
+            violations.push(
+                Violation::new(
+                    ViolationCode::TooManyCopies,
+                    format!("'{}' tiene {} copias y el máximo es {}", group.name, group.copies, limit),
+                )
+                .subject(group.name)
+                .cards(group.card_ids)
+                .counts(i64::from(limit), group.copies),
+            );
+        }
+    }
+}
+
+fn check_type_quotas(rules: &FormatRules, entries: &[LegalityEntry], violations: &mut Vec<Violation>) {
+    for quota in &rules.type_quotas {
+        let card_type = normalize_name(&quota.card_type);
+        let matching: Vec<&LegalityEntry> = entries
+            .iter()
+            .filter(|entry| normalize_name(&entry.card.card_type) == card_type)
+            .collect();
+        let count: i64 = matching.iter().map(|entry| i64::from(entry.quantity)).sum();
+
+        if let Some(min) = quota.min.filter(|&min| count < i64::from(min)) {
+            violations.push(
+                Violation::new(
+                    ViolationCode::TypeQuotaNotMet,
+                    format!("El mazo necesita al menos {} cartas de tipo '{}' y tiene {}", min, quota.card_type, count),
+                )
+                .subject(&quota.card_type)
+                .cards(matching.iter().map(|entry| entry.card.id).collect())
+                .counts(i64::from(min), count),
+            );
+        }
+
+        if let Some(max) = quota.max.filter(|&max| count > i64::from(max)) {
+            violations.push(
+                Violation::new(
+                    ViolationCode::TypeQuotaExceeded,
+                    format!("El mazo admite como mucho {} cartas de tipo '{}' y tiene {}", max, quota.card_type, count),
+                )
+                .subject(&quota.card_type)
+                .cards(matching.iter().map(|entry| entry.card.id).collect())
+                .counts(i64::from(max), count),
+            );
+        }
+    }
+}
+
+fn check_energies(rules: &FormatRules, entries: &[LegalityEntry], violations: &mut Vec<Violation>) {
+    // Energías distintas del mazo, con las cartas de cada una
+    let mut energies: BTreeMap<String, (&str, Vec<Uuid>)> = BTreeMap::new();
+    for entry in entries {
+        if let Some(energy) = entry.card.card_energy.as_deref().map(str::trim).filter(|energy| !energy.is_empty()) {
+            energies
+                .entry(normalize_name(energy))
+                .or_insert_with(|| (energy, Vec::new()))
+                .1
+                .push(entry.card.id);
+        }
+    }
+
+    if let Some(allowed) = &rules.allowed_energies {
+        let allowed: HashSet<String> = allowed.iter().map(|energy| normalize_name(energy)).collect();
+        for (key, (energy, card_ids)) in &energies {
+            if !allowed.contains(key) {
+                violations.push(
+                    Violation::new(ViolationCode::EnergyNotAllowed, format!("La energía '{}' no está permitida en este formato", energy))
+                        .subject(energy)
+                        .cards(card_ids.clone()),
+                );
+            }
+        }
+    }
+
+    if let Some(max) = rules.max_energies.filter(|&max| energies.len() > max) {
+        violations.push(
+            Violation::new(
+                ViolationCode::TooManyEnergies,
+                format!("El mazo usa {} energías distintas y el máximo es {}", energies.len(), max),
+            )
+            .counts(max as i64, energies.len() as i64),
+        );
+    }
+}
+
+fn check_sets(rules: &FormatRules, entries: &[LegalityEntry], violations: &mut Vec<Violation>) {
+    let Some(legal_sets) = &rules.legal_sets else {
+        return;
+    };
+
+    let legal: HashSet<String> = legal_sets.iter().map(|code| code.trim().to_uppercase()).collect();
+    let mut illegal: BTreeMap<String, Vec<Uuid>> = BTreeMap::new();
+    for entry in entries {
+        let code = entry.set_code.to_uppercase();
+        if !legal.contains(&code) {
+            illegal.entry(code).or_default().push(entry.card.id);
+        }
+    }
+
+    for (code, card_ids) in illegal {
+        violations.push(
+            Violation::new(ViolationCode::SetNotLegal, format!("Las cartas de la edición {} no son legales en este formato", code))
+                .subject(&code)
+                .cards(card_ids),
+        );
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::Utc;
+
+    fn card(name: &str, card_type: &str, energy: Option<&str>) -> Card {
+        Card {
+            id: Uuid::new_v4(),
+            set_id: Uuid::new_v4(),
+            collector_number: 1,
+            name: name.to_string(),
+            card_type: card_type.to_string(),
+            card_energy: energy.map(str::to_string),
+            rarity: "Común".to_string(),
+            cost: None,
+            artists: Vec::new(),
+            image_url: None,
+            image_details: None,
+            revealed_at: None,
+            created_at: Utc::now(),
+            updated_at: Utc::now(),
+        }
+    }
+
+    fn entry(card: &Card, quantity: i32) -> LegalityEntry<'_> {
+        LegalityEntry { card, set_code: "LGRO", quantity }
+    }
+
+    fn check(rules: &FormatRules, entries: &[LegalityEntry]) -> LegalityReport {
+        check_legality("standard", rules, entries, &[])
+    }
+
+    fn codes(report: &LegalityReport) -> Vec<ViolationCode> {
+        report.violations.iter().map(|violation| violation.code).collect()
+    }
+
+    // La única infracción del informe con ese código
+    fn only(report: &LegalityReport, code: ViolationCode) -> &Violation {
+        let matching: Vec<&Violation> = report.violations.iter().filter(|violation| violation.code == code).collect();
+        assert_eq!(matching.len(), 1, "expected one {:?} in {:?}", code, report.violations);
+        matching[0]
+    }
+
+    fn counts(violation: &Violation) -> (Option<i64>, Option<i64>) {
+        (violation.limit, violation.actual)
+    }
+
+    #[test]
+    fn empty_rules_allow_any_deck() {
+        let tokar = card("Tokar", "Personaje", Some("Fuego"));
+        let report = check(&FormatRules::default(), &[entry(&tokar, 60)]);
+
+        assert!(report.legal);
+        assert!(report.violations.is_empty());
+        assert_eq!(report.format, "standard");
+        assert_eq!(report.card_count, 60);
+    }
+
+    #[test]
+    fn deck_size_is_checked_against_both_bounds() {
+        let rules = FormatRules { min_cards: Some(40), max_cards: Some(50), ..FormatRules::default() };
+        let tokar = card("Tokar", "Personaje", None);
+
+        let small = check(&rules, &[entry(&tokar, 30)]);
+        assert_eq!(codes(&small), vec![ViolationCode::DeckTooSmall]);
+        assert_eq!(counts(&small.violations[0]), (Some(40), Some(30)));
+
+        let large = check(&rules, &[entry(&tokar, 60)]);
+        assert_eq!(codes(&large), vec![ViolationCode::DeckTooLarge]);
+        assert_eq!(counts(&large.violations[0]), (Some(50), Some(60)));
+
+        assert!(check(&rules, &[entry(&tokar, 40)]).legal);
+        assert!(check(&rules, &[entry(&tokar, 50)]).legal);
+    }
+
+    #[test]
+    fn copies_are_counted_by_name_across_printings() {
+        let rules = FormatRules { max_copies: Some(3), ..FormatRules::default() };
+        let first = card("Tókar", "Personaje", None);
+        let reprint = card("tokar", "Personaje", None);
+        let other = card("Relámpago", "Evento", None);
+
+        let report = check(&rules, &[entry(&first, 2), entry(&reprint, 2), entry(&other, 3)]);
+
+        let violation = only(&report, ViolationCode::TooManyCopies);
+        assert_eq!(violation.subject.as_deref(), Some("Tókar"));
+        assert_eq!(violation.card_ids, vec![first.id, reprint.id]);
+        assert_eq!(counts(violation), (Some(3), Some(4)));
+        assert_eq!(report.violations.len(), 1);
+    }
+
+    #[test]
+    fn copy_limits_override_max_copies_both_ways() {
+        let rules = FormatRules {
+            max_copies: Some(3),
+            copy_limits: BTreeMap::from([("TOKAR".to_string(), 1), ("Relámpago".to_string(), 5)]),
+            ..FormatRules::default()
+        };
+        let tokar = card("Tokar", "Personaje", None);
+        let lightning = card("Relampago", "Evento", None);
+
+        let report = check(&rules, &[entry(&tokar, 2), entry(&lightning, 5)]);
+
+        let violation = only(&report, ViolationCode::TooManyCopies);
+        assert_eq!(violation.subject.as_deref(), Some("Tokar"));
+        assert_eq!(counts(violation), (Some(1), Some(2)));
+        assert_eq!(report.violations.len(), 1);
+    }
+
+    #[test]
+    fn unlimited_types_skip_max_copies_but_not_copy_limits() {
+        let rules = FormatRules {
+            max_copies: Some(3),
+            copy_limits: BTreeMap::from([("Energía de fuego".to_string(), 10)]),
+            unlimited_types: vec!["energia basica".to_string()],
+            ..FormatRules::default()
+        };
+        let water = card("Energía de agua", "Energía Básica", None);
+        let fire = card("Energía de fuego", "Energía Básica", None);
+
+        let report = check(&rules, &[entry(&water, 20), entry(&fire, 12)]);
+
+        let violation = only(&report, ViolationCode::TooManyCopies);
+        assert_eq!(violation.subject.as_deref(), Some("Energía de fuego"));
+        assert_eq!(counts(violation), (Some(10), Some(12)));
+        assert_eq!(report.violations.len(), 1);
+    }
+
+    #[test]
+    fn banned_cards_are_reported_instead_of_their_copies() {
+        let rules = FormatRules {
+            max_copies: Some(3),
+            banned_cards: vec!["TOKAR".to_string()],
+            ..FormatRules::default()
+        };
+        let tokar = card("Tokar", "Personaje", None);
+
+        let report = check(&rules, &[entry(&tokar, 4)]);
+
+        assert_eq!(codes(&report), vec![ViolationCode::CardBanned]);
+        assert_eq!(report.violations[0].subject.as_deref(), Some("Tokar"));
+        assert_eq!(report.violations[0].card_ids, vec![tokar.id]);
+        assert_eq!(counts(&report.violations[0]), (None, None));
+    }
+
+    #[test]
+    fn type_quotas_check_minimum_and_maximum() {
+        let rules = FormatRules {
+            type_quotas: vec![
+                TypeQuota { card_type: "Personaje".to_string(), min: Some(10), max: None },
+                TypeQuota { card_type: "evento".to_string(), min: None, max: Some(5) },
+            ],
+            ..FormatRules::default()
+        };
+        let tokar = card("Tokar", "Personaje", None);
+        let lightning = card("Relámpago", "Evento", None);
+        let storm = card("Tormenta", "Evento", None);
+
+        let report = check(&rules, &[entry(&tokar, 8), entry(&lightning, 3), entry(&storm, 3)]);
+
+        let not_met = only(&report, ViolationCode::TypeQuotaNotMet);
+        assert_eq!(not_met.subject.as_deref(), Some("Personaje"));
+        assert_eq!(not_met.card_ids, vec![tokar.id]);
+        assert_eq!(counts(not_met), (Some(10), Some(8)));
+
+        let exceeded = only(&report, ViolationCode::TypeQuotaExceeded);
+        assert_eq!(exceeded.subject.as_deref(), Some("evento"));
+        assert_eq!(exceeded.card_ids, vec![lightning.id, storm.id]);
+        assert_eq!(counts(exceeded), (Some(5), Some(6)));
+    }
+
+    #[test]
+    fn energies_must_be_allowed_and_within_the_maximum() {
+        let rules = FormatRules {
+            allowed_energies: Some(vec!["fuego".to_string(), "Agua".to_string()]),
+            max_energies: Some(1),
+            ..FormatRules::default()
+        };
+        let fire = card("Tokar", "Personaje", Some("Fuego"));
+        let water = card("Nerea", "Personaje", Some("Agua"));
+        let earth = card("Golem", "Personaje", Some("Tierra"));
+        let colorless = card("Relámpago", "Evento", None);
+
+        let report = check(&rules, &[entry(&fire, 1), entry(&water, 1), entry(&earth, 1), entry(&colorless, 1)]);
+
+        let not_allowed = only(&report, ViolationCode::EnergyNotAllowed);
+        assert_eq!(not_allowed.subject.as_deref(), Some("Tierra"));
+        assert_eq!(not_allowed.card_ids, vec![earth.id]);
+
+        let too_many = only(&report, ViolationCode::TooManyEnergies);
+        assert_eq!(counts(too_many), (Some(1), Some(3)));
+        assert_eq!(report.violations.len(), 2);
+    }
+
+    #[test]
+    fn cards_must_come_from_legal_sets() {
+        let rules = FormatRules { legal_sets: Some(vec![" lgro ".to_string()]), ..FormatRules::default() };
+        let tokar = card("Tokar", "Personaje", None);
+        let old = card("Nerea", "Personaje", None);
+
+        let entries = [entry(&tokar, 3), LegalityEntry { card: &old, set_code: "kd01", quantity: 2 }];
+        let report = check(&rules, &entries);
+
+        assert_eq!(codes(&report), vec![ViolationCode::SetNotLegal]);
+        assert_eq!(report.violations[0].subject.as_deref(), Some("KD01"));
+        assert_eq!(report.violations[0].card_ids, vec![old.id]);
+    }
+
+    #[test]
+    fn unknown_cards_make_the_deck_illegal() {
+        let id = Uuid::new_v4();
+        let report = check_legality("standard", &FormatRules::default(), &[], &[id]);
+
+        assert!(!report.legal);
+        assert_eq!(codes(&report), vec![ViolationCode::UnknownCard]);
+        assert_eq!(report.violations[0].card_ids, vec![id]);
+    }
+
+    #[test]
+    fn every_violation_is_collected() {
+        let rules = FormatRules {
+            min_cards: Some(40),
+            max_copies: Some(3),
+            banned_cards: vec!["Golem".to_string()],
+            type_quotas: vec![TypeQuota { card_type: "Evento".to_string(), min: Some(2), max: None }],
+            allowed_energies: Some(vec!["Fuego".to_string()]),
+            legal_sets: Some(vec!["LGRO".to_string()]),
+            ..FormatRules::default()
+        };
+        let tokar = card("Tokar", "Personaje", Some("Fuego"));
+        let golem = card("Golem", "Personaje", Some("Tierra"));
+
+        let entries = [entry(&tokar, 4), LegalityEntry { card: &golem, set_code: "KD01", quantity: 1 }];
+        let report = check_legality("standard", &rules, &entries, &[Uuid::new_v4()]);
+
+        assert!(!report.legal);
+        assert_eq!(report.card_count, 5);
+        assert_eq!(
+            codes(&report),
+            vec![
+                ViolationCode::UnknownCard,
+                ViolationCode::DeckTooSmall,
+                ViolationCode::CardBanned,
+                ViolationCode::TooManyCopies,
+                ViolationCode::TypeQuotaNotMet,
+                ViolationCode::EnergyNotAllowed,
+                ViolationCode::SetNotLegal,
+            ]
+        );
+    }
+}
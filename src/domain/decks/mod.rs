@@ -0,0 +1,27 @@
+mod model;
+mod repository;
+mod service;
+mod dto;
+mod legality;
+mod decklist;
+mod deck_code;
+mod revision;
+mod analysis;
+mod buildability;
+mod gallery;
+mod simulation;
+mod cooccurrence;
+
+pub use model::*;
+pub use repository::*;
+pub use service::*;
+pub use dto::*;
+pub use legality::*;
+pub use decklist::*;
+pub use deck_code::*;
+pub use revision::*;
+pub use analysis::*;
+pub use buildability::*;
+pub use gallery::*;
+pub use simulation::*;
+pub use cooccurrence::*;
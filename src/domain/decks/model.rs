@@ -0,0 +1,147 @@
+use serde::{Deserialize, Serialize};
+use sqlx::postgres::PgRow;
+use sqlx::types::Json;
+use sqlx::Row;
+use uuid::Uuid;
+use chrono::{DateTime, Utc};
+
+use super::gallery::DeckFork;
+use super::legality::FormatRules;
+
+/// A user's deck. Mirrors the Firestore `decks` rules: public decks are
+/// readable by any authenticated user, private ones only by their owner, and
+/// anonymous sessions cannot create or change decks.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Deck {
+    pub id: Uuid,
+    pub owner_uid: String,
+    pub name: String,
+    pub description: Option<String>,
+    /// Format the deck is built for; legality is checked against it by default
+    pub format_code: Option<String>,
+    pub is_public: bool,
+    pub created_at: DateTime<Utc>,
+    pub updated_at: DateTime<Utc>,
+}
+
+impl Deck {
+    pub fn new(
+        owner_uid: String,
+        name: String,
+        description: Option<String>,
+        format_code: Option<String>,
+        is_public: bool,
+    ) -> Self {
+        Self {
+            id: Uuid::new_v4(),
+            owner_uid,
+            name,
+            description,
+            format_code,
+            is_public,
+            created_at: Utc::now(),
+            updated_at: Utc::now(),
+        }
+    }
+}
+
+impl<'r> sqlx::FromRow<'r, PgRow> for Deck {
+    fn from_row(row: &'r PgRow) -> Result<Self, sqlx::Error> {
+        Ok(Self {
+            id: row.try_get("id")?,
+            owner_uid: row.try_get("owner_uid")?,
+            name: row.try_get("name")?,
+            description: row.try_get("description")?,
+            format_code: row.try_get("format_code")?,
+            is_public: row.try_get("is_public")?,
+            created_at: row.try_get("created_at")?,
+            updated_at: row.try_get("updated_at")?,
+        })
+    }
+}
+
+/// Copies of a card in a deck
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct DeckCard {
+    pub card_id: Uuid,
+    pub quantity: i32,
+}
+
+impl<'r> sqlx::FromRow<'r, PgRow> for DeckCard {
+    fn from_row(row: &'r PgRow) -> Result<Self, sqlx::Error> {
+        Ok(Self {
+            card_id: row.try_get("card_id")?,
+            quantity: row.try_get("quantity")?,
+        })
+    }
+}
+
+/// Deck with its card list
+#[derive(Debug, Serialize)]
+pub struct DeckDetail {
+    #[serde(flatten)]
+    pub deck: Deck,
+    pub card_count: i32,
+    pub cards: Vec<DeckCard>,
+    /// Attribution when the deck was forked from another one
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub forked_from: Option<DeckFork>,
+}
+
+impl DeckDetail {
+    pub fn new(deck: Deck, cards: Vec<DeckCard>, forked_from: Option<DeckFork>) -> Self {
+        let card_count = cards.iter().map(|card| card.quantity).sum();
+        Self { deck, card_count, cards, forked_from }
+    }
+}
+
+/// Timestamp deck lists are sorted by
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum DeckSort {
+    Created,
+    Updated,
+}
+
+impl DeckSort {
+    pub fn parse(value: &str) -> Option<Self> {
+        match value.trim().to_lowercase().as_str() {
+            "created" | "created_at" => Some(Self::Created),
+            "updated" | "updated_at" => Some(Self::Updated),
+            _ => None,
+        }
+    }
+
+    /// Column of `decks` to order by; each one has an index with `owner_uid`
+    pub fn column(&self) -> &'static str {
+        match self {
+            Self::Created => "created_at",
+            Self::Updated => "updated_at",
+        }
+    }
+}
+
+/// A game format and its deck construction rules
+#[derive(Debug, Serialize, Deserialize)]
+pub struct DeckFormat {
+    pub code: String,
+    pub name: String,
+    pub description: Option<String>,
+    pub rules: FormatRules,
+    pub created_at: DateTime<Utc>,
+    pub updated_at: DateTime<Utc>,
+}
+
+impl<'r> sqlx::FromRow<'r, PgRow> for DeckFormat {
+    fn from_row(row: &'r PgRow) -> Result<Self, sqlx::Error> {
+        let Json(rules): Json<FormatRules> = row.try_get("rules")?;
+
+        Ok(Self {
+            code: row.try_get("code")?,
+            name: row.try_get("name")?,
+            description: row.try_get("description")?,
+            rules,
+            created_at: row.try_get("created_at")?,
+            updated_at: row.try_get("updated_at")?,
+        })
+    }
+}
@@ -0,0 +1,845 @@
+use async_trait::async_trait;
+use chrono::{DateTime, Utc};
+use sqlx::types::Json;
+use sqlx::{PgPool, Postgres, Transaction};
+use anyhow::Result;
+use uuid::Uuid;
+
+use super::cooccurrence::{cooccurrence_delta, CardPairCount, CardSuggestionCount};
+use super::gallery::{
+    DeckFork, DeckReactions, GalleryDeck, GalleryFilter, TRENDING_BOOKMARK_WEIGHT, TRENDING_FORK_WEIGHT,
+    TRENDING_HALF_LIFE_HOURS, TRENDING_LIKE_WEIGHT, TRENDING_MAX_AGE_HALF_LIVES,
+};
+use super::legality::FormatRules;
+use super::model::{Deck, DeckCard, DeckFormat, DeckSort};
+use super::revision::{DeckRevision, DeckRevisionDetail};
+
+#[async_trait]
+pub trait DeckRepository {
+    async fn get_decks_by_owner(
+        &self,
+        owner_uid: &str,
+        include_private: bool,
+        sort: DeckSort,
+        descending: bool,
+        limit: i64,
+        offset: i64,
+    ) -> Result<Vec<Deck>>;
+    async fn get_deck_by_id(&self, id: Uuid) -> Result<Option<Deck>>;
+    async fn get_cards(&self, deck_id: Uuid) -> Result<Vec<DeckCard>>;
+    /// Creates the deck and its first revision, recording where it was
+    /// forked from if it is a copy
+    async fn create_deck(
+        &self,
+        deck: Deck,
+        cards: &[DeckCard],
+        author_uid: &str,
+        forked_from: Option<&DeckFork>,
+    ) -> Result<Deck>;
+    /// Replaces the deck's fields and its whole card list, storing a new
+    /// revision. `restored_from` is the revision being restored, if any.
+    async fn update_deck(
+        &self,
+        deck: &Deck,
+        cards: &[DeckCard],
+        author_uid: &str,
+        restored_from: Option<i32>,
+    ) -> Result<Option<Deck>>;
+    async fn delete_deck(&self, id: Uuid) -> Result<bool>;
+    /// Revisions of a deck, newest first
+    async fn get_revisions(&self, deck_id: Uuid) -> Result<Vec<DeckRevision>>;
+    async fn get_revision(&self, deck_id: Uuid, revision: i32) -> Result<Option<DeckRevisionDetail>>;
+    async fn get_latest_revision(&self, deck_id: Uuid) -> Result<Option<DeckRevisionDetail>>;
+    async fn get_fork(&self, deck_id: Uuid) -> Result<Option<DeckFork>>;
+    /// Public decks (or, with `bookmarked_only`, the viewer's bookmarks they can still read)
+    async fn get_gallery(&self, filter: &GalleryFilter, viewer_uid: &str, bookmarked_only: bool) -> Result<Vec<GalleryDeck>>;
+    async fn get_reactions(&self, deck_id: Uuid, viewer_uid: &str) -> Result<DeckReactions>;
+    async fn set_liked(&self, deck_id: Uuid, user_uid: &str, liked: bool) -> Result<()>;
+    async fn set_bookmarked(&self, deck_id: Uuid, user_uid: &str, bookmarked: bool) -> Result<()>;
+    /// Recomputes the stored trending score of one deck or, with `None`, of
+    /// every deck with recent interactions or a score left to decay
+    async fn refresh_trending_scores(&self, deck_id: Option<Uuid>) -> Result<()>;
+    /// Decks whose contribution to the co-occurrence statistics is out of
+    /// date: public decks changed since they were indexed, and indexed decks
+    /// that were deleted or made private
+    async fn get_stale_cooccurrence_decks(&self, limit: i64) -> Result<Vec<Uuid>>;
+    /// Names of the deck's cards, one per card of the list
+    async fn get_card_names(&self, deck_id: Uuid) -> Result<Vec<String>>;
+    /// Replaces what a deck contributes to the statistics with `card_names`
+    /// (normalized, without repeats), or removes it when `deck_updated_at` is `None`
+    async fn save_cooccurrence(&self, deck_id: Uuid, card_names: &[String], deck_updated_at: Option<DateTime<Utc>>) -> Result<()>;
+    /// Empties the statistics so every public deck is indexed again
+    async fn clear_cooccurrence(&self) -> Result<()>;
+    /// Indexed public decks with at least one card
+    async fn count_cooccurrence_decks(&self) -> Result<i64>;
+    async fn get_card_deck_count(&self, card_name: &str) -> Result<i64>;
+    /// Cards played with `card_name`, most frequent first
+    async fn get_played_with(&self, card_name: &str, limit: i64) -> Result<Vec<CardPairCount>>;
+    /// Cards not in `card_names` most played with them, best score first
+    async fn get_card_suggestions(&self, card_names: &[String], limit: i64) -> Result<Vec<CardSuggestionCount>>;
+    async fn get_formats(&self) -> Result<Vec<DeckFormat>>;
+    async fn get_format(&self, code: &str) -> Result<Option<DeckFormat>>;
+    /// Creates the format or replaces its name, description and rules
+    async fn save_format(&self, code: &str, name: &str, description: Option<&str>, rules: &FormatRules) -> Result<DeckFormat>;
+    async fn delete_format(&self, code: &str) -> Result<bool>;
+}
+
+pub struct PgDeckRepository {
+    pool: PgPool,
+}
+
+impl PgDeckRepository {
+    pub fn new(pool: PgPool) -> Self {
+        Self { pool }
+    }
+
+    async fn insert_cards(tx: &mut Transaction<'_, Postgres>, deck_id: Uuid, cards: &[DeckCard]) -> Result<()> {
+        for card in cards {
+            sqlx::query("INSERT INTO deck_cards (deck_id, card_id, quantity) VALUES ($1, $2, $3)")
+                .bind(deck_id)
+                .bind(card.card_id)
+                .bind(card.quantity)
+                .execute(&mut **tx)
+                .await?;
+        }
+
+        Ok(())
+    }
+
+    // El número de revisión se calcula tras actualizar `decks`: el bloqueo de
+    // esa fila serializa los guardados concurrentes del mismo mazo
+    async fn insert_revision(
+        tx: &mut Transaction<'_, Postgres>,
+        deck: &Deck,
+        cards: &[DeckCard],
+        author_uid: &str,
+        restored_from: Option<i32>,
+    ) -> Result<()> {
+        let card_count: i32 = cards.iter().map(|card| card.quantity).sum();
+
+        sqlx::query(
+            r#"
+            INSERT INTO deck_revisions (
+                id, deck_id, revision, name, description, format_code, card_count, cards, author_uid, restored_from, created_at
+            )
+            SELECT $1, $2, COALESCE(MAX(revision), 0) + 1, $3, $4, $5, $6, $7, $8, $9, $10
+            FROM deck_revisions
+            WHERE deck_id = $2
+            "#
+        )
+        .bind(Uuid::new_v4())
+        .bind(deck.id)
+        .bind(&deck.name)
+        .bind(&deck.description)
+        .bind(&deck.format_code)
+        .bind(card_count)
+        .bind(Json(cards))
+        .bind(author_uid)
+        .bind(restored_from)
+        .bind(deck.updated_at)
+        .execute(&mut **tx)
+        .await?;
+
+        Ok(())
+    }
+}
+
+#[async_trait]
+impl DeckRepository for PgDeckRepository {
+    async fn get_decks_by_owner(
+        &self,
+        owner_uid: &str,
+        include_private: bool,
+        sort: DeckSort,
+        descending: bool,
+        limit: i64,
+        offset: i64,
+    ) -> Result<Vec<Deck>> {
+        // Columna y dirección fijas en el SQL para que se usen los índices por propietario
+        let direction = if descending { "DESC" } else { "ASC" };
+        let decks = sqlx::query_as::<_, Deck>(&format!(
+            r#"
+            SELECT id, owner_uid, name, description, format_code, is_public, created_at, updated_at
+            FROM decks
+            WHERE owner_uid = $1 AND ($2 OR is_public)
+            ORDER BY {column} {direction}, id {direction}
+            LIMIT $3 OFFSET $4
+            "#,
+            column = sort.column(),
+            direction = direction,
+        ))
+        .bind(owner_uid)
+        .bind(include_private)
+        .bind(limit)
+        .bind(offset)
+        .fetch_all(&self.pool)
+        .await?;
+
+        Ok(decks)
+    }
+
+    async fn get_deck_by_id(&self, id: Uuid) -> Result<Option<Deck>> {
+        let deck = sqlx::query_as::<_, Deck>(
+            r#"
+            SELECT id, owner_uid, name, description, format_code, is_public, created_at, updated_at
+            FROM decks
+            WHERE id = $1
+            "#
+        )
+        .bind(id)
+        .fetch_optional(&self.pool)
+        .await?;
+
+        Ok(deck)
+    }
+
+    async fn get_cards(&self, deck_id: Uuid) -> Result<Vec<DeckCard>> {
+        let cards = sqlx::query_as::<_, DeckCard>(
+            r#"
+            SELECT d.card_id, d.quantity
+            FROM deck_cards d
+            JOIN cards c ON c.id = d.card_id
+            JOIN card_sets s ON s.id = c.set_id
+            WHERE d.deck_id = $1
+            ORDER BY s.release_date, s.code, c.collector_number
+            "#
+        )
+        .bind(deck_id)
+        .fetch_all(&self.pool)
+        .await?;
+
+        Ok(cards)
+    }
+
+    async fn create_deck(
+        &self,
+        deck: Deck,
+        cards: &[DeckCard],
+        author_uid: &str,
+        forked_from: Option<&DeckFork>,
+    ) -> Result<Deck> {
+        let mut tx = self.pool.begin().await?;
+
+        let deck = sqlx::query_as::<_, Deck>(
+            r#"
+            INSERT INTO decks (id, owner_uid, name, description, format_code, is_public, created_at, updated_at)
+            VALUES ($1, $2, $3, $4, $5, $6, $7, $8)
+            RETURNING id, owner_uid, name, description, format_code, is_public, created_at, updated_at
+            "#
+        )
+        .bind(deck.id)
+        .bind(&deck.owner_uid)
+        .bind(&deck.name)
+        .bind(&deck.description)
+        .bind(&deck.format_code)
+        .bind(deck.is_public)
+        .bind(deck.created_at)
+        .bind(deck.updated_at)
+        .fetch_one(&mut *tx)
+        .await?;
+
+        Self::insert_cards(&mut tx, deck.id, cards).await?;
+        Self::insert_revision(&mut tx, &deck, cards, author_uid, None).await?;
+
+        if let Some(fork) = forked_from {
+            sqlx::query(
+                r#"
+                INSERT INTO deck_forks (deck_id, source_deck_id, source_owner_uid, source_name, source_revision, created_at)
+                VALUES ($1, $2, $3, $4, $5, $6)
+                "#
+            )
+            .bind(deck.id)
+            .bind(fork.source_deck_id)
+            .bind(&fork.source_owner_uid)
+            .bind(&fork.source_name)
+            .bind(fork.source_revision)
+            .bind(fork.created_at)
+            .execute(&mut *tx)
+            .await?;
+        }
+
+        tx.commit().await?;
+
+        Ok(deck)
+    }
+
+    async fn update_deck(
+        &self,
+        deck: &Deck,
+        cards: &[DeckCard],
+        author_uid: &str,
+        restored_from: Option<i32>,
+    ) -> Result<Option<Deck>> {
+        let mut tx = self.pool.begin().await?;
+
+        let deck = sqlx::query_as::<_, Deck>(
+            r#"
+            UPDATE decks
+            SET name = $1, description = $2, format_code = $3, is_public = $4, updated_at = NOW()
+            WHERE id = $5
+            RETURNING id, owner_uid, name, description, format_code, is_public, created_at, updated_at
+            "#
+        )
+        .bind(&deck.name)
+        .bind(&deck.description)
+        .bind(&deck.format_code)
+        .bind(deck.is_public)
+        .bind(deck.id)
+        .fetch_optional(&mut *tx)
+        .await?;
+
+        let Some(deck) = deck else {
+            return Ok(None);
+        };
+
+        sqlx::query("DELETE FROM deck_cards WHERE deck_id = $1")
+            .bind(deck.id)
+            .execute(&mut *tx)
+            .await?;
+        Self::insert_cards(&mut tx, deck.id, cards).await?;
+        Self::insert_revision(&mut tx, &deck, cards, author_uid, restored_from).await?;
+        tx.commit().await?;
+
+        Ok(Some(deck))
+    }
+
+    async fn delete_deck(&self, id: Uuid) -> Result<bool> {
+        let result = sqlx::query("DELETE FROM decks WHERE id = $1")
+            .bind(id)
+            .execute(&self.pool)
+            .await?;
+
+        Ok(result.rows_affected() > 0)
+    }
+
+    async fn get_revisions(&self, deck_id: Uuid) -> Result<Vec<DeckRevision>> {
+        let revisions = sqlx::query_as::<_, DeckRevision>(
+            r#"
+            SELECT id, deck_id, revision, name, description, format_code, card_count, author_uid, restored_from, created_at
+            FROM deck_revisions
+            WHERE deck_id = $1
+            ORDER BY revision DESC
+            "#
+        )
+        .bind(deck_id)
+        .fetch_all(&self.pool)
+        .await?;
+
+        Ok(revisions)
+    }
+
+    async fn get_revision(&self, deck_id: Uuid, revision: i32) -> Result<Option<DeckRevisionDetail>> {
+        let revision = sqlx::query_as::<_, DeckRevisionDetail>(
+            r#"
+            SELECT id, deck_id, revision, name, description, format_code, card_count, cards, author_uid, restored_from, created_at
+            FROM deck_revisions
+            WHERE deck_id = $1 AND revision = $2
+            "#
+        )
+        .bind(deck_id)
+        .bind(revision)
+        .fetch_optional(&self.pool)
+        .await?;
+
+        Ok(revision)
+    }
+
+    async fn get_latest_revision(&self, deck_id: Uuid) -> Result<Option<DeckRevisionDetail>> {
+        let revision = sqlx::query_as::<_, DeckRevisionDetail>(
+            r#"
+            SELECT id, deck_id, revision, name, description, format_code, card_count, cards, author_uid, restored_from, created_at
+            FROM deck_revisions
+            WHERE deck_id = $1
+            ORDER BY revision DESC
+            LIMIT 1
+            "#
+        )
+        .bind(deck_id)
+        .fetch_optional(&self.pool)
+        .await?;
+
+        Ok(revision)
+    }
+
+    async fn get_fork(&self, deck_id: Uuid) -> Result<Option<DeckFork>> {
+        let fork = sqlx::query_as::<_, DeckFork>(
+            r#"
+            SELECT source_deck_id, source_owner_uid, source_name, source_revision, created_at
+            FROM deck_forks
+            WHERE deck_id = $1
+            "#
+        )
+        .bind(deck_id)
+        .fetch_optional(&self.pool)
+        .await?;
+
+        Ok(fork)
+    }
+
+    async fn get_gallery(&self, filter: &GalleryFilter, viewer_uid: &str, bookmarked_only: bool) -> Result<Vec<GalleryDeck>> {
+        let decks = sqlx::query_as::<_, GalleryDeck>(&format!(
+            r#"
+            SELECT *
+            FROM (
+                SELECT
+                    d.id, d.owner_uid, d.name, d.description, d.format_code, d.is_public, d.created_at, d.updated_at,
+                    COALESCE((SELECT SUM(dc.quantity) FROM deck_cards dc WHERE dc.deck_id = d.id), 0)::INT AS card_count,
+                    ARRAY(
+                        SELECT DISTINCT c.card_energy::TEXT
+                        FROM deck_cards dc
+                        JOIN cards c ON c.id = dc.card_id
+                        WHERE dc.deck_id = d.id AND c.card_energy IS NOT NULL
+                        ORDER BY 1
+                    ) AS energies,
+                    (SELECT COUNT(*) FROM deck_likes l WHERE l.deck_id = d.id) AS like_count,
+                    (SELECT COUNT(*) FROM deck_bookmarks b WHERE b.deck_id = d.id) AS bookmark_count,
+                    (SELECT COUNT(*) FROM deck_forks f WHERE f.source_deck_id = d.id) AS fork_count,
+                    d.trending_score,
+                    EXISTS (SELECT 1 FROM deck_likes l WHERE l.deck_id = d.id AND l.user_uid = $1) AS liked,
+                    EXISTS (SELECT 1 FROM deck_bookmarks b WHERE b.deck_id = d.id AND b.user_uid = $1) AS bookmarked
+                FROM decks d
+                WHERE (d.is_public OR ($5 AND d.owner_uid = $1))
+                  AND ($2::TEXT IS NULL OR d.format_code = $2)
+                  AND ($3::TEXT IS NULL OR EXISTS (
+                      SELECT 1
+                      FROM deck_cards dc
+                      JOIN cards c ON c.id = dc.card_id
+                      WHERE dc.deck_id = d.id AND LOWER(c.card_energy) = LOWER($3)
+                  ))
+                  AND ($4::UUID IS NULL OR EXISTS (
+                      SELECT 1
+                      FROM deck_cards dc
+                      JOIN cards c ON c.id = dc.card_id
+                      JOIN cards x ON x.id = $4
+                      WHERE dc.deck_id = d.id AND LOWER(c.name) = LOWER(x.name)
+                  ))
+                  AND (NOT $5 OR EXISTS (SELECT 1 FROM deck_bookmarks b WHERE b.deck_id = d.id AND b.user_uid = $1))
+            ) g
+            ORDER BY {}
+            LIMIT $6 OFFSET $7
+            "#,
+            filter.sort.order_by()
+        ))
+        .bind(viewer_uid)
+        .bind(&filter.format_code)
+        .bind(&filter.energy)
+        .bind(filter.card_id)
+        .bind(bookmarked_only)
+        .bind(filter.limit)
+        .bind(filter.offset)
+        .fetch_all(&self.pool)
+        .await?;
+
+        Ok(decks)
+    }
+
+    async fn get_reactions(&self, deck_id: Uuid, viewer_uid: &str) -> Result<DeckReactions> {
+        let reactions = sqlx::query_as::<_, DeckReactions>(
+            r#"
+            SELECT
+                $1::UUID AS deck_id,
+                (SELECT COUNT(*) FROM deck_likes WHERE deck_id = $1) AS like_count,
+                (SELECT COUNT(*) FROM deck_bookmarks WHERE deck_id = $1) AS bookmark_count,
+                (SELECT COUNT(*) FROM deck_forks WHERE source_deck_id = $1) AS fork_count,
+                EXISTS (SELECT 1 FROM deck_likes WHERE deck_id = $1 AND user_uid = $2) AS liked,
+                EXISTS (SELECT 1 FROM deck_bookmarks WHERE deck_id = $1 AND user_uid = $2) AS bookmarked
+            "#
+        )
+        .bind(deck_id)
+        .bind(viewer_uid)
+        .fetch_one(&self.pool)
+        .await?;
+
+        Ok(reactions)
+    }
+
+    // Dar "me gusta" dos veces o quitarlo sin haberlo dado no es un error
+    async fn set_liked(&self, deck_id: Uuid, user_uid: &str, liked: bool) -> Result<()> {
+        let query = if liked {
+            "INSERT INTO deck_likes (deck_id, user_uid, created_at) VALUES ($1, $2, NOW()) ON CONFLICT DO NOTHING"
+        } else {
+            "DELETE FROM deck_likes WHERE deck_id = $1 AND user_uid = $2"
+        };
+
+        sqlx::query(query)
+            .bind(deck_id)
+            .bind(user_uid)
+            .execute(&self.pool)
+            .await?;
+
+        Ok(())
+    }
+
+    async fn set_bookmarked(&self, deck_id: Uuid, user_uid: &str, bookmarked: bool) -> Result<()> {
+        let query = if bookmarked {
+            "INSERT INTO deck_bookmarks (deck_id, user_uid, created_at) VALUES ($1, $2, NOW()) ON CONFLICT DO NOTHING"
+        } else {
+            "DELETE FROM deck_bookmarks WHERE deck_id = $1 AND user_uid = $2"
+        };
+
+        sqlx::query(query)
+            .bind(deck_id)
+            .bind(user_uid)
+            .execute(&self.pool)
+            .await?;
+
+        Ok(())
+    }
+
+    // La puntuación suma cada interacción multiplicada por su peso y por
+    // 0.5^(edad / vida media), así que lo reciente pesa más que lo antiguo. Las
+    // del propio dueño (guardar o copiar su mazo) no cuentan.
+    async fn refresh_trending_scores(&self, deck_id: Option<Uuid>) -> Result<()> {
+        sqlx::query(
+            r#"
+            WITH interactions AS (
+                SELECT l.deck_id, $3 AS weight, l.created_at
+                FROM deck_likes l
+                JOIN decks d ON d.id = l.deck_id
+                WHERE l.user_uid <> d.owner_uid
+                UNION ALL
+                SELECT b.deck_id, $4, b.created_at
+                FROM deck_bookmarks b
+                JOIN decks d ON d.id = b.deck_id
+                WHERE b.user_uid <> d.owner_uid
+                UNION ALL
+                SELECT f.source_deck_id, $5, f.created_at
+                FROM deck_forks f
+                JOIN decks d ON d.id = f.deck_id
+                WHERE f.source_deck_id IS NOT NULL AND d.owner_uid <> f.source_owner_uid
+            ),
+            scores AS (
+                SELECT deck_id, SUM(weight * POWER(0.5::FLOAT8, EXTRACT(EPOCH FROM NOW() - created_at)::FLOAT8 / 3600 / $2)) AS score
+                FROM interactions
+                WHERE ($1::UUID IS NULL OR deck_id = $1)
+                  AND created_at > NOW() - MAKE_INTERVAL(secs => $2 * $6 * 3600)
+                GROUP BY deck_id
+            )
+            UPDATE decks d
+            SET trending_score = COALESCE(s.score, 0)
+            FROM decks x
+            LEFT JOIN scores s ON s.deck_id = x.id
+            WHERE d.id = x.id
+              AND ($1::UUID IS NULL OR x.id = $1)
+              AND (s.score IS NOT NULL OR x.trending_score <> 0)
+            "#
+        )
+        .bind(deck_id)
+        .bind(TRENDING_HALF_LIFE_HOURS)
+        .bind(TRENDING_LIKE_WEIGHT)
+        .bind(TRENDING_BOOKMARK_WEIGHT)
+        .bind(TRENDING_FORK_WEIGHT)
+        .bind(TRENDING_MAX_AGE_HALF_LIVES)
+        .execute(&self.pool)
+        .await?;
+
+        Ok(())
+    }
+
+    async fn get_stale_cooccurrence_decks(&self, limit: i64) -> Result<Vec<Uuid>> {
+        let ids = sqlx::query_scalar::<_, Uuid>(
+            r#"
+            SELECT d.id
+            FROM decks d
+            LEFT JOIN card_cooccurrence_decks s ON s.deck_id = d.id
+            WHERE d.is_public AND (s.deck_id IS NULL OR s.deck_updated_at < d.updated_at)
+            UNION ALL
+            SELECT s.deck_id
+            FROM card_cooccurrence_decks s
+            LEFT JOIN decks d ON d.id = s.deck_id
+            WHERE d.id IS NULL OR NOT d.is_public
+            LIMIT $1
+            "#
+        )
+        .bind(limit)
+        .fetch_all(&self.pool)
+        .await?;
+
+        Ok(ids)
+    }
+
+    async fn get_card_names(&self, deck_id: Uuid) -> Result<Vec<String>> {
+        let names = sqlx::query_scalar::<_, String>(
+            "SELECT c.name FROM deck_cards d JOIN cards c ON c.id = d.card_id WHERE d.deck_id = $1"
+        )
+        .bind(deck_id)
+        .fetch_all(&self.pool)
+        .await?;
+
+        Ok(names)
+    }
+
+    async fn save_cooccurrence(&self, deck_id: Uuid, card_names: &[String], deck_updated_at: Option<DateTime<Utc>>) -> Result<()> {
+        let mut tx = self.pool.begin().await?;
+
+        // Los cambios de cada mazo se aplican de uno en uno aunque haya varios
+        // procesos refrescando: la diferencia se calcula contra lo ya guardado
+        sqlx::query("SELECT pg_advisory_xact_lock(hashtext('card_cooccurrence'))")
+            .execute(&mut *tx)
+            .await?;
+
+        let previous = sqlx::query_scalar::<_, Vec<String>>("SELECT card_names FROM card_cooccurrence_decks WHERE deck_id = $1")
+            .bind(deck_id)
+            .fetch_optional(&mut *tx)
+            .await?
+            .unwrap_or_default();
+
+        let card_names = if deck_updated_at.is_some() { card_names } else { &[] };
+        let delta = cooccurrence_delta(&previous, card_names);
+
+        if !delta.cards.is_empty() {
+            let (names, counts): (Vec<String>, Vec<i32>) = delta.cards.into_iter().unzip();
+            // Los CHECK se evalúan antes del ON CONFLICT: primero se actualizan
+            // las filas existentes y después se crean las que faltan
+            sqlx::query(
+                r#"
+                UPDATE card_deck_counts c
+                SET deck_count = c.deck_count + d.delta
+                FROM UNNEST($1::TEXT[], $2::INT[]) AS d(card_name, delta)
+                WHERE c.card_name = d.card_name
+                "#
+            )
+            .bind(&names)
+            .bind(&counts)
+            .execute(&mut *tx)
+            .await?;
+
+            sqlx::query(
+                r#"
+                INSERT INTO card_deck_counts (card_name, deck_count)
+                SELECT card_name, delta FROM UNNEST($1::TEXT[], $2::INT[]) AS d(card_name, delta)
+                WHERE delta > 0
+                ON CONFLICT (card_name) DO NOTHING
+                "#
+            )
+            .bind(&names)
+            .bind(&counts)
+            .execute(&mut *tx)
+            .await?;
+
+            sqlx::query("DELETE FROM card_deck_counts WHERE card_name = ANY($1) AND deck_count = 0")
+                .bind(&names)
+                .execute(&mut *tx)
+                .await?;
+        }
+
+        if !delta.pairs.is_empty() {
+            let mut names = Vec::with_capacity(delta.pairs.len());
+            let mut others = Vec::with_capacity(delta.pairs.len());
+            let mut counts = Vec::with_capacity(delta.pairs.len());
+            for (name, other, count) in delta.pairs {
+                names.push(name);
+                others.push(other);
+                counts.push(count);
+            }
+
+            sqlx::query(
+                r#"
+                UPDATE card_pair_counts p
+                SET deck_count = p.deck_count + d.delta
+                FROM UNNEST($1::TEXT[], $2::TEXT[], $3::INT[]) AS d(card_name, other_name, delta)
+                WHERE p.card_name = d.card_name AND p.other_name = d.other_name
+                "#
+            )
+            .bind(&names)
+            .bind(&others)
+            .bind(&counts)
+            .execute(&mut *tx)
+            .await?;
+
+            sqlx::query(
+                r#"
+                INSERT INTO card_pair_counts (card_name, other_name, deck_count)
+                SELECT card_name, other_name, delta
+                FROM UNNEST($1::TEXT[], $2::TEXT[], $3::INT[]) AS d(card_name, other_name, delta)
+                WHERE delta > 0
+                ON CONFLICT (card_name, other_name) DO NOTHING
+                "#
+            )
+            .bind(&names)
+            .bind(&others)
+            .bind(&counts)
+            .execute(&mut *tx)
+            .await?;
+
+            sqlx::query("DELETE FROM card_pair_counts WHERE card_name = ANY($1) AND deck_count = 0")
+                .bind(&names)
+                .execute(&mut *tx)
+                .await?;
+        }
+
+        match deck_updated_at {
+            Some(deck_updated_at) => {
+                sqlx::query(
+                    r#"
+                    INSERT INTO card_cooccurrence_decks (deck_id, card_names, deck_updated_at, indexed_at)
+                    VALUES ($1, $2, $3, NOW())
+                    ON CONFLICT (deck_id) DO UPDATE
+                    SET card_names = EXCLUDED.card_names, deck_updated_at = EXCLUDED.deck_updated_at, indexed_at = NOW()
+                    "#
+                )
+                .bind(deck_id)
+                .bind(card_names)
+                .bind(deck_updated_at)
+                .execute(&mut *tx)
+                .await?;
+            }
+            None => {
+                sqlx::query("DELETE FROM card_cooccurrence_decks WHERE deck_id = $1")
+                    .bind(deck_id)
+                    .execute(&mut *tx)
+                    .await?;
+            }
+        }
+
+        tx.commit().await?;
+        Ok(())
+    }
+
+    async fn clear_cooccurrence(&self) -> Result<()> {
+        let mut tx = self.pool.begin().await?;
+
+        sqlx::query("SELECT pg_advisory_xact_lock(hashtext('card_cooccurrence'))")
+            .execute(&mut *tx)
+            .await?;
+        sqlx::query("TRUNCATE card_cooccurrence_decks, card_deck_counts, card_pair_counts")
+            .execute(&mut *tx)
+            .await?;
+
+        tx.commit().await?;
+        Ok(())
+    }
+
+    async fn count_cooccurrence_decks(&self) -> Result<i64> {
+        let count = sqlx::query_scalar::<_, i64>(
+            "SELECT COUNT(*) FROM card_cooccurrence_decks WHERE cardinality(card_names) > 0"
+        )
+        .fetch_one(&self.pool)
+        .await?;
+
+        Ok(count)
+    }
+
+    async fn get_card_deck_count(&self, card_name: &str) -> Result<i64> {
+        let count = sqlx::query_scalar::<_, i64>(
+            "SELECT COALESCE(MAX(deck_count), 0)::BIGINT FROM card_deck_counts WHERE card_name = $1"
+        )
+        .bind(card_name)
+        .fetch_one(&self.pool)
+        .await?;
+
+        Ok(count)
+    }
+
+    // Cada pareja se guarda una sola vez, así que la carta puede estar en
+    // cualquiera de las dos columnas
+    async fn get_played_with(&self, card_name: &str, limit: i64) -> Result<Vec<CardPairCount>> {
+        let cards = sqlx::query_as::<_, CardPairCount>(
+            r#"
+            SELECT p.other_name, p.deck_count::BIGINT AS deck_count, o.deck_count::BIGINT AS other_deck_count
+            FROM (
+                SELECT other_name, deck_count FROM card_pair_counts WHERE card_name = $1
+                UNION ALL
+                SELECT card_name, deck_count FROM card_pair_counts WHERE other_name = $1
+            ) p
+            JOIN card_deck_counts o ON o.card_name = p.other_name
+            WHERE p.deck_count > 0
+            ORDER BY p.deck_count DESC, o.deck_count, p.other_name
+            LIMIT $2
+            "#
+        )
+        .bind(card_name)
+        .bind(limit)
+        .fetch_all(&self.pool)
+        .await?;
+
+        Ok(cards)
+    }
+
+    async fn get_card_suggestions(&self, card_names: &[String], limit: i64) -> Result<Vec<CardSuggestionCount>> {
+        let suggestions = sqlx::query_as::<_, CardSuggestionCount>(
+            r#"
+            SELECT
+                p.other_name AS card_name,
+                (SUM(p.deck_count::FLOAT8 / NULLIF(c.deck_count, 0)) / cardinality($1::TEXT[]))::FLOAT8 AS score,
+                COUNT(*) AS played_with,
+                MAX(o.deck_count)::BIGINT AS deck_count
+            FROM (
+                SELECT card_name, other_name, deck_count FROM card_pair_counts WHERE card_name = ANY($1)
+                UNION ALL
+                SELECT other_name, card_name, deck_count FROM card_pair_counts WHERE other_name = ANY($1)
+            ) p
+            JOIN card_deck_counts c ON c.card_name = p.card_name
+            JOIN card_deck_counts o ON o.card_name = p.other_name
+            WHERE NOT (p.other_name = ANY($1)) AND p.deck_count > 0
+            GROUP BY p.other_name
+            ORDER BY score DESC, deck_count DESC, p.other_name
+            LIMIT $2
+            "#
+        )
+        .bind(card_names)
+        .bind(limit)
+        .fetch_all(&self.pool)
+        .await?;
+
+        Ok(suggestions)
+    }
+
+    async fn get_formats(&self) -> Result<Vec<DeckFormat>> {
+        let formats = sqlx::query_as::<_, DeckFormat>(
+            r#"
+            SELECT code, name, description, rules, created_at, updated_at
+            FROM deck_formats
+            ORDER BY name
+            "#
+        )
+        .fetch_all(&self.pool)
+        .await?;
+
+        Ok(formats)
+    }
+
+    async fn get_format(&self, code: &str) -> Result<Option<DeckFormat>> {
+        let format = sqlx::query_as::<_, DeckFormat>(
+            r#"
+            SELECT code, name, description, rules, created_at, updated_at
+            FROM deck_formats
+            WHERE code = $1
+            "#
+        )
+        .bind(code)
+        .fetch_optional(&self.pool)
+        .await?;
+
+        Ok(format)
+    }
+
+    async fn save_format(&self, code: &str, name: &str, description: Option<&str>, rules: &FormatRules) -> Result<DeckFormat> {
+        let format = sqlx::query_as::<_, DeckFormat>(
+            r#"
+            INSERT INTO deck_formats (code, name, description, rules, created_at, updated_at)
+            VALUES ($1, $2, $3, $4, NOW(), NOW())
+            ON CONFLICT (code) DO UPDATE
+            SET name = EXCLUDED.name, description = EXCLUDED.description, rules = EXCLUDED.rules, updated_at = NOW()
+            RETURNING code, name, description, rules, created_at, updated_at
+            "#
+        )
+        .bind(code)
+        .bind(name)
+        .bind(description)
+        .bind(Json(rules))
+        .fetch_one(&self.pool)
+        .await?;
+
+        Ok(format)
+    }
+
+    async fn delete_format(&self, code: &str) -> Result<bool> {
+        let result = sqlx::query("DELETE FROM deck_formats WHERE code = $1")
+            .bind(code)
+            .execute(&self.pool)
+            .await?;
+
+        Ok(result.rows_affected() > 0)
+    }
+}
@@ -0,0 +1,262 @@
+use chrono::{DateTime, Utc};
+use serde::Serialize;
+use sqlx::postgres::PgRow;
+use sqlx::types::Json;
+use sqlx::Row;
+use std::collections::{HashMap, HashSet};
+use uuid::Uuid;
+
+use super::model::DeckCard;
+
+/// Immutable snapshot of a deck, stored every time it is saved. Revisions
+/// are numbered per deck starting at 1.
+#[derive(Debug, Clone, Serialize)]
+pub struct DeckRevision {
+    pub id: Uuid,
+    pub deck_id: Uuid,
+    pub revision: i32,
+    pub name: String,
+    pub description: Option<String>,
+    pub format_code: Option<String>,
+    pub card_count: i32,
+    /// Who saved it: the owner, or an admin
+    pub author_uid: String,
+    /// Set when this save restored an older revision
+    pub restored_from: Option<i32>,
+    pub created_at: DateTime<Utc>,
+}
+
+impl<'r> sqlx::FromRow<'r, PgRow> for DeckRevision {
+    fn from_row(row: &'r PgRow) -> Result<Self, sqlx::Error> {
+        Ok(Self {
+            id: row.try_get("id")?,
+            deck_id: row.try_get("deck_id")?,
+            revision: row.try_get("revision")?,
+            name: row.try_get("name")?,
+            description: row.try_get("description")?,
+            format_code: row.try_get("format_code")?,
+            card_count: row.try_get("card_count")?,
+            author_uid: row.try_get("author_uid")?,
+            restored_from: row.try_get("restored_from")?,
+            created_at: row.try_get("created_at")?,
+        })
+    }
+}
+
+/// Revision with its card list
+#[derive(Debug, Serialize)]
+pub struct DeckRevisionDetail {
+    #[serde(flatten)]
+    pub revision: DeckRevision,
+    pub cards: Vec<DeckCard>,
+}
+
+impl DeckRevisionDetail {
+    /// Revision 0, the empty deck the first revision is compared with. It
+    /// keeps `first`'s fields so only the cards show up as changes.
+    pub fn empty_before(first: &DeckRevisionDetail) -> Self {
+        Self {
+            revision: DeckRevision { revision: 0, card_count: 0, ..first.revision.clone() },
+            cards: Vec::new(),
+        }
+    }
+}
+
+impl<'r> sqlx::FromRow<'r, PgRow> for DeckRevisionDetail {
+    fn from_row(row: &'r PgRow) -> Result<Self, sqlx::Error> {
+        let Json(cards): Json<Vec<DeckCard>> = row.try_get("cards")?;
+
+        Ok(Self {
+            revision: DeckRevision::from_row(row)?,
+            cards,
+        })
+    }
+}
+
+/// Change in the copies of a card between two revisions. `quantity` is the
+/// number of copies added or removed.
+#[derive(Debug, Serialize)]
+pub struct DeckCardChange {
+    pub card_id: Uuid,
+    pub quantity: i32,
+    pub before: i32,
+    pub after: i32,
+}
+
+/// Differences between two revisions of a deck
+#[derive(Debug, Serialize)]
+pub struct DeckDiff {
+    pub from: i32,
+    pub to: i32,
+    /// Deck fields other than the cards that changed: `name`, `description`, `format_code`
+    pub changed_fields: Vec<&'static str>,
+    pub added: Vec<DeckCardChange>,
+    pub removed: Vec<DeckCardChange>,
+    pub card_count_change: i32,
+}
+
+/// Compares two revisions. Added cards keep the order of `to` and removed
+/// ones the order of `from`.
+pub fn diff_revisions(from: &DeckRevisionDetail, to: &DeckRevisionDetail) -> DeckDiff {
+    let quantities = |cards: &[DeckCard]| -> HashMap<Uuid, i32> {
+        let mut quantities = HashMap::new();
+        for card in cards {
+            *quantities.entry(card.card_id).or_insert(0) += card.quantity;
+        }
+        quantities
+    };
+    let before = quantities(&from.cards);
+    let after = quantities(&to.cards);
+
+    let mut changed_fields = Vec::new();
+    if from.revision.name != to.revision.name {
+        changed_fields.push("name");
+    }
+    if from.revision.description != to.revision.description {
+        changed_fields.push("description");
+    }
+    if from.revision.format_code != to.revision.format_code {
+        changed_fields.push("format_code");
+    }
+
+    let change = |card_id: Uuid| {
+        let before = before.get(&card_id).copied().unwrap_or(0);
+        let after = after.get(&card_id).copied().unwrap_or(0);
+        DeckCardChange { card_id, quantity: (after - before).abs(), before, after }
+    };
+
+    // Una carta repetida en la lista sólo aparece una vez en el resultado
+    let mut seen = HashSet::new();
+    let added: Vec<DeckCardChange> = to
+        .cards
+        .iter()
+        .filter(|card| seen.insert(card.card_id))
+        .map(|card| change(card.card_id))
+        .filter(|change| change.after > change.before)
+        .collect();
+
+    seen.clear();
+    let removed: Vec<DeckCardChange> = from
+        .cards
+        .iter()
+        .filter(|card| seen.insert(card.card_id))
+        .map(|card| change(card.card_id))
+        .filter(|change| change.before > change.after)
+        .collect();
+
+    DeckDiff {
+        from: from.revision.revision,
+        to: to.revision.revision,
+        changed_fields,
+        added,
+        removed,
+        card_count_change: after.values().sum::<i32>() - before.values().sum::<i32>(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn revision(number: i32, name: &str, cards: &[(Uuid, i32)]) -> DeckRevisionDetail {
+        DeckRevisionDetail {
+            revision: DeckRevision {
+                id: Uuid::new_v4(),
+                deck_id: Uuid::nil(),
+                revision: number,
+                name: name.to_string(),
+                description: None,
+                format_code: Some("standard".to_string()),
+                card_count: cards.iter().map(|(_, quantity)| quantity).sum(),
+                author_uid: "owner".to_string(),
+                restored_from: None,
+                created_at: Utc::now(),
+            },
+            cards: cards.iter().map(|&(card_id, quantity)| DeckCard { card_id, quantity }).collect(),
+        }
+    }
+
+    fn changes(changes: &[DeckCardChange]) -> Vec<(Uuid, i32, i32, i32)> {
+        changes.iter().map(|change| (change.card_id, change.quantity, change.before, change.after)).collect()
+    }
+
+    #[test]
+    fn reports_added_and_removed_copies() {
+        let (a, b, c) = (Uuid::new_v4(), Uuid::new_v4(), Uuid::new_v4());
+        let from = revision(1, "Fuego", &[(a, 3), (b, 2)]);
+        let to = revision(2, "Fuego", &[(a, 1), (c, 4)]);
+
+        let diff = diff_revisions(&from, &to);
+
+        assert_eq!((diff.from, diff.to), (1, 2));
+        assert_eq!(changes(&diff.added), vec![(c, 4, 0, 4)]);
+        assert_eq!(changes(&diff.removed), vec![(a, 2, 3, 1), (b, 2, 2, 0)]);
+        assert_eq!(diff.card_count_change, 0);
+        assert!(diff.changed_fields.is_empty());
+    }
+
+    #[test]
+    fn added_follow_the_new_order_and_removed_the_old_one() {
+        let ids: Vec<Uuid> = (0..4).map(|_| Uuid::new_v4()).collect();
+        let from = revision(1, "Fuego", &[(ids[3], 1), (ids[1], 1), (ids[2], 1)]);
+        let to = revision(2, "Fuego", &[(ids[2], 2), (ids[0], 1), (ids[3], 2)]);
+
+        let diff = diff_revisions(&from, &to);
+
+        let added: Vec<Uuid> = diff.added.iter().map(|change| change.card_id).collect();
+        let removed: Vec<Uuid> = diff.removed.iter().map(|change| change.card_id).collect();
+        assert_eq!(added, vec![ids[2], ids[0], ids[3]]);
+        assert_eq!(removed, vec![ids[1]]);
+        assert_eq!(diff.card_count_change, 2);
+    }
+
+    #[test]
+    fn repeated_cards_are_summed_and_listed_once() {
+        let (a, b) = (Uuid::new_v4(), Uuid::new_v4());
+        let from = revision(1, "Fuego", &[(a, 1), (b, 2), (a, 1)]);
+        let to = revision(2, "Fuego", &[(a, 2), (a, 1), (b, 1), (b, 1)]);
+
+        let diff = diff_revisions(&from, &to);
+
+        // a pasa de 2 a 3 copias; b sigue con 2 aunque esté en dos filas
+        assert_eq!(changes(&diff.added), vec![(a, 1, 2, 3)]);
+        assert!(diff.removed.is_empty());
+        assert_eq!(diff.card_count_change, 1);
+    }
+
+    #[test]
+    fn lists_the_changed_fields() {
+        let a = Uuid::new_v4();
+        let from = revision(1, "Fuego", &[(a, 1)]);
+        let mut to = revision(2, "Fuego y agua", &[(a, 1)]);
+        to.revision.format_code = None;
+
+        let diff = diff_revisions(&from, &to);
+        assert_eq!(diff.changed_fields, vec!["name", "format_code"]);
+        assert!(diff.added.is_empty() && diff.removed.is_empty());
+
+        to.revision.name = "Fuego".to_string();
+        to.revision.format_code = from.revision.format_code.clone();
+        to.revision.description = Some("Agresivo".to_string());
+        assert_eq!(diff_revisions(&from, &to).changed_fields, vec!["description"]);
+        assert!(diff_revisions(&from, &from).changed_fields.is_empty());
+    }
+
+    #[test]
+    fn first_revision_is_compared_with_an_empty_deck() {
+        let (a, b) = (Uuid::new_v4(), Uuid::new_v4());
+        let first = revision(1, "Fuego", &[(a, 3), (b, 1)]);
+
+        let empty = DeckRevisionDetail::empty_before(&first);
+        assert_eq!(empty.revision.revision, 0);
+        assert_eq!(empty.revision.card_count, 0);
+        assert!(empty.cards.is_empty());
+
+        let diff = diff_revisions(&empty, &first);
+        assert_eq!((diff.from, diff.to), (0, 1));
+        assert_eq!(changes(&diff.added), vec![(a, 3, 0, 3), (b, 1, 0, 1)]);
+        assert!(diff.removed.is_empty());
+        assert!(diff.changed_fields.is_empty());
+        assert_eq!(diff.card_count_change, 4);
+    }
+}
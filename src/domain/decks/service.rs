@@ -0,0 +1,441 @@
+use anyhow::Result;
+use chrono::Utc;
+use uuid::Uuid;
+
+use super::analysis::{analyze_deck, AnalysisEntry, DeckAnalysis};
+use super::buildability::{check_buildability, BuildabilityReport};
+use super::cooccurrence::{
+    cooccurrence_names, CardSuggestion, CardSuggestions, CooccurrenceRefresh, PlayedWith, PlayedWithCard,
+};
+use super::deck_code::{
+    decode_deck_code, encode_deck_code, resolve_deck_code, DeckCodeCard, DeckCodeContents, DecodedDeck, EncodedDeck,
+    DECK_CODE_VERSION,
+};
+use super::decklist::{export_decklist, parse_decklist, resolve_decklist, DecklistEntry, DecklistFormat, DecklistImport};
+use super::dto::{DeckAnalysisDto, DeckDto, DeckFormatDto, ForkDeckDto, SampleHandDto};
+use super::gallery::{DeckFork, DeckReactions, GalleryDeck, GalleryFilter};
+use super::legality::{check_legality, LegalityEntry, LegalityReport};
+use super::model::{Deck, DeckCard, DeckDetail, DeckFormat, DeckSort};
+use super::repository::DeckRepository;
+use super::revision::{diff_revisions, DeckDiff, DeckRevision, DeckRevisionDetail};
+use super::simulation::{deal_sample_hand, SampleHand};
+use crate::domain::cards::{Card, CardCatalog, CardPrice};
+use crate::domain::collections::CollectionEntry;
+
+pub struct DeckService<R: DeckRepository> {
+    repository: R,
+}
+
+impl<R: DeckRepository> DeckService<R> {
+    pub fn new(repository: R) -> Self {
+        Self { repository }
+    }
+
+    pub async fn get_decks_by_owner(
+        &self,
+        owner_uid: &str,
+        include_private: bool,
+        sort: DeckSort,
+        descending: bool,
+        limit: i64,
+        offset: i64,
+    ) -> Result<Vec<Deck>> {
+        self.repository
+            .get_decks_by_owner(owner_uid, include_private, sort, descending, limit, offset)
+            .await
+    }
+
+    pub async fn get_deck_by_id(&self, id: Uuid) -> Result<Option<Deck>> {
+        self.repository.get_deck_by_id(id).await
+    }
+
+    pub async fn get_deck_detail(&self, deck: Deck) -> Result<DeckDetail> {
+        let cards = self.repository.get_cards(deck.id).await?;
+        let forked_from = self.repository.get_fork(deck.id).await?;
+        Ok(DeckDetail::new(deck, cards, forked_from))
+    }
+
+    /// Creates a deck; `author_uid` is who saves it, the owner or an admin
+    pub async fn create_deck(&self, owner_uid: String, dto: &DeckDto, author_uid: &str) -> Result<DeckDetail> {
+        let deck = Deck::new(owner_uid, dto.name(), dto.description(), dto.format_code(), dto.is_public);
+        let deck = self.repository.create_deck(deck, &dto.cards, author_uid, None).await?;
+        self.get_deck_detail(deck).await
+    }
+
+    /// Saves a deck, replacing its whole card list
+    pub async fn update_deck(&self, deck: &Deck, dto: &DeckDto, author_uid: &str) -> Result<Option<DeckDetail>> {
+        let updated = Deck {
+            name: dto.name(),
+            description: dto.description(),
+            format_code: dto.format_code(),
+            is_public: dto.is_public,
+            ..deck.clone()
+        };
+
+        self.save(&updated, &dto.cards, author_uid, None).await
+    }
+
+    /// Replaces only the card list, keeping the rest of the deck
+    pub async fn replace_cards(&self, deck: &Deck, cards: &[DeckCard], author_uid: &str) -> Result<Option<DeckDetail>> {
+        self.save(deck, cards, author_uid, None).await
+    }
+
+    /// Saves an old revision's name, description, format and cards as a new
+    /// revision. Visibility is not part of revisions and stays as it is.
+    pub async fn restore_revision(
+        &self,
+        deck: &Deck,
+        revision: &DeckRevisionDetail,
+        author_uid: &str,
+    ) -> Result<Option<DeckDetail>> {
+        let restored = Deck {
+            name: revision.revision.name.clone(),
+            description: revision.revision.description.clone(),
+            format_code: revision.revision.format_code.clone(),
+            ..deck.clone()
+        };
+
+        self.save(&restored, &revision.cards, author_uid, Some(revision.revision.revision)).await
+    }
+
+    async fn save(
+        &self,
+        deck: &Deck,
+        cards: &[DeckCard],
+        author_uid: &str,
+        restored_from: Option<i32>,
+    ) -> Result<Option<DeckDetail>> {
+        match self.repository.update_deck(deck, cards, author_uid, restored_from).await? {
+            Some(deck) => Ok(Some(self.get_deck_detail(deck).await?)),
+            None => Ok(None),
+        }
+    }
+
+    pub async fn get_cards(&self, deck_id: Uuid) -> Result<Vec<DeckCard>> {
+        self.repository.get_cards(deck_id).await
+    }
+
+    /// Copies a deck with `cards`, its current list, into `owner_uid`'s
+    /// account, recording the original and the revision copied. The copy
+    /// starts its own history at revision 1.
+    pub async fn fork_deck(&self, source: &Deck, cards: &[DeckCard], owner_uid: String, dto: &ForkDeckDto) -> Result<DeckDetail> {
+        let revision = self.repository.get_latest_revision(source.id).await?;
+
+        let fork = DeckFork {
+            source_deck_id: Some(source.id),
+            source_owner_uid: source.owner_uid.clone(),
+            source_name: source.name.clone(),
+            source_revision: revision.map(|revision| revision.revision.revision),
+            created_at: Utc::now(),
+        };
+
+        let name = dto.name().unwrap_or_else(|| source.name.clone());
+        let deck = Deck::new(owner_uid, name, source.description.clone(), source.format_code.clone(), dto.is_public);
+        let author_uid = deck.owner_uid.clone();
+        let deck = self.repository.create_deck(deck, cards, &author_uid, Some(&fork)).await?;
+        self.repository.refresh_trending_scores(Some(source.id)).await?;
+        self.get_deck_detail(deck).await
+    }
+
+    pub async fn get_gallery(&self, filter: &GalleryFilter, viewer_uid: &str) -> Result<Vec<GalleryDeck>> {
+        self.repository.get_gallery(filter, viewer_uid, false).await
+    }
+
+    /// Decks the user bookmarked that they can still read
+    pub async fn get_bookmarks(&self, filter: &GalleryFilter, user_uid: &str) -> Result<Vec<GalleryDeck>> {
+        self.repository.get_gallery(filter, user_uid, true).await
+    }
+
+    pub async fn get_reactions(&self, deck_id: Uuid, viewer_uid: &str) -> Result<DeckReactions> {
+        self.repository.get_reactions(deck_id, viewer_uid).await
+    }
+
+    pub async fn set_liked(&self, deck_id: Uuid, user_uid: &str, liked: bool) -> Result<DeckReactions> {
+        self.repository.set_liked(deck_id, user_uid, liked).await?;
+        self.repository.refresh_trending_scores(Some(deck_id)).await?;
+        self.repository.get_reactions(deck_id, user_uid).await
+    }
+
+    pub async fn set_bookmarked(&self, deck_id: Uuid, user_uid: &str, bookmarked: bool) -> Result<DeckReactions> {
+        self.repository.set_bookmarked(deck_id, user_uid, bookmarked).await?;
+        self.repository.refresh_trending_scores(Some(deck_id)).await?;
+        self.repository.get_reactions(deck_id, user_uid).await
+    }
+
+    /// Lets the trending scores of the gallery decay with time
+    pub async fn refresh_trending_scores(&self) -> Result<()> {
+        self.repository.refresh_trending_scores(None).await
+    }
+
+    pub async fn delete_deck(&self, id: Uuid) -> Result<bool> {
+        self.repository.delete_deck(id).await
+    }
+
+    pub async fn get_revisions(&self, deck_id: Uuid) -> Result<Vec<DeckRevision>> {
+        self.repository.get_revisions(deck_id).await
+    }
+
+    pub async fn get_revision(&self, deck_id: Uuid, revision: i32) -> Result<Option<DeckRevisionDetail>> {
+        self.repository.get_revision(deck_id, revision).await
+    }
+
+    pub async fn get_latest_revision(&self, deck_id: Uuid) -> Result<Option<DeckRevisionDetail>> {
+        self.repository.get_latest_revision(deck_id).await
+    }
+
+    /// Compares two revisions. `to` defaults to the latest one and `from` to
+    /// the revision before `to`. `None` when a requested revision is missing.
+    pub async fn diff_revisions(&self, deck_id: Uuid, from: Option<i32>, to: Option<i32>) -> Result<Option<DeckDiff>> {
+        let to = match to {
+            Some(revision) => self.repository.get_revision(deck_id, revision).await?,
+            None => self.repository.get_latest_revision(deck_id).await?,
+        };
+        let Some(to) = to else {
+            return Ok(None);
+        };
+
+        let from = from.unwrap_or(to.revision.revision - 1);
+        let from = match self.repository.get_revision(deck_id, from).await? {
+            Some(from) => from,
+            // La primera revisión se compara con un mazo vacío
+            None if from == 0 => DeckRevisionDetail::empty_before(&to),
+            None => return Ok(None),
+        };
+
+        Ok(Some(diff_revisions(&from, &to)))
+    }
+
+    pub async fn get_formats(&self) -> Result<Vec<DeckFormat>> {
+        self.repository.get_formats().await
+    }
+
+    pub async fn get_format(&self, code: &str) -> Result<Option<DeckFormat>> {
+        self.repository.get_format(code).await
+    }
+
+    pub async fn save_format(&self, code: &str, dto: &DeckFormatDto) -> Result<DeckFormat> {
+        self.repository
+            .save_format(code, &dto.name(), dto.description().as_deref(), &dto.rules)
+            .await
+    }
+
+    pub async fn delete_format(&self, code: &str) -> Result<bool> {
+        self.repository.delete_format(code).await
+    }
+
+    /// Checks a card list against a format. Cards missing from the catalog
+    /// (deleted or not visible to the caller) are reported as unknown.
+    pub fn check_legality(&self, cards: &[DeckCard], format: &DeckFormat, catalog: &CardCatalog) -> LegalityReport {
+        let mut entries = Vec::with_capacity(cards.len());
+        let mut unknown = Vec::new();
+
+        for deck_card in cards {
+            match catalog.by_id(deck_card.card_id) {
+                Some(card) => entries.push(LegalityEntry {
+                    card,
+                    set_code: catalog.set_code(card).unwrap_or_default(),
+                    quantity: deck_card.quantity,
+                }),
+                None => unknown.push(deck_card.card_id),
+            }
+        }
+
+        check_legality(&format.code, &format.rules, &entries, &unknown)
+    }
+
+    /// Reads a text decklist and resolves it against the catalog
+    pub fn import_decklist(&self, text: &str, catalog: &CardCatalog) -> DecklistImport {
+        let (lines, invalid) = parse_decklist(text);
+        resolve_decklist(lines, invalid, catalog)
+    }
+
+    /// Writes a deck as a text or markdown decklist. Cards the catalog does
+    /// not include (not visible to the caller) are left out.
+    pub fn export_decklist(&self, detail: &DeckDetail, catalog: &CardCatalog, format: DecklistFormat) -> String {
+        let entries: Vec<DecklistEntry> = detail
+            .cards
+            .iter()
+            .filter_map(|deck_card| {
+                catalog.by_id(deck_card.card_id).map(|card| DecklistEntry {
+                    card,
+                    set_code: catalog.set_code(card).unwrap_or_default(),
+                    quantity: deck_card.quantity,
+                })
+            })
+            .collect();
+
+        export_decklist(&detail.deck.name, &entries, format)
+    }
+
+    /// Encodes a card list as a shareable deck code. Cards the catalog does
+    /// not include (not visible to the caller) are left out.
+    pub fn encode_deck_code(
+        &self,
+        cards: &[DeckCard],
+        format_code: Option<String>,
+        catalog: &CardCatalog,
+    ) -> Result<EncodedDeck> {
+        let mut card_count = 0;
+        let mut entries = Vec::with_capacity(cards.len());
+        for deck_card in cards {
+            let Some(card) = catalog.by_id(deck_card.card_id) else {
+                continue;
+            };
+            let (Some(set_code), Ok(collector_number), Ok(quantity)) = (
+                catalog.set_code(card),
+                u32::try_from(card.collector_number),
+                u32::try_from(deck_card.quantity),
+            ) else {
+                continue;
+            };
+
+            card_count += deck_card.quantity;
+            entries.push(DeckCodeCard { set_code: set_code.to_string(), collector_number, quantity });
+        }
+
+        let contents = DeckCodeContents { version: DECK_CODE_VERSION, format_code, cards: entries };
+        Ok(EncodedDeck { code: encode_deck_code(&contents)?, version: DECK_CODE_VERSION, card_count })
+    }
+
+    /// Decodes a deck code and resolves its cards against the catalog
+    pub fn decode_deck_code(&self, code: &str, catalog: &CardCatalog) -> Result<DecodedDeck> {
+        Ok(resolve_deck_code(decode_deck_code(code)?, catalog))
+    }
+
+    /// Statistics and draw odds of a card list. Cards missing from the
+    /// catalog still count towards the deck size.
+    pub fn analyze(&self, cards: &[DeckCard], catalog: &CardCatalog, dto: DeckAnalysisDto) -> DeckAnalysis {
+        let mut entries = Vec::with_capacity(cards.len());
+        let mut unknown = Vec::new();
+
+        for deck_card in cards {
+            match catalog.by_id(deck_card.card_id) {
+                Some(card) => entries.push(AnalysisEntry { card, quantity: deck_card.quantity }),
+                None => unknown.push((deck_card.card_id, deck_card.quantity)),
+            }
+        }
+
+        analyze_deck(&entries, &unknown, dto.rules, &dto.targets)
+    }
+
+    /// Compares a card list with a user's collection. `prices` should cover
+    /// every printing of the deck's cards (see `printing_ids`).
+    pub fn check_buildability(
+        &self,
+        cards: &[DeckCard],
+        collection: &[CollectionEntry],
+        catalog: &CardCatalog,
+        prices: &[CardPrice],
+    ) -> BuildabilityReport {
+        check_buildability(cards, collection, catalog, prices)
+    }
+
+    /// Deals a sample hand from a revision of a deck. Fails when the cards to
+    /// put on the bottom are not in the kept hand.
+    pub fn sample_hand(
+        &self,
+        revision: &DeckRevisionDetail,
+        catalog: &CardCatalog,
+        dto: &SampleHandDto,
+        seed: u64,
+    ) -> Result<SampleHand> {
+        deal_sample_hand(revision, catalog, dto, seed)
+    }
+
+    /// Brings the co-occurrence statistics up to date, reading the decks that
+    /// changed in batches of `batch_size` until none is left
+    pub async fn refresh_cooccurrence(&self, batch_size: i64) -> Result<CooccurrenceRefresh> {
+        let mut refresh = CooccurrenceRefresh::default();
+
+        loop {
+            let stale = self.repository.get_stale_cooccurrence_decks(batch_size).await?;
+            for &deck_id in &stale {
+                match self.repository.get_deck_by_id(deck_id).await? {
+                    Some(deck) if deck.is_public => {
+                        let names = self.repository.get_card_names(deck_id).await?;
+                        let names = cooccurrence_names(names.iter().map(String::as_str));
+                        self.repository.save_cooccurrence(deck_id, &names, Some(deck.updated_at)).await?;
+                        refresh.indexed += 1;
+                    }
+                    _ => {
+                        self.repository.save_cooccurrence(deck_id, &[], None).await?;
+                        refresh.removed += 1;
+                    }
+                }
+            }
+
+            if (stale.len() as i64) < batch_size {
+                return Ok(refresh);
+            }
+        }
+    }
+
+    /// Empties the statistics and indexes every public deck again, e.g.
+    /// after cards were renamed
+    pub async fn rebuild_cooccurrence(&self, batch_size: i64) -> Result<CooccurrenceRefresh> {
+        self.repository.clear_cooccurrence().await?;
+        self.refresh_cooccurrence(batch_size).await
+    }
+
+    /// Cards most often played with `card` in public decks. Cards the
+    /// catalog does not include are left out.
+    pub async fn played_with(&self, card: &Card, catalog: &CardCatalog, limit: i64) -> Result<PlayedWith> {
+        let name = cooccurrence_names([card.name.as_str()]).pop().unwrap_or_default();
+        let total_decks = self.repository.count_cooccurrence_decks().await?;
+        let deck_count = self.repository.get_card_deck_count(&name).await?;
+
+        let cards = self
+            .repository
+            .get_played_with(&name, limit)
+            .await?
+            .into_iter()
+            .filter_map(|pair| {
+                let other = catalog.named(&pair.other_name).into_iter().next()?;
+                Some(PlayedWithCard {
+                    card: catalog.reference(other),
+                    decks: pair.deck_count,
+                    rate: ratio(pair.deck_count, deck_count),
+                    lift: ratio(pair.deck_count * total_decks, deck_count * pair.other_deck_count),
+                })
+            })
+            .collect();
+
+        Ok(PlayedWith { card: catalog.reference(card), deck_count, total_decks, cards })
+    }
+
+    /// Cards not in the list that public decks play most with its cards
+    pub async fn suggest_cards(&self, cards: &[DeckCard], catalog: &CardCatalog, limit: i64) -> Result<CardSuggestions> {
+        let names = cooccurrence_names(
+            cards
+                .iter()
+                .filter_map(|deck_card| catalog.by_id(deck_card.card_id))
+                .map(|card| card.name.as_str()),
+        );
+        let total_decks = self.repository.count_cooccurrence_decks().await?;
+        if names.is_empty() {
+            return Ok(CardSuggestions { total_decks, cards: Vec::new() });
+        }
+
+        let cards = self
+            .repository
+            .get_card_suggestions(&names, limit)
+            .await?
+            .into_iter()
+            .filter_map(|suggestion| {
+                let card = catalog.named(&suggestion.card_name).into_iter().next()?;
+                Some(CardSuggestion {
+                    card: catalog.reference(card),
+                    score: suggestion.score,
+                    played_with: suggestion.played_with,
+                    deck_count: suggestion.deck_count,
+                })
+            })
+            .collect();
+
+        Ok(CardSuggestions { total_decks, cards })
+    }
+}
+
+fn ratio(numerator: i64, denominator: i64) -> f64 {
+    if denominator > 0 { numerator as f64 / denominator as f64 } else { 0.0 }
+}
@@ -0,0 +1,335 @@
+use anyhow::{bail, Result};
+use rand::seq::SliceRandom;
+use rand::SeedableRng;
+use rand_chacha::ChaCha8Rng;
+use serde::Serialize;
+use std::iter;
+use uuid::Uuid;
+
+use super::analysis::MulliganRule;
+use super::dto::SampleHandDto;
+use super::revision::DeckRevisionDetail;
+use crate::domain::cards::CardCatalog;
+
+/// A card dealt in a sample hand
+#[derive(Debug, Clone, Serialize)]
+pub struct DrawnCard {
+    pub card_id: Uuid,
+    /// `null` for cards the catalog does not include (not visible to the caller)
+    pub name: Option<String>,
+    pub set_code: Option<String>,
+    pub collector_number: Option<i32>,
+}
+
+/// One of the opening hands drawn: the first one, or the one after a mulligan
+#[derive(Debug, Serialize)]
+pub struct OpeningHand {
+    /// Mulligans taken before drawing it
+    pub mulligan: u32,
+    pub cards: Vec<DrawnCard>,
+    /// London mulligan: cards of the kept hand put on the bottom of the library
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub bottomed: Vec<DrawnCard>,
+}
+
+#[derive(Debug, Serialize)]
+pub struct TurnDraw {
+    pub turn: u32,
+    /// Empty on the first turn of the player on the play, or once the library runs out
+    pub cards: Vec<DrawnCard>,
+}
+
+/// A shuffled deck dealt server-side. Sending `seed` back with the same
+/// revision and rules replays the exact same sequence.
+#[derive(Debug, Serialize)]
+pub struct SampleHand {
+    /// Decimal string: JavaScript numbers cannot hold every 64-bit seed
+    pub seed: String,
+    /// Deck revision the cards were dealt from
+    pub revision: i32,
+    pub card_count: usize,
+    pub mulligan: MulliganRule,
+    pub mulligans: u32,
+    /// Every opening hand drawn, in order; the last one is kept
+    pub hands: Vec<OpeningHand>,
+    /// Kept hand, without the cards put on the bottom
+    pub hand: Vec<DrawnCard>,
+    pub draws: Vec<TurnDraw>,
+    /// Cards left in the library after the last draw
+    pub library_count: usize,
+}
+
+/// Shuffles a revision of a deck with a ChaCha8 generator seeded with `seed`,
+/// deals the opening hands and draws the requested turns. Each mulligan
+/// shuffles the whole deck again with the same generator, so the sequence
+/// depends only on the seed, the card list and the rules.
+pub fn deal_sample_hand(
+    revision: &DeckRevisionDetail,
+    catalog: &CardCatalog,
+    dto: &SampleHandDto,
+    seed: u64,
+) -> Result<SampleHand> {
+    // La lista se expande en el orden guardado: barajar parte siempre del mismo mazo
+    let deck: Vec<Uuid> = revision
+        .cards
+        .iter()
+        .flat_map(|card| iter::repeat_n(card.card_id, card.quantity.max(0) as usize))
+        .collect();
+
+    let mut rng = ChaCha8Rng::seed_from_u64(seed);
+    let mut hands: Vec<Vec<Uuid>> = Vec::new();
+    let mut library: Vec<Uuid> = Vec::new();
+
+    for mulligan in 0..=dto.mulligans {
+        let size = match dto.mulligan {
+            MulliganRule::Reduced => dto.hand_size.saturating_sub(mulligan),
+            _ => dto.hand_size,
+        };
+
+        library = deck.clone();
+        library.shuffle(&mut rng);
+        hands.push(library.drain(..(size as usize).min(library.len())).collect());
+    }
+
+    let mut hand = hands.last().cloned().unwrap_or_default();
+    let mut bottomed = Vec::new();
+    if dto.mulligan == MulliganRule::London && dto.mulligans > 0 {
+        if dto.bottom.is_empty() {
+            // Sin elección explícita van al fondo las últimas cartas robadas
+            bottomed = hand.split_off(hand.len().saturating_sub(dto.mulligans as usize));
+        } else {
+            for card_id in &dto.bottom {
+                let Some(position) = hand.iter().position(|id| id == card_id) else {
+                    bail!("La carta {} no está en la mano que se queda y no se puede poner al fondo", card_id);
+                };
+                bottomed.push(hand.remove(position));
+            }
+        }
+        library.extend(bottomed.iter().copied());
+    }
+
+    let drawn = |card_id: Uuid| {
+        let card = catalog.by_id(card_id).map(|card| catalog.reference(card));
+        DrawnCard {
+            card_id,
+            name: card.as_ref().map(|card| card.name.clone()),
+            set_code: card.as_ref().map(|card| card.set_code.clone()),
+            collector_number: card.as_ref().map(|card| card.collector_number),
+        }
+    };
+
+    let mut remaining = library.into_iter();
+    let draws = (1..=dto.turns)
+        .map(|turn| {
+            let count = if turn == 1 && dto.on_the_play { 0 } else { dto.draws_per_turn };
+            TurnDraw { turn, cards: remaining.by_ref().take(count as usize).map(drawn).collect() }
+        })
+        .collect();
+
+    let kept = hands.len() - 1;
+    Ok(SampleHand {
+        seed: seed.to_string(),
+        revision: revision.revision.revision,
+        card_count: deck.len(),
+        mulligan: dto.mulligan,
+        mulligans: dto.mulligans,
+        hands: hands
+            .into_iter()
+            .enumerate()
+            .map(|(mulligan, cards)| OpeningHand {
+                mulligan: mulligan as u32,
+                cards: cards.into_iter().map(drawn).collect(),
+                bottomed: if mulligan == kept { bottomed.iter().copied().map(drawn).collect() } else { Vec::new() },
+            })
+            .collect(),
+        hand: hand.into_iter().map(drawn).collect(),
+        draws,
+        library_count: remaining.len(),
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::domain::cards::Card;
+    use crate::domain::decks::{DeckCard, DeckRevision};
+    use chrono::Utc;
+    use std::collections::HashMap;
+
+    const SEED: u64 = 20240719;
+
+    // Mazo de 40 cartas: 20 cartas distintas con 2 copias cada una. La última
+    // no está en el catálogo, como una carta que quien baraja aún no puede ver.
+    fn fixture() -> (DeckRevisionDetail, CardCatalog) {
+        let set_id = Uuid::new_v4();
+        let cards: Vec<Card> = (1..=20)
+            .map(|number| Card {
+                id: Uuid::new_v4(),
+                set_id,
+                collector_number: number,
+                name: format!("Carta {}", number),
+                card_type: "Personaje".to_string(),
+                card_energy: None,
+                rarity: "Común".to_string(),
+                cost: None,
+                artists: Vec::new(),
+                image_url: None,
+                image_details: None,
+                revealed_at: None,
+                created_at: Utc::now(),
+                updated_at: Utc::now(),
+            })
+            .collect();
+
+        let revision = DeckRevisionDetail {
+            revision: DeckRevision {
+                id: Uuid::new_v4(),
+                deck_id: Uuid::new_v4(),
+                revision: 3,
+                name: "Fuego".to_string(),
+                description: None,
+                format_code: None,
+                card_count: 40,
+                author_uid: "owner".to_string(),
+                restored_from: None,
+                created_at: Utc::now(),
+            },
+            cards: cards.iter().map(|card| DeckCard { card_id: card.id, quantity: 2 }).collect(),
+        };
+
+        let visible = cards.into_iter().take(19).collect();
+        let catalog = CardCatalog::new(visible, HashMap::from([(set_id, "LGRO".to_string())]));
+        (revision, catalog)
+    }
+
+    fn ids(cards: &[DrawnCard]) -> Vec<Uuid> {
+        cards.iter().map(|card| card.card_id).collect()
+    }
+
+    fn deal(dto: &SampleHandDto, seed: u64) -> SampleHand {
+        let (revision, catalog) = fixture();
+        deal_sample_hand(&revision, &catalog, dto, seed).unwrap()
+    }
+
+    #[test]
+    fn same_seed_and_options_replay_the_same_hands_and_draws() {
+        let (revision, catalog) = fixture();
+        let dto = SampleHandDto { mulligans: 2, turns: 5, ..SampleHandDto::default() };
+
+        let first = deal_sample_hand(&revision, &catalog, &dto, SEED).unwrap();
+        let second = deal_sample_hand(&revision, &catalog, &dto, SEED).unwrap();
+
+        assert_eq!(first.seed, SEED.to_string());
+        assert_eq!(first.revision, 3);
+        assert_eq!(first.card_count, 40);
+        for (a, b) in first.hands.iter().zip(&second.hands) {
+            assert_eq!(ids(&a.cards), ids(&b.cards));
+            assert_eq!(ids(&a.bottomed), ids(&b.bottomed));
+        }
+        assert_eq!(ids(&first.hand), ids(&second.hand));
+        let draws = |hand: &SampleHand| hand.draws.iter().map(|turn| ids(&turn.cards)).collect::<Vec<_>>();
+        assert_eq!(draws(&first), draws(&second));
+
+        let other = deal_sample_hand(&revision, &catalog, &dto, SEED + 1).unwrap();
+        assert_ne!(ids(&first.hands[0].cards), ids(&other.hands[0].cards));
+    }
+
+    #[test]
+    fn every_card_is_dealt_from_the_deck_exactly_once() {
+        let (revision, catalog) = fixture();
+        let dto = SampleHandDto { mulligans: 1, turns: 50, on_the_play: false, ..SampleHandDto::default() };
+        let hand = deal_sample_hand(&revision, &catalog, &dto, SEED).unwrap();
+
+        let mut dealt: Vec<Uuid> = ids(&hand.hand);
+        dealt.extend(hand.draws.iter().flat_map(|turn| ids(&turn.cards)));
+        assert_eq!(dealt.len(), 40);
+        assert_eq!(hand.library_count, 0);
+
+        let mut counts: HashMap<Uuid, i32> = HashMap::new();
+        for id in dealt {
+            *counts.entry(id).or_default() += 1;
+        }
+        assert_eq!(counts.len(), revision.cards.len());
+        assert!(counts.values().all(|&count| count == 2));
+    }
+
+    #[test]
+    fn london_mulligan_puts_cards_of_the_kept_hand_on_the_bottom() {
+        let dto = SampleHandDto { mulligans: 2, turns: 40, on_the_play: false, ..SampleHandDto::default() };
+        let hand = deal(&dto, SEED);
+
+        assert_eq!(hand.hands.len(), 3);
+        assert!(hand.hands.iter().all(|opening| opening.cards.len() == 7));
+        assert!(hand.hands[..2].iter().all(|opening| opening.bottomed.is_empty()));
+
+        // Sin elección, van al fondo las dos últimas cartas de la mano que se queda
+        let kept = &hand.hands[2];
+        assert_eq!(ids(&kept.bottomed), ids(&kept.cards)[5..].to_vec());
+        assert_eq!(ids(&hand.hand), ids(&kept.cards)[..5].to_vec());
+
+        // y son las últimas que se roban
+        let drawn: Vec<Uuid> = hand.draws.iter().flat_map(|turn| ids(&turn.cards)).collect();
+        assert_eq!(drawn.len(), 35);
+        assert_eq!(drawn[33..].to_vec(), ids(&kept.bottomed));
+    }
+
+    #[test]
+    fn london_mulligan_bottoms_the_chosen_cards() {
+        let (revision, catalog) = fixture();
+        let default = SampleHandDto { mulligans: 1, ..SampleHandDto::default() };
+        let kept = ids(&deal_sample_hand(&revision, &catalog, &default, SEED).unwrap().hands[1].cards);
+
+        let dto = SampleHandDto { mulligans: 1, bottom: vec![kept[2]], ..SampleHandDto::default() };
+        let hand = deal_sample_hand(&revision, &catalog, &dto, SEED).unwrap();
+
+        assert_eq!(ids(&hand.hands[1].bottomed), vec![kept[2]]);
+        assert_eq!(ids(&hand.hand), [&kept[..2], &kept[3..]].concat());
+        assert_eq!(hand.library_count, 34);
+
+        let missing = SampleHandDto { mulligans: 1, bottom: vec![Uuid::new_v4()], ..SampleHandDto::default() };
+        assert!(deal_sample_hand(&revision, &catalog, &missing, SEED).is_err());
+    }
+
+    #[test]
+    fn reduced_mulligan_draws_one_card_fewer_each_time() {
+        let dto = SampleHandDto { mulligan: MulliganRule::Reduced, mulligans: 2, ..SampleHandDto::default() };
+        let hand = deal(&dto, SEED);
+
+        let sizes: Vec<usize> = hand.hands.iter().map(|opening| opening.cards.len()).collect();
+        assert_eq!(sizes, vec![7, 6, 5]);
+        assert!(hand.hands.iter().all(|opening| opening.bottomed.is_empty()));
+        assert_eq!(ids(&hand.hand), ids(&hand.hands[2].cards));
+        assert_eq!(hand.library_count, 35);
+    }
+
+    #[test]
+    fn player_on_the_play_skips_the_first_draw() {
+        let on_the_play = SampleHandDto { turns: 3, draws_per_turn: 2, ..SampleHandDto::default() };
+        let hand = deal(&on_the_play, SEED);
+
+        let counts: Vec<usize> = hand.draws.iter().map(|turn| turn.cards.len()).collect();
+        assert_eq!(hand.draws.iter().map(|turn| turn.turn).collect::<Vec<_>>(), vec![1, 2, 3]);
+        assert_eq!(counts, vec![0, 2, 2]);
+        assert_eq!(hand.library_count, 29);
+
+        let on_the_draw = SampleHandDto { on_the_play: false, ..on_the_play };
+        let hand = deal(&on_the_draw, SEED);
+        assert_eq!(hand.draws.iter().map(|turn| turn.cards.len()).collect::<Vec<_>>(), vec![2, 2, 2]);
+        assert_eq!(hand.library_count, 27);
+    }
+
+    #[test]
+    fn cards_missing_from_the_catalog_are_dealt_without_details() {
+        let dto = SampleHandDto { turns: 40, ..SampleHandDto::default() };
+        let hand = deal(&dto, SEED);
+
+        let dealt: Vec<&DrawnCard> = hand.hand.iter().chain(hand.draws.iter().flat_map(|turn| &turn.cards)).collect();
+        let hidden: Vec<&&DrawnCard> = dealt.iter().filter(|card| card.name.is_none()).collect();
+        assert_eq!(hidden.len(), 2);
+        assert!(hidden.iter().all(|card| card.set_code.is_none() && card.collector_number.is_none()));
+        assert!(dealt
+            .iter()
+            .filter(|card| card.name.is_some())
+            .all(|card| card.set_code.as_deref() == Some("LGRO")));
+    }
+}
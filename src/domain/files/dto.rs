@@ -0,0 +1,13 @@
+use chrono::{DateTime, Utc};
+use serde::Serialize;
+
+use super::model::UserFile;
+
+// Archivo con una URL firmada y temporal para descargarlo
+#[derive(Debug, Serialize)]
+pub struct UserFileResponse {
+    #[serde(flatten)]
+    pub file: UserFile,
+    pub url: String,
+    pub url_expires_at: DateTime<Utc>,
+}
@@ -0,0 +1,9 @@
+mod model;
+mod repository;
+mod service;
+mod dto;
+
+pub use model::*;
+pub use repository::*;
+pub use service::*;
+pub use dto::*;
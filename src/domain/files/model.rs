@@ -0,0 +1,47 @@
+use serde::Serialize;
+use sqlx::postgres::PgRow;
+use sqlx::Row;
+use uuid::Uuid;
+use chrono::{DateTime, Utc};
+
+// Archivo privado de un usuario (por ejemplo, fotos de su colección)
+#[derive(Debug, Serialize)]
+pub struct UserFile {
+    pub id: Uuid,
+    pub owner_uid: String,
+    // La clave de almacenamiento no se expone: los archivos sólo se sirven con URLs firmadas
+    #[serde(skip_serializing)]
+    pub storage_key: String,
+    pub file_name: Option<String>,
+    pub content_type: String,
+    pub size_bytes: i64,
+    pub created_at: DateTime<Utc>,
+}
+
+impl UserFile {
+    pub fn new(owner_uid: String, storage_key: String, file_name: Option<String>, content_type: String, size_bytes: i64) -> Self {
+        Self {
+            id: Uuid::new_v4(),
+            owner_uid,
+            storage_key,
+            file_name,
+            content_type,
+            size_bytes,
+            created_at: Utc::now(),
+        }
+    }
+}
+
+impl<'r> sqlx::FromRow<'r, PgRow> for UserFile {
+    fn from_row(row: &'r PgRow) -> Result<Self, sqlx::Error> {
+        Ok(Self {
+            id: row.try_get("id")?,
+            owner_uid: row.try_get("owner_uid")?,
+            storage_key: row.try_get("storage_key")?,
+            file_name: row.try_get("file_name")?,
+            content_type: row.try_get("content_type")?,
+            size_bytes: row.try_get("size_bytes")?,
+            created_at: row.try_get("created_at")?,
+        })
+    }
+}
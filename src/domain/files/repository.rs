@@ -0,0 +1,95 @@
+use async_trait::async_trait;
+use sqlx::PgPool;
+use anyhow::Result;
+use uuid::Uuid;
+
+use super::model::UserFile;
+
+#[async_trait]
+pub trait UserFileRepository {
+    async fn create_file(&self, file: UserFile) -> Result<UserFile>;
+    async fn get_files_by_owner(&self, owner_uid: &str) -> Result<Vec<UserFile>>;
+    async fn get_file_by_id(&self, id: Uuid) -> Result<Option<UserFile>>;
+    async fn delete_file(&self, id: Uuid, owner_uid: &str) -> Result<Option<UserFile>>;
+}
+
+pub struct PgUserFileRepository {
+    pool: PgPool,
+}
+
+impl PgUserFileRepository {
+    pub fn new(pool: PgPool) -> Self {
+        Self { pool }
+    }
+}
+
+#[async_trait]
+impl UserFileRepository for PgUserFileRepository {
+    async fn create_file(&self, file: UserFile) -> Result<UserFile> {
+        let created = sqlx::query_as::<_, UserFile>(
+            r#"
+            INSERT INTO user_files (id, owner_uid, storage_key, file_name, content_type, size_bytes, created_at)
+            VALUES ($1, $2, $3, $4, $5, $6, $7)
+            RETURNING id, owner_uid, storage_key, file_name, content_type, size_bytes, created_at
+            "#
+        )
+        .bind(file.id)
+        .bind(file.owner_uid)
+        .bind(file.storage_key)
+        .bind(file.file_name)
+        .bind(file.content_type)
+        .bind(file.size_bytes)
+        .bind(file.created_at)
+        .fetch_one(&self.pool)
+        .await?;
+
+        Ok(created)
+    }
+
+    async fn get_files_by_owner(&self, owner_uid: &str) -> Result<Vec<UserFile>> {
+        let files = sqlx::query_as::<_, UserFile>(
+            r#"
+            SELECT id, owner_uid, storage_key, file_name, content_type, size_bytes, created_at
+            FROM user_files
+            WHERE owner_uid = $1
+            ORDER BY created_at DESC
+            "#
+        )
+        .bind(owner_uid)
+        .fetch_all(&self.pool)
+        .await?;
+
+        Ok(files)
+    }
+
+    async fn get_file_by_id(&self, id: Uuid) -> Result<Option<UserFile>> {
+        let file = sqlx::query_as::<_, UserFile>(
+            r#"
+            SELECT id, owner_uid, storage_key, file_name, content_type, size_bytes, created_at
+            FROM user_files
+            WHERE id = $1
+            "#
+        )
+        .bind(id)
+        .fetch_optional(&self.pool)
+        .await?;
+
+        Ok(file)
+    }
+
+    async fn delete_file(&self, id: Uuid, owner_uid: &str) -> Result<Option<UserFile>> {
+        let file = sqlx::query_as::<_, UserFile>(
+            r#"
+            DELETE FROM user_files
+            WHERE id = $1 AND owner_uid = $2
+            RETURNING id, owner_uid, storage_key, file_name, content_type, size_bytes, created_at
+            "#
+        )
+        .bind(id)
+        .bind(owner_uid)
+        .fetch_optional(&self.pool)
+        .await?;
+
+        Ok(file)
+    }
+}
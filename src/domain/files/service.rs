@@ -0,0 +1,40 @@
+use anyhow::Result;
+use uuid::Uuid;
+
+use super::model::UserFile;
+use super::repository::UserFileRepository;
+
+pub struct UserFileService<R: UserFileRepository> {
+    repository: R,
+}
+
+impl<R: UserFileRepository> UserFileService<R> {
+    pub fn new(repository: R) -> Self {
+        Self { repository }
+    }
+
+    pub async fn create_file(&self, file: UserFile) -> Result<UserFile> {
+        self.repository.create_file(file).await
+    }
+
+    pub async fn get_files_by_owner(&self, owner_uid: &str) -> Result<Vec<UserFile>> {
+        self.repository.get_files_by_owner(owner_uid).await
+    }
+
+    pub async fn get_file_by_id(&self, id: Uuid) -> Result<Option<UserFile>> {
+        self.repository.get_file_by_id(id).await
+    }
+
+    /// Files of other users are reported as missing, so their IDs cannot be probed
+    pub async fn get_owned_file(&self, id: Uuid, owner_uid: &str) -> Result<Option<UserFile>> {
+        Ok(self
+            .repository
+            .get_file_by_id(id)
+            .await?
+            .filter(|file| file.owner_uid == owner_uid))
+    }
+
+    pub async fn delete_file(&self, id: Uuid, owner_uid: &str) -> Result<Option<UserFile>> {
+        self.repository.delete_file(id, owner_uid).await
+    }
+}
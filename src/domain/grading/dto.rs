@@ -0,0 +1,56 @@
+use anyhow::{anyhow, Result};
+use serde::Deserialize;
+use uuid::Uuid;
+
+use super::model::{Grades, MAX_GRADE, MIN_GRADE};
+use crate::domain::cards::Validable;
+
+const MAX_NOTES_LENGTH: usize = 1000;
+
+#[derive(Debug, Deserialize)]
+pub struct GradingSubmissionDto {
+    pub card_id: Uuid,
+    pub notes: Option<String>,
+}
+
+impl GradingSubmissionDto {
+    // Unas notas vacías se guardan como ausentes
+    pub fn notes(&self) -> Option<String> {
+        self.notes
+            .as_deref()
+            .map(str::trim)
+            .filter(|notes| !notes.is_empty())
+            .map(str::to_string)
+    }
+}
+
+impl Validable for GradingSubmissionDto {
+    fn validate(&self) -> Result<()> {
+        if self.notes().is_some_and(|notes| notes.chars().count() > MAX_NOTES_LENGTH) {
+            return Err(anyhow!("Las notas no pueden superar los {} caracteres", MAX_NOTES_LENGTH));
+        }
+
+        Ok(())
+    }
+}
+
+fn validate_grade(field: &str, grade: f32) -> Result<()> {
+    if !(MIN_GRADE..=MAX_GRADE).contains(&grade) || (grade * 2.0).fract() != 0.0 {
+        return Err(anyhow!(
+            "La nota de '{}' debe estar entre {} y {} en medios puntos",
+            field, MIN_GRADE, MAX_GRADE
+        ));
+    }
+
+    Ok(())
+}
+
+impl Validable for Grades {
+    fn validate(&self) -> Result<()> {
+        validate_grade("centering", self.centering)?;
+        validate_grade("corners", self.corners)?;
+        validate_grade("edges", self.edges)?;
+        validate_grade("surface", self.surface)?;
+        validate_grade("final_grade", self.final_grade)
+    }
+}
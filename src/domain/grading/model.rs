@@ -0,0 +1,135 @@
+use serde::{Deserialize, Serialize};
+use sqlx::postgres::PgRow;
+use sqlx::Row;
+use uuid::Uuid;
+use chrono::{DateTime, Utc};
+
+// Estados de una solicitud: en cola y asignada a un grader; al gradarla pasa a 'graded'
+pub const STATUS_SUBMITTED: &str = "submitted";
+pub const STATUS_IN_REVIEW: &str = "in_review";
+
+// Escala de notas, en medios puntos
+pub const MIN_GRADE: f32 = 1.0;
+pub const MAX_GRADE: f32 = 10.0;
+
+/// A card sent by a user to be graded. Graders take submissions from the
+/// queue, and once graded the certificate number identifies it publicly.
+#[derive(Debug, Serialize, Deserialize)]
+pub struct GradingSubmission {
+    pub id: Uuid,
+    pub owner_uid: String,
+    pub card_id: Uuid,
+    pub notes: Option<String>,
+    pub status: String,
+    pub grader_uid: Option<String>,
+    pub centering: Option<f32>,
+    pub corners: Option<f32>,
+    pub edges: Option<f32>,
+    pub surface: Option<f32>,
+    pub final_grade: Option<f32>,
+    pub certificate_number: Option<String>,
+    pub claimed_at: Option<DateTime<Utc>>,
+    pub graded_at: Option<DateTime<Utc>>,
+    pub created_at: DateTime<Utc>,
+    pub updated_at: DateTime<Utc>,
+}
+
+impl GradingSubmission {
+    pub fn new(owner_uid: String, card_id: Uuid, notes: Option<String>) -> Self {
+        Self {
+            id: Uuid::new_v4(),
+            owner_uid,
+            card_id,
+            notes,
+            status: STATUS_SUBMITTED.to_string(),
+            grader_uid: None,
+            centering: None,
+            corners: None,
+            edges: None,
+            surface: None,
+            final_grade: None,
+            certificate_number: None,
+            claimed_at: None,
+            graded_at: None,
+            created_at: Utc::now(),
+            updated_at: Utc::now(),
+        }
+    }
+}
+
+impl<'r> sqlx::FromRow<'r, PgRow> for GradingSubmission {
+    fn from_row(row: &'r PgRow) -> Result<Self, sqlx::Error> {
+        Ok(Self {
+            id: row.try_get("id")?,
+            owner_uid: row.try_get("owner_uid")?,
+            card_id: row.try_get("card_id")?,
+            notes: row.try_get("notes")?,
+            status: row.try_get("status")?,
+            grader_uid: row.try_get("grader_uid")?,
+            centering: row.try_get("centering")?,
+            corners: row.try_get("corners")?,
+            edges: row.try_get("edges")?,
+            surface: row.try_get("surface")?,
+            final_grade: row.try_get("final_grade")?,
+            certificate_number: row.try_get("certificate_number")?,
+            claimed_at: row.try_get("claimed_at")?,
+            graded_at: row.try_get("graded_at")?,
+            created_at: row.try_get("created_at")?,
+            updated_at: row.try_get("updated_at")?,
+        })
+    }
+}
+
+/// Sub-grades and final grade recorded by a grader
+#[derive(Debug, Clone, Copy, Serialize, Deserialize)]
+pub struct Grades {
+    pub centering: f32,
+    pub corners: f32,
+    pub edges: f32,
+    pub surface: f32,
+    pub final_grade: f32,
+}
+
+/// Public view of a graded card: no owner or grader identities
+#[derive(Debug, Serialize, Deserialize)]
+pub struct GradingCertificate {
+    pub certificate_number: String,
+    pub card_id: Uuid,
+    pub card_name: String,
+    pub set_code: String,
+    pub collector_number: i32,
+    #[serde(flatten)]
+    pub grades: Grades,
+    pub graded_at: DateTime<Utc>,
+}
+
+impl<'r> sqlx::FromRow<'r, PgRow> for GradingCertificate {
+    fn from_row(row: &'r PgRow) -> Result<Self, sqlx::Error> {
+        Ok(Self {
+            certificate_number: row.try_get("certificate_number")?,
+            card_id: row.try_get("card_id")?,
+            card_name: row.try_get("card_name")?,
+            set_code: row.try_get("set_code")?,
+            collector_number: row.try_get("collector_number")?,
+            grades: Grades {
+                centering: row.try_get("centering")?,
+                corners: row.try_get("corners")?,
+                edges: row.try_get("edges")?,
+                surface: row.try_get("surface")?,
+                final_grade: row.try_get("final_grade")?,
+            },
+            graded_at: row.try_get("graded_at")?,
+        })
+    }
+}
+
+/// Result of a grader acting on a submission
+#[derive(Debug)]
+pub enum GradingChange {
+    /// The action was applied; holds the submission as it was left
+    Applied(Box<GradingSubmission>),
+    NotFound,
+    /// The submission is not in the state the action needs, e.g. already
+    /// claimed by another grader
+    Conflict { status: String },
+}
@@ -0,0 +1,254 @@
+use async_trait::async_trait;
+use sqlx::PgPool;
+use anyhow::Result;
+use uuid::Uuid;
+
+use super::model::{GradingCertificate, GradingChange, GradingSubmission, Grades};
+
+#[async_trait]
+pub trait GradingRepository {
+    async fn get_submissions_by_owner(&self, owner_uid: &str) -> Result<Vec<GradingSubmission>>;
+    async fn get_submission_by_id(&self, id: Uuid) -> Result<Option<GradingSubmission>>;
+    async fn create_submission(&self, submission: GradingSubmission) -> Result<GradingSubmission>;
+    /// Deletes a submission still waiting in the queue
+    async fn delete_submission(&self, id: Uuid) -> Result<GradingChange>;
+    /// Submissions waiting for a grader, oldest first
+    async fn get_queue(&self, limit: i64) -> Result<Vec<GradingSubmission>>;
+    async fn get_assigned(&self, grader_uid: &str) -> Result<Vec<GradingSubmission>>;
+    async fn claim(&self, id: Uuid, grader_uid: &str) -> Result<GradingChange>;
+    /// Returns a claimed submission to the queue. `any_grader` lets admins
+    /// release submissions claimed by someone else.
+    async fn release(&self, id: Uuid, grader_uid: &str, any_grader: bool) -> Result<GradingChange>;
+    async fn grade(&self, id: Uuid, grader_uid: &str, any_grader: bool, grades: Grades) -> Result<GradingChange>;
+    async fn get_certificate(&self, certificate_number: &str) -> Result<Option<GradingCertificate>>;
+}
+
+pub struct PgGradingRepository {
+    pool: PgPool,
+}
+
+impl PgGradingRepository {
+    pub fn new(pool: PgPool) -> Self {
+        Self { pool }
+    }
+
+    // Si una transición no afecta a ninguna fila, distingue si la solicitud no existe
+    // o si está en otro estado (por ejemplo, tomada por otro grader)
+    async fn unchanged(&self, id: Uuid) -> Result<GradingChange> {
+        let status: Option<String> = sqlx::query_scalar("SELECT status FROM grading_submissions WHERE id = $1")
+            .bind(id)
+            .fetch_optional(&self.pool)
+            .await?;
+
+        Ok(match status {
+            Some(status) => GradingChange::Conflict { status },
+            None => GradingChange::NotFound,
+        })
+    }
+}
+
+#[async_trait]
+impl GradingRepository for PgGradingRepository {
+    async fn get_submissions_by_owner(&self, owner_uid: &str) -> Result<Vec<GradingSubmission>> {
+        let submissions = sqlx::query_as::<_, GradingSubmission>(
+            r#"
+            SELECT id, owner_uid, card_id, notes, status, grader_uid, centering, corners, edges, surface,
+                   final_grade, certificate_number, claimed_at, graded_at, created_at, updated_at
+            FROM grading_submissions
+            WHERE owner_uid = $1
+            ORDER BY created_at DESC
+            "#
+        )
+        .bind(owner_uid)
+        .fetch_all(&self.pool)
+        .await?;
+
+        Ok(submissions)
+    }
+
+    async fn get_submission_by_id(&self, id: Uuid) -> Result<Option<GradingSubmission>> {
+        let submission = sqlx::query_as::<_, GradingSubmission>(
+            r#"
+            SELECT id, owner_uid, card_id, notes, status, grader_uid, centering, corners, edges, surface,
+                   final_grade, certificate_number, claimed_at, graded_at, created_at, updated_at
+            FROM grading_submissions
+            WHERE id = $1
+            "#
+        )
+        .bind(id)
+        .fetch_optional(&self.pool)
+        .await?;
+
+        Ok(submission)
+    }
+
+    async fn create_submission(&self, submission: GradingSubmission) -> Result<GradingSubmission> {
+        let submission = sqlx::query_as::<_, GradingSubmission>(
+            r#"
+            INSERT INTO grading_submissions (id, owner_uid, card_id, notes, status, created_at, updated_at)
+            VALUES ($1, $2, $3, $4, $5, $6, $7)
+            RETURNING id, owner_uid, card_id, notes, status, grader_uid, centering, corners, edges, surface,
+                      final_grade, certificate_number, claimed_at, graded_at, created_at, updated_at
+            "#
+        )
+        .bind(submission.id)
+        .bind(&submission.owner_uid)
+        .bind(submission.card_id)
+        .bind(&submission.notes)
+        .bind(&submission.status)
+        .bind(submission.created_at)
+        .bind(submission.updated_at)
+        .fetch_one(&self.pool)
+        .await?;
+
+        Ok(submission)
+    }
+
+    async fn delete_submission(&self, id: Uuid) -> Result<GradingChange> {
+        let submission = sqlx::query_as::<_, GradingSubmission>(
+            r#"
+            DELETE FROM grading_submissions
+            WHERE id = $1 AND status = 'submitted'
+            RETURNING id, owner_uid, card_id, notes, status, grader_uid, centering, corners, edges, surface,
+                      final_grade, certificate_number, claimed_at, graded_at, created_at, updated_at
+            "#
+        )
+        .bind(id)
+        .fetch_optional(&self.pool)
+        .await?;
+
+        match submission {
+            Some(submission) => Ok(GradingChange::Applied(Box::new(submission))),
+            None => self.unchanged(id).await,
+        }
+    }
+
+    async fn get_queue(&self, limit: i64) -> Result<Vec<GradingSubmission>> {
+        let submissions = sqlx::query_as::<_, GradingSubmission>(
+            r#"
+            SELECT id, owner_uid, card_id, notes, status, grader_uid, centering, corners, edges, surface,
+                   final_grade, certificate_number, claimed_at, graded_at, created_at, updated_at
+            FROM grading_submissions
+            WHERE status = 'submitted'
+            ORDER BY created_at
+            LIMIT $1
+            "#
+        )
+        .bind(limit)
+        .fetch_all(&self.pool)
+        .await?;
+
+        Ok(submissions)
+    }
+
+    async fn get_assigned(&self, grader_uid: &str) -> Result<Vec<GradingSubmission>> {
+        let submissions = sqlx::query_as::<_, GradingSubmission>(
+            r#"
+            SELECT id, owner_uid, card_id, notes, status, grader_uid, centering, corners, edges, surface,
+                   final_grade, certificate_number, claimed_at, graded_at, created_at, updated_at
+            FROM grading_submissions
+            WHERE status = 'in_review' AND grader_uid = $1
+            ORDER BY claimed_at
+            "#
+        )
+        .bind(grader_uid)
+        .fetch_all(&self.pool)
+        .await?;
+
+        Ok(submissions)
+    }
+
+    async fn claim(&self, id: Uuid, grader_uid: &str) -> Result<GradingChange> {
+        // La condición sobre el estado evita que dos graders tomen la misma solicitud
+        let submission = sqlx::query_as::<_, GradingSubmission>(
+            r#"
+            UPDATE grading_submissions
+            SET status = 'in_review', grader_uid = $1, claimed_at = NOW(), updated_at = NOW()
+            WHERE id = $2 AND status = 'submitted'
+            RETURNING id, owner_uid, card_id, notes, status, grader_uid, centering, corners, edges, surface,
+                      final_grade, certificate_number, claimed_at, graded_at, created_at, updated_at
+            "#
+        )
+        .bind(grader_uid)
+        .bind(id)
+        .fetch_optional(&self.pool)
+        .await?;
+
+        match submission {
+            Some(submission) => Ok(GradingChange::Applied(Box::new(submission))),
+            None => self.unchanged(id).await,
+        }
+    }
+
+    async fn release(&self, id: Uuid, grader_uid: &str, any_grader: bool) -> Result<GradingChange> {
+        let submission = sqlx::query_as::<_, GradingSubmission>(
+            r#"
+            UPDATE grading_submissions
+            SET status = 'submitted', grader_uid = NULL, claimed_at = NULL, updated_at = NOW()
+            WHERE id = $1 AND status = 'in_review' AND ($2 OR grader_uid = $3)
+            RETURNING id, owner_uid, card_id, notes, status, grader_uid, centering, corners, edges, surface,
+                      final_grade, certificate_number, claimed_at, graded_at, created_at, updated_at
+            "#
+        )
+        .bind(id)
+        .bind(any_grader)
+        .bind(grader_uid)
+        .fetch_optional(&self.pool)
+        .await?;
+
+        match submission {
+            Some(submission) => Ok(GradingChange::Applied(Box::new(submission))),
+            None => self.unchanged(id).await,
+        }
+    }
+
+    async fn grade(&self, id: Uuid, grader_uid: &str, any_grader: bool, grades: Grades) -> Result<GradingChange> {
+        // El número de certificado sale de una secuencia: único y no reutilizable
+        let submission = sqlx::query_as::<_, GradingSubmission>(
+            r#"
+            UPDATE grading_submissions
+            SET status = 'graded', grader_uid = $1, centering = $2, corners = $3, edges = $4,
+                surface = $5, final_grade = $6,
+                certificate_number = nextval('grading_certificate_seq')::TEXT,
+                graded_at = NOW(), updated_at = NOW()
+            WHERE id = $7 AND status = 'in_review' AND ($8 OR grader_uid = $1)
+            RETURNING id, owner_uid, card_id, notes, status, grader_uid, centering, corners, edges, surface,
+                      final_grade, certificate_number, claimed_at, graded_at, created_at, updated_at
+            "#
+        )
+        .bind(grader_uid)
+        .bind(grades.centering)
+        .bind(grades.corners)
+        .bind(grades.edges)
+        .bind(grades.surface)
+        .bind(grades.final_grade)
+        .bind(id)
+        .bind(any_grader)
+        .fetch_optional(&self.pool)
+        .await?;
+
+        match submission {
+            Some(submission) => Ok(GradingChange::Applied(Box::new(submission))),
+            None => self.unchanged(id).await,
+        }
+    }
+
+    async fn get_certificate(&self, certificate_number: &str) -> Result<Option<GradingCertificate>> {
+        let certificate = sqlx::query_as::<_, GradingCertificate>(
+            r#"
+            SELECT g.certificate_number, g.card_id, c.name AS card_name, s.code AS set_code,
+                   c.collector_number, g.centering, g.corners, g.edges, g.surface, g.final_grade,
+                   g.graded_at
+            FROM grading_submissions g
+            JOIN cards c ON c.id = g.card_id
+            JOIN card_sets s ON s.id = c.set_id
+            WHERE g.certificate_number = $1 AND g.status = 'graded'
+            "#
+        )
+        .bind(certificate_number)
+        .fetch_optional(&self.pool)
+        .await?;
+
+        Ok(certificate)
+    }
+}
@@ -0,0 +1,59 @@
+use anyhow::Result;
+use uuid::Uuid;
+
+use super::dto::GradingSubmissionDto;
+use super::model::{GradingCertificate, GradingChange, GradingSubmission, Grades};
+use super::repository::GradingRepository;
+
+pub struct GradingService<R: GradingRepository> {
+    repository: R,
+}
+
+impl<R: GradingRepository> GradingService<R> {
+    pub fn new(repository: R) -> Self {
+        Self { repository }
+    }
+
+    pub async fn get_submissions_by_owner(&self, owner_uid: &str) -> Result<Vec<GradingSubmission>> {
+        self.repository.get_submissions_by_owner(owner_uid).await
+    }
+
+    pub async fn get_submission_by_id(&self, id: Uuid) -> Result<Option<GradingSubmission>> {
+        self.repository.get_submission_by_id(id).await
+    }
+
+    pub async fn submit(&self, owner_uid: String, dto: &GradingSubmissionDto) -> Result<GradingSubmission> {
+        let submission = GradingSubmission::new(owner_uid, dto.card_id, dto.notes());
+        self.repository.create_submission(submission).await
+    }
+
+    /// Withdraws a submission; only possible before a grader claims it
+    pub async fn withdraw(&self, id: Uuid) -> Result<GradingChange> {
+        self.repository.delete_submission(id).await
+    }
+
+    pub async fn get_queue(&self, limit: i64) -> Result<Vec<GradingSubmission>> {
+        self.repository.get_queue(limit).await
+    }
+
+    pub async fn get_assigned(&self, grader_uid: &str) -> Result<Vec<GradingSubmission>> {
+        self.repository.get_assigned(grader_uid).await
+    }
+
+    pub async fn claim(&self, id: Uuid, grader_uid: &str) -> Result<GradingChange> {
+        self.repository.claim(id, grader_uid).await
+    }
+
+    pub async fn release(&self, id: Uuid, grader_uid: &str, any_grader: bool) -> Result<GradingChange> {
+        self.repository.release(id, grader_uid, any_grader).await
+    }
+
+    /// Records the grades and issues the certificate number
+    pub async fn grade(&self, id: Uuid, grader_uid: &str, any_grader: bool, grades: Grades) -> Result<GradingChange> {
+        self.repository.grade(id, grader_uid, any_grader, grades).await
+    }
+
+    pub async fn get_certificate(&self, certificate_number: &str) -> Result<Option<GradingCertificate>> {
+        self.repository.get_certificate(certificate_number.trim()).await
+    }
+}
@@ -1 +1,7 @@
-pub mod cards; 
\ No newline at end of file
+pub mod cards;
+pub mod collections;
+pub mod decks;
+pub mod files;
+pub mod grading;
+pub mod trades;
+pub mod wishlists;
@@ -0,0 +1,43 @@
+use anyhow::{anyhow, Result};
+use serde::Deserialize;
+use uuid::Uuid;
+
+use crate::domain::cards::Validable;
+use crate::domain::collections::MAX_QUANTITY;
+
+#[derive(Debug, Deserialize)]
+pub struct SetTradeHaveDto {
+    pub quantity: i32,
+}
+
+impl Validable for SetTradeHaveDto {
+    fn validate(&self) -> Result<()> {
+        // Cero retira la oferta
+        if self.quantity < 0 || self.quantity > MAX_QUANTITY {
+            return Err(anyhow!("La cantidad debe estar entre 0 y {}", MAX_QUANTITY));
+        }
+
+        Ok(())
+    }
+}
+
+#[derive(Debug, Deserialize)]
+pub struct TradeWantDto {
+    pub card_id: Uuid,
+    #[serde(default = "default_quantity")]
+    pub quantity: i32,
+}
+
+fn default_quantity() -> i32 {
+    1
+}
+
+impl Validable for TradeWantDto {
+    fn validate(&self) -> Result<()> {
+        if self.quantity <= 0 || self.quantity > MAX_QUANTITY {
+            return Err(anyhow!("La cantidad debe estar entre 1 y {}", MAX_QUANTITY));
+        }
+
+        Ok(())
+    }
+}
@@ -0,0 +1,108 @@
+use serde::{Deserialize, Serialize};
+use sqlx::postgres::PgRow;
+use sqlx::Row;
+use uuid::Uuid;
+use chrono::{DateTime, Utc};
+
+/// Copies of a collection entry offered for trade. `quantity` never exceeds
+/// the copies still owned: removing copies from the collection shrinks it.
+#[derive(Debug, Serialize, Deserialize)]
+pub struct TradeHave {
+    pub entry_id: Uuid,
+    pub owner_uid: String,
+    pub card_id: Uuid,
+    pub condition: String,
+    pub language: String,
+    pub finish: String,
+    pub quantity: i32,
+    pub owned: i32,
+    pub updated_at: DateTime<Utc>,
+}
+
+impl<'r> sqlx::FromRow<'r, PgRow> for TradeHave {
+    fn from_row(row: &'r PgRow) -> Result<Self, sqlx::Error> {
+        Ok(Self {
+            entry_id: row.try_get("entry_id")?,
+            owner_uid: row.try_get("owner_uid")?,
+            card_id: row.try_get("card_id")?,
+            condition: row.try_get("condition")?,
+            language: row.try_get("language")?,
+            finish: row.try_get("finish")?,
+            quantity: row.try_get("quantity")?,
+            owned: row.try_get("owned")?,
+            updated_at: row.try_get("updated_at")?,
+        })
+    }
+}
+
+/// A card a user is looking for, in any printing
+#[derive(Debug, Serialize, Deserialize)]
+pub struct TradeWant {
+    pub id: Uuid,
+    pub owner_uid: String,
+    pub card_id: Uuid,
+    pub quantity: i32,
+    pub created_at: DateTime<Utc>,
+    pub updated_at: DateTime<Utc>,
+}
+
+impl<'r> sqlx::FromRow<'r, PgRow> for TradeWant {
+    fn from_row(row: &'r PgRow) -> Result<Self, sqlx::Error> {
+        Ok(Self {
+            id: row.try_get("id")?,
+            owner_uid: row.try_get("owner_uid")?,
+            card_id: row.try_get("card_id")?,
+            quantity: row.try_get("quantity")?,
+            created_at: row.try_get("created_at")?,
+            updated_at: row.try_get("updated_at")?,
+        })
+    }
+}
+
+/// One card that can change hands with a partner: the copies one side offers,
+/// capped by the copies the other side wants
+#[derive(Debug, Serialize)]
+pub struct TradeCard {
+    pub card_id: Uuid,
+    pub quantity: i64,
+}
+
+/// Row of the matching queries: a card shared between a partner's list and the user's
+#[derive(Debug)]
+pub struct TradeMatchLine {
+    pub partner_uid: String,
+    pub card_id: Uuid,
+    pub quantity: i64,
+}
+
+impl<'r> sqlx::FromRow<'r, PgRow> for TradeMatchLine {
+    fn from_row(row: &'r PgRow) -> Result<Self, sqlx::Error> {
+        Ok(Self {
+            partner_uid: row.try_get("partner_uid")?,
+            card_id: row.try_get("card_id")?,
+            quantity: row.try_get("quantity")?,
+        })
+    }
+}
+
+/// A potential trading partner
+#[derive(Debug, Serialize)]
+pub struct TradeMatch {
+    pub partner_uid: String,
+    /// Cards the partner offers that the user wants
+    pub they_have: Vec<TradeCard>,
+    /// Cards the user offers that the partner wants
+    pub they_want: Vec<TradeCard>,
+    /// Cards that can be swapped one for one: the smaller of both sides
+    pub mutual_matches: usize,
+}
+
+/// Result of offering copies of a collection entry for trade
+#[derive(Debug)]
+pub enum HaveChange {
+    Updated(TradeHave),
+    /// A quantity of zero withdrew the offer
+    Removed,
+    NotOwned,
+    Insufficient { owned: i32 },
+}
@@ -0,0 +1,210 @@
+use async_trait::async_trait;
+use sqlx::PgPool;
+use anyhow::Result;
+use uuid::Uuid;
+
+use super::model::{HaveChange, TradeHave, TradeMatchLine, TradeWant};
+
+// Cartas buscadas por cada usuario: su lista de intercambio más las listas de
+// deseos marcadas para intercambio. Si una carta aparece en varias, cuenta la
+// mayor cantidad, no la suma.
+const WANTED_CARDS: &str = r#"
+    SELECT owner_uid, card_id, MAX(quantity) AS quantity
+    FROM (
+        SELECT owner_uid, card_id, quantity FROM trade_wants
+        UNION ALL
+        SELECT l.owner_uid, i.card_id, i.quantity
+        FROM wishlist_items i
+        JOIN wishlists l ON l.id = i.wishlist_id
+        WHERE l.for_trade
+    ) wanted
+    GROUP BY owner_uid, card_id
+"#;
+
+#[async_trait]
+pub trait TradeRepository {
+    async fn get_haves(&self, owner_uid: &str) -> Result<Vec<TradeHave>>;
+    async fn set_have(&self, owner_uid: &str, entry_id: Uuid, quantity: i32) -> Result<HaveChange>;
+    async fn get_wants(&self, owner_uid: &str) -> Result<Vec<TradeWant>>;
+    async fn set_want(&self, owner_uid: &str, card_id: Uuid, quantity: i32) -> Result<TradeWant>;
+    async fn delete_want(&self, owner_uid: &str, card_id: Uuid) -> Result<bool>;
+    /// Cards other users offer that `owner_uid` wants, in their trade wants or
+    /// in a wishlist marked for trade
+    async fn get_offers_for(&self, owner_uid: &str) -> Result<Vec<TradeMatchLine>>;
+    /// Cards `owner_uid` offers that other users want
+    async fn get_requests_for(&self, owner_uid: &str) -> Result<Vec<TradeMatchLine>>;
+}
+
+pub struct PgTradeRepository {
+    pool: PgPool,
+}
+
+impl PgTradeRepository {
+    pub fn new(pool: PgPool) -> Self {
+        Self { pool }
+    }
+}
+
+#[async_trait]
+impl TradeRepository for PgTradeRepository {
+    async fn get_haves(&self, owner_uid: &str) -> Result<Vec<TradeHave>> {
+        // Si se retiraron copias de la colección, se ofrecen como mucho las que quedan
+        let haves = sqlx::query_as::<_, TradeHave>(
+            r#"
+            SELECT h.entry_id, e.owner_uid, e.card_id, e.condition, e.language, e.finish,
+                   LEAST(h.quantity, e.quantity) AS quantity, e.quantity AS owned, h.updated_at
+            FROM trade_haves h
+            JOIN collection_entries e ON e.id = h.entry_id
+            JOIN cards c ON c.id = e.card_id
+            JOIN card_sets s ON s.id = c.set_id
+            WHERE e.owner_uid = $1
+            ORDER BY s.release_date, s.code, c.collector_number, e.condition, e.language, e.finish
+            "#
+        )
+        .bind(owner_uid)
+        .fetch_all(&self.pool)
+        .await?;
+
+        Ok(haves)
+    }
+
+    async fn set_have(&self, owner_uid: &str, entry_id: Uuid, quantity: i32) -> Result<HaveChange> {
+        let mut tx = self.pool.begin().await?;
+
+        // Se bloquea la entrada para que no cambie la cantidad mientras se valida la oferta
+        let owned: Option<i32> = sqlx::query_scalar(
+            "SELECT quantity FROM collection_entries WHERE owner_uid = $1 AND id = $2 FOR UPDATE"
+        )
+        .bind(owner_uid)
+        .bind(entry_id)
+        .fetch_optional(&mut *tx)
+        .await?;
+
+        let Some(owned) = owned else {
+            return Ok(HaveChange::NotOwned);
+        };
+
+        if quantity > owned {
+            return Ok(HaveChange::Insufficient { owned });
+        }
+
+        if quantity == 0 {
+            sqlx::query("DELETE FROM trade_haves WHERE entry_id = $1")
+                .bind(entry_id)
+                .execute(&mut *tx)
+                .await?;
+            tx.commit().await?;
+            return Ok(HaveChange::Removed);
+        }
+
+        let have = sqlx::query_as::<_, TradeHave>(
+            r#"
+            WITH saved AS (
+                INSERT INTO trade_haves (entry_id, quantity, created_at, updated_at)
+                VALUES ($1, $2, NOW(), NOW())
+                ON CONFLICT (entry_id) DO UPDATE SET quantity = EXCLUDED.quantity, updated_at = NOW()
+                RETURNING entry_id, quantity, updated_at
+            )
+            SELECT saved.entry_id, e.owner_uid, e.card_id, e.condition, e.language, e.finish,
+                   saved.quantity, e.quantity AS owned, saved.updated_at
+            FROM saved
+            JOIN collection_entries e ON e.id = saved.entry_id
+            "#
+        )
+        .bind(entry_id)
+        .bind(quantity)
+        .fetch_one(&mut *tx)
+        .await?;
+
+        tx.commit().await?;
+
+        Ok(HaveChange::Updated(have))
+    }
+
+    async fn get_wants(&self, owner_uid: &str) -> Result<Vec<TradeWant>> {
+        let wants = sqlx::query_as::<_, TradeWant>(
+            r#"
+            SELECT w.id, w.owner_uid, w.card_id, w.quantity, w.created_at, w.updated_at
+            FROM trade_wants w
+            JOIN cards c ON c.id = w.card_id
+            JOIN card_sets s ON s.id = c.set_id
+            WHERE w.owner_uid = $1
+            ORDER BY s.release_date, s.code, c.collector_number
+            "#
+        )
+        .bind(owner_uid)
+        .fetch_all(&self.pool)
+        .await?;
+
+        Ok(wants)
+    }
+
+    async fn set_want(&self, owner_uid: &str, card_id: Uuid, quantity: i32) -> Result<TradeWant> {
+        let want = sqlx::query_as::<_, TradeWant>(
+            r#"
+            INSERT INTO trade_wants (id, owner_uid, card_id, quantity, created_at, updated_at)
+            VALUES ($1, $2, $3, $4, NOW(), NOW())
+            ON CONFLICT (owner_uid, card_id) DO UPDATE SET quantity = EXCLUDED.quantity, updated_at = NOW()
+            RETURNING id, owner_uid, card_id, quantity, created_at, updated_at
+            "#
+        )
+        .bind(Uuid::new_v4())
+        .bind(owner_uid)
+        .bind(card_id)
+        .bind(quantity)
+        .fetch_one(&self.pool)
+        .await?;
+
+        Ok(want)
+    }
+
+    async fn delete_want(&self, owner_uid: &str, card_id: Uuid) -> Result<bool> {
+        let result = sqlx::query("DELETE FROM trade_wants WHERE owner_uid = $1 AND card_id = $2")
+            .bind(owner_uid)
+            .bind(card_id)
+            .execute(&self.pool)
+            .await?;
+
+        Ok(result.rows_affected() > 0)
+    }
+
+    async fn get_offers_for(&self, owner_uid: &str) -> Result<Vec<TradeMatchLine>> {
+        let lines = sqlx::query_as::<_, TradeMatchLine>(&format!(
+            r#"
+            SELECT e.owner_uid AS partner_uid, e.card_id,
+                   LEAST(SUM(LEAST(h.quantity, e.quantity)), MAX(w.quantity))::BIGINT AS quantity
+            FROM trade_haves h
+            JOIN collection_entries e ON e.id = h.entry_id
+            JOIN ({}) w ON w.card_id = e.card_id AND w.owner_uid = $1
+            WHERE e.owner_uid <> $1
+            GROUP BY e.owner_uid, e.card_id
+            "#,
+            WANTED_CARDS
+        ))
+        .bind(owner_uid)
+        .fetch_all(&self.pool)
+        .await?;
+
+        Ok(lines)
+    }
+
+    async fn get_requests_for(&self, owner_uid: &str) -> Result<Vec<TradeMatchLine>> {
+        let lines = sqlx::query_as::<_, TradeMatchLine>(&format!(
+            r#"
+            SELECT w.owner_uid AS partner_uid, e.card_id,
+                   LEAST(SUM(LEAST(h.quantity, e.quantity)), MAX(w.quantity))::BIGINT AS quantity
+            FROM trade_haves h
+            JOIN collection_entries e ON e.id = h.entry_id AND e.owner_uid = $1
+            JOIN ({}) w ON w.card_id = e.card_id
+            WHERE w.owner_uid <> $1
+            GROUP BY w.owner_uid, e.card_id
+            "#,
+            WANTED_CARDS
+        ))
+        .bind(owner_uid)
+        .fetch_all(&self.pool)
+        .await?;
+
+        Ok(lines)
+    }
+}
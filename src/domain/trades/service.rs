@@ -0,0 +1,85 @@
+use anyhow::Result;
+use std::collections::HashMap;
+use uuid::Uuid;
+
+use super::model::{HaveChange, TradeCard, TradeHave, TradeMatch, TradeWant};
+use super::repository::TradeRepository;
+
+pub struct TradeService<R: TradeRepository> {
+    repository: R,
+}
+
+impl<R: TradeRepository> TradeService<R> {
+    pub fn new(repository: R) -> Self {
+        Self { repository }
+    }
+
+    pub async fn get_haves(&self, owner_uid: &str) -> Result<Vec<TradeHave>> {
+        self.repository.get_haves(owner_uid).await
+    }
+
+    /// Offers copies of a collection entry for trade; zero withdraws the offer
+    pub async fn set_have(&self, owner_uid: &str, entry_id: Uuid, quantity: i32) -> Result<HaveChange> {
+        self.repository.set_have(owner_uid, entry_id, quantity).await
+    }
+
+    pub async fn get_wants(&self, owner_uid: &str) -> Result<Vec<TradeWant>> {
+        self.repository.get_wants(owner_uid).await
+    }
+
+    pub async fn set_want(&self, owner_uid: &str, card_id: Uuid, quantity: i32) -> Result<TradeWant> {
+        self.repository.set_want(owner_uid, card_id, quantity).await
+    }
+
+    pub async fn delete_want(&self, owner_uid: &str, card_id: Uuid) -> Result<bool> {
+        self.repository.delete_want(owner_uid, card_id).await
+    }
+
+    /// Users whose haves intersect the user's wants or the other way round.
+    /// Partners who can swap in both directions come first, ranked by the
+    /// number of one-for-one swaps, then by the total number of cards matched.
+    pub async fn find_matches(&self, owner_uid: &str, limit: usize) -> Result<Vec<TradeMatch>> {
+        let mut partners: HashMap<String, TradeMatch> = HashMap::new();
+
+        for line in self.repository.get_offers_for(owner_uid).await? {
+            partner(&mut partners, &line.partner_uid)
+                .they_have
+                .push(TradeCard { card_id: line.card_id, quantity: line.quantity });
+        }
+
+        for line in self.repository.get_requests_for(owner_uid).await? {
+            partner(&mut partners, &line.partner_uid)
+                .they_want
+                .push(TradeCard { card_id: line.card_id, quantity: line.quantity });
+        }
+
+        let mut matches: Vec<TradeMatch> = partners
+            .into_values()
+            .map(|mut trade_match| {
+                trade_match.mutual_matches = trade_match.they_have.len().min(trade_match.they_want.len());
+                trade_match.they_have.sort_by_key(|card| card.card_id);
+                trade_match.they_want.sort_by_key(|card| card.card_id);
+                trade_match
+            })
+            .collect();
+
+        matches.sort_by(|a, b| {
+            b.mutual_matches
+                .cmp(&a.mutual_matches)
+                .then_with(|| (b.they_have.len() + b.they_want.len()).cmp(&(a.they_have.len() + a.they_want.len())))
+                .then_with(|| a.partner_uid.cmp(&b.partner_uid))
+        });
+        matches.truncate(limit);
+
+        Ok(matches)
+    }
+}
+
+fn partner<'a>(partners: &'a mut HashMap<String, TradeMatch>, partner_uid: &str) -> &'a mut TradeMatch {
+    partners.entry(partner_uid.to_string()).or_insert_with(|| TradeMatch {
+        partner_uid: partner_uid.to_string(),
+        they_have: Vec::new(),
+        they_want: Vec::new(),
+        mutual_matches: 0,
+    })
+}
@@ -0,0 +1,79 @@
+use anyhow::{anyhow, Result};
+use serde::Deserialize;
+
+use super::model::{DEFAULT_PRIORITY, MAX_PRIORITY, MIN_PRIORITY};
+use crate::domain::cards::Validable;
+use crate::domain::collections::MAX_QUANTITY;
+
+const MAX_NAME_LENGTH: usize = 100;
+const MAX_DESCRIPTION_LENGTH: usize = 1000;
+
+#[derive(Debug, Deserialize)]
+pub struct WishlistDto {
+    pub name: String,
+    pub description: Option<String>,
+    #[serde(default)]
+    pub is_public: bool,
+    #[serde(default)]
+    pub for_trade: bool,
+}
+
+impl WishlistDto {
+    pub fn name(&self) -> String {
+        self.name.trim().to_string()
+    }
+
+    // Una descripción vacía se guarda como ausente
+    pub fn description(&self) -> Option<String> {
+        self.description
+            .as_deref()
+            .map(str::trim)
+            .filter(|description| !description.is_empty())
+            .map(str::to_string)
+    }
+}
+
+impl Validable for WishlistDto {
+    fn validate(&self) -> Result<()> {
+        let name = self.name();
+        if name.is_empty() || name.chars().count() > MAX_NAME_LENGTH {
+            return Err(anyhow!("El nombre debe tener entre 1 y {} caracteres", MAX_NAME_LENGTH));
+        }
+
+        if self.description().is_some_and(|description| description.chars().count() > MAX_DESCRIPTION_LENGTH) {
+            return Err(anyhow!("La descripción no puede superar los {} caracteres", MAX_DESCRIPTION_LENGTH));
+        }
+
+        Ok(())
+    }
+}
+
+#[derive(Debug, Deserialize)]
+pub struct WishlistItemDto {
+    #[serde(default = "default_quantity")]
+    pub quantity: i32,
+    #[serde(default = "default_priority")]
+    pub priority: i16,
+}
+
+fn default_quantity() -> i32 {
+    1
+}
+
+fn default_priority() -> i16 {
+    DEFAULT_PRIORITY
+}
+
+impl Validable for WishlistItemDto {
+    fn validate(&self) -> Result<()> {
+        if self.quantity <= 0 || self.quantity > MAX_QUANTITY {
+            return Err(anyhow!("La cantidad debe estar entre 1 y {}", MAX_QUANTITY));
+        }
+
+        if !(MIN_PRIORITY..=MAX_PRIORITY).contains(&self.priority) {
+            return Err(anyhow!("La prioridad debe estar entre {} y {}", MIN_PRIORITY, MAX_PRIORITY));
+        }
+
+        Ok(())
+    }
+}
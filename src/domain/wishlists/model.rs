@@ -0,0 +1,101 @@
+use serde::{Deserialize, Serialize};
+use sqlx::postgres::PgRow;
+use sqlx::Row;
+use uuid::Uuid;
+use chrono::{DateTime, Utc};
+
+// Prioridad de una carta en la lista: 1 es la más baja y 5 la más alta
+pub const MIN_PRIORITY: i16 = 1;
+pub const MAX_PRIORITY: i16 = 5;
+pub const DEFAULT_PRIORITY: i16 = 3;
+
+/// A named list of wanted cards. Mirrors the Firestore `wishlists` rules:
+/// public lists are readable by any authenticated user, private ones only by
+/// their owner, and a share token lets anyone holding the link read it.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Wishlist {
+    pub id: Uuid,
+    pub owner_uid: String,
+    pub name: String,
+    pub description: Option<String>,
+    pub is_public: bool,
+    /// Its items count as trade wants when matching trading partners
+    pub for_trade: bool,
+    /// Only shown to the owner
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub share_token: Option<String>,
+    pub created_at: DateTime<Utc>,
+    pub updated_at: DateTime<Utc>,
+}
+
+impl Wishlist {
+    pub fn new(owner_uid: String, name: String, description: Option<String>, is_public: bool, for_trade: bool) -> Self {
+        Self {
+            id: Uuid::new_v4(),
+            owner_uid,
+            name,
+            description,
+            is_public,
+            for_trade,
+            share_token: None,
+            created_at: Utc::now(),
+            updated_at: Utc::now(),
+        }
+    }
+}
+
+impl<'r> sqlx::FromRow<'r, PgRow> for Wishlist {
+    fn from_row(row: &'r PgRow) -> Result<Self, sqlx::Error> {
+        Ok(Self {
+            id: row.try_get("id")?,
+            owner_uid: row.try_get("owner_uid")?,
+            name: row.try_get("name")?,
+            description: row.try_get("description")?,
+            is_public: row.try_get("is_public")?,
+            for_trade: row.try_get("for_trade")?,
+            share_token: row.try_get("share_token")?,
+            created_at: row.try_get("created_at")?,
+            updated_at: row.try_get("updated_at")?,
+        })
+    }
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct WishlistItem {
+    pub id: Uuid,
+    pub wishlist_id: Uuid,
+    pub card_id: Uuid,
+    pub quantity: i32,
+    pub priority: i16,
+    pub created_at: DateTime<Utc>,
+    pub updated_at: DateTime<Utc>,
+}
+
+impl<'r> sqlx::FromRow<'r, PgRow> for WishlistItem {
+    fn from_row(row: &'r PgRow) -> Result<Self, sqlx::Error> {
+        Ok(Self {
+            id: row.try_get("id")?,
+            wishlist_id: row.try_get("wishlist_id")?,
+            card_id: row.try_get("card_id")?,
+            quantity: row.try_get("quantity")?,
+            priority: row.try_get("priority")?,
+            created_at: row.try_get("created_at")?,
+            updated_at: row.try_get("updated_at")?,
+        })
+    }
+}
+
+/// Wishlist with its cards, highest priority first
+#[derive(Debug, Serialize)]
+pub struct WishlistDetail {
+    #[serde(flatten)]
+    pub wishlist: Wishlist,
+    pub items: Vec<WishlistItem>,
+}
+
+/// Link that gives read access to a wishlist, private or not
+#[derive(Debug, Serialize)]
+pub struct WishlistShareLink {
+    pub share_token: String,
+    pub url: String,
+}
@@ -0,0 +1,199 @@
+use async_trait::async_trait;
+use sqlx::PgPool;
+use anyhow::Result;
+use uuid::Uuid;
+
+use super::model::{Wishlist, WishlistItem};
+
+#[async_trait]
+pub trait WishlistRepository {
+    async fn get_wishlists_by_owner(&self, owner_uid: &str, include_private: bool) -> Result<Vec<Wishlist>>;
+    async fn get_wishlist_by_id(&self, id: Uuid) -> Result<Option<Wishlist>>;
+    async fn get_wishlist_by_share_token(&self, token: &str) -> Result<Option<Wishlist>>;
+    async fn create_wishlist(&self, wishlist: Wishlist) -> Result<Wishlist>;
+    async fn update_wishlist(&self, id: Uuid, name: &str, description: Option<&str>, is_public: bool, for_trade: bool) -> Result<Option<Wishlist>>;
+    async fn delete_wishlist(&self, id: Uuid) -> Result<bool>;
+    /// Sets or clears the share token
+    async fn update_share_token(&self, id: Uuid, token: Option<&str>) -> Result<Option<Wishlist>>;
+    async fn get_items(&self, wishlist_id: Uuid) -> Result<Vec<WishlistItem>>;
+    async fn set_item(&self, wishlist_id: Uuid, card_id: Uuid, quantity: i32, priority: i16) -> Result<WishlistItem>;
+    async fn delete_item(&self, wishlist_id: Uuid, card_id: Uuid) -> Result<bool>;
+}
+
+pub struct PgWishlistRepository {
+    pool: PgPool,
+}
+
+impl PgWishlistRepository {
+    pub fn new(pool: PgPool) -> Self {
+        Self { pool }
+    }
+}
+
+#[async_trait]
+impl WishlistRepository for PgWishlistRepository {
+    async fn get_wishlists_by_owner(&self, owner_uid: &str, include_private: bool) -> Result<Vec<Wishlist>> {
+        let wishlists = sqlx::query_as::<_, Wishlist>(
+            r#"
+            SELECT id, owner_uid, name, description, is_public, for_trade, share_token, created_at, updated_at
+            FROM wishlists
+            WHERE owner_uid = $1 AND ($2 OR is_public)
+            ORDER BY created_at
+            "#
+        )
+        .bind(owner_uid)
+        .bind(include_private)
+        .fetch_all(&self.pool)
+        .await?;
+
+        Ok(wishlists)
+    }
+
+    async fn get_wishlist_by_id(&self, id: Uuid) -> Result<Option<Wishlist>> {
+        let wishlist = sqlx::query_as::<_, Wishlist>(
+            r#"
+            SELECT id, owner_uid, name, description, is_public, for_trade, share_token, created_at, updated_at
+            FROM wishlists
+            WHERE id = $1
+            "#
+        )
+        .bind(id)
+        .fetch_optional(&self.pool)
+        .await?;
+
+        Ok(wishlist)
+    }
+
+    async fn get_wishlist_by_share_token(&self, token: &str) -> Result<Option<Wishlist>> {
+        let wishlist = sqlx::query_as::<_, Wishlist>(
+            r#"
+            SELECT id, owner_uid, name, description, is_public, for_trade, share_token, created_at, updated_at
+            FROM wishlists
+            WHERE share_token = $1
+            "#
+        )
+        .bind(token)
+        .fetch_optional(&self.pool)
+        .await?;
+
+        Ok(wishlist)
+    }
+
+    async fn create_wishlist(&self, wishlist: Wishlist) -> Result<Wishlist> {
+        let wishlist = sqlx::query_as::<_, Wishlist>(
+            r#"
+            INSERT INTO wishlists (id, owner_uid, name, description, is_public, for_trade, share_token, created_at, updated_at)
+            VALUES ($1, $2, $3, $4, $5, $6, $7, $8, $9)
+            RETURNING id, owner_uid, name, description, is_public, for_trade, share_token, created_at, updated_at
+            "#
+        )
+        .bind(wishlist.id)
+        .bind(&wishlist.owner_uid)
+        .bind(&wishlist.name)
+        .bind(&wishlist.description)
+        .bind(wishlist.is_public)
+        .bind(wishlist.for_trade)
+        .bind(&wishlist.share_token)
+        .bind(wishlist.created_at)
+        .bind(wishlist.updated_at)
+        .fetch_one(&self.pool)
+        .await?;
+
+        Ok(wishlist)
+    }
+
+    async fn update_wishlist(&self, id: Uuid, name: &str, description: Option<&str>, is_public: bool, for_trade: bool) -> Result<Option<Wishlist>> {
+        let wishlist = sqlx::query_as::<_, Wishlist>(
+            r#"
+            UPDATE wishlists
+            SET name = $1, description = $2, is_public = $3, for_trade = $4, updated_at = NOW()
+            WHERE id = $5
+            RETURNING id, owner_uid, name, description, is_public, for_trade, share_token, created_at, updated_at
+            "#
+        )
+        .bind(name)
+        .bind(description)
+        .bind(is_public)
+        .bind(for_trade)
+        .bind(id)
+        .fetch_optional(&self.pool)
+        .await?;
+
+        Ok(wishlist)
+    }
+
+    async fn delete_wishlist(&self, id: Uuid) -> Result<bool> {
+        let result = sqlx::query("DELETE FROM wishlists WHERE id = $1")
+            .bind(id)
+            .execute(&self.pool)
+            .await?;
+
+        Ok(result.rows_affected() > 0)
+    }
+
+    async fn update_share_token(&self, id: Uuid, token: Option<&str>) -> Result<Option<Wishlist>> {
+        let wishlist = sqlx::query_as::<_, Wishlist>(
+            r#"
+            UPDATE wishlists
+            SET share_token = $1, updated_at = NOW()
+            WHERE id = $2
+            RETURNING id, owner_uid, name, description, is_public, for_trade, share_token, created_at, updated_at
+            "#
+        )
+        .bind(token)
+        .bind(id)
+        .fetch_optional(&self.pool)
+        .await?;
+
+        Ok(wishlist)
+    }
+
+    async fn get_items(&self, wishlist_id: Uuid) -> Result<Vec<WishlistItem>> {
+        let items = sqlx::query_as::<_, WishlistItem>(
+            r#"
+            SELECT i.id, i.wishlist_id, i.card_id, i.quantity, i.priority, i.created_at, i.updated_at
+            FROM wishlist_items i
+            JOIN cards c ON c.id = i.card_id
+            JOIN card_sets s ON s.id = c.set_id
+            WHERE i.wishlist_id = $1
+            ORDER BY i.priority DESC, s.release_date, s.code, c.collector_number
+            "#
+        )
+        .bind(wishlist_id)
+        .fetch_all(&self.pool)
+        .await?;
+
+        Ok(items)
+    }
+
+    async fn set_item(&self, wishlist_id: Uuid, card_id: Uuid, quantity: i32, priority: i16) -> Result<WishlistItem> {
+        let item = sqlx::query_as::<_, WishlistItem>(
+            r#"
+            INSERT INTO wishlist_items (id, wishlist_id, card_id, quantity, priority, created_at, updated_at)
+            VALUES ($1, $2, $3, $4, $5, NOW(), NOW())
+            ON CONFLICT (wishlist_id, card_id) DO UPDATE
+            SET quantity = EXCLUDED.quantity, priority = EXCLUDED.priority, updated_at = NOW()
+            RETURNING id, wishlist_id, card_id, quantity, priority, created_at, updated_at
+            "#
+        )
+        .bind(Uuid::new_v4())
+        .bind(wishlist_id)
+        .bind(card_id)
+        .bind(quantity)
+        .bind(priority)
+        .fetch_one(&self.pool)
+        .await?;
+
+        Ok(item)
+    }
+
+    async fn delete_item(&self, wishlist_id: Uuid, card_id: Uuid) -> Result<bool> {
+        let result = sqlx::query("DELETE FROM wishlist_items WHERE wishlist_id = $1 AND card_id = $2")
+            .bind(wishlist_id)
+            .bind(card_id)
+            .execute(&self.pool)
+            .await?;
+
+        Ok(result.rows_affected() > 0)
+    }
+}
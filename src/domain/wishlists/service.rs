@@ -0,0 +1,83 @@
+use anyhow::{Result, anyhow};
+use ring::rand::{SecureRandom, SystemRandom};
+use uuid::Uuid;
+
+use super::dto::{WishlistDto, WishlistItemDto};
+use super::model::{Wishlist, WishlistDetail, WishlistItem};
+use super::repository::WishlistRepository;
+
+// 192 bits aleatorios: el token es la única autorización para leer una lista compartida
+const SHARE_TOKEN_BYTES: usize = 24;
+
+pub struct WishlistService<R: WishlistRepository> {
+    repository: R,
+    random: SystemRandom,
+}
+
+impl<R: WishlistRepository> WishlistService<R> {
+    pub fn new(repository: R) -> Self {
+        Self { repository, random: SystemRandom::new() }
+    }
+
+    pub async fn get_wishlists_by_owner(&self, owner_uid: &str, include_private: bool) -> Result<Vec<Wishlist>> {
+        self.repository.get_wishlists_by_owner(owner_uid, include_private).await
+    }
+
+    pub async fn get_wishlist_by_id(&self, id: Uuid) -> Result<Option<Wishlist>> {
+        self.repository.get_wishlist_by_id(id).await
+    }
+
+    pub async fn get_wishlist_detail(&self, wishlist: Wishlist) -> Result<WishlistDetail> {
+        let items = self.repository.get_items(wishlist.id).await?;
+        Ok(WishlistDetail { wishlist, items })
+    }
+
+    /// Wishlist behind a share link, without the token itself
+    pub async fn get_shared_wishlist(&self, token: &str) -> Result<Option<WishlistDetail>> {
+        let Some(mut wishlist) = self.repository.get_wishlist_by_share_token(token).await? else {
+            return Ok(None);
+        };
+
+        wishlist.share_token = None;
+        Ok(Some(self.get_wishlist_detail(wishlist).await?))
+    }
+
+    pub async fn create_wishlist(&self, owner_uid: String, dto: &WishlistDto) -> Result<Wishlist> {
+        let wishlist = Wishlist::new(owner_uid, dto.name(), dto.description(), dto.is_public, dto.for_trade);
+        self.repository.create_wishlist(wishlist).await
+    }
+
+    pub async fn update_wishlist(&self, id: Uuid, dto: &WishlistDto) -> Result<Option<Wishlist>> {
+        self.repository
+            .update_wishlist(id, &dto.name(), dto.description().as_deref(), dto.is_public, dto.for_trade)
+            .await
+    }
+
+    pub async fn delete_wishlist(&self, id: Uuid) -> Result<bool> {
+        self.repository.delete_wishlist(id).await
+    }
+
+    /// Creates a new share token; any previous link stops working
+    pub async fn rotate_share_token(&self, id: Uuid) -> Result<Option<Wishlist>> {
+        let mut bytes = [0u8; SHARE_TOKEN_BYTES];
+        self.random
+            .fill(&mut bytes)
+            .map_err(|_| anyhow!("No se pudo generar el token para compartir"))?;
+        let token = base64::encode_config(bytes, base64::URL_SAFE_NO_PAD);
+
+        self.repository.update_share_token(id, Some(&token)).await
+    }
+
+    pub async fn revoke_share_token(&self, id: Uuid) -> Result<Option<Wishlist>> {
+        self.repository.update_share_token(id, None).await
+    }
+
+    /// Adds a card or replaces its quantity and priority
+    pub async fn set_item(&self, wishlist_id: Uuid, card_id: Uuid, dto: &WishlistItemDto) -> Result<WishlistItem> {
+        self.repository.set_item(wishlist_id, card_id, dto.quantity, dto.priority).await
+    }
+
+    pub async fn delete_item(&self, wishlist_id: Uuid, card_id: Uuid) -> Result<bool> {
+        self.repository.delete_item(wishlist_id, card_id).await
+    }
+}
@@ -0,0 +1,267 @@
+use anyhow::{Result, anyhow};
+use image::codecs::avif::AvifEncoder;
+use image::codecs::jpeg::JpegEncoder;
+use image::codecs::png::PngEncoder;
+use image::codecs::webp::WebPEncoder;
+use image::imageops::FilterType;
+use image::metadata::Orientation;
+use image::{DynamicImage, ImageDecoder, ImageFormat, ImageReader};
+use img_parts::{Bytes, DynImage, ImageEXIF};
+use std::io::Cursor;
+
+use crate::config::ImageConfig;
+
+// Tamaño de la imagen a partir de la que se calcula el blurhash; más resolución no mejora el resultado
+const BLURHASH_SAMPLE_SIZE: u32 = 32;
+
+// pHash: DCT sobre una imagen de 32x32 en escala de grises, de la que se usan las 8x8 frecuencias más bajas
+const HASH_SAMPLE_SIZE: usize = 32;
+const HASH_FREQUENCIES: usize = 8;
+
+// Proporción de una carta estándar (63 x 88 mm)
+const CARD_ASPECT_RATIO: f64 = 63.0 / 88.0;
+
+// Detección del fondo de una foto: lado de la muestra, diferencia de gris respecto al fondo
+// y fracción mínima de píxeles distintos para que una fila o columna se considere parte de la carta
+const TRIM_SAMPLE_SIZE: u32 = 128;
+const TRIM_THRESHOLD: i32 = 40;
+const TRIM_MIN_FRACTION: f64 = 0.2;
+
+/// Encoded derivative ready to be stored
+pub struct Derivative {
+    pub kind: &'static str,
+    pub format: &'static str,
+    pub extension: &'static str,
+    pub content_type: &'static str,
+    pub width: u32,
+    pub height: u32,
+    pub data: Vec<u8>,
+}
+
+/// Result of running an uploaded image through the pipeline
+pub struct ProcessedImage {
+    pub width: u32,
+    pub height: u32,
+    pub blurhash: String,
+    pub perceptual_hash: u64,
+    pub derivatives: Vec<Derivative>,
+}
+
+fn decode(data: &[u8]) -> Result<(DynamicImage, ImageFormat, Orientation)> {
+    let reader = ImageReader::new(Cursor::new(data)).with_guessed_format()?;
+    let format = reader.format().ok_or_else(|| anyhow!("Formato de imagen desconocido"))?;
+
+    let mut decoder = reader.into_decoder()?;
+    let orientation = decoder.orientation().unwrap_or(Orientation::NoTransforms);
+    let image = DynamicImage::from_decoder(decoder)?;
+
+    Ok((image, format, orientation))
+}
+
+/// Removes EXIF metadata (camera, GPS...) from an upload before it is stored.
+/// The EXIF orientation is applied to the pixels first so the image still
+/// displays upright; otherwise the file is rewritten without re-encoding.
+pub fn strip_metadata(data: &[u8]) -> Result<Vec<u8>> {
+    let (mut image, format, orientation) = decode(data)?;
+
+    if orientation == Orientation::NoTransforms {
+        let mut container = DynImage::from_bytes(Bytes::copy_from_slice(data))?
+            .ok_or_else(|| anyhow!("Formato de imagen desconocido"))?;
+        container.set_exif(None);
+        return Ok(container.encoder().bytes().to_vec());
+    }
+
+    image.apply_orientation(orientation);
+
+    let mut output = Vec::new();
+    match format {
+        ImageFormat::Jpeg => image.to_rgb8().write_with_encoder(JpegEncoder::new_with_quality(&mut output, 92))?,
+        ImageFormat::Png => image.write_with_encoder(PngEncoder::new(&mut output))?,
+        ImageFormat::WebP => image.to_rgba8().write_with_encoder(WebPEncoder::new_lossless(&mut output))?,
+        other => return Err(anyhow!("Formato de imagen no soportado: {:?}", other)),
+    }
+
+    Ok(output)
+}
+
+/// Computes dimensions and blurhash and encodes the configured derivatives.
+/// CPU-bound: call it from a blocking task.
+pub fn process_image(data: &[u8], config: &ImageConfig) -> Result<ProcessedImage> {
+    let (mut image, _, orientation) = decode(data)?;
+    image.apply_orientation(orientation);
+
+    let (width, height) = (image.width(), image.height());
+    let mut derivatives = Vec::new();
+
+    // Miniatura en JPEG, que cualquier cliente puede mostrar
+    let thumbnail = image.thumbnail(config.thumbnail_width.min(width), u32::MAX);
+    let mut data = Vec::new();
+    thumbnail
+        .to_rgb8()
+        .write_with_encoder(JpegEncoder::new_with_quality(&mut data, config.quality))?;
+    derivatives.push(Derivative {
+        kind: "thumbnail",
+        format: "jpeg",
+        extension: "jpg",
+        content_type: "image/jpeg",
+        width: thumbnail.width(),
+        height: thumbnail.height(),
+        data,
+    });
+
+    // No se generan variantes más anchas que el original
+    for &target_width in config.variant_widths.iter().filter(|target_width| **target_width <= width) {
+        let resized = image.resize(target_width, u32::MAX, FilterType::Lanczos3);
+
+        for format in &config.variant_formats {
+            derivatives.push(encode_variant(&resized, format, config)?);
+        }
+    }
+
+    Ok(ProcessedImage {
+        width,
+        height,
+        blurhash: blurhash_for(&image)?,
+        perceptual_hash: hash_image(&image),
+        derivatives,
+    })
+}
+
+fn encode_variant(image: &DynamicImage, format: &str, config: &ImageConfig) -> Result<Derivative> {
+    let mut data = Vec::new();
+    let rgba = image.to_rgba8();
+
+    let (format, extension, content_type) = match format {
+        // El codificador WebP disponible sin dependencias nativas sólo es sin pérdida
+        "webp" => {
+            rgba.write_with_encoder(WebPEncoder::new_lossless(&mut data))?;
+            ("webp", "webp", "image/webp")
+        }
+        "avif" => {
+            rgba.write_with_encoder(AvifEncoder::new_with_speed_quality(&mut data, config.avif_speed, config.quality))?;
+            ("avif", "avif", "image/avif")
+        }
+        other => return Err(anyhow!("Formato de variante no soportado: {}", other)),
+    };
+
+    Ok(Derivative {
+        kind: "resized",
+        format,
+        extension,
+        content_type,
+        width: image.width(),
+        height: image.height(),
+        data,
+    })
+}
+
+fn blurhash_for(image: &DynamicImage) -> Result<String> {
+    let sample = image.thumbnail(BLURHASH_SAMPLE_SIZE, BLURHASH_SAMPLE_SIZE).to_rgba8();
+
+    // Más componentes en el eje largo: las cartas son verticales y los íconos suelen ser cuadrados
+    let (components_x, components_y) = if sample.height() > sample.width() { (3, 4) } else { (4, 3) };
+
+    blurhash::encode(components_x, components_y, sample.width(), sample.height(), sample.as_raw())
+        .map_err(|e| anyhow!("No se pudo calcular el blurhash: {:?}", e))
+}
+
+/// Perceptual hash of an encoded image (a reference scan or a photo of a card).
+/// CPU-bound: call it from a blocking task.
+pub fn perceptual_hash(data: &[u8]) -> Result<u64> {
+    let (mut image, _, orientation) = decode(data)?;
+    image.apply_orientation(orientation);
+
+    Ok(hash_image(&image))
+}
+
+/// Number of differing bits between two hashes
+pub fn hamming_distance(a: u64, b: u64) -> u32 {
+    (a ^ b).count_ones()
+}
+
+// Normaliza la imagen antes del hash: sin el fondo que rodea a la carta, recortada a su proporción,
+// en escala de grises y con tamaño fijo, para que escaneos y fotos sean comparables
+fn hash_image(image: &DynamicImage) -> u64 {
+    let image = &trim_background(image);
+    let (width, height) = (image.width() as f64, image.height() as f64);
+    let cropped = if width / height > CARD_ASPECT_RATIO {
+        let crop_width = (height * CARD_ASPECT_RATIO).round() as u32;
+        image.crop_imm((image.width() - crop_width) / 2, 0, crop_width, image.height())
+    } else {
+        let crop_height = (width / CARD_ASPECT_RATIO).round() as u32;
+        image.crop_imm(0, (image.height() - crop_height) / 2, image.width(), crop_height)
+    };
+
+    let sample = cropped
+        .resize_exact(HASH_SAMPLE_SIZE as u32, HASH_SAMPLE_SIZE as u32, FilterType::Triangle)
+        .to_luma8();
+    let pixels: Vec<f64> = sample.pixels().map(|pixel| pixel.0[0] as f64).collect();
+
+    // DCT-II bidimensional, calculada sólo para las frecuencias que se usan
+    let mut coefficients = Vec::with_capacity(HASH_FREQUENCIES * HASH_FREQUENCIES);
+    for u in 0..HASH_FREQUENCIES {
+        for v in 0..HASH_FREQUENCIES {
+            let mut sum = 0.0;
+            for y in 0..HASH_SAMPLE_SIZE {
+                for x in 0..HASH_SAMPLE_SIZE {
+                    sum += pixels[y * HASH_SAMPLE_SIZE + x]
+                        * dct_basis(u, y)
+                        * dct_basis(v, x);
+                }
+            }
+            coefficients.push(sum);
+        }
+    }
+
+    // El coeficiente DC (brillo medio) no se usa para la mediana: haría el hash sensible a la exposición
+    let mut sorted: Vec<f64> = coefficients[1..].to_vec();
+    sorted.sort_by(|a, b| a.total_cmp(b));
+    let median = sorted[sorted.len() / 2];
+
+    coefficients
+        .iter()
+        .enumerate()
+        .filter(|(_, coefficient)| **coefficient > median)
+        .fold(0u64, |hash, (bit, _)| hash | (1 << bit))
+}
+
+// Recorta el fondo uniforme alrededor de la carta, estimado a partir del color de los bordes
+fn trim_background(image: &DynamicImage) -> DynamicImage {
+    let sample = image.thumbnail(TRIM_SAMPLE_SIZE, TRIM_SAMPLE_SIZE).to_luma8();
+    let (width, height) = sample.dimensions();
+    if width < 3 || height < 3 {
+        return image.clone();
+    }
+
+    let mut border: Vec<u8> = (0..width)
+        .flat_map(|x| [sample.get_pixel(x, 0).0[0], sample.get_pixel(x, height - 1).0[0]])
+        .chain((0..height).flat_map(|y| [sample.get_pixel(0, y).0[0], sample.get_pixel(width - 1, y).0[0]]))
+        .collect();
+    border.sort_unstable();
+    let background = border[border.len() / 2] as i32;
+
+    let differs = |x: u32, y: u32| (sample.get_pixel(x, y).0[0] as i32 - background).abs() > TRIM_THRESHOLD;
+    let is_content_row = |y: u32| (0..width).filter(|&x| differs(x, y)).count() as f64 > width as f64 * TRIM_MIN_FRACTION;
+    let is_content_column = |x: u32| (0..height).filter(|&y| differs(x, y)).count() as f64 > height as f64 * TRIM_MIN_FRACTION;
+
+    let (Some(top), Some(bottom)) = ((0..height).find(|&y| is_content_row(y)), (0..height).rfind(|&y| is_content_row(y))) else {
+        return image.clone();
+    };
+    let (Some(left), Some(right)) = ((0..width).find(|&x| is_content_column(x)), (0..width).rfind(|&x| is_content_column(x))) else {
+        return image.clone();
+    };
+
+    // Se vuelve a la escala original
+    let scale_x = image.width() as f64 / width as f64;
+    let scale_y = image.height() as f64 / height as f64;
+    let x = (left as f64 * scale_x) as u32;
+    let y = (top as f64 * scale_y) as u32;
+    let crop_width = (((right + 1) as f64 * scale_x) as u32).min(image.width()) - x;
+    let crop_height = (((bottom + 1) as f64 * scale_y) as u32).min(image.height()) - y;
+
+    image.crop_imm(x, y, crop_width, crop_height)
+}
+
+fn dct_basis(frequency: usize, position: usize) -> f64 {
+    (std::f64::consts::PI * (2 * position + 1) as f64 * frequency as f64 / (2 * HASH_SAMPLE_SIZE) as f64).cos()
+}
@@ -1,3 +1,5 @@
 pub mod database;
+pub mod images;
+pub mod storage;
 
-pub use database::*; 
\ No newline at end of file
+pub use database::*;
\ No newline at end of file
@@ -0,0 +1,269 @@
+use anyhow::{Result, anyhow};
+use async_trait::async_trait;
+use chrono::Utc;
+use reqwest::{Client, Url};
+use ring::{digest, hmac};
+use std::path::{Component, Path, PathBuf};
+use std::sync::Arc;
+
+use crate::config::{S3Config, StorageConfig};
+
+/// Where uploaded files live. Keys are relative, '/'-separated paths such as
+/// `card-sets/<id>/icon-<uuid>.png`.
+#[async_trait]
+pub trait Storage: Send + Sync {
+    /// Stores the object and returns its public URL
+    async fn put(&self, key: &str, content_type: &str, data: Vec<u8>) -> Result<String>;
+    async fn delete(&self, key: &str) -> Result<()>;
+    /// Reads an object, or `None` if it does not exist
+    async fn get(&self, key: &str) -> Result<Option<Vec<u8>>>;
+    fn public_url(&self, key: &str) -> String;
+}
+
+pub fn storage_from_config(config: &StorageConfig) -> Result<Arc<dyn Storage>> {
+    match config.backend.as_str() {
+        "local" => Ok(Arc::new(LocalStorage::new(&config.local_path, &config.public_url))),
+        "s3" => {
+            let s3 = config
+                .s3
+                .as_ref()
+                .ok_or_else(|| anyhow!("Falta la configuración de S3"))?;
+            Ok(Arc::new(S3Storage::new(s3.clone(), &config.public_url)?))
+        }
+        other => Err(anyhow!("Backend de almacenamiento desconocido: {}", other)),
+    }
+}
+
+/// Storage for private per-user files. It never has a public URL: files are
+/// only served through signed URLs, so the local directory is not exposed
+/// under `/media` and S3 uses its own bucket.
+pub fn private_storage_from_config(config: &StorageConfig) -> Result<Arc<dyn Storage>> {
+    match config.backend.as_str() {
+        "local" => Ok(Arc::new(LocalStorage::new(&config.private_local_path, ""))),
+        "s3" => {
+            let mut s3 = config
+                .s3
+                .clone()
+                .ok_or_else(|| anyhow!("Falta la configuración de S3"))?;
+            s3.bucket = s3.private_bucket.clone();
+            Ok(Arc::new(S3Storage::new(s3, "")?))
+        }
+        other => Err(anyhow!("Backend de almacenamiento desconocido: {}", other)),
+    }
+}
+
+// Evita que una clave se salga del directorio raíz (por ejemplo con "..")
+fn validate_key(key: &str) -> Result<()> {
+    let is_safe = !key.is_empty()
+        && Path::new(key)
+            .components()
+            .all(|component| matches!(component, Component::Normal(_)));
+
+    if is_safe {
+        Ok(())
+    } else {
+        Err(anyhow!("Clave de almacenamiento inválida: {}", key))
+    }
+}
+
+// Almacenamiento en el sistema de archivos local; el servidor sirve los archivos bajo /media
+pub struct LocalStorage {
+    root: PathBuf,
+    public_url: String,
+}
+
+impl LocalStorage {
+    pub fn new(root: &str, public_url: &str) -> Self {
+        Self {
+            root: PathBuf::from(root),
+            public_url: public_url.trim_end_matches('/').to_string(),
+        }
+    }
+
+    fn path_for(&self, key: &str) -> Result<PathBuf> {
+        validate_key(key)?;
+        Ok(self.root.join(key))
+    }
+}
+
+#[async_trait]
+impl Storage for LocalStorage {
+    async fn put(&self, key: &str, _content_type: &str, data: Vec<u8>) -> Result<String> {
+        let path = self.path_for(key)?;
+
+        if let Some(parent) = path.parent() {
+            tokio::fs::create_dir_all(parent).await?;
+        }
+        tokio::fs::write(&path, data).await?;
+
+        Ok(self.public_url(key))
+    }
+
+    async fn delete(&self, key: &str) -> Result<()> {
+        match tokio::fs::remove_file(self.path_for(key)?).await {
+            Ok(()) => Ok(()),
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => Ok(()),
+            Err(e) => Err(e.into()),
+        }
+    }
+
+    async fn get(&self, key: &str) -> Result<Option<Vec<u8>>> {
+        match tokio::fs::read(self.path_for(key)?).await {
+            Ok(data) => Ok(Some(data)),
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => Ok(None),
+            Err(e) => Err(e.into()),
+        }
+    }
+
+    fn public_url(&self, key: &str) -> String {
+        format!("{}/{}", self.public_url, key)
+    }
+}
+
+// Almacenamiento compatible con S3 (AWS, MinIO, R2...) firmado con AWS Signature V4
+pub struct S3Storage {
+    config: S3Config,
+    endpoint: Url,
+    public_url: String,
+    client: Client,
+}
+
+impl S3Storage {
+    pub fn new(config: S3Config, public_url: &str) -> Result<Self> {
+        let endpoint = Url::parse(&config.endpoint)
+            .map_err(|e| anyhow!("S3_ENDPOINT inválido: {}", e))?;
+
+        let client = Client::builder()
+            .use_rustls_tls()
+            .build()
+            .map_err(|e| anyhow!("Failed to create HTTP client: {}", e))?;
+
+        Ok(Self {
+            config,
+            endpoint,
+            public_url: public_url.trim_end_matches('/').to_string(),
+            client,
+        })
+    }
+
+    // Se usa el estilo de ruta (endpoint/bucket/clave), soportado por todos los proveedores compatibles
+    fn object_path(&self, key: &str) -> String {
+        let encoded_key = key.split('/').map(uri_encode).collect::<Vec<_>>().join("/");
+        format!("{}/{}/{}", self.endpoint.path().trim_end_matches('/'), uri_encode(&self.config.bucket), encoded_key)
+    }
+
+    async fn send(&self, method: reqwest::Method, key: &str, content_type: Option<&str>, body: Vec<u8>) -> Result<reqwest::Response> {
+        validate_key(key)?;
+
+        let path = self.object_path(key);
+        let host = match (self.endpoint.host_str(), self.endpoint.port()) {
+            (Some(host), Some(port)) => format!("{}:{}", host, port),
+            (Some(host), None) => host.to_string(),
+            (None, _) => return Err(anyhow!("S3_ENDPOINT no tiene host")),
+        };
+
+        let now = Utc::now();
+        let amz_date = now.format("%Y%m%dT%H%M%SZ").to_string();
+        let date = now.format("%Y%m%d").to_string();
+        let payload_hash = hex::encode(digest::digest(&digest::SHA256, &body));
+
+        let canonical_request = format!(
+            "{}\n{}\n\nhost:{}\nx-amz-content-sha256:{}\nx-amz-date:{}\n\nhost;x-amz-content-sha256;x-amz-date\n{}",
+            method.as_str(),
+            path,
+            host,
+            payload_hash,
+            amz_date,
+            payload_hash,
+        );
+
+        let scope = format!("{}/{}/s3/aws4_request", date, self.config.region);
+        let string_to_sign = format!(
+            "AWS4-HMAC-SHA256\n{}\n{}\n{}",
+            amz_date,
+            scope,
+            hex::encode(digest::digest(&digest::SHA256, canonical_request.as_bytes())),
+        );
+
+        let signing_key = [date.as_str(), self.config.region.as_str(), "s3", "aws4_request"]
+            .iter()
+            .fold(format!("AWS4{}", self.config.secret_access_key).into_bytes(), |key, part| {
+                hmac::sign(&hmac::Key::new(hmac::HMAC_SHA256, &key), part.as_bytes())
+                    .as_ref()
+                    .to_vec()
+            });
+        let signature = hex::encode(hmac::sign(
+            &hmac::Key::new(hmac::HMAC_SHA256, &signing_key),
+            string_to_sign.as_bytes(),
+        ));
+
+        let authorization = format!(
+            "AWS4-HMAC-SHA256 Credential={}/{}, SignedHeaders=host;x-amz-content-sha256;x-amz-date, Signature={}",
+            self.config.access_key_id, scope, signature,
+        );
+
+        let mut url = self.endpoint.clone();
+        url.set_path(&path);
+
+        let mut request = self
+            .client
+            .request(method, url)
+            .header("x-amz-date", amz_date)
+            .header("x-amz-content-sha256", payload_hash)
+            .header("authorization", authorization);
+
+        if let Some(content_type) = content_type {
+            request = request.header("content-type", content_type);
+        }
+
+        Ok(request.body(body).send().await?)
+    }
+}
+
+async fn ensure_success(response: reqwest::Response) -> Result<reqwest::Response> {
+    if !response.status().is_success() {
+        let status = response.status();
+        let body = response.text().await.unwrap_or_default();
+        return Err(anyhow!("Error de S3 ({}): {}", status, body));
+    }
+
+    Ok(response)
+}
+
+#[async_trait]
+impl Storage for S3Storage {
+    async fn put(&self, key: &str, content_type: &str, data: Vec<u8>) -> Result<String> {
+        ensure_success(self.send(reqwest::Method::PUT, key, Some(content_type), data).await?).await?;
+        Ok(self.public_url(key))
+    }
+
+    async fn delete(&self, key: &str) -> Result<()> {
+        ensure_success(self.send(reqwest::Method::DELETE, key, None, Vec::new()).await?).await?;
+        Ok(())
+    }
+
+    async fn get(&self, key: &str) -> Result<Option<Vec<u8>>> {
+        let response = self.send(reqwest::Method::GET, key, None, Vec::new()).await?;
+        if response.status() == reqwest::StatusCode::NOT_FOUND {
+            return Ok(None);
+        }
+
+        let data = ensure_success(response).await?.bytes().await?;
+        Ok(Some(data.to_vec()))
+    }
+
+    fn public_url(&self, key: &str) -> String {
+        format!("{}/{}", self.public_url, key)
+    }
+}
+
+// Codificación de URI de SigV4: sólo los caracteres no reservados quedan sin escapar
+fn uri_encode(segment: &str) -> String {
+    segment
+        .bytes()
+        .map(|byte| match byte {
+            b'A'..=b'Z' | b'a'..=b'z' | b'0'..=b'9' | b'-' | b'_' | b'.' | b'~' => (byte as char).to_string(),
+            _ => format!("%{:02X}", byte),
+        })
+        .collect()
+}
@@ -1,4 +1,5 @@
 mod api;
+mod cli;
 mod config;
 mod domain;
 mod infrastructure;
@@ -22,6 +23,12 @@ async fn main() -> Result<(), Box<dyn std::error::Error>> {
         .with(tracing_subscriber::fmt::layer())
         .init();
 
+    // Subcommands (catalog import/export) run without starting the server
+    let args: Vec<String> = std::env::args().skip(1).collect();
+    if !args.is_empty() {
+        return cli::run(args).await;
+    }
+
     // Load configuration
     let config = config::Config::from_env()
         .expect("Failed to load configuration from environment");
@@ -31,8 +38,13 @@ async fn main() -> Result<(), Box<dyn std::error::Error>> {
         .await
         .expect("Failed to initialize database");
 
+    // Verificación de tokens de Firebase (emulador o claves públicas de Google)
+    let firebase_auth = config::firebase::FirebaseAuth::new(config.firebase.clone())
+        .await
+        .expect("Failed to initialize Firebase Auth");
+
     // Build our application con rutas completas
-    let app = api::create_router_with_db(pool);
+    let app = api::create_router_with_db(pool, &config, firebase_auth);
 
     // Create a listener using either listenfd (for hot reloading) or a new TcpListener
     let mut listenfd = ListenFd::from_env();
@@ -0,0 +1,98 @@
+use axum::{
+    body::Body,
+    http::{header, HeaderMap, HeaderValue, StatusCode},
+    response::{IntoResponse, Response},
+};
+use chrono::{DateTime, Utc};
+use ring::digest::{digest, SHA256};
+use serde::Serialize;
+
+use crate::utils::response::ApiResponse;
+
+/// Cache-Control for responses that depend on who is asking
+pub const PRIVATE_CACHE_CONTROL: &str = "private, no-cache";
+
+/// Builds a 200 response with strong `ETag`, `Last-Modified` and `Cache-Control`
+/// headers, or a bodiless 304 when the client's cached copy is still valid.
+pub fn conditional_response<T: Serialize>(
+    headers: &HeaderMap,
+    data: T,
+    last_modified: Option<DateTime<Utc>>,
+    cache_control: &str,
+) -> Response {
+    let response = ApiResponse::ok(data);
+    let body = match serde_json::to_vec(&response.body()) {
+        Ok(body) => body,
+        Err(e) => return ApiResponse::<()>::internal_error(e.to_string()).into_response(),
+    };
+
+    let etag = strong_etag(&body);
+    let not_modified = is_not_modified(headers, &etag, last_modified);
+
+    let mut builder = Response::builder()
+        .status(if not_modified { StatusCode::NOT_MODIFIED } else { StatusCode::OK })
+        .header(header::ETAG, &etag)
+        // El contenido del catálogo depende del usuario (conjuntos no publicados)
+        .header(header::VARY, "Authorization");
+
+    if let Ok(value) = HeaderValue::from_str(cache_control) {
+        builder = builder.header(header::CACHE_CONTROL, value);
+    }
+
+    if let Some(last_modified) = last_modified {
+        builder = builder.header(header::LAST_MODIFIED, http_date(last_modified));
+    }
+
+    let result = if not_modified {
+        builder.body(Body::empty())
+    } else {
+        builder
+            .header(header::CONTENT_TYPE, "application/json")
+            .body(Body::from(body))
+    };
+
+    result.unwrap_or_else(|e| ApiResponse::<()>::internal_error(e.to_string()).into_response())
+}
+
+/// Strong entity tag derived from the exact bytes of the response body
+fn strong_etag(body: &[u8]) -> String {
+    let hash = digest(&SHA256, body);
+    format!("\"{}\"", base64::encode_config(hash.as_ref(), base64::URL_SAFE_NO_PAD))
+}
+
+// Según RFC 9110, If-None-Match tiene prioridad y, si está presente, If-Modified-Since se ignora
+fn is_not_modified(headers: &HeaderMap, etag: &str, last_modified: Option<DateTime<Utc>>) -> bool {
+    if let Some(if_none_match) = headers.get(header::IF_NONE_MATCH) {
+        return if_none_match
+            .to_str()
+            .map(|value| etag_matches(value, etag))
+            .unwrap_or(false);
+    }
+
+    let (Some(last_modified), Some(if_modified_since)) = (last_modified, headers.get(header::IF_MODIFIED_SINCE)) else {
+        return false;
+    };
+
+    match if_modified_since.to_str().ok().and_then(parse_http_date) {
+        // Las fechas HTTP tienen precisión de segundos
+        Some(since) => last_modified.timestamp() <= since.timestamp(),
+        None => false,
+    }
+}
+
+// If-None-Match usa comparación débil: se ignora el prefijo W/
+fn etag_matches(if_none_match: &str, etag: &str) -> bool {
+    if_none_match.split(',').map(str::trim).any(|candidate| {
+        candidate == "*" || candidate.strip_prefix("W/").unwrap_or(candidate) == etag
+    })
+}
+
+fn http_date(date: DateTime<Utc>) -> String {
+    date.format("%a, %d %b %Y %H:%M:%S GMT").to_string()
+}
+
+fn parse_http_date(value: &str) -> Option<DateTime<Utc>> {
+    DateTime::parse_from_rfc2822(value)
+        .ok()
+        .map(|date| date.with_timezone(&Utc))
+}
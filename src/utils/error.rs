@@ -6,6 +6,13 @@ use thiserror::Error;
 
 use crate::utils::response::ApiResponse;
 
+// Restricciones únicas conocidas y el campo de la API que representan
+const UNIQUE_CONSTRAINT_FIELDS: &[(&str, &str)] = &[
+    ("card_sets_code_unique_idx", "code"),
+    ("card_sets_slug_key", "slug"),
+    ("cards_set_id_collector_number_key", "collector_number"),
+];
+
 #[derive(Error, Debug)]
 pub enum AppError {
     #[error("Authentication error: {0}")]
@@ -52,3 +59,21 @@ impl IntoResponse for AppError {
         ApiResponse::<()>::error(error_message, status, Some(status.as_u16())).into_response()
     }
 }
+
+/// Returns the API field behind a unique-constraint violation, if the error is one
+pub fn unique_violation_field(error: &anyhow::Error) -> Option<String> {
+    let database_error = error.downcast_ref::<sqlx::Error>()?.as_database_error()?;
+
+    if !database_error.is_unique_violation() {
+        return None;
+    }
+
+    let constraint = database_error.constraint().unwrap_or_default();
+    let field = UNIQUE_CONSTRAINT_FIELDS
+        .iter()
+        .find(|(name, _)| *name == constraint)
+        .map(|(_, field)| field.to_string())
+        .unwrap_or_else(|| constraint.to_string());
+
+    Some(field)
+}
@@ -1,14 +1,19 @@
 use axum::{
     async_trait,
-    extract::{FromRequest, Request},
-    http::StatusCode,
+    extract::{FromRef, FromRequest, FromRequestParts, Request},
+    http::{header, request::Parts, StatusCode},
     Json,
 };
 use serde::de::DeserializeOwned;
-use serde_json::{json, Value};
+use serde_json::Value;
 
+use crate::config::firebase::{FirebaseAuth, FirebaseClaims};
+use crate::utils::error::AppError;
 use crate::utils::response::{ApiResponse, validation_error};
 
+// Roles con acceso al contenido interno del catálogo
+const STAFF_ROLES: &[&str] = &["admin", "staff", "moderator"];
+
 // Extractor personalizado para JSON
 pub struct ValidatedJson<T>(pub T);
 
@@ -72,4 +77,70 @@ fn extract_field_name(message: &str) -> &str {
         }
     }
     "desconocido"
-} 
\ No newline at end of file
+}
+
+// Usuario autenticado a partir del token de Firebase de la cabecera Authorization
+#[derive(Debug, Clone)]
+pub struct AuthUser {
+    pub uid: String,
+    pub role: Option<String>,
+    pub sign_in_provider: Option<String>,
+}
+
+impl AuthUser {
+    pub fn is_staff(&self) -> bool {
+        self.role
+            .as_deref()
+            .map(|role| STAFF_ROLES.contains(&role))
+            .unwrap_or(false)
+    }
+
+    pub fn is_admin(&self) -> bool {
+        self.role.as_deref() == Some("admin")
+    }
+
+    // Sesiones de invitado (`signInAnonymously`): pueden leer pero no crear contenido
+    pub fn is_anonymous(&self) -> bool {
+        self.sign_in_provider.as_deref() == Some("anonymous")
+    }
+
+    // Los administradores también pueden gradar
+    pub fn is_grader(&self) -> bool {
+        self.role.as_deref() == Some("grader") || self.is_admin()
+    }
+}
+
+impl From<FirebaseClaims> for AuthUser {
+    fn from(claims: FirebaseClaims) -> Self {
+        Self {
+            uid: claims.sub,
+            role: claims.role,
+            sign_in_provider: claims.firebase.and_then(|firebase| firebase.sign_in_provider),
+        }
+    }
+}
+
+#[async_trait]
+impl<S> FromRequestParts<S> for AuthUser
+where
+    FirebaseAuth: FromRef<S>,
+    S: Send + Sync,
+{
+    type Rejection = AppError;
+
+    async fn from_request_parts(parts: &mut Parts, state: &S) -> Result<Self, Self::Rejection> {
+        let authorization = parts
+            .headers
+            .get(header::AUTHORIZATION)
+            .and_then(|value| value.to_str().ok())
+            .ok_or_else(|| AppError::Authentication("Falta la cabecera Authorization".to_string()))?;
+
+        let token = authorization
+            .strip_prefix("Bearer ")
+            .ok_or_else(|| AppError::Authentication("La cabecera Authorization debe usar el esquema Bearer".to_string()))?;
+
+        let claims = FirebaseAuth::from_ref(state).verify_token(token.trim()).await?;
+
+        Ok(AuthUser::from(claims))
+    }
+}
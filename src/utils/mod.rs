@@ -1,6 +1,9 @@
 pub mod error;
 pub mod response;
 pub mod extractors;
+pub mod cache;
+pub mod slug;
+pub mod signed_url;
 
 pub use error::AppError;
 pub use response::{ApiResponse, json_response, list_response};
@@ -6,6 +6,8 @@ use axum::{
 use serde::{Deserialize, Serialize};
 use serde_json::{json, Value};
 
+use crate::utils::error::unique_violation_field;
+
 /// Standardized API response structure
 #[derive(Debug, Serialize, Deserialize)]
 pub struct ApiResponse<T> {
@@ -53,6 +55,16 @@ impl<T> ApiResponse<T> {
         Self::success(data, StatusCode::CREATED)
     }
 
+    /// Create an error response that still carries data, such as a detailed report
+    pub fn error_with_data(data: T, message: String, status_code: StatusCode) -> Self {
+        Self {
+            status_code: status_code.as_u16(),
+            message: Some(message),
+            error: Some(status_code.as_u16()),
+            data: Some(data),
+        }
+    }
+
     /// Create a not found error response
     pub fn not_found(message: String) -> Self {
         Self::error(message, StatusCode::NOT_FOUND, Some(404))
@@ -63,6 +75,11 @@ impl<T> ApiResponse<T> {
         Self::error(message, StatusCode::BAD_REQUEST, Some(400))
     }
 
+    /// Create a conflict error response
+    pub fn conflict(message: String) -> Self {
+        Self::error(message, StatusCode::CONFLICT, Some(409))
+    }
+
     /// Create an internal server error response
     pub fn internal_error(message: String) -> Self {
         Self::error(message, StatusCode::INTERNAL_SERVER_ERROR, Some(500))
@@ -79,15 +96,22 @@ impl<T> ApiResponse<T> {
     }
 }
 
-impl<T: Serialize> IntoResponse for ApiResponse<T> {
-    fn into_response(self) -> Response {
-        let status_code = StatusCode::from_u16(self.status_code).unwrap_or(StatusCode::INTERNAL_SERVER_ERROR);
-        let json = Json(json!({
+impl<T: Serialize> ApiResponse<T> {
+    /// JSON body sent to the client for this response
+    pub fn body(&self) -> Value {
+        json!({
             "status_code": self.status_code,
             "message": self.message,
             "error": self.error,
             "data": self.data,
-        }));
+        })
+    }
+}
+
+impl<T: Serialize> IntoResponse for ApiResponse<T> {
+    fn into_response(self) -> Response {
+        let status_code = StatusCode::from_u16(self.status_code).unwrap_or(StatusCode::INTERNAL_SERVER_ERROR);
+        let json = Json(self.body());
 
         (status_code, json).into_response()
     }
@@ -113,3 +137,11 @@ pub fn error_response<T>(message: String, status_code: u16) -> ApiResponse<T> {
 pub fn validation_error<T>(message: String, details: Option<Value>) -> ApiResponse<T> {
     ApiResponse::<T>::bad_request(message)
 }
+
+/// Helper function to map a service error, turning unique violations into 409 Conflict
+pub fn service_error_response<T>(error: anyhow::Error) -> ApiResponse<T> {
+    match unique_violation_field(&error) {
+        Some(field) => ApiResponse::conflict(format!("Ya existe un registro con el mismo valor en el campo '{}'", field)),
+        None => error_response(error.to_string(), 500),
+    }
+}
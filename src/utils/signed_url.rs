@@ -0,0 +1,131 @@
+use chrono::{DateTime, TimeZone, Utc};
+use ring::hmac;
+
+/// Signs and verifies time-limited URLs with HMAC-SHA256. The signature covers
+/// the path and the expiry, so neither can be changed without invalidating it.
+#[derive(Clone)]
+pub struct UrlSigner {
+    key: hmac::Key,
+    ttl_secs: i64,
+}
+
+/// Signed URL with the moment it stops working
+pub struct SignedUrl {
+    pub url: String,
+    pub expires_at: DateTime<Utc>,
+}
+
+impl UrlSigner {
+    pub fn new(secret: &str, ttl_secs: i64) -> Self {
+        Self {
+            key: hmac::Key::new(hmac::HMAC_SHA256, secret.as_bytes()),
+            ttl_secs,
+        }
+    }
+
+    /// Signs `path` (e.g. `/api/v1/files/<id>`) with the configured lifetime
+    pub fn sign(&self, path: &str) -> SignedUrl {
+        let expires = Utc::now().timestamp() + self.ttl_secs;
+        let signature = hmac::sign(&self.key, Self::message(path, expires).as_bytes());
+
+        SignedUrl {
+            url: format!(
+                "{}?expires={}&signature={}",
+                path,
+                expires,
+                base64::encode_config(signature.as_ref(), base64::URL_SAFE_NO_PAD),
+            ),
+            expires_at: Utc.timestamp_opt(expires, 0).single().unwrap_or_else(Utc::now),
+        }
+    }
+
+    /// Checks the signature in constant time and that the URL has not expired
+    pub fn verify(&self, path: &str, expires: i64, signature: &str) -> bool {
+        if expires < Utc::now().timestamp() {
+            return false;
+        }
+
+        let Ok(signature) = base64::decode_config(signature, base64::URL_SAFE_NO_PAD) else {
+            return false;
+        };
+
+        hmac::verify(&self.key, Self::message(path, expires).as_bytes(), &signature).is_ok()
+    }
+
+    fn message(path: &str, expires: i64) -> String {
+        format!("{}\n{}", path, expires)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const PATH: &str = "/api/v1/files/0b4f7c1e-8a1d-4f57-9d3e-5f0c2a9b7e11";
+
+    // Separa una URL firmada en la ruta, la expiración y la firma
+    fn parts(signed: &SignedUrl) -> (String, i64, String) {
+        let (path, query) = signed.url.split_once('?').unwrap();
+        let (expires, signature) = query.split_once('&').unwrap();
+        (
+            path.to_string(),
+            expires.strip_prefix("expires=").unwrap().parse().unwrap(),
+            signature.strip_prefix("signature=").unwrap().to_string(),
+        )
+    }
+
+    #[test]
+    fn accepts_its_own_signature_until_it_expires() {
+        let signer = UrlSigner::new("secreto", 300);
+        let signed = signer.sign(PATH);
+        let (path, expires, signature) = parts(&signed);
+
+        assert_eq!(path, PATH);
+        assert_eq!(signed.expires_at.timestamp(), expires);
+        assert!(expires > Utc::now().timestamp());
+        assert!(signer.verify(&path, expires, &signature));
+    }
+
+    #[test]
+    fn rejects_a_tampered_path_or_expiry() {
+        let signer = UrlSigner::new("secreto", 300);
+        let (path, expires, signature) = parts(&signer.sign(PATH));
+
+        assert!(!signer.verify("/api/v1/files/otro", expires, &signature));
+        assert!(!signer.verify(&format!("{}/", path), expires, &signature));
+        assert!(!signer.verify(&path, expires + 3600, &signature));
+        assert!(!signer.verify(&path, expires - 1, &signature));
+    }
+
+    #[test]
+    fn rejects_a_tampered_or_malformed_signature() {
+        let signer = UrlSigner::new("secreto", 300);
+        let (path, expires, signature) = parts(&signer.sign(PATH));
+
+        let mut tampered = signature.clone().into_bytes();
+        tampered[0] = if tampered[0] == b'A' { b'B' } else { b'A' };
+        assert!(!signer.verify(&path, expires, std::str::from_utf8(&tampered).unwrap()));
+        assert!(!signer.verify(&path, expires, &signature[..signature.len() - 4]));
+        assert!(!signer.verify(&path, expires, "no es base64!"));
+        assert!(!signer.verify(&path, expires, ""));
+    }
+
+    #[test]
+    fn rejects_an_expired_url() {
+        let signer = UrlSigner::new("secreto", -1);
+        let (path, expires, signature) = parts(&signer.sign(PATH));
+
+        assert!(expires < Utc::now().timestamp());
+        assert!(!signer.verify(&path, expires, &signature));
+    }
+
+    #[test]
+    fn rejects_a_signature_made_with_another_secret() {
+        let signer = UrlSigner::new("secreto", 300);
+        let other = UrlSigner::new("otro secreto", 300);
+        let (path, expires, signature) = parts(&other.sign(PATH));
+
+        assert!(!signer.verify(&path, expires, &signature));
+        assert!(other.verify(&path, expires, &signature));
+    }
+}